@@ -0,0 +1,92 @@
+use criterion::{criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion, Throughput};
+
+use evm_asm::{assemble, codegen, parse, tokenize, AssembleOptions};
+
+// a block using every kind of token and value, repeated to make a program of about `lines` lines
+fn program(lines: usize) -> String {
+	const BLOCK: &str = "\
+iload 0 {
+	dup push 2 % push 0 =
+	push { drop push \"even\" } push { tostr push \" is odd\" concat } if
+}
+push [1 2.5 -3 \"four\" true nil [5 6]]
+push { push 1 + } map
+push { + } push 0 reduce
+iload 1 \"a string with some words in it\"
+push 10 iota
+push 1 load call print
+";
+	let count = BLOCK.lines().count();
+	BLOCK.repeat(lines.div_ceil(count))
+}
+
+const SIZES: &[(&str, usize)] = &[("small", 100), ("medium", 10_000), ("large", 100_000)];
+
+fn tokenizer(c: &mut Criterion) {
+	let mut group = c.benchmark_group("tokenize");
+
+	for &(name, lines) in SIZES {
+		let source = program(lines);
+		group.throughput(Throughput::Bytes(source.len() as u64));
+		group.bench_with_input(BenchmarkId::from_parameter(name), &source, |b, source| {
+			b.iter(|| tokenize(source, "<bench>").unwrap())
+		});
+	}
+}
+
+fn parser(c: &mut Criterion) {
+	let mut group = c.benchmark_group("parse");
+
+	for &(name, lines) in SIZES {
+		let source = program(lines);
+		let tokens = tokenize(&source, "<bench>").unwrap();
+		group.throughput(Throughput::Bytes(source.len() as u64));
+		group.bench_with_input(BenchmarkId::from_parameter(name), &tokens, |b, tokens| {
+			b.iter_batched(|| tokens.clone(), |tokens| parse(tokens).unwrap(), BatchSize::LargeInput)
+		});
+	}
+}
+
+fn generator(c: &mut Criterion) {
+	let mut group = c.benchmark_group("codegen");
+
+	for &(name, lines) in SIZES {
+		let source = program(lines);
+		let commands = parse(tokenize(&source, "<bench>").unwrap()).unwrap();
+		group.throughput(Throughput::Bytes(source.len() as u64));
+		group.bench_with_input(BenchmarkId::from_parameter(name), &commands, |b, commands| {
+			b.iter_batched(|| commands.clone(), codegen::generate, BatchSize::LargeInput)
+		});
+	}
+}
+
+// runs of top-level commands on a thread each; small programs stay on one
+fn parallel_generator(c: &mut Criterion) {
+	let mut group = c.benchmark_group("codegen-parallel");
+
+	for &(name, lines) in SIZES {
+		let source = program(lines);
+		let commands = parse(tokenize(&source, "<bench>").unwrap()).unwrap();
+		group.throughput(Throughput::Bytes(source.len() as u64));
+		group.bench_with_input(BenchmarkId::from_parameter(name), &commands, |b, commands| {
+			b.iter_batched(|| commands.clone(), codegen::generate_parallel, BatchSize::LargeInput)
+		});
+	}
+}
+
+// the whole pipeline, including the optimizer and validation
+fn assembler(c: &mut Criterion) {
+	let mut group = c.benchmark_group("assemble");
+	let options = AssembleOptions::default();
+
+	for &(name, lines) in SIZES {
+		let source = program(lines);
+		group.throughput(Throughput::Bytes(source.len() as u64));
+		group.bench_with_input(BenchmarkId::from_parameter(name), &source, |b, source| {
+			b.iter(|| assemble(source, &options).unwrap())
+		});
+	}
+}
+
+criterion_group!(benches, tokenizer, parser, generator, parallel_generator, assembler);
+criterion_main!(benches);
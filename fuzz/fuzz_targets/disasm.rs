@@ -0,0 +1,12 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+use evm_asm::disasm;
+
+// any bytes must decode or fail with an error, in every decoding mode
+fuzz_target!(|bytes: &[u8]| {
+	let _ = disasm::disassemble(bytes);
+	let _ = disasm::disassemble_best_effort(bytes, 0, bytes.len());
+	let _ = disasm::annotate(bytes, 0, bytes.len());
+});
@@ -0,0 +1,15 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+use evm_asm::lexer::{Loc, Token, TokenType};
+use evm_asm::ast::parse_with_tests;
+
+// any token stream, ended with Eof as tokenize always does, must parse or fail with an error
+fuzz_target!(|types: Vec<TokenType>| {
+	let loc = Loc {line: 1, col: 1, filename: "<fuzz>".to_owned()};
+	let mut tokens: Vec<Token> = types.into_iter().map(|typ| Token::new(typ, loc.clone())).collect();
+	tokens.push(Token::new(TokenType::Eof, loc));
+
+	let _ = parse_with_tests(tokens);
+});
@@ -0,0 +1,19 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+use evm_asm::ast::Command;
+use evm_asm::{codegen, disasm};
+
+// whatever codegen accepts, the disassembler must decode back to a program that encodes the same;
+// bytes are compared rather than commands because NaN is not equal to itself
+fuzz_target!(|commands: Vec<Command>| {
+	if codegen::validate(&commands).is_err() {
+		return;
+	}
+
+	let bytecode = codegen::generate(commands);
+	let decoded = disasm::disassemble(&bytecode).expect("generated bytecode must decode");
+
+	assert_eq!(codegen::generate(decoded), bytecode);
+});
@@ -0,0 +1,28 @@
+/* C interface to the evm-asm assembler; build the library with
+   `cargo rustc --lib --crate-type cdylib --release --features ffi` */
+#ifndef EVMASM_H
+#define EVMASM_H
+
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+/* Assembles NUL-terminated source text. Returns the bytecode and stores its length in *len, or
+   returns NULL on failure, when evmasm_last_error describes the problem. */
+uint8_t *evmasm_assemble(const char *source, size_t *len);
+
+/* Releases bytecode returned by evmasm_assemble; len is the length it stored. */
+void evmasm_free(uint8_t *bytes, size_t len);
+
+/* The message of the last failure on the calling thread, or NULL. Owned by the library and valid
+   until the next failure on the same thread. */
+const char *evmasm_last_error(void);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif
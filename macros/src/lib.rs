@@ -0,0 +1,30 @@
+// evm_asm!("push 1 push 2 +") assembles its string at compile time into a `&'static [u8]` of
+// bytecode, as `evm-asm` would write it. a program that does not assemble is a compile error on
+// the string, with the diagnostic as the assembler prints it
+
+use proc_macro::TokenStream;
+use quote::quote;
+use syn::{parse_macro_input, LitStr};
+
+use evm_asm::diagnostics::Diagnostic;
+use evm_asm::{assemble, AssembleOptions};
+
+#[proc_macro]
+pub fn evm_asm(input: TokenStream) -> TokenStream {
+	let literal = parse_macro_input!(input as LitStr);
+	let source = literal.value();
+
+	match assemble(&source, &AssembleOptions::default()) {
+		Ok(bytecode) => {
+			let bytes = proc_macro2::Literal::byte_string(&bytecode);
+			quote!({
+				const BYTECODE: &[u8] = #bytes;
+				BYTECODE
+			}).into()
+		},
+		Err(e) => {
+			let message = Diagnostic::from(&e).render_with_source(&source);
+			syn::Error::new(literal.span(), message).to_compile_error().into()
+		}
+	}
+}
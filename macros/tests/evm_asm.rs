@@ -0,0 +1,19 @@
+use evm_asm::{assemble, AssembleOptions};
+use evm_asm_macros::evm_asm;
+
+const PROGRAM: &[u8] = evm_asm!("push 1 push 2 +");
+
+#[test]
+fn same_as_assemble() {
+	assert_eq!(PROGRAM, assemble("push 1 push 2 +", &AssembleOptions::default()).unwrap());
+}
+
+#[test]
+fn raw_strings() {
+	let program: &'static [u8] = evm_asm!(r#"
+		push "hello"
+		print
+	"#);
+
+	assert_eq!(program, assemble("push \"hello\" print", &AssembleOptions::default()).unwrap());
+}
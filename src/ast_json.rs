@@ -0,0 +1,177 @@
+use serde_json::{json, Value as Json};
+use thiserror::Error;
+
+use crate::opcode;
+use crate::parser::*;
+use crate::tokenizer::Loc;
+
+// the ast-json schema, written by --emit ast-json and disasm -f ast-json:
+//
+//   program  {"version": 1, "commands": [command...]}
+//   command  {"op": NAME, "span"?: span}, plus for push {"value": value}
+//            and for iload {"register": 0-15, "value": value}
+//   value    {"type": "nil"} | {"type": "number", "value": NUMBER} | {"type": "string", "value": STRING}
+//            | {"type": "boolean", "value": BOOL} | {"type": "array", "values": [value...]}
+//            | {"type": "function", "body": [command...]}
+//   span     {"file": STRING, "line": NUMBER, "column": NUMBER}, where the command's mnemonic starts
+//
+// NAME is the command's mnemonic as written in source. spans are left out when the location is
+// unknown, as for disassembled bytecode. the version goes up whenever a field is removed or changes
+// meaning; new optional fields keep it
+pub const SCHEMA_VERSION: u64 = 1;
+
+// numbers commands in the same pre-order as parse_with_locs to find each one's location
+struct Writer<'a> {
+	locs: &'a [Loc],
+	next: usize,
+}
+
+impl Writer<'_> {
+	fn value(&mut self, value: &Value) -> Json {
+		match value {
+			Value::Nil              => json!({"type": "nil"}),
+			Value::Number(val)      => json!({"type": "number", "value": val}),
+			Value::String(val)      => json!({"type": "string", "value": val}),
+			Value::Boolean(val)     => json!({"type": "boolean", "value": val}),
+			Value::Function(cmds)   => json!({"type": "function", "body": self.commands(cmds)}),
+			Value::Array(vals)      => json!({"type": "array", "values": vals.iter().map(|val| self.value(val)).collect::<Vec<_>>()})
+		}
+	}
+
+	fn command(&mut self, command: &Command) -> Json {
+		let id = self.next;
+		self.next += 1;
+
+		let mut json = match command {
+			Command::Push(value)       => json!({"op": "push", "value": self.value(value)}),
+			Command::ILoad(reg, value) => json!({"op": "iload", "register": reg, "value": self.value(value)}),
+			x => json!({"op": get_command_name(x)})
+		};
+
+		if let Some(loc) = self.locs.get(id) {
+			json["span"] = json!({"file": loc.filename, "line": loc.line, "column": loc.col});
+		}
+
+		json
+	}
+
+	fn commands(&mut self, commands: &[Command]) -> Json {
+		Json::Array(commands.iter().map(|command| self.command(command)).collect())
+	}
+}
+
+pub fn value_to_json(value: &Value) -> Json {
+	Writer {locs: &[], next: 0}.value(value)
+}
+
+pub fn command_to_json(command: &Command) -> Json {
+	Writer {locs: &[], next: 0}.command(command)
+}
+
+pub fn to_json(commands: &[Command]) -> Json {
+	Writer {locs: &[], next: 0}.commands(commands)
+}
+
+// a whole program in the versioned schema; `locs` as returned by parse_with_locs, or empty
+pub fn program_to_json(commands: &[Command], locs: &[Loc]) -> Json {
+	json!({"version": SCHEMA_VERSION, "commands": Writer {locs, next: 0}.commands(commands)})
+}
+
+#[derive(Debug, Error)]
+pub enum AstJsonError {
+	#[error("Invalid JSON: {0}")]
+	Json(#[from] serde_json::Error),
+	#[error("Unsupported ast-json version {0}, this assembler reads version {SCHEMA_VERSION}")]
+	UnsupportedVersion(u64),
+	#[error("{message} at {path}")]
+	Invalid {path: String, message: String},
+}
+
+fn invalid(path: &str, message: impl Into<String>) -> AstJsonError {
+	AstJsonError::Invalid {path: path.to_owned(), message: message.into()}
+}
+
+fn field<'j>(json: &'j Json, path: &str, name: &str) -> Result<&'j Json, AstJsonError> {
+	json.get(name).ok_or_else(|| invalid(path, format!("Missing field `{}`", name)))
+}
+
+// the reverse of Writer; `path` is where in the document the current item is, for errors
+struct Reader<'a> {
+	filename: &'a str,
+	locs: Vec<Loc>,
+}
+
+impl Reader<'_> {
+	fn value(&mut self, json: &Json, path: &str) -> Result<Value, AstJsonError> {
+		let typ = field(json, path, "type")?.as_str().ok_or_else(|| invalid(path, "`type` is not a string"))?;
+
+		match typ {
+			"nil" => Ok(Value::Nil),
+			"number" => field(json, path, "value")?.as_f64().map(Value::Number).ok_or_else(|| invalid(path, "Number value is not a number")),
+			"string" => field(json, path, "value")?.as_str().map(|val| Value::String(val.to_owned())).ok_or_else(|| invalid(path, "String value is not a string")),
+			"boolean" => field(json, path, "value")?.as_bool().map(Value::Boolean).ok_or_else(|| invalid(path, "Boolean value is not a boolean")),
+			"function" => self.commands(field(json, path, "body")?, &format!("{}.body", path)).map(Value::Function),
+			"array" => {
+				let values = field(json, path, "values")?.as_array().ok_or_else(|| invalid(path, "`values` is not an array"))?;
+				values.iter().enumerate().map(|(i, val)| self.value(val, &format!("{}.values[{}]", path, i))).collect::<Result<_, _>>().map(Value::Array)
+			},
+			typ => Err(invalid(path, format!("Unknown value type `{}`", typ)))
+		}
+	}
+
+	fn command(&mut self, json: &Json, path: &str) -> Result<Command, AstJsonError> {
+		let op = field(json, path, "op")?.as_str().ok_or_else(|| invalid(path, "`op` is not a string"))?;
+		let Some(command) = opcode::COMMANDS.iter().find(|command| get_command_name(command) == op) else {
+			return Err(invalid(path, format!("Unknown op `{}`", op)));
+		};
+
+		// the command's location comes before those of the commands in its value
+		let span = json.get("span");
+		self.locs.push(Loc {
+			line: span.and_then(|span| span["line"].as_u64()).unwrap_or(0),
+			col: span.and_then(|span| span["column"].as_u64()).unwrap_or(0),
+			filename: span.and_then(|span| span["file"].as_str()).unwrap_or(self.filename).to_owned()
+		});
+
+		match command {
+			Command::Push(_) => Ok(Command::Push(self.value(field(json, path, "value")?, &format!("{}.value", path))?)),
+			Command::ILoad(..) => {
+				let reg = field(json, path, "register")?.as_u64().and_then(|reg| u8::try_from(reg).ok())
+					.ok_or_else(|| invalid(path, "`register` is not a register number"))?;
+				Ok(Command::ILoad(reg, self.value(field(json, path, "value")?, &format!("{}.value", path))?))
+			},
+			command => Ok(command.clone())
+		}
+	}
+
+	fn commands(&mut self, json: &Json, path: &str) -> Result<Vec<Command>, AstJsonError> {
+		let commands = json.as_array().ok_or_else(|| invalid(path, "Commands are not an array"))?;
+		commands.iter().enumerate().map(|(i, command)| self.command(command, &format!("{}[{}]", path, i))).collect()
+	}
+}
+
+// reads a program written in the schema above, returning its commands and their locations like
+// parse_with_locs; commands without a span are placed at line 0 of `filename`. a bare array of
+// commands, as written before the schema had a version, is read as version 1
+pub fn program_from_json(json: &Json, filename: &str) -> Result<(Vec<Command>, Vec<Loc>), AstJsonError> {
+	let mut reader = Reader {filename, locs: vec![]};
+
+	let commands = match json {
+		Json::Array(_) => reader.commands(json, "commands")?,
+		_ => {
+			let version = field(json, "program", "version")?.as_u64().ok_or_else(|| invalid("version", "`version` is not a number"))?;
+
+			if version != SCHEMA_VERSION {
+				return Err(AstJsonError::UnsupportedVersion(version));
+			}
+
+			reader.commands(field(json, "program", "commands")?, "commands")?
+		}
+	};
+
+	Ok((commands, reader.locs))
+}
+
+pub fn program_from_str(text: &str, filename: &str) -> Result<(Vec<Command>, Vec<Loc>), AstJsonError> {
+	program_from_json(&serde_json::from_str(text)?, filename)
+}
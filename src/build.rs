@@ -0,0 +1,119 @@
+use std::fmt::Write;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use thiserror::Error;
+
+use crate::diagnostic::Diagnostic;
+use crate::{assemble_all, AssembleOptions};
+
+// for build scripts: assembles every .evmasm file in a directory into a Rust module of byte arrays.
+// in build.rs:
+//
+//   evm_asm::build::assemble_dir("asm", std::env::var("OUT_DIR").unwrap()).unwrap();
+//
+// and in the crate:
+//
+//   include!(concat!(env!("OUT_DIR"), "/evm_asm.rs"));
+//
+// which defines a `pub const` per file, named after it: asm/hello-world.evmasm is HELLO_WORLD
+
+pub const MODULE: &str = "evm_asm.rs";
+pub const EXTENSION: &str = "evmasm";
+
+#[derive(Debug, Error)]
+pub enum BuildError {
+	#[error("{}: {source}", path.display())]
+	Io {path: PathBuf, source: io::Error},
+	// the diagnostic rendered with its source, for cargo to show as the build script's output
+	#[error("{message}")]
+	Assemble {path: PathBuf, message: String},
+	#[error("{} and {} are both named {name}", first.display(), second.display())]
+	DuplicateName {name: String, first: PathBuf, second: PathBuf},
+}
+
+fn io_error(path: &Path) -> impl FnOnce(io::Error) -> BuildError + '_ {
+	move |source| BuildError::Io {path: path.to_owned(), source}
+}
+
+// the file stem in upper snake case, which is a valid and conventional constant name
+fn const_name(path: &Path) -> String {
+	let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+	let mut name: String = stem.chars().map(|c| if c.is_ascii_alphanumeric() { c.to_ascii_uppercase() } else { '_' }).collect();
+
+	if !name.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_') {
+		name.insert(0, '_');
+	}
+
+	name
+}
+
+// writes the module to `out_dir` and returns its path; prints cargo:rerun-if-changed for the
+// directory, so adding or removing a file reruns the build script, and for each file
+pub fn assemble_dir(dir: impl AsRef<Path>, out_dir: impl AsRef<Path>) -> Result<PathBuf, BuildError> {
+	assemble_dir_with(dir, out_dir, &AssembleOptions::default())
+}
+
+// with the given optimizer options and size limit
+pub fn assemble_dir_with(dir: impl AsRef<Path>, out_dir: impl AsRef<Path>, options: &AssembleOptions) -> Result<PathBuf, BuildError> {
+	let dir = dir.as_ref();
+	println!("cargo:rerun-if-changed={}", dir.display());
+
+	let mut files = vec![];
+
+	for entry in fs::read_dir(dir).map_err(io_error(dir))? {
+		let path = entry.map_err(io_error(dir))?.path();
+
+		if path.is_file() && path.extension().is_some_and(|ext| ext == EXTENSION) {
+			files.push(path);
+		}
+	}
+
+	// sorted so the module is the same on every platform
+	files.sort();
+
+	let mut module = format!("// generated by evm_asm::build::assemble_dir from {}; do not edit\n", dir.display());
+	let mut names: Vec<(String, &Path)> = vec![];
+
+	let mut sources = vec![];
+
+	for path in &files {
+		println!("cargo:rerun-if-changed={}", path.display());
+
+		let name = const_name(path);
+
+		if let Some((_, first)) = names.iter().find(|(other, _)| *other == name) {
+			return Err(BuildError::DuplicateName {name, first: first.to_path_buf(), second: path.clone()});
+		}
+
+		sources.push(fs::read_to_string(path).map_err(io_error(path))?);
+		names.push((name, path));
+	}
+
+	// assembled in parallel; the first error in file order is the one reported
+	let filenames: Vec<String> = files.iter().map(|path| path.to_string_lossy().into_owned()).collect();
+	let inputs: Vec<(&str, &str)> = filenames.iter().map(String::as_str).zip(sources.iter().map(String::as_str)).collect();
+
+	for (((name, path), source), result) in names.iter().zip(&sources).zip(assemble_all(&inputs, options)) {
+		let bytecode = result.map_err(|e| BuildError::Assemble {path: path.to_path_buf(), message: Diagnostic::from(&e).render_with_source(source)})?;
+
+		event!(INFO, file = %path.display(), bytes = bytecode.len(), "assembled");
+		write!(module, "\n// {}\npub const {}: &[u8] = &[", path.display(), name).unwrap();
+
+		for (i, byte) in bytecode.iter().enumerate() {
+			if i % 16 == 0 {
+				module.push_str("\n\t");
+			} else {
+				module.push(' ');
+			}
+			write!(module, "0x{:02x},", byte).unwrap();
+		}
+
+		module.push_str("\n];\n");
+	}
+
+	let out = out_dir.as_ref().join(MODULE);
+	fs::write(&out, module).map_err(io_error(&out))?;
+	Ok(out)
+}
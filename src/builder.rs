@@ -0,0 +1,101 @@
+use alloc::vec::Vec;
+
+use crate::codegen::{self, CodegenError};
+use crate::parser::{Command, Value};
+
+// builds a program in Rust instead of source text:
+//
+//   ProgramBuilder::new().iload_function(0, |f| f.push(Value::Number(1.0)).add()).push(Value::Number(2.0)).push(Value::Number(0.0)).call().build()
+//
+// each method appends one command; function and iload_function take a closure that builds the body
+#[derive(Default)]
+pub struct ProgramBuilder {
+	commands: Vec<Command>,
+}
+
+macro_rules! commands {
+	($($name:ident => $command:ident),* $(,)?) => {
+		$(
+			// named after the mnemonics, so `not` appends a command rather than negating the builder
+			#[allow(clippy::should_implement_trait)]
+			pub fn $name(self) -> ProgramBuilder {
+				self.command(Command::$command)
+			}
+		)*
+	}
+}
+
+impl ProgramBuilder {
+	pub fn new() -> ProgramBuilder {
+		ProgramBuilder::default()
+	}
+
+	pub fn command(mut self, command: Command) -> ProgramBuilder {
+		self.commands.push(command);
+		self
+	}
+
+	pub fn push(self, value: Value) -> ProgramBuilder {
+		self.command(Command::Push(value))
+	}
+
+	pub fn iload(self, reg: u8, value: Value) -> ProgramBuilder {
+		self.command(Command::ILoad(reg, value))
+	}
+
+	// pushes a function whose body is built by `body`
+	pub fn function(self, body: impl FnOnce(ProgramBuilder) -> ProgramBuilder) -> ProgramBuilder {
+		self.push(Value::Function(body(ProgramBuilder::new()).commands))
+	}
+
+	// loads a function whose body is built by `body` into a register
+	pub fn iload_function(self, reg: u8, body: impl FnOnce(ProgramBuilder) -> ProgramBuilder) -> ProgramBuilder {
+		self.iload(reg, Value::Function(body(ProgramBuilder::new()).commands))
+	}
+
+	commands! {
+		dup => Dup,
+		swap => Swap,
+		load => Load,
+		drop => Drop,
+		query => Query,
+		info => Info,
+		if_ => If,
+		each => Each,
+		reduce => Reduce,
+		reverse => Reverse,
+		map => Map,
+		filter => Filter,
+		call => Call,
+		to_str => ToStr,
+		to_num => ToNum,
+		add => Add,
+		sub => Sub,
+		mul => Mul,
+		div => Div,
+		modulo => Mod,
+		eq => Eq,
+		not_eq => NotEq,
+		greater => Greater,
+		greater_eq => GreaterEq,
+		less => Less,
+		less_eq => LessEq,
+		and => And,
+		or => Or,
+		not => Not,
+		concat => Concat,
+		match_ => Match,
+		split => Split,
+		iota => Iota,
+		print => Print,
+		read_line => ReadLine,
+		assert => Assert,
+		assert_eq => AssertEq,
+	}
+
+	// the program, checked the same way the assembler checks parsed source before encoding it
+	pub fn build(self) -> Result<Vec<Command>, CodegenError> {
+		codegen::validate(&self.commands)?;
+		Ok(self.commands)
+	}
+}
@@ -0,0 +1,56 @@
+use std::collections::hash_map::DefaultHasher;
+use std::fs;
+use std::hash::{Hash, Hasher};
+use std::io;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use crate::optimizer;
+
+// a directory of assembled programs, for --cache and AssembleOptions::cache. each file is the
+// bytecode of one source, named by a hash of the source, the optimizer options and the assembler's
+// version, so a source assembled again unchanged is read back rather than assembled. only programs
+// that assembled are kept, so errors are reported every time. the hash is std's, which may change
+// between Rust releases; that only costs a miss
+
+#[derive(Clone, Debug)]
+pub struct Cache {
+	dir: PathBuf,
+}
+
+// numbers the temporary files of the threads writing at once
+static WRITES: AtomicUsize = AtomicUsize::new(0);
+
+impl Cache {
+	pub fn new(dir: impl Into<PathBuf>) -> Cache {
+		Cache {dir: dir.into()}
+	}
+
+	// two hashes of 64 bits, so different sources do not collide in practice
+	pub fn key(source: &str, options: &optimizer::Options) -> String {
+		let hash = |seed: u8| {
+			let mut hasher = DefaultHasher::new();
+			(seed, env!("CARGO_PKG_VERSION"), options, source).hash(&mut hasher);
+			hasher.finish()
+		};
+
+		format!("{:016x}{:016x}", hash(0), hash(1))
+	}
+
+	fn path(&self, key: &str) -> PathBuf {
+		self.dir.join(key).with_extension("bin")
+	}
+
+	pub fn get(&self, key: &str) -> Option<Vec<u8>> {
+		fs::read(self.path(key)).ok()
+	}
+
+	// written to a temporary file and renamed, so a reader never sees part of it
+	pub fn put(&self, key: &str, bytecode: &[u8]) -> io::Result<()> {
+		fs::create_dir_all(&self.dir)?;
+
+		let temporary = self.dir.join(format!("{}.{}.{}.tmp", key, std::process::id(), WRITES.fetch_add(1, Ordering::Relaxed)));
+		fs::write(&temporary, bytecode)?;
+		fs::rename(&temporary, self.path(key))
+	}
+}
@@ -0,0 +1,175 @@
+use serde_json::{json, Value as Json};
+use std::fmt::Write;
+
+use crate::cfg::{calls, register};
+use crate::parser::{get_command_name, Command, Value};
+
+// which functions call which, for --emit call-graph-dot and call-graph-json. a function is a
+// register an iload sets to a function literal, as that is what call, if and the loops take; the
+// calls in a function literal anywhere inside it count as its own, and those outside any as the
+// program's. a call through a computed register cannot be followed and is only counted
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Caller {
+	Program,
+	Register(u8),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Call {
+	pub from: Caller,
+	pub to: u8,
+	pub via: &'static str, // the calling command
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct CallGraph {
+	pub functions: Vec<u8>, // in the order of their first iload
+	pub calls: Vec<Call>, // each once, in program order
+	pub unresolved: Vec<(Caller, &'static str)>, // calls through a computed register or one without a function
+}
+
+impl Caller {
+	fn id(self) -> String {
+		match self {
+			Caller::Program => "program".to_owned(),
+			Caller::Register(reg) => format!("r{}", reg)
+		}
+	}
+}
+
+// the registers iloaded with a function, before the calls are followed
+fn functions(commands: &[Command], out: &mut Vec<u8>) {
+	fn in_value(value: &Value, out: &mut Vec<u8>) {
+		match value {
+			Value::Function(commands) => functions(commands, out),
+			Value::Array(values) => values.iter().for_each(|value| in_value(value, out)),
+			_ => {}
+		}
+	}
+
+	for command in commands {
+		if let Command::ILoad(reg, Value::Function(_)) = command {
+			if !out.contains(reg) {
+				out.push(*reg);
+			}
+		}
+
+		if let Command::Push(value) | Command::ILoad(_, value) = command {
+			in_value(value, out);
+		}
+	}
+}
+
+impl CallGraph {
+	fn commands(&mut self, commands: &[Command], caller: Caller) {
+		for (i, command) in commands.iter().enumerate() {
+			if calls(command) {
+				let via = get_command_name(command);
+
+				match register(commands, i).filter(|reg| self.functions.contains(reg)) {
+					Some(to) => {
+						let call = Call {from: caller, to, via};
+
+						if !self.calls.contains(&call) {
+							self.calls.push(call);
+						}
+					},
+					None => self.unresolved.push((caller, via))
+				}
+			}
+
+			match command {
+				Command::ILoad(reg, value) => self.value(value, Caller::Register(*reg)),
+				Command::Push(value) => self.value(value, caller),
+				_ => {}
+			}
+		}
+	}
+
+	fn value(&mut self, value: &Value, caller: Caller) {
+		match value {
+			Value::Function(commands) => self.commands(commands, caller),
+			Value::Array(values) => values.iter().for_each(|value| self.value(value, caller)),
+			_ => {}
+		}
+	}
+
+	// the functions `reg` can reach through one call or more
+	fn reachable(&self, reg: u8) -> Vec<u8> {
+		let mut seen = vec![];
+		let mut stack = vec![reg];
+
+		while let Some(from) = stack.pop() {
+			for call in self.calls.iter().filter(|call| call.from == Caller::Register(from)) {
+				if !seen.contains(&call.to) {
+					seen.push(call.to);
+					stack.push(call.to);
+				}
+			}
+		}
+
+		seen
+	}
+
+	// the sets of functions that call each other, directly or not, each sorted; a function calling
+	// itself is a set of one
+	pub fn cycles(&self) -> Vec<Vec<u8>> {
+		let reachable: Vec<(u8, Vec<u8>)> = self.functions.iter().map(|reg| (*reg, self.reachable(*reg))).collect();
+		let mut cycles: Vec<Vec<u8>> = vec![];
+
+		for (reg, reaches) in &reachable {
+			if !reaches.contains(reg) {
+				continue;
+			}
+
+			let mut cycle: Vec<u8> = reaches.iter().copied().filter(|other| reachable.iter().any(|(from, to)| from == other && to.contains(reg))).collect();
+			cycle.sort();
+
+			if !cycles.contains(&cycle) {
+				cycles.push(cycle);
+			}
+		}
+
+		cycles.sort();
+		cycles
+	}
+
+	// the graph as a Graphviz digraph, with the calls within a cycle in red
+	pub fn dot(&self) -> String {
+		let cycles = self.cycles();
+		let mut out = String::from("digraph calls {\n\tnode [shape=box, fontname=\"monospace\"];\n\tprogram [shape=ellipse];\n");
+
+		for reg in &self.functions {
+			let color = if cycles.iter().any(|cycle| cycle.contains(reg)) { ", color=red" } else { "" };
+			writeln!(out, "\tr{} [label=\"register {}\"{}];", reg, reg, color).unwrap();
+		}
+
+		for call in &self.calls {
+			let recursive = matches!(call.from, Caller::Register(from) if cycles.iter().any(|cycle| cycle.contains(&from) && cycle.contains(&call.to)));
+			writeln!(out, "\t{} -> r{} [label=\"{}\"{}];", call.from.id(), call.to, call.via, if recursive { ", color=red" } else { "" }).unwrap();
+		}
+
+		out.push_str("}\n");
+		out
+	}
+
+	pub fn to_json(&self) -> Json {
+		let unresolved = |caller: Caller| self.unresolved.iter().filter(|(from, _)| *from == caller).count();
+		let mut nodes = vec![json!({"id": "program", "unresolved": unresolved(Caller::Program)})];
+		nodes.extend(self.functions.iter().map(|reg| json!({"id": Caller::Register(*reg).id(), "register": reg, "unresolved": unresolved(Caller::Register(*reg))})));
+
+		json!({
+			"nodes": nodes,
+			"edges": self.calls.iter().map(|call| json!({"from": call.from.id(), "to": Caller::Register(call.to).id(), "via": call.via})).collect::<Vec<_>>(),
+			"cycles": self.cycles().iter().map(|cycle| cycle.iter().map(|reg| Caller::Register(*reg).id()).collect::<Vec<_>>()).collect::<Vec<_>>()
+		})
+	}
+}
+
+pub fn call_graph(commands: &[Command]) -> CallGraph {
+	let mut graph = CallGraph::default();
+	functions(commands, &mut graph.functions);
+	graph.commands(commands, Caller::Program);
+	graph
+}
@@ -0,0 +1,248 @@
+use alloc::collections::BTreeMap;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use alloc::{format, vec};
+use core::fmt::Write;
+
+use crate::parser::{get_command_name, Command, Value};
+
+// the control flow of a program as a Graphviz digraph, for --emit cfg-dot. a block is a run of
+// commands that ends with one that calls a function: call, if or a loop. each function literal is a
+// cluster of blocks, inside the cluster of the code that holds it. solid edges go from a block to
+// the block after it, dashed edges from a call to the functions in the register it calls, where the
+// register is a pushed number; a computed register has no edge. blocks after a command that always
+// fails are grey, and have no edge to them
+
+struct Graph<'a> {
+	out: String,
+	next: usize, // id of the next block
+	clusters: usize,
+	edges: Vec<(usize, usize)>,
+	calls: Vec<(usize, u8, &'static str)>, // block, register and the command calling it
+	functions: Vec<(u8, usize)>, // register and entry block of each function an iload sets
+	bodies: BTreeMap<u8, &'a [Command]>,
+}
+
+pub(crate) fn calls(command: &Command) -> bool {
+	matches!(command, Command::Call | Command::If | Command::Each | Command::Map | Command::Filter | Command::Reduce)
+}
+
+// the register a calling command pops, if the program pushes it as a number
+pub(crate) fn register(commands: &[Command], i: usize) -> Option<u8> {
+	// reduce pops its initial value above the register
+	let distance = if matches!(commands[i], Command::Reduce) { 2 } else { 1 };
+
+	match commands.get(i.checked_sub(distance)?) {
+		Some(Command::Push(Value::Number(reg))) if *reg as u8 as f64 == *reg => Some(*reg as u8),
+		_ => None
+	}
+}
+
+// the functions of the registers the program iloads once, with a literal, wherever the iload is
+pub(crate) fn functions(commands: &[Command]) -> BTreeMap<u8, &[Command]> {
+	fn visit<'a>(commands: &'a [Command], iloads: &mut BTreeMap<u8, Vec<&'a Value>>) {
+		for command in commands {
+			if let Command::ILoad(reg, value) = command {
+				iloads.entry(*reg).or_default().push(value);
+			}
+
+			if let Command::Push(value) | Command::ILoad(_, value) = command {
+				in_value(value, iloads);
+			}
+		}
+	}
+
+	fn in_value<'a>(value: &'a Value, iloads: &mut BTreeMap<u8, Vec<&'a Value>>) {
+		match value {
+			Value::Function(body) => visit(body, iloads),
+			Value::Array(values) => values.iter().for_each(|value| in_value(value, iloads)),
+			_ => {}
+		}
+	}
+
+	let mut iloads = BTreeMap::new();
+	visit(commands, &mut iloads);
+
+	iloads.into_iter().filter_map(|(reg, values)| match values[..] {
+		[Value::Function(body)] => Some((reg, &body[..])),
+		_ => None
+	}).collect()
+}
+
+// the first command of `commands` that always fails, so those after it never run: assert of a
+// falsy literal, assert_eq of two different literals, division by a literal zero, or a call of a
+// function that always fails, by call, by if on a truthy literal or by a loop over a literal array
+// with items. `functions` are those of functions(); a function that calls itself is not followed
+pub(crate) fn divergence(commands: &[Command], functions: &BTreeMap<u8, &[Command]>) -> Option<usize> {
+	fn find(commands: &[Command], functions: &BTreeMap<u8, &[Command]>, calling: &mut Vec<u8>) -> Option<usize> {
+		let pushed = |i: usize, back: usize| match i.checked_sub(back).map(|j| &commands[j]) {
+			Some(Command::Push(value)) => Some(value),
+			_ => None
+		};
+
+		(0..commands.len()).find(|&i| {
+			let calls = |calling: &mut Vec<u8>| match register(commands, i) {
+				Some(reg) if !calling.contains(&reg) && functions.contains_key(&reg) => {
+					calling.push(reg);
+					let fails = find(functions[&reg], functions, calling).is_some();
+					calling.pop();
+					fails
+				},
+				_ => false
+			};
+
+			match &commands[i] {
+				Command::Assert => matches!(pushed(i, 1), Some(Value::Nil | Value::Boolean(false))),
+				Command::AssertEq => match (pushed(i, 2), pushed(i, 1)) {
+					(Some(a), Some(b)) => plain(a) && plain(b) && a != b,
+					_ => false
+				},
+				Command::Div | Command::Mod => matches!(pushed(i, 1), Some(Value::Number(val)) if *val == 0.0),
+				Command::Call => calls(calling),
+				Command::If => matches!(pushed(i, 2), Some(value) if !matches!(value, Value::Nil | Value::Boolean(false))) && calls(calling),
+				Command::Each | Command::Map | Command::Filter => matches!(pushed(i, 2), Some(Value::Array(items)) if !items.is_empty()) && calls(calling),
+				Command::Reduce => matches!(pushed(i, 3), Some(Value::Array(items)) if !items.is_empty()) && calls(calling),
+				_ => false
+			}
+		})
+	}
+
+	// values without functions, which compare as the interpreter compares them
+	fn plain(value: &Value) -> bool {
+		match value {
+			Value::Function(_) => false,
+			Value::Array(values) => values.iter().all(plain),
+			_ => true
+		}
+	}
+
+	find(commands, functions, &mut vec![])
+}
+
+// a command as one line of a block, with function bodies left to their clusters
+fn text(command: &Command) -> String {
+	match command {
+		Command::Push(Value::Function(_)) => "push {...}".to_string(),
+		Command::ILoad(reg, Value::Function(_)) => format!("iload {} {{...}}", reg),
+		command => command.to_string()
+	}
+}
+
+fn escape(text: &str) -> String {
+	text.chars().map(|c| match c {
+		'"' => "\\\"".to_string(),
+		'\\' => "\\\\".to_string(),
+		'\n' => "\\n".to_string(),
+		c => c.to_string()
+	}).collect()
+}
+
+impl Graph<'_> {
+	fn block(&mut self, id: usize, lines: &[String], reachable: bool, depth: usize) {
+		let label: String = match lines.is_empty() {
+			true => "(empty)\\l".to_string(),
+			false => lines.iter().map(|line| format!("{}\\l", escape(line))).collect()
+		};
+		let style = if reachable { "" } else { ", color=grey, fontcolor=grey" };
+
+		let _ = writeln!(self.out, "{}b{} [label=\"{}\"{}];", "\t".repeat(depth), id, label, style);
+	}
+
+	// the blocks of `commands` in a cluster; returns the first block
+	fn cluster(&mut self, label: &str, commands: &[Command], depth: usize) -> usize {
+		let indent = "\t".repeat(depth);
+		let _ = writeln!(self.out, "{}subgraph cluster_{} {{\n{}\tlabel=\"{}\";", indent, self.clusters, indent, escape(label));
+		self.clusters += 1;
+
+		let entry = self.next;
+		self.next += 1;
+
+		let mut current = entry;
+		let mut lines = vec![];
+		let mut reachable = true;
+		let failing = divergence(commands, &self.bodies);
+
+		for (i, command) in commands.iter().enumerate() {
+			lines.push(text(command));
+
+			if let Command::Push(value) | Command::ILoad(_, value) = command {
+				let register = match command {
+					Command::ILoad(reg, _) => Some(*reg),
+					_ => None
+				};
+
+				self.value(value, register, depth + 1);
+			}
+
+			if calls(command) {
+				if let Some(reg) = register(commands, i) {
+					self.calls.push((current, reg, get_command_name(command)));
+				}
+
+				if i + 1 < commands.len() {
+					self.block(current, &lines, reachable, depth + 1);
+					lines.clear();
+
+					if failing.is_none_or(|failing| i < failing) {
+						self.edges.push((current, self.next));
+					} else {
+						reachable = false;
+					}
+
+					current = self.next;
+					self.next += 1;
+				}
+			}
+		}
+
+		self.block(current, &lines, reachable, depth + 1);
+		let _ = writeln!(self.out, "{}}}", indent);
+		entry
+	}
+
+	// clusters for the functions in a pushed or iloaded value; `register` is the one an iload sets
+	fn value(&mut self, value: &Value, register: Option<u8>, depth: usize) {
+		match value {
+			Value::Function(body) => {
+				let label = match register {
+					Some(reg) => format!("register {}", reg),
+					None => "function".to_string()
+				};
+				let entry = self.cluster(&label, body, depth);
+
+				if let Some(reg) = register {
+					self.functions.push((reg, entry));
+				}
+			},
+			Value::Array(values) => values.iter().for_each(|value| self.value(value, None, depth)),
+			_ => {}
+		}
+	}
+}
+
+pub fn dot(commands: &[Command]) -> String {
+	let mut graph = Graph {
+		out: String::from("digraph cfg {\n\tnode [shape=box, fontname=\"monospace\"];\n"),
+		next: 0,
+		clusters: 0,
+		edges: vec![],
+		calls: vec![],
+		functions: vec![],
+		bodies: functions(commands),
+	};
+
+	graph.cluster("program", commands, 1);
+
+	for (from, to) in &graph.edges {
+		let _ = writeln!(graph.out, "\tb{} -> b{};", from, to);
+	}
+
+	for (from, reg, name) in &graph.calls {
+		for (_, entry) in graph.functions.iter().filter(|(other, _)| other == reg) {
+			let _ = writeln!(graph.out, "\tb{} -> b{} [style=dashed, label=\"{}\"];", from, entry, name);
+		}
+	}
+
+	graph.out.push_str("}\n");
+	graph.out
+}
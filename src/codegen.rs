@@ -1,50 +1,315 @@
+use alloc::vec::Vec;
+use alloc::vec;
 use bytes::{BytesMut, BufMut};
+use thiserror::Error;
+
+use crate::opcode;
 use crate::parser::*;
 
+pub const MAX_NESTING: usize = 256;
+pub const REGISTER_COUNT: u8 = 16;
+
+// programs smaller than this are not worth the threads of generate_parallel
+#[cfg(feature = "std")]
+const PARALLEL_BYTES: usize = 1 << 20;
+
+#[derive(Debug, Error)]
+pub enum CodegenError {
+	#[error("{what} length {len} does not fit in 64 bits")]
+	LengthOutOfRange {what: &'static str, len: usize},
+	#[error("Values nested {depth} levels deep, maximum is {}", MAX_NESTING)]
+	NestingTooDeep {depth: usize},
+	#[error("Register must be between 0-{max}: {0}", max = REGISTER_COUNT - 1)]
+	InvalidRegister(u8),
+	#[error("{what} {len} is over the limit of {max}; raise it with --{limit}")]
+	OverLimit {what: &'static str, limit: &'static str, len: usize, max: usize},
+}
+
+// what the VM the bytecode is for will load, for one that rejects programs within the format's own
+// bounds. none by default
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct TargetLimits {
+	pub max_array_length: Option<usize>, // values
+	pub max_function_length: Option<usize>, // commands in a function literal
+	pub max_string_size: Option<usize>, // bytes
+	pub max_nesting_depth: Option<usize>, // arrays and functions around a value, at most MAX_NESTING
+}
+
+fn check_limit(what: &'static str, limit: &'static str, len: usize, max: Option<usize>) -> Result<(), CodegenError> {
+	match max {
+		Some(max) if len > max => Err(CodegenError::OverLimit {what, limit, len, max}),
+		_ => Ok(())
+	}
+}
+
+fn check_len(what: &'static str, len: usize) -> Result<(), CodegenError> {
+	match u64::try_from(len) {
+		Ok(_) => Ok(()),
+		Err(_) => Err(CodegenError::LengthOutOfRange {what, len})
+	}
+}
+
+// depth counts the arrays and functions around a value, as the disassembler does, so everything
+// that passes here decodes again
+fn nest(depth: usize, limits: &TargetLimits) -> Result<usize, CodegenError> {
+	check_limit("Nesting depth", "max-nesting-depth", depth + 1, limits.max_nesting_depth)?;
+
+	if depth + 1 > MAX_NESTING {
+		return Err(CodegenError::NestingTooDeep {depth: depth + 1});
+	}
+
+	Ok(depth + 1)
+}
+
+fn validate_value(value: &Value, depth: usize, limits: &TargetLimits) -> Result<(), CodegenError> {
+	match value {
+		Value::String(val) => {
+			check_len("String", val.len())?;
+			check_limit("String size", "max-string-size", val.len(), limits.max_string_size)
+		},
+		Value::Function(commands) => {
+			check_len("Function", commands.len())?;
+			check_limit("Function length", "max-function-length", commands.len(), limits.max_function_length)?;
+			validate_commands(commands, nest(depth, limits)?, limits)
+		},
+		Value::Array(values) => {
+			check_len("Array", values.len())?;
+			check_limit("Array length", "max-array-length", values.len(), limits.max_array_length)?;
+			let depth = nest(depth, limits)?;
+			values.iter().try_for_each(|value| validate_value(value, depth, limits))
+		},
+		_ => Ok(())
+	}
+}
+
+fn validate_commands(commands: &[Command], depth: usize, limits: &TargetLimits) -> Result<(), CodegenError> {
+	for command in commands {
+		match command {
+			Command::Push(value) => validate_value(value, depth, limits)?,
+			Command::ILoad(reg, value) => {
+				if *reg >= REGISTER_COUNT {
+					return Err(CodegenError::InvalidRegister(*reg));
+				}
+				validate_value(value, depth, limits)?
+			},
+			_ => {}
+		}
+	}
+
+	Ok(())
+}
+
+// checks everything generate assumes: lengths fit their u64 fields, nesting is bounded and registers exist
+pub fn validate(commands: &[Command]) -> Result<(), CodegenError> {
+	validate_commands(commands, 0, &TargetLimits::default())
+}
+
+// validate, and within `limits` as well
+pub fn validate_limited(commands: &[Command], limits: &TargetLimits) -> Result<(), CodegenError> {
+	validate_commands(commands, 0, limits)
+}
+
 pub fn generate_value(value: Value) -> BytesMut {
-	let mut buf = BytesMut::new();
+	let mut buf = BytesMut::with_capacity(value_size(&value));
+	put_value(&mut buf, value);
+	buf
+}
 
-	buf.put_u8(unsafe {*<*const _>::from(&value).cast::<u8>()}); // safe because of repr(u8) on enum
+// nested values and function bodies are written straight into the one buffer
+fn put_value(buf: &mut BytesMut, value: Value) {
+	buf.put_u8(opcode::value_tag(&value));
 
 	match value {
 		Value::Nil => {},
 		Value::Number(val) => buf.put_slice(&val.to_le_bytes()),
 		Value::String(val) => {
 			buf.put_u64_le(val.len() as u64);
-			buf.put_slice((&val).as_bytes())
+			buf.put_slice(val.as_bytes())
 		},
 		Value::Boolean(val) => buf.put_u8(val as u8),
 		Value::Function(commands) => {
+			event!(TRACE, commands = commands.len(), "function");
 			buf.put_u64_le(commands.len() as u64);
-			buf.extend_from_slice(&generate(commands));
+			put_commands(buf, commands, &mut |_| {});
 		},
 		Value::Array(values) => {
 			buf.put_u64_le(values.len() as u64);
 
 			for value in values {
-				buf.extend_from_slice(&generate_value(value))
+				put_value(buf, value);
 			}
 		}
 	}
+}
+
+pub fn generate(commands: Vec<Command>) -> BytesMut {
+	generate_with_progress(commands, &mut |_| {})
+}
 
+// calls `progress` with the percentage of top-level commands encoded when it changes, and with 100
+// at the end
+#[cfg_attr(feature = "tracing", tracing::instrument(name = "generate", level = "debug", skip_all, fields(commands = commands.len())))]
+pub fn generate_with_progress(commands: Vec<Command>, progress: &mut dyn FnMut(u8)) -> BytesMut {
+	let buf = encode(commands, progress);
+	event!(DEBUG, bytes = buf.len(), "generated");
 	buf
 }
 
-pub fn generate(commands: Vec<Command>) -> BytesMut {
-	let mut buf = BytesMut::new();
+// encodes runs of top-level commands on a thread each and joins their buffers. no command refers to
+// another by offset, so a run encodes as it would alone and the buffers need no relocating. the
+// same bytes as generate, which small programs, single cores and browsers get
+#[cfg(feature = "std")]
+#[cfg_attr(feature = "tracing", tracing::instrument(name = "generate", level = "debug", skip_all, fields(commands = commands.len())))]
+pub fn generate_parallel(commands: Vec<Command>) -> BytesMut {
+	#[cfg(not(target_arch = "wasm32"))]
+	use rayon::prelude::*;
 
-	for command in commands {
-		buf.put_u8(unsafe {*<*const _>::from(&command).cast::<u8>()}); // safe because of repr(u8) on enum
+	#[cfg(not(target_arch = "wasm32"))]
+	let threads = rayon::current_num_threads();
+	#[cfg(target_arch = "wasm32")]
+	let threads = 1;
+
+	let sizes: Vec<usize> = commands.iter().map(command_size).collect();
+	let size: usize = sizes.iter().sum();
+
+	if size < PARALLEL_BYTES || threads == 1 {
+		return generate(commands);
+	}
+
+	// a few runs a thread, of about the same size, so one large command does not hold up the rest
+	let target = (size / (threads * 4)).max(PARALLEL_BYTES / 16);
+	let mut runs = vec![(vec![], 0)];
+
+	for (command, size) in commands.into_iter().zip(sizes) {
+		let (run, run_size) = runs.last_mut().unwrap();
+
+		if *run_size >= target {
+			runs.push((vec![command], size));
+		} else {
+			run.push(command);
+			*run_size += size;
+		}
+	}
+
+	#[cfg(not(target_arch = "wasm32"))]
+	let runs = runs.into_par_iter();
+	#[cfg(target_arch = "wasm32")]
+	let runs = runs.into_iter();
+
+	let parts: Vec<BytesMut> = runs.map(|(run, size)| {
+		let mut buf = BytesMut::with_capacity(size);
+		put_commands(&mut buf, run, &mut |_| {});
+		buf
+	}).collect();
+
+	let mut buf = BytesMut::with_capacity(size);
+
+	for part in &parts {
+		buf.put_slice(part);
+	}
+
+	event!(DEBUG, bytes = buf.len(), runs = parts.len(), "generated");
+	buf
+}
+
+// sized up front, so the buffer is allocated once
+fn encode(commands: Vec<Command>, progress: &mut dyn FnMut(u8)) -> BytesMut {
+	let mut buf = BytesMut::with_capacity(commands.iter().map(command_size).sum());
+	put_commands(&mut buf, commands, progress);
+	progress(100);
+	buf
+}
+
+fn put_commands(buf: &mut BytesMut, commands: Vec<Command>, progress: &mut dyn FnMut(u8)) {
+	let count = commands.len();
+	let mut percent = 0;
+
+	for (i, command) in commands.into_iter().enumerate() {
+		if (i * 100 / count) as u8 != percent {
+			percent = (i * 100 / count) as u8;
+			progress(percent);
+		}
+
+		buf.put_u8(opcode::opcode(&command));
 
 		match command {
-			Command::Push(value) => buf.extend_from_slice(&generate_value(value)),
+			Command::Push(value) => put_value(buf, value),
 			Command::ILoad(reg, value) => {
 				buf.put_u8(reg);
-				buf.extend_from_slice(&generate_value(value));
+				put_value(buf, value);
 			},
 			_ => {}
 		}
 	}
+}
 
-	buf
-}
\ No newline at end of file
+// the first console I/O command in the program, which only run mode's host provides
+pub fn find_console_io(commands: &[Command]) -> Option<&Command> {
+	fn in_value(value: &Value) -> Option<&Command> {
+		match value {
+			Value::Function(commands) => find_console_io(commands),
+			Value::Array(values) => values.iter().find_map(in_value),
+			_ => None
+		}
+	}
+
+	commands.iter().find_map(|command| match command {
+		Command::Print | Command::ReadLine => Some(command),
+		Command::Push(value) | Command::ILoad(_, value) => in_value(value),
+		_ => None
+	})
+}
+
+// size of the encoding generate_value would produce, without producing it
+pub fn value_size(value: &Value) -> usize {
+	1 + match value {
+		Value::Nil => 0,
+		Value::Number(_) => 8,
+		Value::String(val) => 8 + val.len(),
+		Value::Boolean(_) => 1,
+		Value::Function(commands) => 8 + commands.iter().map(command_size).sum::<usize>(),
+		Value::Array(values) => 8 + values.iter().map(value_size).sum::<usize>()
+	}
+}
+
+pub fn command_size(command: &Command) -> usize {
+	1 + match command {
+		Command::Push(value) => value_size(value),
+		Command::ILoad(_, value) => 1 + value_size(value),
+		_ => 0
+	}
+}
+
+fn collect_values<'a>(commands: &'a [Command], out: &mut Vec<&'a Value>) {
+	fn visit<'a>(value: &'a Value, out: &mut Vec<&'a Value>) {
+		match value {
+			Value::Function(commands) => {
+				out.push(value);
+				collect_values(commands, out);
+			},
+			Value::Array(values) => {
+				out.push(value);
+				values.iter().for_each(|value| visit(value, out));
+			},
+			Value::String(_) => out.push(value),
+			_ => {}
+		}
+	}
+
+	for command in commands {
+		if let Command::Push(value) | Command::ILoad(_, value) = command {
+			visit(value, out);
+		}
+	}
+}
+
+// the function literals and constants with the largest encodings, biggest first
+pub fn largest_values(commands: &[Command], count: usize) -> Vec<(usize, &Value)> {
+	let mut values = vec![];
+	collect_values(commands, &mut values);
+
+	let mut sized: Vec<_> = values.into_iter().map(|value| (value_size(value), value)).collect();
+	sized.sort_by_key(|(size, _)| core::cmp::Reverse(*size));
+	sized.truncate(count);
+	sized
+}
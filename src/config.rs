@@ -0,0 +1,80 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use thiserror::Error;
+
+use crate::diagnostic::{Level, Lints};
+use crate::{formatter, lint};
+
+// settings from evm-asm.toml, in the current directory or the nearest parent that has one. a table
+// per subcommand, with the names of its command-line options, which override it:
+//
+//   [fmt]
+//   indent = 4
+//   max-width = 80
+//   align-comments = true
+//   functions = "compact"
+//
+//   [lint]
+//   magic-number = "warn" # any warning's name or code, or `warnings`: allow, warn or deny
+//   max-nesting = 4
+
+pub const FILENAME: &str = "evm-asm.toml";
+
+#[derive(Clone, Debug, Default)]
+pub struct Config {
+	pub fmt: formatter::Options,
+	pub lints: Lints,
+	pub lint: lint::Options,
+}
+
+#[derive(Debug, Error)]
+pub enum ConfigError {
+	#[error("{}: {source}", path.display())]
+	Io {path: PathBuf, source: io::Error},
+	#[error("{}: {message}", path.display())]
+	Invalid {path: PathBuf, message: String},
+}
+
+// the config file that applies in `dir`
+pub fn find(dir: &Path) -> Option<PathBuf> {
+	dir.ancestors().map(|dir| dir.join(FILENAME)).find(|path| path.is_file())
+}
+
+pub fn load(path: &Path) -> Result<Config, ConfigError> {
+	let text = fs::read_to_string(path).map_err(|source| ConfigError::Io {path: path.to_owned(), source})?;
+	let invalid = |message: String| ConfigError::Invalid {path: path.to_owned(), message};
+	let table: toml::Table = text.parse().map_err(|e: toml::de::Error| invalid(e.message().to_owned()))?;
+	let mut config = Config::default();
+
+	for (name, section) in &table {
+		match (name.as_str(), section) {
+			("fmt", toml::Value::Table(settings)) => {
+				for (key, value) in settings {
+					// strings without their quotes; numbers and booleans as written
+					let value = match value {
+						toml::Value::String(text) => text.clone(),
+						value => value.to_string()
+					};
+
+					config.fmt.set(key, &value).map_err(invalid)?;
+				}
+			},
+			("lint", toml::Value::Table(settings)) => {
+				for (key, value) in settings {
+					match value {
+						toml::Value::String(level) => {
+							let level = Level::from_name(level).ok_or_else(|| invalid(format!("Invalid level for {}, expected allow, warn or deny: {}", key, level)))?;
+							config.lints.set(key, level).map_err(invalid)?;
+						},
+						value => config.lint.set(key, &value.to_string()).map_err(invalid)?
+					}
+				}
+			},
+			_ => return Err(invalid(format!("Unknown section: {}", name)))
+		}
+	}
+
+	Ok(config)
+}
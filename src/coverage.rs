@@ -0,0 +1,95 @@
+use serde_json::{json, Value as Json};
+
+use crate::tokenizer::Loc;
+
+// how many times each command ran, indexed by command id
+pub struct Coverage {
+	pub hits: Vec<u64>,
+}
+
+impl Coverage {
+	pub fn new(commands: usize) -> Coverage {
+		Coverage {hits: vec![0; commands]}
+	}
+
+	pub fn record(&mut self, id: usize) {
+		if let Some(hits) = self.hits.get_mut(id) {
+			*hits += 1;
+		}
+	}
+
+	pub fn covered(&self) -> usize {
+		self.hits.iter().filter(|hits| **hits > 0).count()
+	}
+
+	fn summary(&self) -> String {
+		let percent = if self.hits.is_empty() {100.0} else {self.covered() as f64 * 100.0 / self.hits.len() as f64};
+		format!("covered {} of {} commands ({:.1}%)", self.covered(), self.hits.len(), percent)
+	}
+
+	// (line, commands on it, how many of those ran, most runs of any of them) for every line with commands
+	fn lines(&self, locs: &[Loc]) -> Vec<(u64, usize, usize, u64)> {
+		let mut lines: Vec<(u64, usize, usize, u64)> = vec![];
+
+		for (id, hits) in self.hits.iter().enumerate() {
+			let Some(loc) = locs.get(id) else {
+				continue;
+			};
+
+			let index = match lines.binary_search_by_key(&loc.line, |line| line.0) {
+				Ok(index) => index,
+				Err(index) => {
+					lines.insert(index, (loc.line, 0, 0, 0));
+					index
+				}
+			};
+
+			let line = &mut lines[index];
+			line.1 += 1;
+			line.2 += (*hits > 0) as usize;
+			line.3 = line.3.max(*hits);
+		}
+
+		lines
+	}
+
+	// the source with each line prefixed by its run count: `-` for lines without commands, `#####`
+	// for lines that never ran, and a `*` after the count when only some of the line's commands ran;
+	// without source, the commands that never ran are listed instead
+	pub fn annotate(&self, locs: &[Loc], source: Option<&str>) -> String {
+		let mut out = String::new();
+
+		match source {
+			Some(source) => {
+				let lines = self.lines(locs);
+
+				for (n, text) in source.lines().enumerate() {
+					let count = match lines.iter().find(|line| line.0 == n as u64 + 1) {
+						None => "-".to_owned(),
+						Some((_, _, 0, _)) => "#####".to_owned(),
+						Some((_, commands, covered, hits)) => format!("{}{}", hits, if covered < commands {"*"} else {""})
+					};
+
+					out.push_str(&format!("{:>9}: {:>5}: {}\n", count, n + 1, text));
+				}
+			},
+			None => {
+				for (id, _) in self.hits.iter().enumerate().filter(|(_, hits)| **hits == 0) {
+					out.push_str(&format!("command {} never ran\n", id));
+				}
+			}
+		}
+
+		out.push_str(&self.summary());
+		out.push('\n');
+		out
+	}
+
+	pub fn to_json(&self, locs: &[Loc]) -> Json {
+		let lines: Vec<_> = self.lines(locs).iter().map(|(line, commands, covered, hits)| {
+			json!({"line": line, "commands": commands, "covered": covered, "hits": hits})
+		}).collect();
+
+		json!({"commands": self.hits.len(), "covered": self.covered(), "hits": self.hits, "lines": lines})
+	}
+}
@@ -0,0 +1,317 @@
+use serde_json::{json, Value as Json};
+use std::cell::RefCell;
+use std::fs;
+use std::io::{self, BufRead, Write};
+use std::rc::Rc;
+
+use crate::debugger::{Breakpoint, Debugger, Resume, Stop};
+use crate::interpreter::{Host, StubHost, Value};
+use crate::parser;
+use crate::Error;
+use crate::tokenizer;
+
+const THREAD_ID: u64 = 1;
+const STACK_REFERENCE: u64 = 1;
+const REGISTERS_REFERENCE: u64 = 2;
+
+// stdin and stdout carry the protocol, so print is sent to the editor as output events and readln
+// always reaches the end of input
+struct DapHost {
+	output: Rc<RefCell<Vec<String>>>,
+}
+
+impl Host for DapHost {
+	fn query(&mut self, selector: &str) -> Result<Value, String> {
+		StubHost.query(selector)
+	}
+
+	fn info(&mut self, item: &Value, key: &str) -> Result<Value, String> {
+		StubHost.info(item, key)
+	}
+
+	fn print(&mut self, text: &str) -> Result<(), String> {
+		self.output.borrow_mut().push(format!("{}\n", text));
+		Ok(())
+	}
+
+	fn read_line(&mut self) -> Result<Option<String>, String> {
+		Ok(None)
+	}
+}
+
+struct Session {
+	seq: u64,
+	path: String,
+	source: Vec<String>,
+	debugger: Option<Debugger>,
+	output: Rc<RefCell<Vec<String>>>,
+	stop_on_entry: bool,
+	failed: Option<usize>, // command that stopped the program with a runtime error
+}
+
+// reads one Content-Length framed message, None at the end of input
+pub(crate) fn read_message(input: &mut impl BufRead) -> io::Result<Option<Json>> {
+	let mut len = None;
+
+	loop {
+		let mut line = String::new();
+
+		if input.read_line(&mut line)? == 0 {
+			return Ok(None);
+		}
+
+		let line = line.trim_end();
+
+		if line.is_empty() {
+			break;
+		}
+
+		if let Some(val) = line.strip_prefix("Content-Length:") {
+			len = val.trim().parse::<usize>().ok();
+		}
+	}
+
+	let Some(len) = len else {
+		return Err(io::Error::new(io::ErrorKind::InvalidData, "message without Content-Length"));
+	};
+
+	let mut body = vec![0; len];
+	input.read_exact(&mut body)?;
+	serde_json::from_slice(&body).map(Some).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
+
+impl Session {
+	fn send(&mut self, mut message: Json) -> io::Result<()> {
+		self.seq += 1;
+		message["seq"] = json!(self.seq);
+
+		let body = message.to_string();
+		let mut out = io::stdout().lock();
+		write!(out, "Content-Length: {}\r\n\r\n{}", body.len(), body)?;
+		out.flush()
+	}
+
+	fn event(&mut self, event: &str, body: Json) -> io::Result<()> {
+		self.send(json!({"type": "event", "event": event, "body": body}))
+	}
+
+	fn respond(&mut self, request: &Json, result: Result<Json, String>) -> io::Result<()> {
+		let mut response = json!({"type": "response", "request_seq": request["seq"], "command": request["command"], "success": result.is_ok()});
+
+		match result {
+			Ok(body) => response["body"] = body,
+			Err(message) => response["message"] = json!(message)
+		}
+
+		self.send(response)
+	}
+
+	fn flush_output(&mut self) -> io::Result<()> {
+		let lines: Vec<String> = self.output.borrow_mut().drain(..).collect();
+
+		for text in lines {
+			self.event("output", json!({"category": "stdout", "output": text}))?;
+		}
+
+		Ok(())
+	}
+
+	fn launch(&mut self, args: &Json) -> Result<Json, String> {
+		let path = args["program"].as_str().ok_or("launch needs a program")?.to_owned();
+		let source = fs::read_to_string(&path).map_err(|e| format!("Cannot read {}: {}", path, e))?;
+		let tokens = tokenizer::tokenize(&source, &path).map_err(|e| Error::from(e).to_string())?;
+		let (commands, locs) = parser::parse_with_locs(tokens).map_err(|e| Error::from(e).to_string())?;
+
+		let mut debugger = Debugger::new(&commands, locs);
+		debugger.vm.host = Box::new(DapHost {output: self.output.clone()});
+
+		self.debugger = Some(debugger);
+		self.path = path;
+		self.source = source.lines().map(str::to_owned).collect();
+		self.stop_on_entry = args["stopOnEntry"].as_bool().unwrap_or(false);
+		Ok(json!({}))
+	}
+
+	fn debugger(&mut self) -> Result<&mut Debugger, String> {
+		self.debugger.as_mut().ok_or("No program launched".to_owned())
+	}
+
+	fn set_breakpoints(&mut self, args: &Json) -> Result<Json, String> {
+		let lines: Vec<u64> = args["breakpoints"].as_array().into_iter().flatten().filter_map(|bp| bp["line"].as_u64()).collect();
+		let debugger = self.debugger()?;
+
+		debugger.breakpoints.retain(|bp| !matches!(bp, Breakpoint::Line(_)));
+		debugger.breakpoints.extend(lines.iter().map(|line| Breakpoint::Line(*line)));
+
+		// a line without commands never stops the program
+		let verified: Vec<_> = lines.iter().map(|line| {
+			json!({"verified": debugger.locs.iter().any(|loc| loc.line == *line), "line": line})
+		}).collect();
+
+		Ok(json!({"breakpoints": verified}))
+	}
+
+	fn frame(&self, index: usize, id: usize) -> Json {
+		let debugger = self.debugger.as_ref().unwrap();
+		let Some(loc) = debugger.loc(id) else {
+			return json!({"id": index, "name": format!("command {}", id), "line": 0, "column": 0});
+		};
+
+		// frames are named after the source line they stopped on
+		let name = self.source.get(loc.line as usize - 1).map(|line| line.trim().to_owned()).unwrap_or(format!("command {}", id));
+		json!({"id": index, "name": name, "source": {"path": self.path}, "line": loc.line, "column": loc.col.max(1)})
+	}
+
+	fn stack_trace(&mut self) -> Result<Json, String> {
+		// after a runtime error the program has been abandoned, so only the failing command is left
+		let ids = match self.failed {
+			Some(id) => vec![id],
+			None => {
+				let debugger = self.debugger()?;
+				let current = debugger.vm.peek().map_err(|e| e.to_string())?.map(|instr| instr.id);
+				current.into_iter().chain(debugger.vm.callers()).collect()
+			}
+		};
+
+		let frames: Vec<_> = ids.iter().enumerate().map(|(index, id)| self.frame(index, *id)).collect();
+		Ok(json!({"stackFrames": frames, "totalFrames": frames.len()}))
+	}
+
+	fn variables(&mut self, args: &Json) -> Result<Json, String> {
+		let debugger = self.debugger()?;
+
+		let variables: Vec<_> = match args["variablesReference"].as_u64() {
+			Some(STACK_REFERENCE) => debugger.vm.stack.iter().enumerate().rev()
+				.map(|(i, value)| json!({"name": i.to_string(), "value": value.to_string(), "type": value.type_name(), "variablesReference": 0}))
+				.collect(),
+			Some(REGISTERS_REFERENCE) => debugger.vm.registers.iter().enumerate()
+				.map(|(i, value)| json!({"name": format!("r{}", i), "value": value.to_string(), "type": value.type_name(), "variablesReference": 0}))
+				.collect(),
+			_ => vec![]
+		};
+
+		Ok(json!({"variables": variables}))
+	}
+
+	// runs until the next stop and reports it with a stopped, or terminated, event
+	fn resume(&mut self, mode: Resume) -> io::Result<()> {
+		if self.failed.is_some() {
+			return self.event("terminated", json!({}));
+		}
+
+		let Some(debugger) = &mut self.debugger else {
+			return Ok(());
+		};
+
+		let result = debugger.resume(mode).map_err(|e| (e.command, e.to_string(), e.report(&debugger.locs, None)));
+		self.flush_output()?;
+
+		match result {
+			Ok(Stop::Finished) => self.event("terminated", json!({})),
+			Ok(Stop::Breakpoint) => self.event("stopped", json!({"reason": "breakpoint", "threadId": THREAD_ID})),
+			Ok(Stop::Step) => self.event("stopped", json!({"reason": "step", "threadId": THREAD_ID})),
+			Err((command, message, report)) => {
+				self.failed = Some(command);
+				self.event("output", json!({"category": "stderr", "output": format!("{}\n", report)}))?;
+				self.event("stopped", json!({"reason": "exception", "description": message, "threadId": THREAD_ID}))
+			}
+		}
+	}
+
+	// runs backwards one command, or to a breakpoint; stepping back from a runtime error returns to
+	// the command before it
+	fn reverse(&mut self, to_breakpoint: bool) -> io::Result<()> {
+		let Some(debugger) = &mut self.debugger else {
+			return Ok(());
+		};
+
+		let stop = if to_breakpoint {debugger.reverse_continue()} else {debugger.step_back(1); Stop::Step};
+		self.failed = None;
+
+		let reason = if stop == Stop::Breakpoint {"breakpoint"} else {"step"};
+		self.event("stopped", json!({"reason": reason, "threadId": THREAD_ID}))
+	}
+
+	// starts the program once the editor has sent its breakpoints
+	fn start(&mut self) -> io::Result<()> {
+		let Some(debugger) = &mut self.debugger else {
+			return Ok(());
+		};
+
+		let first = debugger.vm.peek().ok().flatten().map(|instr| instr.id);
+
+		match first {
+			None => self.event("terminated", json!({})),
+			Some(_) if self.stop_on_entry => self.event("stopped", json!({"reason": "entry", "threadId": THREAD_ID})),
+			Some(id) if debugger.at_breakpoint(id) => self.event("stopped", json!({"reason": "breakpoint", "threadId": THREAD_ID})),
+			Some(_) => self.resume(Resume::Continue)
+		}
+	}
+}
+
+// serves the Debug Adapter Protocol on stdin and stdout until the editor disconnects
+pub fn serve() -> io::Result<()> {
+	let stdin = io::stdin();
+	let mut input = stdin.lock();
+	let mut session = Session {seq: 0, path: String::new(), source: vec![], debugger: None, output: Rc::new(RefCell::new(vec![])), stop_on_entry: false, failed: None};
+
+	while let Some(request) = read_message(&mut input)? {
+		let args = &request["arguments"];
+		let resume = match request["command"].as_str().unwrap_or_default() {
+			"continue" => Some(Resume::Continue),
+			"next" => Some(Resume::StepOver),
+			"stepIn" => Some(Resume::Step),
+			"stepOut" => Some(Resume::StepOut),
+			_ => None
+		};
+
+		if let Some(mode) = resume {
+			session.respond(&request, Ok(json!({"allThreadsContinued": true})))?;
+			session.resume(mode)?;
+			continue;
+		}
+
+		match request["command"].as_str().unwrap_or_default() {
+			"initialize" => {
+				session.respond(&request, Ok(json!({"supportsConfigurationDoneRequest": true, "supportsStepBack": true})))?;
+				session.event("initialized", json!({}))?;
+			},
+			"launch" => {
+				let result = session.launch(args);
+				session.respond(&request, result)?;
+			},
+			"setBreakpoints" => {
+				let result = session.set_breakpoints(args);
+				session.respond(&request, result)?;
+			},
+			"stepBack" | "reverseContinue" => {
+				session.respond(&request, Ok(json!({})))?;
+				session.reverse(request["command"] == "reverseContinue")?;
+			},
+			"configurationDone" => {
+				session.respond(&request, Ok(json!({})))?;
+				session.start()?;
+			},
+			"threads" => session.respond(&request, Ok(json!({"threads": [{"id": THREAD_ID, "name": "main"}]})))?,
+			"stackTrace" => {
+				let result = session.stack_trace();
+				session.respond(&request, result)?;
+			},
+			"scopes" => session.respond(&request, Ok(json!({"scopes": [
+				{"name": "Stack", "variablesReference": STACK_REFERENCE, "expensive": false},
+				{"name": "Registers", "variablesReference": REGISTERS_REFERENCE, "expensive": false}
+			]})))?,
+			"variables" => {
+				let result = session.variables(args);
+				session.respond(&request, result)?;
+			},
+			"disconnect" | "terminate" => {
+				session.respond(&request, Ok(json!({})))?;
+				return Ok(());
+			},
+			command => session.respond(&request, Err(format!("Unsupported request: {}", command)))?
+		}
+	}
+
+	Ok(())
+}
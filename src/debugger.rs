@@ -0,0 +1,278 @@
+use std::fs;
+use std::io::{self, BufRead, Write};
+
+use crate::interpreter::{self, ConsoleHost, RuntimeError, Vm};
+use crate::parser::Command;
+use crate::tokenizer::Loc;
+
+// commands the debugger can step back over
+const HISTORY: usize = 100_000;
+
+const HELP: &str = "\
+  s, step          executes one command
+  n, next          executes one command, running any function it calls to completion
+  o, out           runs until the current function returns
+  c, continue      runs until a breakpoint or the end of the program
+  r, back [N]      steps back N commands (default 1); printed output is not taken back
+  rc               runs backwards until a breakpoint or the start of the history
+  b LINE           sets a breakpoint on a source line
+  b #INDEX         sets a breakpoint on a command index
+  d N              deletes breakpoint N
+  l, list          lists breakpoints
+  stack            prints the stack
+  regs             prints the registers
+  w, where         prints the current position
+  save FILE        writes a snapshot that run --resume continues from
+  q, quit          exits";
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Breakpoint {
+	Line(u64),
+	Command(usize)
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Resume {
+	Step,
+	StepOver,
+	StepOut,
+	Continue
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Stop {
+	Step,
+	Breakpoint,
+	Finished
+}
+
+pub struct Debugger {
+	pub vm: Vm,
+	pub locs: Vec<Loc>, // location of each command by index, empty when running bytecode
+	pub breakpoints: Vec<Breakpoint>,
+}
+
+impl Debugger {
+	pub fn new(commands: &[Command], locs: Vec<Loc>) -> Debugger {
+		let mut vm = Vm::new(ConsoleHost);
+		vm.history_limit = HISTORY;
+		vm.load(interpreter::lower(commands));
+
+		Debugger {vm, locs, breakpoints: vec![]}
+	}
+
+	pub fn loc(&self, id: usize) -> Option<&Loc> {
+		self.locs.get(id)
+	}
+
+	pub fn at_breakpoint(&self, id: usize) -> bool {
+		self.breakpoints.iter().any(|breakpoint| match breakpoint {
+			Breakpoint::Line(line) => self.loc(id).is_some_and(|loc| loc.line == *line),
+			Breakpoint::Command(index) => *index == id
+		})
+	}
+
+	// executes at least one command, then keeps going until `mode` is satisfied, a breakpoint is
+	// reached or the program ends
+	pub fn resume(&mut self, mode: Resume) -> Result<Stop, RuntimeError> {
+		if self.vm.peek()?.is_none() {
+			return Ok(Stop::Finished);
+		}
+
+		let depth = self.vm.depth();
+		self.vm.step()?;
+
+		loop {
+			let Some(id) = self.vm.peek()?.map(|instr| instr.id) else {
+				return Ok(Stop::Finished);
+			};
+
+			if self.at_breakpoint(id) {
+				return Ok(Stop::Breakpoint);
+			}
+
+			let done = match mode {
+				Resume::Step => true,
+				Resume::StepOver => self.vm.depth() <= depth,
+				Resume::StepOut => self.vm.depth() < depth,
+				Resume::Continue => false
+			};
+
+			if done {
+				return Ok(Stop::Step);
+			}
+
+			self.vm.step()?;
+		}
+	}
+
+	// undoes up to `count` commands; returns false if the history ran out first
+	pub fn step_back(&mut self, count: usize) -> bool {
+		(0..count).all(|_| self.vm.step_back())
+	}
+
+	// steps back until a breakpoint or the start of the history
+	pub fn reverse_continue(&mut self) -> Stop {
+		while self.vm.step_back() {
+			let id = self.vm.peek().ok().flatten().map(|instr| instr.id);
+
+			if id.is_some_and(|id| self.at_breakpoint(id)) {
+				return Stop::Breakpoint;
+			}
+		}
+
+		Stop::Step
+	}
+
+	fn print_position(&mut self, source: &[&str]) {
+		let Ok(Some(instr)) = self.vm.peek() else {
+			println!("program finished");
+			return;
+		};
+
+		let id = instr.id;
+		let mut text = instr.command.to_string();
+
+		if text.chars().count() > 60 {
+			text = format!("{}...", text.chars().take(60).collect::<String>());
+		}
+
+		match self.loc(id) {
+			Some(loc) => {
+				println!("command {} at {}: {}", id, loc, text);
+
+				if let Some(line) = source.get(loc.line as usize - 1) {
+					println!("{:>5} | {}", loc.line, line);
+				}
+			},
+			None => println!("command {}: {}", id, text)
+		}
+	}
+
+	// interactive debugging session on stdin; `source` is the program text, if there is one
+	pub fn debug(&mut self, source: Option<&str>) -> io::Result<()> {
+		let lines: Vec<&str> = source.map(|s| s.lines().collect()).unwrap_or_default();
+		let stdin = io::stdin();
+		let mut input = stdin.lock().lines();
+
+		println!("Type help for a list of commands.");
+		self.print_position(&lines);
+
+		loop {
+			print!("(dbg) ");
+			io::stdout().flush()?;
+
+			let Some(line) = input.next() else {
+				return Ok(());
+			};
+
+			let line = line?;
+			let mut words = line.split_whitespace();
+
+			let resume = match (words.next(), words.next()) {
+				(Some("s" | "step"), _) => Resume::Step,
+				(Some("n" | "next"), _) => Resume::StepOver,
+				(Some("o" | "out"), _) => Resume::StepOut,
+				(Some("c" | "continue"), _) => Resume::Continue,
+				(Some("r" | "back"), count) => {
+					match count.map(str::parse::<usize>).unwrap_or(Ok(1)) {
+						Ok(count) => {
+							if !self.step_back(count) {
+								println!("reached the start of the history");
+							}
+							self.print_position(&lines);
+						},
+						Err(_) => println!("expected a number of commands: {}", count.unwrap())
+					}
+					continue;
+				},
+				(Some("rc"), _) => {
+					match self.reverse_continue() {
+						Stop::Breakpoint => print!("breakpoint: "),
+						_ => print!("start of history: ")
+					}
+					self.print_position(&lines);
+					continue;
+				},
+				(Some("b" | "break"), Some(arg)) => {
+					let breakpoint = match arg.strip_prefix('#') {
+						Some(index) => index.parse().ok().map(Breakpoint::Command),
+						None => arg.parse().ok().map(Breakpoint::Line)
+					};
+
+					match breakpoint {
+						Some(breakpoint) => {
+							self.breakpoints.push(breakpoint);
+							println!("breakpoint {} set", self.breakpoints.len() - 1);
+						},
+						None => println!("expected a line number or #command index: {}", arg)
+					}
+					continue;
+				},
+				(Some("d" | "delete"), Some(arg)) => {
+					match arg.parse::<usize>() {
+						Ok(n) if n < self.breakpoints.len() => {
+							self.breakpoints.remove(n);
+						},
+						_ => println!("no breakpoint {}", arg)
+					}
+					continue;
+				},
+				(Some("l" | "list"), _) => {
+					for (n, breakpoint) in self.breakpoints.iter().enumerate() {
+						match breakpoint {
+							Breakpoint::Line(line) => println!("{}: line {}", n, line),
+							Breakpoint::Command(index) => println!("{}: command {}", n, index)
+						}
+					}
+					continue;
+				},
+				(Some("stack"), _) => {
+					for (i, value) in self.vm.stack.iter().enumerate().rev() {
+						println!("{:>4}: {}", i, value);
+					}
+					continue;
+				},
+				(Some("regs"), _) => {
+					for (i, value) in self.vm.registers.iter().enumerate() {
+						println!("r{:<3} {}", i, value);
+					}
+					continue;
+				},
+				(Some("save"), Some(path)) => {
+					match fs::write(path, format!("{:#}\n", self.vm.snapshot())) {
+						Ok(()) => println!("snapshot written to {}", path),
+						Err(e) => println!("cannot write snapshot: {}", e)
+					}
+					continue;
+				},
+				(Some("w" | "where"), _) => {
+					self.print_position(&lines);
+					continue;
+				},
+				(Some("q" | "quit"), _) => return Ok(()),
+				(Some("h" | "help"), _) => {
+					println!("{}", HELP);
+					continue;
+				},
+				(None, _) => continue,
+				(Some(word), _) => {
+					println!("unknown command {}, try help", word);
+					continue;
+				}
+			};
+
+			match self.resume(resume) {
+				Ok(Stop::Breakpoint) => {
+					print!("breakpoint: ");
+					self.print_position(&lines);
+				},
+				Ok(_) => self.print_position(&lines),
+				Err(e) => {
+					println!("runtime error: {}", e.report(&self.locs, source));
+					self.vm.abort();
+				}
+			}
+		}
+	}
+}
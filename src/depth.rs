@@ -0,0 +1,195 @@
+use std::collections::BTreeMap;
+use std::ptr;
+
+use thiserror::Error;
+
+use crate::cfg::register;
+use crate::parser::{get_command_name, stack_effect, Command, Value};
+use crate::tokenizer::Loc;
+
+// static stack depths, for `evm-asm --check-stack`: the fewest and the most values there can be on
+// the stack at each command, over every path, from an empty stack. a call follows the function
+// literal last iloaded into its register, when that iload is sure to have run; the bodies of if
+// and the loops may run or not, and loops any number of times. a command that takes
+// more values than the most there can be underflows on every path that reaches it, which is an
+// error where the command is sure to run: at the top level and in functions called from there
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Depth {
+	pub min: usize,
+	pub max: Option<usize>, // None when unbounded, after a call that cannot be followed or a loop that grows the stack
+}
+
+#[derive(Clone, Debug)]
+pub struct Analysis {
+	pub end: Depth, // after the last command
+	pub peak: Option<usize>, // the most values there can be at any point
+}
+
+#[derive(Debug, Error)]
+pub enum StackError {
+	#[error("Stack underflow on {loc}: {command} takes {takes} values, but there are at most {depth}")]
+	Underflow {command: &'static str, takes: usize, depth: usize, loc: Loc, called: Vec<Loc>},
+	#[error("Stack overflow on {loc}: there are at least {depth} values, more than the maximum of {max}")]
+	Overflow {command: &'static str, depth: usize, max: usize, loc: Loc, called: Vec<Loc>},
+}
+
+impl StackError {
+	pub fn loc(&self) -> &Loc {
+		match self {
+			StackError::Underflow {loc, ..} | StackError::Overflow {loc, ..} => loc
+		}
+	}
+
+	// the calls that led to the command, innermost first
+	pub fn called(&self) -> &[Loc] {
+		match self {
+			StackError::Underflow {called, ..} | StackError::Overflow {called, ..} => called
+		}
+	}
+
+	pub fn command(&self) -> &'static str {
+		match self {
+			StackError::Underflow {command, ..} | StackError::Overflow {command, ..} => command
+		}
+	}
+}
+
+impl Depth {
+	const UNKNOWN: Depth = Depth {min: 0, max: None};
+
+	// either of two paths
+	fn union(self, other: Depth) -> Depth {
+		Depth {min: self.min.min(other.min), max: self.max.zip(other.max).map(|(a, b)| a.max(b))}
+	}
+
+	// on the paths with at least `pops` values
+	fn apply(self, pops: usize, pushes: usize) -> Depth {
+		Depth {min: self.min.max(pops) - pops + pushes, max: self.max.map(|max| max.saturating_sub(pops) + pushes)}
+	}
+
+	// before a loop body, given the depth after it runs once: a bound the body moves has none
+	fn widen(self, after: Depth) -> Depth {
+		Depth {
+			min: if after.min < self.min { 0 } else { self.min },
+			max: self.max.zip(after.max).filter(|(before, after)| after <= before).map(|(before, _)| before)
+		}
+	}
+}
+
+struct Analyzer<'a> {
+	locs: &'a [Loc],
+	max_stack: Option<usize>,
+	ids: BTreeMap<*const Command, usize>, // index in `locs` of each command
+	registers: BTreeMap<u8, &'a [Command]>, // the function in each register, where it is known
+	calling: Vec<(&'a [Command], &'a Command)>, // functions being followed, so recursion is not, and the commands that called them
+	peak: Option<usize>,
+}
+
+// the index in `locs` of each command: pre-order, as `locs` is
+pub(crate) fn number(commands: &[Command], ids: &mut BTreeMap<*const Command, usize>) {
+	fn in_value(value: &Value, ids: &mut BTreeMap<*const Command, usize>) {
+		match value {
+			Value::Function(commands) => number(commands, ids),
+			Value::Array(values) => values.iter().for_each(|value| in_value(value, ids)),
+			_ => {}
+		}
+	}
+
+	for command in commands {
+		let id = ids.len();
+		ids.insert(command, id);
+
+		if let Command::Push(value) | Command::ILoad(_, value) = command {
+			in_value(value, ids);
+		}
+	}
+}
+
+impl<'a> Analyzer<'a> {
+	fn loc(&self, command: &Command) -> Loc {
+		self.locs.get(self.ids[&(command as *const Command)]).cloned().unwrap_or_else(|| Loc {line: 0, col: 0, filename: String::new()})
+	}
+
+	fn called(&self) -> Vec<Loc> {
+		self.calling.iter().rev().map(|(_, command)| self.loc(command)).collect()
+	}
+
+	fn run(&mut self, commands: &'a [Command], mut depth: Depth, certain: bool) -> Result<Depth, StackError> {
+		for (i, command) in commands.iter().enumerate() {
+			// the values a command pops itself, before any function it runs
+			let pops = match command {
+				Command::Call => 1,
+				Command::If | Command::Each | Command::Map | Command::Filter => 2,
+				command => stack_effect(command).map_or(0, |(pops, _)| pops)
+			};
+
+			if let Some(max) = depth.max.filter(|max| *max < pops && certain) {
+				return Err(StackError::Underflow {command: get_command_name(command), takes: pops, depth: max, loc: self.loc(command), called: self.called()});
+			}
+
+			depth = depth.apply(pops, 0);
+
+			depth = match command {
+				Command::Call => self.call(command, register(commands, i), depth, certain)?,
+				Command::If => depth.union(self.call(command, register(commands, i), depth, false)?),
+				Command::Each | Command::Map | Command::Filter | Command::Reduce => {
+					// reduce pushes its initial value back, and map and filter pop each result
+					let before = if matches!(command, Command::Reduce) { depth.apply(0, 1) } else { depth };
+					let after = self.call(command, register(commands, i), before.apply(0, 1), false)?;
+					let after = if matches!(command, Command::Map | Command::Filter) { after.apply(1, 0) } else { after };
+					let looped = before.widen(after);
+
+					if matches!(command, Command::Map | Command::Filter) { looped.apply(0, 1) } else { looped }
+				},
+				Command::ILoad(reg, value) => {
+					// an iload that may not have run leaves either function in the register
+					match value {
+						Value::Function(body) if certain => self.registers.insert(*reg, body),
+						_ => self.registers.remove(reg)
+					};
+
+					depth
+				},
+				command => depth.apply(0, stack_effect(command).map_or(0, |(_, pushes)| pushes))
+			};
+
+			self.peak = self.peak.zip(depth.max).map(|(peak, max)| peak.max(max));
+
+			if let Some(max) = self.max_stack.filter(|max| depth.min > *max && certain) {
+				return Err(StackError::Overflow {command: get_command_name(command), depth: depth.min, max, loc: self.loc(command), called: self.called()});
+			}
+		}
+
+		Ok(depth)
+	}
+
+	// the depth after the function in `reg` returns
+	fn call(&mut self, command: &'a Command, reg: Option<u8>, depth: Depth, certain: bool) -> Result<Depth, StackError> {
+		match reg.and_then(|reg| self.registers.get(&reg).copied()) {
+			Some(body) if !self.calling.iter().any(|(calling, _)| ptr::eq(*calling, body)) => {
+				self.calling.push((body, command));
+				let depth = self.run(body, depth, certain);
+				self.calling.pop();
+				depth
+			},
+			_ => {
+				// a function that is not followed may iload anything
+				self.registers.clear();
+				Ok(Depth::UNKNOWN)
+			}
+		}
+	}
+}
+
+// `locs` are the locations of the commands, as parse_with_locs gives them; a program without them,
+// as one read from bytecode, has its errors at line 0
+pub fn analyze(commands: &[Command], locs: &[Loc], max_stack: Option<usize>) -> Result<Analysis, StackError> {
+	let mut ids = BTreeMap::new();
+	number(commands, &mut ids);
+
+	let mut analyzer = Analyzer {locs, max_stack, ids, registers: BTreeMap::new(), calling: vec![], peak: Some(0)};
+	let end = analyzer.run(commands, Depth {min: 0, max: Some(0)}, true)?;
+
+	Ok(Analysis {end, peak: analyzer.peak})
+}
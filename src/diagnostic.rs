@@ -0,0 +1,827 @@
+use serde_json::{json, Value as Json};
+
+use crate::ast_json::{AstJsonError, SCHEMA_VERSION};
+use crate::codegen::{CodegenError, MAX_NESTING, REGISTER_COUNT};
+use crate::depth::StackError;
+use crate::disasm::DisasmError;
+use crate::interpreter::RuntimeError;
+use crate::messages::{Locale, Message};
+use crate::parser::ParseError;
+use crate::tokenizer::{Loc, TokenType, TokenizeError};
+use crate::typecheck::TypeError;
+use crate::Error;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Severity {
+	Error,
+	Warning,
+	Note,
+}
+
+impl Severity {
+	pub fn name(self) -> &'static str {
+		match self {
+			Severity::Error => "error",
+			Severity::Warning => "warning",
+			Severity::Note => "note"
+		}
+	}
+}
+
+// `len` characters of source starting at `loc`
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Span {
+	pub loc: Loc,
+	pub len: u64,
+}
+
+impl Span {
+	pub fn new(loc: &Loc, len: u64) -> Span {
+		Span {loc: loc.clone(), len: len.max(1)}
+	}
+}
+
+#[derive(Clone, Debug)]
+pub struct Label {
+	pub span: Span,
+	pub message: Message,
+}
+
+// a problem found by any phase; `code` identifies the kind of problem and never changes wording,
+// and the messages are written out in their locale when the diagnostic is rendered
+#[derive(Clone, Debug)]
+pub struct Diagnostic {
+	pub severity: Severity,
+	pub code: &'static str,
+	pub message: Message,
+	pub primary_span: Option<Span>,
+	pub labels: Vec<Label>,
+	pub notes: Vec<Message>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Format {
+	Text,
+	Json,
+	Sarif,
+}
+
+impl Format {
+	pub fn from_name(name: &str) -> Option<Format> {
+		match name {
+			"text" => Some(Format::Text),
+			"json" => Some(Format::Json),
+			"sarif" => Some(Format::Sarif),
+			_ => None
+		}
+	}
+}
+
+// every warning's name, for -W, --allow and --deny; `warnings` names them all
+pub const WARNINGS: &[(&str, &str)] = &[
+	("console-io", "W001"),
+	("dropped-push", "W002"),
+	("uncalled-function", "W003"),
+	("magic-number", "W004"),
+	("deep-nesting", "W005"),
+	("unreachable", "W006"),
+];
+
+// warnings that are only reported when asked for, because they flag code that is often fine
+const ALLOWED_BY_DEFAULT: &[&str] = &["W004"];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Level {
+	Allow,
+	Warn,
+	Deny,
+}
+
+impl Level {
+	pub fn from_name(name: &str) -> Option<Level> {
+		match name {
+			"allow" => Some(Level::Allow),
+			"warn" => Some(Level::Warn),
+			"deny" => Some(Level::Deny),
+			_ => None
+		}
+	}
+}
+
+// what to do with each warning, set by flags in the order given: a later flag wins, and `warnings`
+// resets every warning named before it
+#[derive(Clone, Debug, Default)]
+pub struct Lints {
+	all: Option<Level>,
+	levels: Vec<(&'static str, Level)>, // by code
+}
+
+impl Lints {
+	// `name` is a warning's name or code, or `warnings`
+	pub fn set(&mut self, name: &str, level: Level) -> Result<(), String> {
+		if name == "warnings" {
+			self.all = Some(level);
+			self.levels.clear();
+			return Ok(());
+		}
+
+		let Some(&(_, code)) = WARNINGS.iter().find(|(warning, code)| *warning == name || code.eq_ignore_ascii_case(name)) else {
+			return Err(format!("Unknown warning: {}", name));
+		};
+
+		self.levels.retain(|(other, _)| *other != code);
+		self.levels.push((code, level));
+		Ok(())
+	}
+
+	pub fn level(&self, code: &str) -> Level {
+		let default = if ALLOWED_BY_DEFAULT.contains(&code) { Level::Allow } else { Level::Warn };
+		self.levels.iter().find(|(other, _)| *other == code).map(|(_, level)| *level).or(self.all).unwrap_or(default)
+	}
+
+	// the diagnostic as the flags make it: errors pass through, allowed warnings are dropped and
+	// denied ones become errors
+	pub fn apply(&self, diagnostic: Diagnostic) -> Option<Diagnostic> {
+		if diagnostic.severity != Severity::Warning {
+			return Some(diagnostic);
+		}
+
+		match self.level(diagnostic.code) {
+			Level::Allow => None,
+			Level::Warn => Some(diagnostic),
+			Level::Deny => {
+				let flag = match self.levels.iter().any(|(code, _)| *code == diagnostic.code) {
+					true => WARNINGS.iter().find(|(_, code)| *code == diagnostic.code).map_or(diagnostic.code, |(name, _)| name),
+					false => "warnings"
+				};
+				let mut diagnostic = diagnostic.with_note(Message::new("deny").arg("flag", flag));
+				diagnostic.severity = Severity::Error;
+				Some(diagnostic)
+			}
+		}
+	}
+}
+
+fn at(span: &Span) -> String {
+	format!("{}:{}:{}", span.loc.filename, span.loc.line, span.loc.col)
+}
+
+fn span_to_json(span: &Span) -> Json {
+	json!({"file": span.loc.filename, "line": span.loc.line, "column": span.loc.col, "length": span.len})
+}
+
+// a file name as a SARIF artifact URI: relative paths stay relative references, absolute ones
+// become file: URIs, and characters a URI cannot hold are percent-encoded
+fn sarif_uri(filename: &str) -> String {
+	let path = filename.replace('\\', "/");
+	let mut uri = String::new();
+
+	for byte in path.bytes() {
+		match byte {
+			b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' | b'/' | b':' => uri.push(byte as char),
+			_ => uri.push_str(&format!("%{:02X}", byte))
+		}
+	}
+
+	if path.starts_with('/') {
+		format!("file://{}", uri)
+	} else if path.as_bytes().get(1) == Some(&b':') {
+		format!("file:///{}", uri) // a Windows drive letter
+	} else {
+		uri
+	}
+}
+
+fn sarif_location(span: &Span) -> Json {
+	json!({"physicalLocation": {
+		"artifactLocation": {"uri": sarif_uri(&span.loc.filename)},
+		"region": {"startLine": span.loc.line, "startColumn": span.loc.col.max(1), "endColumn": span.loc.col.max(1) + span.len}
+	}})
+}
+
+impl Diagnostic {
+	pub fn new(severity: Severity, code: &'static str, message: impl Into<Message>) -> Diagnostic {
+		Diagnostic {severity, code, message: message.into(), primary_span: None, labels: vec![], notes: vec![]}
+	}
+
+	pub fn error(code: &'static str, message: impl Into<Message>) -> Diagnostic {
+		Diagnostic::new(Severity::Error, code, message)
+	}
+
+	pub fn warning(code: &'static str, message: impl Into<Message>) -> Diagnostic {
+		Diagnostic::new(Severity::Warning, code, message)
+	}
+
+	pub fn with_span(mut self, span: Span) -> Diagnostic {
+		self.primary_span = Some(span);
+		self
+	}
+
+	pub fn with_label(mut self, span: Span, message: impl Into<Message>) -> Diagnostic {
+		self.labels.push(Label {span, message: message.into()});
+		self
+	}
+
+	pub fn with_note(mut self, note: impl Into<Message>) -> Diagnostic {
+		self.notes.push(note.into());
+		self
+	}
+
+	// renders every message of the diagnostic in `locale` from now on
+	pub fn localize(mut self, locale: Locale) -> Diagnostic {
+		self.message = self.message.localize(locale);
+		self.labels = self.labels.into_iter().map(|label| Label {span: label.span, message: label.message.localize(locale)}).collect();
+		self.notes = self.notes.into_iter().map(|note| note.localize(locale)).collect();
+		self
+	}
+
+	// for a terminal, without the source the spans point into
+	pub fn render(&self) -> String {
+		let mut out = self.header();
+
+		if let Some(span) = &self.primary_span {
+			out.push_str(&format!("\n  --> {}", at(span)));
+		}
+
+		for label in &self.labels {
+			out.push_str(&format!("\n  --> {}: {}", at(&label.span), label.message));
+		}
+
+		for note in &self.notes {
+			out.push_str(&format!("\n   = note: {}", note));
+		}
+
+		out
+	}
+
+	// for a terminal, rustc style: every line a span is on, underlined with ^ for the primary span and
+	// - for labels; `source` is the file the primary span is in, labels in other files are listed after it
+	pub fn render_with_source(&self, source: &str) -> String {
+		let Some(primary) = &self.primary_span else {
+			return self.render();
+		};
+
+		let lines: Vec<&str> = source.lines().collect();
+		let (local, elsewhere): (Vec<&Label>, Vec<&Label>) = self.labels.iter().partition(|label| label.span.loc.filename == primary.loc.filename);
+
+		let mut marks: Vec<(&Span, char, String)> = vec![(primary, '^', String::new())];
+		marks.extend(local.iter().map(|label| (&label.span, '-', label.message.to_string())));
+		marks.sort_by_key(|(span, ..)| (span.loc.line, span.loc.col));
+
+		let width = marks.iter().map(|(span, ..)| span.loc.line.to_string().len()).max().unwrap_or(1);
+		let pad = " ".repeat(width);
+		let mut out = format!("{}\n{}--> {}\n{} |", self.header(), pad, at(primary), pad);
+		let mut last_line = None;
+
+		for (span, mark, message) in marks {
+			let line = span.loc.line;
+			let text = lines.get((line as usize).wrapping_sub(1)).copied().unwrap_or("");
+
+			if last_line != Some(line) {
+				if last_line.is_some_and(|last| line > last + 1) {
+					out.push_str("\n...");
+				}
+
+				out.push_str(&format!("\n{:>width$} | {}", line, text, width = width));
+			}
+
+			// tabs are kept so the underline lines up however wide the terminal shows them
+			let start = (span.loc.col.max(1) - 1) as usize;
+			let indent: String = text.chars().chain(std::iter::repeat(' ')).take(start).map(|c| if c == '\t' {'\t'} else {' '}).collect();
+			let len = (span.len as usize).min(text.chars().count().saturating_sub(start)).max(1);
+
+			out.push_str(format!("\n{} | {}{} {}", pad, indent, mark.to_string().repeat(len), message).trim_end());
+			last_line = Some(line);
+		}
+
+		for label in elsewhere {
+			out.push_str(&format!("\n{} --> {}: {}", pad, at(&label.span), label.message));
+		}
+
+		for note in &self.notes {
+			out.push_str(&format!("\n{} = note: {}", pad, note));
+		}
+
+		out
+	}
+
+	fn header(&self) -> String {
+		format!("{}[{}]: {}", self.severity.name(), self.code, self.message)
+	}
+
+	pub fn to_json(&self) -> Json {
+		json!({
+			"severity": self.severity.name(),
+			"code": self.code,
+			"message": self.message.to_string(),
+			// for tools that translate messages themselves; null for text that is not in the catalog
+			"messageId": (!self.message.id.is_empty()).then_some(self.message.id),
+			"arguments": self.message.rendered_args().map(|(name, text)| (name.to_owned(), json!(text))).collect::<serde_json::Map<_, _>>(),
+			"span": self.primary_span.as_ref().map(span_to_json),
+			"labels": self.labels.iter().map(|label| json!({"span": span_to_json(&label.span), "message": label.message.to_string()})).collect::<Vec<_>>(),
+			"notes": self.notes.iter().map(Message::to_string).collect::<Vec<_>>()
+		})
+	}
+
+	fn to_sarif(&self, rule_index: usize) -> Json {
+		let text = std::iter::once(&self.message).chain(&self.notes).map(Message::to_string).collect::<Vec<_>>().join("\n");
+		let related: Vec<_> = self.labels.iter().enumerate().map(|(i, label)| {
+			let mut location = sarif_location(&label.span);
+			location["id"] = json!(i);
+			location["message"] = json!({"text": label.message.to_string()});
+			location
+		}).collect();
+
+		json!({
+			"ruleId": self.code,
+			"ruleIndex": rule_index,
+			"level": self.severity.name(),
+			"message": {"text": text},
+			"locations": self.primary_span.iter().map(sarif_location).collect::<Vec<_>>(),
+			"relatedLocations": related
+		})
+	}
+}
+
+// a rule for each code used, described from EXPLANATIONS so dashboards can show what it means
+fn sarif_rule(code: &str, severity: Severity) -> Json {
+	let mut rule = json!({"id": code, "defaultConfiguration": {"level": severity.name()}});
+
+	if let Some(explanation) = explain(code) {
+		rule["shortDescription"] = json!({"text": explanation.title});
+		rule["fullDescription"] = json!({"text": explanation.description});
+		rule["help"] = json!({"text": explanation.render()});
+	}
+
+	rule
+}
+
+// a SARIF 2.1.0 log with one run holding every diagnostic
+pub fn sarif(diagnostics: &[Diagnostic]) -> Json {
+	let mut rules: Vec<(&str, Severity)> = diagnostics.iter().map(|d| (d.code, d.severity)).collect();
+	rules.sort_by_key(|(code, _)| *code);
+	rules.dedup_by_key(|(code, _)| *code);
+
+	let results: Vec<Json> = diagnostics.iter().map(|d| d.to_sarif(rules.iter().position(|(code, _)| *code == d.code).unwrap())).collect();
+
+	json!({
+		"$schema": "https://json.schemastore.org/sarif-2.1.0.json",
+		"version": "2.1.0",
+		"runs": [{
+			"tool": {"driver": {
+				"name": env!("CARGO_PKG_NAME"),
+				"version": env!("CARGO_PKG_VERSION"),
+				"rules": rules.iter().map(|(code, severity)| sarif_rule(code, *severity)).collect::<Vec<_>>()
+			}},
+			// columns count characters, as the tokenizer does
+			"columnKind": "unicodeCodePoints",
+			"results": results
+		}]
+	})
+}
+
+// the diagnostics as one block of text in the given format; text shows snippets of `source` when given
+pub fn emit(diagnostics: &[Diagnostic], format: Format, source: Option<&str>) -> String {
+	match format {
+		Format::Text => diagnostics.iter().map(|d| match source {
+			Some(source) => d.render_with_source(source),
+			None => d.render()
+		}).collect::<Vec<_>>().join("\n"),
+		Format::Json => Json::Array(diagnostics.iter().map(Diagnostic::to_json).collect()).to_string(),
+		Format::Sarif => format!("{:#}", sarif(diagnostics))
+	}
+}
+
+fn token_len(token: &TokenType) -> u64 {
+	match token {
+		TokenType::Eof => 1,
+		TokenType::String(val) => val.chars().count() as u64 + 2,
+		token => token.to_string().chars().count() as u64
+	}
+}
+
+impl From<&TokenizeError> for Diagnostic {
+	fn from(e: &TokenizeError) -> Diagnostic {
+		match e {
+			TokenizeError::UnexpectedIdentifier {ident, loc} => Diagnostic::error("T001", Message::new("T001").arg("ident", ident))
+				.with_span(Span::new(loc, ident.chars().count() as u64)),
+			TokenizeError::UnterminatedString {loc, end} => Diagnostic::error("T002", Message::new("T002"))
+				.with_span(Span::new(end, 1))
+				.with_label(Span::new(loc, 1), Message::new("T002.start"))
+				.with_note(Message::new("T002.note")),
+			TokenizeError::TooManyTokens {max, loc} => Diagnostic::error("T003", Message::new("T003").arg("max", max))
+				.with_span(Span::new(loc, 1))
+				.with_note(Message::new("T003.note")),
+			TokenizeError::MalformedNumber {text, loc} => Diagnostic::error("T005", Message::new("T005").arg("text", text))
+				.with_span(Span::new(loc, text.chars().count() as u64))
+				.with_note(Message::new("T005.note"))
+		}
+	}
+}
+
+// what the parser expected, translated where the catalog has it
+fn expected(expected: &str) -> Message {
+	match expected {
+		"number" => Message::new("P002.number"),
+		"test name" => Message::new("P002.test-name"),
+		"test body" => Message::new("P002.test-body"),
+		text => Message::text(text)
+	}
+}
+
+impl From<&ParseError> for Diagnostic {
+	fn from(e: &ParseError) -> Diagnostic {
+		match e {
+			ParseError::UnexpectedToken {token, loc} => Diagnostic::error("P001", Message::new("P001").arg("token", token))
+				.with_span(Span::new(loc, token_len(token))),
+			ParseError::Expected {expected: what, token, loc} => Diagnostic::error("P002", Message::new("P002").arg("token", token).arg_message("expected", expected(what)))
+				.with_span(Span::new(loc, token_len(token))),
+			ParseError::InvalidNumber {text, loc} => Diagnostic::error("P003", Message::new("P003").arg("text", text))
+				.with_span(Span::new(loc, text.chars().count() as u64)),
+			ParseError::RegisterNotInteger {reg, loc} => Diagnostic::error("P004", Message::new("P004").arg("reg", reg))
+				.with_span(Span::new(loc, reg.to_string().len() as u64)),
+			ParseError::RegisterOutOfRange {reg, loc} => Diagnostic::error("P005", Message::new("P005").arg("max", REGISTER_COUNT - 1).arg("reg", reg))
+				.with_span(Span::new(loc, reg.to_string().len() as u64)),
+			ParseError::ExpectArray {loc} => Diagnostic::error("P006", Message::new("P006"))
+				.with_span(Span::new(loc, 1)),
+			ParseError::NestingTooDeep {loc} => Diagnostic::error("P007", Message::new("P007").arg("max", MAX_NESTING))
+				.with_span(Span::new(loc, 1)),
+			ParseError::TooManyNodes {max, loc} => Diagnostic::error("P008", Message::new("P008").arg("max", max))
+				.with_span(Span::new(loc, 1))
+				.with_note(Message::new("P008.note"))
+		}
+	}
+}
+
+impl From<&AstJsonError> for Diagnostic {
+	fn from(e: &AstJsonError) -> Diagnostic {
+		match e {
+			AstJsonError::Json(error) => Diagnostic::error("J001", Message::new("J001").arg("error", error)),
+			AstJsonError::UnsupportedVersion(version) => Diagnostic::error("J002", Message::new("J002").arg("version", version).arg("supported", SCHEMA_VERSION)),
+			AstJsonError::Invalid {path, message} => Diagnostic::error("J003", Message::new("J003").arg("message", message))
+				.with_note(Message::new("J003.path").arg("path", path))
+		}
+	}
+}
+
+impl From<&CodegenError> for Diagnostic {
+	fn from(e: &CodegenError) -> Diagnostic {
+		match e {
+			CodegenError::LengthOutOfRange {what, len} => Diagnostic::error("C001", Message::new("C001").arg("what", what).arg("len", len)),
+			CodegenError::NestingTooDeep {depth} => Diagnostic::error("C002", Message::new("C002").arg("depth", depth).arg("max", MAX_NESTING))
+				.with_note(Message::new("C002.note").arg("max", MAX_NESTING)),
+			CodegenError::InvalidRegister(reg) => Diagnostic::error("C003", Message::new("C003").arg("max", REGISTER_COUNT - 1).arg("reg", reg))
+				.with_note(Message::new("C003.note").arg("count", REGISTER_COUNT)),
+			CodegenError::OverLimit {what, limit, len, max} => Diagnostic::error("C005", Message::new("C005").arg("what", what).arg("len", len).arg("max", max))
+				.with_note(Message::new("C005.note").arg("limit", limit))
+		}
+	}
+}
+
+impl From<&DisasmError> for Diagnostic {
+	fn from(e: &DisasmError) -> Diagnostic {
+		let message = match e {
+			DisasmError::UnexpectedEnd {offset, expected} => Message::new("D001").arg("offset", offset).arg("expected", expected),
+			DisasmError::UnknownOpcode {offset, opcode} => Message::new("D002").arg("opcode", opcode).arg("offset", offset),
+			DisasmError::UnknownValueTag {offset, tag} => Message::new("D003").arg("tag", tag).arg("offset", offset),
+			DisasmError::InvalidUtf8 {offset} => Message::new("D004").arg("offset", offset),
+			DisasmError::LengthTooLarge {offset, len, remaining} => Message::new("D005").arg("len", len).arg("offset", offset).arg("remaining", remaining),
+			DisasmError::NestingTooDeep {offset} => Message::new("D006").arg("max", MAX_NESTING).arg("offset", offset),
+			DisasmError::RangeOutOfBounds {offset, len, size} => Message::new("D007").arg("len", len).arg("offset", offset).arg("size", size)
+		};
+
+		Diagnostic::error(message.id, message)
+	}
+}
+
+impl RuntimeError {
+	// spans come from `locs`, the locations of the program's commands; each enclosing call becomes a label
+	pub fn diagnostic(&self, locs: &[Loc]) -> Diagnostic {
+		// the interpreter's messages are not in the catalog, so they stay in English
+		let mut diagnostic = Diagnostic::error("R001", Message::new("R001").arg("message", &self.message));
+
+		match locs.get(self.command) {
+			Some(loc) => diagnostic = diagnostic.with_span(Span::new(loc, 1)),
+			None => diagnostic = diagnostic.with_note(Message::new("R001.command").arg("command", self.command))
+		}
+
+		for id in &self.backtrace {
+			match locs.get(*id) {
+				Some(loc) => diagnostic = diagnostic.with_label(Span::new(loc, 1), Message::new("R001.called")),
+				None => diagnostic = diagnostic.with_note(Message::new("R001.called-command").arg("command", id))
+			}
+		}
+
+		diagnostic
+	}
+}
+
+impl From<&StackError> for Diagnostic {
+	fn from(e: &StackError) -> Diagnostic {
+		let (diagnostic, called) = match e {
+			StackError::Underflow {command, takes, depth, ..} => (Diagnostic::error("S001", Message::new("S001").arg("command", command).arg("takes", takes).arg("depth", depth))
+				.with_note(Message::new("S001.note")), "S001.called"),
+			StackError::Overflow {command, depth, max, ..} => (Diagnostic::error("S002", Message::new("S002").arg("command", command).arg("depth", depth).arg("max", max))
+				.with_note(Message::new("S002.note")), "S002.called")
+		};
+
+		// only calls are followed where the command is sure to run
+		e.called().iter().fold(diagnostic.with_span(Span::new(e.loc(), e.command().len() as u64)), |diagnostic, loc| {
+			diagnostic.with_label(Span::new(loc, "call".len() as u64), Message::new(called))
+		})
+	}
+}
+
+impl From<&TypeError> for Diagnostic {
+	fn from(e: &TypeError) -> Diagnostic {
+		let (diagnostic, called) = match e {
+			TypeError::Mismatch {command, expected, found, ..} => (Diagnostic::error("K001", Message::new("K001").arg("command", command).arg("expected", expected).arg("found", found)), "K001.called"),
+			TypeError::NotAFunction {reg, found, ..} => (Diagnostic::error("K002", Message::new("K002").arg("reg", reg).arg("found", found)), "K002.called")
+		};
+
+		e.called().iter().fold(diagnostic.with_span(Span::new(e.loc(), e.command().len() as u64)), |diagnostic, loc| {
+			diagnostic.with_label(Span::new(loc, "call".len() as u64), Message::new(called))
+		})
+	}
+}
+
+impl From<&Error> for Diagnostic {
+	fn from(e: &Error) -> Diagnostic {
+		match e {
+			Error::Tokenize(e) => e.into(),
+			Error::Parse(e) => e.into(),
+			Error::AstJson(e) => e.into(),
+			Error::Codegen(e) => e.into(),
+			Error::Disasm(e) => e.into(),
+			Error::Runtime(e) => e.diagnostic(&[]),
+			Error::TooLarge {size, max} => Diagnostic::error("C004", Message::new("C004").arg("size", size).arg("max", max)),
+			Error::FileTooLarge {size, max} => Diagnostic::error("T004", Message::new("T004").arg("size", size).arg("max", max))
+				.with_note(Message::new("T004.note")),
+			Error::ChecksumMismatch {expected, actual} => Diagnostic::error("D008", Message::new("D008").arg("actual", format!("{:08x}", actual)).arg("expected", format!("{:08x}", expected)))
+				.with_note(Message::new("D008.note"))
+		}
+	}
+}
+
+// the long form of each code, for `evm-asm explain CODE`; every code a diagnostic can have is here
+pub struct Explanation {
+	pub code: &'static str,
+	pub title: &'static str,
+	pub description: &'static str,
+	pub example: &'static str, // source (or for D codes, a description of the bytes) showing the problem and a fix
+}
+
+pub const EXPLANATIONS: &[Explanation] = &[
+	Explanation {
+		code: "T001",
+		title: "Unexpected identifier",
+		description: "A word in the source is not a command, a literal (true, false, nil) or a directive. Mnemonics are lowercase and case-sensitive.",
+		example: "push 1 PRINT  ; T001: PRINT is not a command\npush 1 print"
+	},
+	Explanation {
+		code: "T002",
+		title: "Unterminated string",
+		description: "A string literal has no closing quote before the end of the file. Strings may span lines, so the missing quote can be far before where the error is reported; the diagnostic labels where the string starts.",
+		example: "push \"hello print  ; T002\npush \"hello\" print"
+	},
+	Explanation {
+		code: "T003",
+		title: "Too many tokens",
+		description: "The source has more tokens than --max-tokens (or AssembleOptions::limits) allows. The limit keeps a service from running out of memory on a huge input; tokenizing stops at the first token past it.",
+		example: "evm-asm --max-tokens 1000000 big.asm  ; T003 if big.asm has more than a million tokens"
+	},
+	Explanation {
+		code: "T004",
+		title: "Source file too large",
+		description: "The source is larger than --max-file-size (or AssembleOptions::limits) allows. The command line checks the size before reading the file.",
+		example: "evm-asm --max-file-size 10000000 big.asm  ; T004 if big.asm is larger than 10 MB"
+	},
+	Explanation {
+		code: "T005",
+		title: "Malformed number",
+		description: "A word that starts like a number is not one. A number is an optional minus, then digits with at most one dot among them, and at least one digit: 1, -2, 0.5, .5 and 3. are numbers. It ends at whitespace, a bracket or brace, a quote or a comment, so 1.2.3, 1-2 and 5abc are errors rather than several tokens. Exponents are not supported. Before numbers were strict they ended where their digits did; --legacy-numbers reads them that way.",
+		example: "push 1.2.3  ; T005\npush 1.2 push .3"
+	},
+	Explanation {
+		code: "P001",
+		title: "Unexpected token",
+		description: "A token appears where neither a command nor a value can start, such as a closing bracket with no opening one, or a value without a push.",
+		example: "push 1 2  ; P001: 2 needs its own push\npush 1 push 2"
+	},
+	Explanation {
+		code: "P002",
+		title: "Expected a different token",
+		description: "A command or directive is followed by the wrong kind of token: iload needs a register number, .test a name and a body.",
+		example: "iload \"a\" 1  ; P002: expected number\niload 0 1"
+	},
+	Explanation {
+		code: "P003",
+		title: "Invalid number",
+		description: "A number literal cannot be read as a 64-bit float, as with a decimal point and no digits. Numbers are digits with at most one decimal point; exponents are not supported.",
+		example: "push .  ; P003\npush 0.5"
+	},
+	Explanation {
+		code: "P004",
+		title: "Register is not an integer",
+		description: "iload's register number has a fractional part. Registers are numbered 0 to 15.",
+		example: "iload 1.5 nil  ; P004\niload 1 nil"
+	},
+	Explanation {
+		code: "P005",
+		title: "Register out of range",
+		description: "iload's register number is not between 0 and 15, the registers the VM has.",
+		example: "iload 16 nil  ; P005\niload 15 nil"
+	},
+	Explanation {
+		code: "P006",
+		title: "Expected an array after .expect",
+		description: ".expect after a .test block gives the whole stack the test must leave, as an array from bottom to top.",
+		example: ".test \"adds\" {push 1 push 2 +} .expect 3  ; P006\n.test \"adds\" {push 1 push 2 +} .expect [3]"
+	},
+	Explanation {
+		code: "P007",
+		title: "Values nested too deep",
+		description: "Arrays and functions are nested more than 256 levels deep, more than the bytecode format allows. Deeper data has to be built at run time.",
+		example: "push [[[ ... ]]]  ; P007 with more than 256 levels"
+	},
+	Explanation {
+		code: "P008",
+		title: "Too many commands and values",
+		description: "The program has more commands and values, the nodes of its tree, than --max-nodes (or AssembleOptions::limits) allows. Parsing stops at the first node past the limit, before the rest of the tree takes memory.",
+		example: "evm-asm --max-nodes 1000000 big.asm  ; P008 if big.asm has more than a million commands and values"
+	},
+	Explanation {
+		code: "J001",
+		title: "Invalid JSON",
+		description: "An ast-json input is not valid JSON.",
+		example: "{\"version\": 1, \"commands\": [}  ; J001\n{\"version\": 1, \"commands\": []}"
+	},
+	Explanation {
+		code: "J002",
+		title: "Unsupported ast-json version",
+		description: "An ast-json program has a version this assembler does not read. The version only changes when a field is removed or changes meaning; regenerate the program with a matching assembler.",
+		example: "{\"version\": 2, \"commands\": []}  ; J002\n{\"version\": 1, \"commands\": []}"
+	},
+	Explanation {
+		code: "J003",
+		title: "Invalid ast-json program",
+		description: "An ast-json program is valid JSON but does not follow the schema: a field is missing or has the wrong type, or an op or value type is unknown. The note gives the path to the problem. The schema is described at the top of src/ast_json.rs.",
+		example: "{\"version\": 1, \"commands\": [{\"op\": \"psuh\"}]}  ; J003\n{\"version\": 1, \"commands\": [{\"op\": \"push\", \"value\": {\"type\": \"nil\"}}]}"
+	},
+	Explanation {
+		code: "C001",
+		title: "Length does not fit in 64 bits",
+		description: "A string, array or function is longer than the bytecode's 64-bit length fields can hold. Only programs built in Rust, not parsed from source, can get here.",
+		example: "ProgramBuilder::new().push(Value::String(huge))  ; C001"
+	},
+	Explanation {
+		code: "C002",
+		title: "Values nested too deep",
+		description: "Arrays and functions are nested more than 256 levels deep, more than the disassembler and VM accept. Source reports this as P007; this code is for programs from ast-json or built in Rust.",
+		example: "Value::Array(vec![Value::Array(vec![ ... ])])  ; C002 with more than 256 levels"
+	},
+	Explanation {
+		code: "C003",
+		title: "Register out of range",
+		description: "An iload register is not between 0 and 15. Source reports this as P005; this code is for programs from ast-json or built in Rust.",
+		example: "{\"op\": \"iload\", \"register\": 16, \"value\": {\"type\": \"nil\"}}  ; C003"
+	},
+	Explanation {
+		code: "C004",
+		title: "Bytecode too large",
+		description: "The assembled bytecode is larger than --max-size (or AssembleOptions::max_size). The notes list the largest constants, which are usually the cause.",
+		example: "evm-asm --max-size 1024 big.evmasm  ; C004 if big.evmasm assembles to more than 1024 bytes"
+	},
+	Explanation {
+		code: "C005",
+		title: "Over a target limit",
+		description: "An array, function literal or string is longer, or values are nested deeper, than --max-array-length, --max-function-length, --max-string-size or --max-nesting-depth (AssembleOptions::target) allow. These are for a VM that rejects such programs when it loads them, so they fail here instead. The limits apply to the optimized program, which may have merged or inlined what the source wrote apart.",
+		example: "evm-asm --max-array-length 2 prog.evmasm  ; C005 if prog.evmasm pushes [1 2 3]"
+	},
+	Explanation {
+		code: "D001",
+		title: "Unexpected end of bytecode",
+		description: "The bytecode ends in the middle of a command or value, usually because the file was truncated.",
+		example: "00 01 00 00  ; D001: push, number tag, then 2 of the number's 8 bytes"
+	},
+	Explanation {
+		code: "D002",
+		title: "Unknown opcode",
+		description: "A byte where a command should start is not an opcode, because the input is not bytecode, is corrupt, or was assembled for a newer VM.",
+		example: "ff  ; D002"
+	},
+	Explanation {
+		code: "D003",
+		title: "Unknown value tag",
+		description: "A byte where a value should start is not a value tag (nil, number, string, boolean, function or array).",
+		example: "00 09  ; D003: push, then tag 9"
+	},
+	Explanation {
+		code: "D004",
+		title: "Invalid UTF-8 in string",
+		description: "A string constant's bytes are not valid UTF-8. Strings in source are always valid, so the bytecode is corrupt or was not made by this assembler.",
+		example: "00 02 02 00 00 00 00 00 00 00 c3 28  ; D004"
+	},
+	Explanation {
+		code: "D005",
+		title: "Length exceeds the remaining bytes",
+		description: "A string, array or function claims more elements or bytes than are left in the bytecode, so it cannot be valid.",
+		example: "00 02 ff ff ff ff ff ff ff ff  ; D005: a string of 2^64 - 1 bytes"
+	},
+	Explanation {
+		code: "D006",
+		title: "Values nested too deep",
+		description: "Arrays and functions in the bytecode are nested more than 256 levels deep, which the assembler never produces.",
+		example: "00 05 01 00 00 00 00 00 00 00 05 ...  ; D006 with more than 256 nested arrays"
+	},
+	Explanation {
+		code: "D007",
+		title: "Range outside the program",
+		description: "The range given to disasm with --offset and --len reach past the end of the bytecode.",
+		example: "evm-asm disasm --offset 100 small.bin  ; D007 if small.bin is shorter than 100 bytes"
+	},
+	Explanation {
+		code: "D008",
+		title: "Checksum mismatch",
+		description: "The CRC-32 of the bytecode differs from the one given to `evm-asm verify --checksum`: the file is not the program that checksum was published for, or was damaged on the way. Nothing is decoded.",
+		example: "evm-asm verify --checksum 1c291ca3 prog.bin  ; D008 if prog.bin changed since its checksum was taken"
+	},
+	Explanation {
+		code: "R001",
+		title: "Runtime error",
+		description: "The program failed while running: a command got values of the wrong type, the stack was too short, an assertion failed, or a limit was reached. Labels show the calls that led to the failing command.",
+		example: "push \"a\" push 1 +  ; R001: + needs two numbers\npush \"a\" push \"1\" concat"
+	},
+	Explanation {
+		code: "S001",
+		title: "Stack underflow",
+		description: "With --check-stack, a command takes more values than the stack can hold at that point on any path, counting from an empty stack. Calls are followed into the function of their register when the program iloads it once, with a literal; only commands sure to run are reported, not those in the bodies of if and the loops.",
+		example: "push 1 +  ; S001: + takes 2 values, but there is at most 1\npush 1 push 2 +"
+	},
+	Explanation {
+		code: "S002",
+		title: "Stack overflow",
+		description: "With --check-stack=MAX, a command leaves more than MAX values on the stack on every path, counting from an empty stack.",
+		example: "push 1 push 2 push 3  ; S002 with --check-stack=2\npush 1 push 2 + push 3"
+	},
+	Explanation {
+		code: "K001",
+		title: "Value of the wrong kind",
+		description: "With --typecheck, a command gets a value of a kind it always fails on, such as a string for + or a number for each. Kinds are followed through the stack, the registers and calls as --check-stack follows them; a value of unknown kind, as one the host returns, passes.",
+		example: "push \"a\" push 1 +  ; K001: + expects number, got string\npush \"a\" push \"1\" concat"
+	},
+	Explanation {
+		code: "K002",
+		title: "Register without a function",
+		description: "With --typecheck, call, if or a loop reads a register that holds a value other than a function.",
+		example: "iload 0 5 push 0 call  ; K002: register 0 holds number\niload 0 {push 5} push 0 call"
+	},
+	Explanation {
+		code: "W001",
+		title: "Console I/O on a host without it",
+		description: "The program uses print or readln, which only run mode's host provides. Pass --host-io when the target host supports console I/O.",
+		example: "push 1 print  ; W001 unless assembled with --host-io"
+	},
+	Explanation {
+		code: "W002",
+		title: "Pushed value dropped right away",
+		description: "A push is followed by drop, so the value is thrown away unused. Remove both commands, or use the value.",
+		example: "push \"unused\" drop  ; W002"
+	},
+	Explanation {
+		code: "W003",
+		title: "Function never called",
+		description: "Only functions in registers can be called, by call, if, each, map, filter and reduce with the register's number. A function pushed onto the stack can never be called, and one loaded into a register whose number the program never pushes is never called either.",
+		example: "push { push 1 }  ; W003: load it with iload instead\niload 3 { push 1 }  ; W003 if the program never pushes 3"
+	},
+	Explanation {
+		code: "W004",
+		title: "Magic number",
+		description: "A number other than -1, 0, 1 or 2, or a register number, is pushed without a name. Loading it into a register once with iload and reading it with load gives it a single definition. Allowed unless turned on with -W magic-number.",
+		example: "push 86400 *  ; W004\niload 15 86400\npush 15 load *"
+	},
+	Explanation {
+		code: "W005",
+		title: "Functions nested too deep",
+		description: "A function literal is inside more function literals than the lint's max-nesting setting, 3 unless set in the [lint] table of evm-asm.toml. Deeply nested functions are hard to follow; load the inner ones into registers of their own.",
+		example: "iload 0 { iload 1 { iload 2 { iload 3 { push 1 } } } }  ; W005 at iload 3"
+	},
+	Explanation {
+		code: "W006",
+		title: "Unreachable command",
+		description: "A command before this one always fails, so it and the rest of its program or function never run: assert of false or nil, assert_eq of two different literals, division by a literal zero, or a call of a function that always fails. The control flow graph of --emit cfg-dot shows the blocks after it in grey.",
+		example: "push false assert push 1 print  ; W006 at the push after assert\npush 1 print"
+	},
+];
+
+pub fn explain(code: &str) -> Option<&'static Explanation> {
+	EXPLANATIONS.iter().find(|explanation| explanation.code.eq_ignore_ascii_case(code))
+}
+
+impl Explanation {
+	pub fn render(&self) -> String {
+		let example: Vec<String> = self.example.lines().map(|line| format!("    {}", line)).collect();
+		format!("{}: {}\n\n{}\n\nExample:\n\n{}\n", self.code, self.title, self.description, example.join("\n"))
+	}
+}
@@ -0,0 +1,336 @@
+use alloc::borrow::ToOwned;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use alloc::{format, vec};
+use thiserror::Error;
+
+use crate::codegen;
+use crate::listing;
+use crate::opcode;
+use crate::parser::*;
+
+#[derive(Debug, Error)]
+pub enum DisasmError {
+	#[error("Unexpected end of bytecode at offset {offset}: expected {expected}")]
+	UnexpectedEnd {offset: usize, expected: &'static str},
+	#[error("Unknown opcode {opcode} at offset {offset}")]
+	UnknownOpcode {offset: usize, opcode: u8},
+	#[error("Unknown value tag {tag} at offset {offset}")]
+	UnknownValueTag {offset: usize, tag: u8},
+	#[error("Invalid UTF-8 in string at offset {offset}")]
+	InvalidUtf8 {offset: usize},
+	#[error("Length {len} at offset {offset} exceeds the {remaining} bytes remaining")]
+	LengthTooLarge {offset: usize, len: u64, remaining: usize},
+	#[error("Values nested more than {} levels deep at offset {offset}", codegen::MAX_NESTING)]
+	NestingTooDeep {offset: usize},
+	#[error("Range of {len} bytes at offset {offset} is outside the {size} byte program")]
+	RangeOutOfBounds {offset: usize, len: usize, size: usize},
+}
+
+struct Reader<'a> {
+	bytes: &'a [u8],
+	pos: usize,
+	depth: usize,
+}
+
+impl Reader<'_> {
+	// a reader over `len` bytes starting at `offset`; offsets stay relative to the whole program
+	fn new(bytes: &[u8], offset: usize, len: usize) -> Result<Reader<'_>, DisasmError> {
+		match offset.checked_add(len) {
+			Some(end) if end <= bytes.len() => Ok(Reader {bytes: &bytes[..end], pos: offset, depth: 0}),
+			_ => Err(DisasmError::RangeOutOfBounds {offset, len, size: bytes.len()})
+		}
+	}
+
+	fn take(&mut self, len: usize, expected: &'static str) -> Result<&[u8], DisasmError> {
+		if self.bytes.len() - self.pos < len {
+			return Err(DisasmError::UnexpectedEnd {offset: self.pos, expected});
+		}
+
+		self.pos += len;
+		Ok(&self.bytes[self.pos - len..self.pos])
+	}
+
+	fn u8(&mut self, expected: &'static str) -> Result<u8, DisasmError> {
+		Ok(self.take(1, expected)?[0])
+	}
+
+	fn u64(&mut self, expected: &'static str) -> Result<u64, DisasmError> {
+		Ok(u64::from_le_bytes(self.take(8, expected)?.try_into().unwrap()))
+	}
+
+	// reads a length field, rejecting lengths that cannot fit in the rest of the input
+	// (every element takes at least one byte)
+	fn len(&mut self, expected: &'static str) -> Result<u64, DisasmError> {
+		let offset = self.pos;
+		let len = self.u64(expected)?;
+		let remaining = self.bytes.len() - self.pos;
+
+		if len > remaining as u64 {
+			return Err(DisasmError::LengthTooLarge {offset, len, remaining});
+		}
+
+		Ok(len)
+	}
+
+	fn enter(&mut self, offset: usize) -> Result<(), DisasmError> {
+		self.depth += 1;
+
+		if self.depth > codegen::MAX_NESTING {
+			return Err(DisasmError::NestingTooDeep {offset});
+		}
+
+		Ok(())
+	}
+}
+
+fn read_value(reader: &mut Reader) -> Result<Value, DisasmError> {
+	let offset = reader.pos;
+
+	match reader.u8("value tag")? {
+		opcode::NIL => Ok(Value::Nil),
+		opcode::NUMBER => Ok(Value::Number(f64::from_le_bytes(reader.take(8, "number")?.try_into().unwrap()))),
+		opcode::STRING => {
+			let len = reader.len("string length")? as usize;
+			let bytes = reader.take(len, "string contents")?;
+
+			match String::from_utf8(bytes.to_vec()) {
+				Ok(val) => Ok(Value::String(val)),
+				Err(_) => Err(DisasmError::InvalidUtf8 {offset})
+			}
+		},
+		opcode::BOOLEAN => Ok(Value::Boolean(reader.u8("boolean")? != 0)),
+		opcode::FUNCTION => {
+			let len = reader.len("function length")?;
+			reader.enter(offset)?;
+			let commands = read_commands(reader, Some(len))?;
+			reader.depth -= 1;
+
+			Ok(Value::Function(commands))
+		},
+		opcode::ARRAY => {
+			let len = reader.len("array length")?;
+			let mut values = vec![];
+			reader.enter(offset)?;
+
+			for _ in 0..len {
+				values.push(read_value(reader)?);
+			}
+
+			reader.depth -= 1;
+			Ok(Value::Array(values))
+		},
+		tag => Err(DisasmError::UnknownValueTag {offset, tag})
+	}
+}
+
+fn read_command(reader: &mut Reader) -> Result<Command, DisasmError> {
+	let offset = reader.pos;
+	let op = reader.u8("opcode")?;
+
+	match opcode::command(op) {
+		Some(Command::Push(_)) => Ok(Command::Push(read_value(reader)?)),
+		Some(Command::ILoad(_, _)) => {
+			let reg = reader.u8("register")?;
+			Ok(Command::ILoad(reg, read_value(reader)?))
+		},
+		Some(command) => Ok(command.clone()),
+		None => Err(DisasmError::UnknownOpcode {offset, opcode: op})
+	}
+}
+
+// reads `count` commands, or commands until the end of the input
+fn read_commands(reader: &mut Reader, count: Option<u64>) -> Result<Vec<Command>, DisasmError> {
+	let mut commands = vec![];
+
+	match count {
+		Some(count) => {
+			for _ in 0..count {
+				commands.push(read_command(reader)?);
+			}
+		},
+		None => {
+			while reader.pos < reader.bytes.len() {
+				commands.push(read_command(reader)?);
+			}
+		}
+	}
+
+	Ok(commands)
+}
+
+// decodes a complete program, the inverse of codegen::generate
+pub fn disassemble(bytes: &[u8]) -> Result<Vec<Command>, DisasmError> {
+	disassemble_range(bytes, 0, bytes.len())
+}
+
+// decodes the commands in `len` bytes starting at `offset`, which must be the start of a command
+pub fn disassemble_range(bytes: &[u8], offset: usize, len: usize) -> Result<Vec<Command>, DisasmError> {
+	read_commands(&mut Reader::new(bytes, offset, len)?, None)
+}
+
+pub enum Decoded {
+	Command(Command),
+	Undecodable {offset: usize, len: usize, error: DisasmError},
+}
+
+// decodes as much of a damaged program as possible: when a command fails to decode, the decoder
+// skips ahead one byte at a time until a command decodes again, reporting each skipped region
+// with the error that started it
+pub fn disassemble_best_effort(bytes: &[u8], offset: usize, len: usize) -> Result<Vec<Decoded>, DisasmError> {
+	let mut decoded = vec![];
+	let mut reader = Reader::new(bytes, offset, len)?;
+
+	while reader.pos < reader.bytes.len() {
+		let start = reader.pos;
+		reader.depth = 0;
+
+		match read_command(&mut reader) {
+			Ok(command) => decoded.push(Decoded::Command(command)),
+			Err(error) => {
+				reader.pos = start + 1;
+
+				match decoded.last_mut() {
+					Some(Decoded::Undecodable {offset, len, ..}) if *offset + *len == start => *len += 1,
+					_ => decoded.push(Decoded::Undecodable {offset: start, len: 1, error})
+				}
+			}
+		}
+	}
+
+	Ok(decoded)
+}
+
+// what verify found in a valid program
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Verified {
+	pub size: usize, // bytes
+	pub commands: usize, // including those in function bodies
+	pub checksum: u32,
+}
+
+// checks bytecode from an untrusted source without running it, for hosts to call before loading:
+// every opcode and value tag is known, every length fits in the bytes after it, registers are in
+// range and values nest at most codegen::MAX_NESTING levels. bytecode carries no checksum of its
+// own, so `checksum` is one published with the program, compared before anything is decoded
+pub fn verify(bytes: &[u8], checksum: Option<u32>) -> Result<Verified, crate::Error> {
+	let actual = crc32(bytes);
+
+	if let Some(expected) = checksum.filter(|expected| *expected != actual) {
+		return Err(crate::Error::ChecksumMismatch {expected, actual});
+	}
+
+	let commands = disassemble(bytes)?;
+	codegen::validate(&commands)?;
+
+	fn count(commands: &[Command]) -> usize {
+		fn in_value(value: &Value) -> usize {
+			match value {
+				Value::Function(commands) => count(commands),
+				Value::Array(values) => values.iter().map(in_value).sum(),
+				_ => 0
+			}
+		}
+
+		commands.iter().map(|command| 1 + match command {
+			Command::Push(value) | Command::ILoad(_, value) => in_value(value),
+			_ => 0
+		}).sum()
+	}
+
+	Ok(Verified {size: bytes.len(), commands: count(&commands), checksum: actual})
+}
+
+const CRC_TABLE: [u32; 256] = {
+	let mut table = [0; 256];
+	let mut i = 0;
+
+	while i < 256 {
+		let mut crc = i as u32;
+		let mut bit = 0;
+
+		while bit < 8 {
+			crc = if crc & 1 == 1 { 0xedb8_8320 ^ (crc >> 1) } else { crc >> 1 };
+			bit += 1;
+		}
+
+		table[i] = crc;
+		i += 1;
+	}
+
+	table
+};
+
+// CRC-32 as zlib and `crc32` compute it
+pub fn crc32(bytes: &[u8]) -> u32 {
+	!bytes.iter().fold(!0, |crc, byte| CRC_TABLE[((crc ^ *byte as u32) & 0xff) as usize] ^ (crc >> 8))
+}
+
+const PREVIEW_CHARS: usize = 24;
+
+// short form of a decoded value plus a note on its size
+fn preview(value: &Value) -> (String, String) {
+	let mut text = match value {
+		Value::String(val) if val.chars().count() > PREVIEW_CHARS => format!("\"{}...\"", val.chars().take(PREVIEW_CHARS).collect::<String>()),
+		value => value.to_string()
+	};
+
+	if text.chars().count() > PREVIEW_CHARS * 2 {
+		text = format!("{}...", text.chars().take(PREVIEW_CHARS * 2).collect::<String>());
+	}
+
+	let note = match value {
+		Value::String(val) => format!("string, {} bytes", val.len()),
+		Value::Array(vals) => format!("array, {} values", vals.len()),
+		Value::Function(cmds) => format!("function, {} commands", cmds.len()),
+		Value::Number(_) => "number".to_owned(),
+		Value::Boolean(_) => "boolean".to_owned(),
+		Value::Nil => "nil".to_owned()
+	};
+
+	(text, note)
+}
+
+fn annotate_commands(out: &mut String, reader: &mut Reader, count: Option<u64>, depth: usize) -> Result<(), DisasmError> {
+	let mut read = 0;
+
+	while count.map_or(reader.pos < reader.bytes.len(), |count| read < count) {
+		let start = reader.pos;
+		let op = reader.u8("opcode")?;
+		read += 1;
+
+		let mnemonic = match opcode::command(op) {
+			Some(Command::Push(_)) => "push".to_owned(),
+			Some(Command::ILoad(_, _)) => format!("iload {}", reader.u8("register")?),
+			Some(command) => {
+				listing::write_line(out, Some(start), &reader.bytes[start..reader.pos], depth, &command.to_string());
+				continue;
+			},
+			None => return Err(DisasmError::UnknownOpcode {offset: start, opcode: op})
+		};
+
+		if reader.bytes.get(reader.pos) == Some(&opcode::FUNCTION) {
+			reader.u8("value tag")?;
+			let len = reader.len("function length")?;
+			reader.enter(start)?;
+
+			listing::write_line(out, Some(start), &reader.bytes[start..reader.pos], depth, &format!("{} {{  ; function, {} commands", mnemonic, len));
+			annotate_commands(out, reader, Some(len), depth + 1)?;
+			reader.depth -= 1;
+			listing::write_line(out, None, &[], depth, "}");
+		} else {
+			let (text, note) = preview(&read_value(reader)?);
+			listing::write_line(out, Some(start), &reader.bytes[start..reader.pos], depth, &format!("{} {}  ; {}", mnemonic, text, note));
+		}
+	}
+
+	Ok(())
+}
+
+// listing of `len` bytes of bytecode starting at `offset`, with offsets, encoded bytes and
+// previews of decoded constants
+pub fn annotate(bytes: &[u8], offset: usize, len: usize) -> Result<String, DisasmError> {
+	let mut out = String::new();
+	annotate_commands(&mut out, &mut Reader::new(bytes, offset, len)?, None, 0)?;
+	Ok(out)
+}
@@ -0,0 +1,179 @@
+use std::fmt::Write;
+
+use crate::html::escape;
+use crate::parser;
+use crate::tokenizer::{self, Comment, Loc, TokenType};
+use crate::Error;
+
+// a reference for a library of programs, for `evm-asm doc`. the closest the language has to a named
+// function is a register set with iload, so `;;;` comments on the lines right before an iload
+// document that register:
+//
+//   ;;; ( a b -- sum )
+//   ;;; adds two numbers
+//   iload 0 {+}
+//
+// a first line in parentheses with `--` in it declares the stack signature. every iload outside a
+// function is listed, documented or not, and those inside one if they are documented
+
+#[derive(Clone, Debug)]
+pub struct Entry {
+	pub register: u8,
+	pub loc: Loc,
+	pub function: bool, // whether the register holds a function literal
+	pub signature: Option<String>,
+	pub text: String, // the doc comment's lines, without the `;;;`
+}
+
+// a documented file
+pub struct File {
+	pub filename: String,
+	pub entries: Vec<Entry>,
+}
+
+// the `;;;` comment on `line` with nothing else on its line
+fn doc_comment<'a>(comments: &'a [Comment], lines: &[&str], line: u64) -> Option<&'a Comment> {
+	let comment = comments.iter().find(|comment| comment.loc.line == line && comment.text.starts_with(";;;"))?;
+	let before: String = lines.get(line as usize - 1)?.chars().take(comment.loc.col as usize - 1).collect();
+	before.trim().is_empty().then_some(comment)
+}
+
+pub fn document(source: &str, filename: &str) -> Result<File, Error> {
+	let (tokens, comments) = tokenizer::tokenize_with_comments(source, filename)?;
+	parser::parse_with_tests(tokens.clone())?;
+
+	let lines: Vec<&str> = source.lines().collect();
+	let mut entries = vec![];
+	let mut depth = 0;
+
+	for (i, token) in tokens.iter().enumerate() {
+		match &token.typ {
+			TokenType::LeftCurly => depth += 1,
+			TokenType::RightCurly => depth -= 1,
+			TokenType::ILoad => {
+				// the parser accepted it, so a register number follows
+				let TokenType::Number(register) = &tokens[i + 1].typ else { continue };
+
+				let mut doc = vec![];
+				let mut line = token.loc.line;
+
+				while let Some(comment) = line.checked_sub(1).filter(|line| *line > 0).and_then(|line| doc_comment(&comments, &lines, line)) {
+					doc.push(comment.text[3..].strip_prefix(' ').unwrap_or(&comment.text[3..]));
+					line -= 1;
+				}
+
+				if doc.is_empty() && depth > 0 {
+					continue;
+				}
+
+				doc.reverse();
+
+				let signature = doc.first().map(|line| line.trim()).filter(|line| line.starts_with('(') && line.ends_with(')') && line.contains("--"));
+
+				if signature.is_some() {
+					doc.remove(0);
+				}
+
+				entries.push(Entry {
+					register: register.parse::<f64>().unwrap_or_default() as u8,
+					loc: token.loc.clone(),
+					function: tokens[i + 2].typ == TokenType::LeftCurly,
+					signature: signature.map(str::to_owned),
+					text: doc.join("\n").trim().to_owned(),
+				});
+			},
+			_ => {}
+		}
+	}
+
+	Ok(File {filename: filename.to_owned(), entries})
+}
+
+fn anchor(filename: &str, register: u8) -> String {
+	let name: String = filename.chars().map(|c| if c.is_ascii_alphanumeric() { c.to_ascii_lowercase() } else { '-' }).collect();
+	format!("{}-r{}", name, register)
+}
+
+fn title(entry: &Entry) -> String {
+	format!("register {}{}", entry.register, if entry.function { "" } else { " (value)" })
+}
+
+// one Markdown document, with a list of contents linking to each entry
+pub fn markdown(files: &[File]) -> String {
+	let mut out = String::from("# Reference\n\n");
+
+	for file in files {
+		writeln!(out, "- `{}`", file.filename).unwrap();
+
+		for entry in &file.entries {
+			writeln!(out, "  - [{}](#{})", title(entry), anchor(&file.filename, entry.register)).unwrap();
+		}
+	}
+
+	for file in files {
+		write!(out, "\n## `{}`\n", file.filename).unwrap();
+
+		for entry in &file.entries {
+			write!(out, "\n<a id=\"{}\"></a>\n### {}\n\n", anchor(&file.filename, entry.register), title(entry)).unwrap();
+
+			if let Some(signature) = &entry.signature {
+				write!(out, "`{}`\n\n", signature).unwrap();
+			}
+
+			if !entry.text.is_empty() {
+				write!(out, "{}\n\n", entry.text).unwrap();
+			}
+
+			writeln!(out, "Defined on line {}.", entry.loc.line).unwrap();
+		}
+	}
+
+	out
+}
+
+fn escaped(text: &str) -> String {
+	let mut out = String::new();
+	text.chars().for_each(|c| escape(&mut out, c));
+	out
+}
+
+// one standalone HTML page, with a list of contents linking to each entry
+pub fn html(files: &[File]) -> String {
+	let mut out = String::from("<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>Reference</title>\n<style>\n");
+	out.push_str("body { max-width: 50em; margin: 2em auto; font: 16px/1.5 sans-serif; color: #24292f; }\ncode { font-family: ui-monospace, monospace; }\n.loc { color: #57606a; }\n");
+	out.push_str("</style>\n</head>\n<body>\n<h1>Reference</h1>\n<nav>\n<ul>\n");
+
+	for file in files {
+		write!(out, "<li><code>{}</code>\n<ul>\n", escaped(&file.filename)).unwrap();
+
+		for entry in &file.entries {
+			writeln!(out, "<li><a href=\"#{}\">{}</a></li>", anchor(&file.filename, entry.register), title(entry)).unwrap();
+		}
+
+		out.push_str("</ul>\n</li>\n");
+	}
+
+	out.push_str("</ul>\n</nav>\n");
+
+	for file in files {
+		writeln!(out, "<h2><code>{}</code></h2>", escaped(&file.filename)).unwrap();
+
+		for entry in &file.entries {
+			writeln!(out, "<h3 id=\"{}\">{}</h3>", anchor(&file.filename, entry.register), title(entry)).unwrap();
+
+			if let Some(signature) = &entry.signature {
+				writeln!(out, "<p><code>{}</code></p>", escaped(signature)).unwrap();
+			}
+
+			// a blank line in the comment starts a paragraph
+			for paragraph in entry.text.split("\n\n").filter(|paragraph| !paragraph.is_empty()) {
+				writeln!(out, "<p>{}</p>", escaped(paragraph)).unwrap();
+			}
+
+			writeln!(out, "<p class=\"loc\">Defined on line {}.</p>", entry.loc.line).unwrap();
+		}
+	}
+
+	out.push_str("</body>\n</html>\n");
+	out
+}
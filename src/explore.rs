@@ -0,0 +1,230 @@
+use std::fmt::Write as _;
+use std::io::{self, BufRead, Write};
+
+use crate::codegen;
+use crate::disasm::{self, Decoded, DisasmError};
+use crate::listing;
+use crate::parser::{Command, Value};
+
+// `evm-asm explore`: pages through bytecode as a listing of decoded commands, each with its offset
+// and first bytes, above a hex dump of the bytes of the selected command. bytes that do not decode
+// are a row of their own with the error, and decoding picks up after them as disasm --best-effort
+// does
+
+const PAGE: usize = 16;
+const DUMP_BYTES: usize = 64; // of the selected row, 16 a line
+
+const HELP: &str = "\
+  (enter), n       shows the next page
+  p                shows the previous page
+  j, k             selects the next or previous row
+  g OFFSET         selects the command at OFFSET, decimal or 0x hex
+  /TEXT            selects the next command whose text contains TEXT; / alone repeats the search
+  x BYTES          selects the next command whose bytes contain BYTES, in hex: x 01 ff
+  q, quit          exits";
+
+pub struct Row {
+	pub offset: usize,
+	pub len: usize,
+	pub depth: usize,
+	pub text: String,
+}
+
+pub struct Explorer {
+	bytes: Vec<u8>,
+	pub rows: Vec<Row>,
+	pub selected: usize,
+	search: Option<String>,
+}
+
+fn rows(out: &mut Vec<Row>, commands: &[Command], mut offset: usize, depth: usize) -> usize {
+	for command in commands {
+		match command {
+			Command::Push(Value::Function(body)) | Command::ILoad(_, Value::Function(body)) => {
+				let header = codegen::command_size(command) - body.iter().map(codegen::command_size).sum::<usize>();
+				let text = match command {
+					Command::ILoad(reg, _) => format!("iload {} {{", reg),
+					_ => "push {".to_owned()
+				};
+
+				out.push(Row {offset, len: header, depth, text});
+				offset = rows(out, body, offset + header, depth + 1);
+			},
+			_ => {
+				let len = codegen::command_size(command);
+				out.push(Row {offset, len, depth, text: command.to_string()});
+				offset += len;
+			}
+		}
+	}
+
+	offset
+}
+
+// a number as a decimal or, with 0x, a hexadecimal offset
+fn parse_offset(text: &str) -> Option<usize> {
+	match text.strip_prefix("0x") {
+		Some(hex) => usize::from_str_radix(hex, 16).ok(),
+		None => text.parse().ok()
+	}
+}
+
+impl Explorer {
+	pub fn new(bytes: Vec<u8>) -> Result<Explorer, DisasmError> {
+		let mut out = vec![];
+		let mut offset = 0;
+
+		for decoded in disasm::disassemble_best_effort(&bytes, 0, bytes.len())? {
+			match decoded {
+				Decoded::Command(command) => offset = rows(&mut out, &[command], offset, 0),
+				Decoded::Undecodable {offset: start, len, error} => {
+					out.push(Row {offset: start, len, depth: 0, text: format!("?? {}", error)});
+					offset = start + len;
+				}
+			}
+		}
+
+		Ok(Explorer {bytes, rows: out, selected: 0, search: None})
+	}
+
+	fn bytes(&self, row: &Row) -> &[u8] {
+		&self.bytes[row.offset..(row.offset + row.len).min(self.bytes.len())]
+	}
+
+	// the page holding the selected row, and the hex dump of its bytes
+	pub fn view(&self) -> String {
+		let mut out = String::new();
+		let start = self.selected / PAGE * PAGE;
+
+		for (i, row) in self.rows.iter().enumerate().skip(start).take(PAGE) {
+			let mut line = String::new();
+			listing::write_line(&mut line, Some(row.offset), self.bytes(row), row.depth, &row.text);
+			write!(out, "{} {}", if i == self.selected { ">" } else { " " }, line).unwrap();
+		}
+
+		let Some(row) = self.rows.get(self.selected) else {
+			out.push_str("  (no bytes)\n");
+			return out;
+		};
+
+		let bytes = self.bytes(row);
+		writeln!(out, "\n  {} byte{} at {:08x}:", bytes.len(), if bytes.len() == 1 { "" } else { "s" }, row.offset).unwrap();
+
+		for (i, chunk) in bytes.chunks(16).take(DUMP_BYTES / 16).enumerate() {
+			let hex: Vec<String> = chunk.iter().map(|b| format!("{:02x}", b)).collect();
+			let text: String = chunk.iter().map(|b| if b.is_ascii_graphic() || *b == b' ' { *b as char } else { '.' }).collect();
+			writeln!(out, "  {:08x}  {:<47}  {}", row.offset + i * 16, hex.join(" "), text).unwrap();
+		}
+
+		if bytes.len() > DUMP_BYTES {
+			writeln!(out, "  ...").unwrap();
+		}
+
+		out
+	}
+
+	// the first row after the selected one, wrapping around, that matches
+	fn find(&self, matches: impl Fn(&Row) -> bool) -> Option<usize> {
+		(1..=self.rows.len()).map(|step| (self.selected + step) % self.rows.len()).find(|i| matches(&self.rows[*i]))
+	}
+
+	// carries out a command line; None to quit, otherwise what to print
+	pub fn command(&mut self, line: &str) -> Option<String> {
+		let line = line.trim();
+		let last = self.rows.len().saturating_sub(1);
+
+		let found = match line.split_once(' ').unwrap_or((line, "")) {
+			("q" | "quit", _) => return None,
+			("h" | "help", _) => return Some(format!("{}\n", HELP)),
+			("" | "n", _) => {
+				self.selected = ((self.selected / PAGE + 1) * PAGE).min(last);
+				true
+			},
+			("p", _) => {
+				self.selected = (self.selected / PAGE).saturating_sub(1) * PAGE;
+				true
+			},
+			("j", _) => {
+				self.selected = (self.selected + 1).min(last);
+				true
+			},
+			("k", _) => {
+				self.selected = self.selected.saturating_sub(1);
+				true
+			},
+			("g", offset) => {
+				let Some(offset) = parse_offset(offset.trim()) else {
+					return Some(format!("expected an offset: {}\n", offset));
+				};
+
+				// the innermost row holding the offset
+				match self.rows.iter().rposition(|row| row.offset <= offset && offset < row.offset + row.len.max(1)) {
+					Some(i) => {
+						self.selected = i;
+						true
+					},
+					None => return Some(format!("offset {:#x} is past the end, at {:#x}\n", offset, self.bytes.len()))
+				}
+			},
+			("x", hex) => {
+				let needle: Option<Vec<u8>> = hex.split_whitespace().map(|byte| u8::from_str_radix(byte, 16).ok()).collect();
+
+				match needle.filter(|needle| !needle.is_empty()) {
+					Some(needle) => self.select(|explorer, row| explorer.bytes(row).windows(needle.len()).any(|window| window == needle.as_slice())),
+					None => return Some(format!("expected bytes in hex: {}\n", hex))
+				}
+			},
+			_ if line.starts_with('/') => {
+				if line.len() > 1 {
+					self.search = Some(line[1..].to_lowercase());
+				}
+
+				match self.search.clone() {
+					Some(text) => self.select(|_, row| row.text.to_lowercase().contains(&text)),
+					None => return Some("no search to repeat\n".to_owned())
+				}
+			},
+			(word, _) => return Some(format!("unknown command {}, try help\n", word))
+		};
+
+		Some(match found {
+			true => self.view(),
+			false => "not found\n".to_owned()
+		})
+	}
+
+	fn select(&mut self, matches: impl Fn(&Explorer, &Row) -> bool) -> bool {
+		match self.find(|row| matches(self, row)) {
+			Some(i) => {
+				self.selected = i;
+				true
+			},
+			None => false
+		}
+	}
+}
+
+// the interactive session on stdin
+pub fn explore(bytes: Vec<u8>) -> io::Result<()> {
+	let mut explorer = Explorer::new(bytes).map_err(io::Error::other)?;
+	let stdin = io::stdin();
+	let mut input = stdin.lock().lines();
+
+	println!("Type help for a list of commands.");
+	print!("{}", explorer.view());
+
+	loop {
+		print!("(explore) ");
+		io::stdout().flush()?;
+
+		let Some(line) = input.next() else {
+			println!();
+			return Ok(());
+		};
+
+		match explorer.command(&line?) {
+			Some(output) => print!("{}", output),
+			None => return Ok(())
+		}
+	}
+}
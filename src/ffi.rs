@@ -0,0 +1,64 @@
+use std::cell::RefCell;
+use std::ffi::{c_char, CStr, CString};
+use std::ptr;
+
+use crate::diagnostic::Diagnostic;
+use crate::AssembleOptions;
+
+// the C interface declared in include/evmasm.h. bytecode is handed out as a boxed slice that only
+// evmasm_free may release; a failed call returns NULL and leaves its message for evmasm_last_error
+
+thread_local! {
+	static LAST_ERROR: RefCell<Option<CString>> = const {RefCell::new(None)};
+}
+
+fn set_error(message: String) {
+	// a message with a NUL in it is cut there rather than lost
+	let message = CString::new(message).unwrap_or_else(|e| {
+		let end = e.nul_position();
+		CString::new(&e.into_vec()[..end]).unwrap()
+	});
+
+	LAST_ERROR.with(|last| *last.borrow_mut() = Some(message));
+}
+
+// safety: `source` must be a NUL-terminated string and `len` must point to writable memory
+#[no_mangle]
+#[allow(clippy::missing_safety_doc)]
+pub unsafe extern "C" fn evmasm_assemble(source: *const c_char, len: *mut usize) -> *mut u8 {
+	if source.is_null() || len.is_null() {
+		set_error("evmasm_assemble: source and len must not be NULL".to_owned());
+		return ptr::null_mut();
+	}
+
+	let Ok(source) = CStr::from_ptr(source).to_str() else {
+		set_error("evmasm_assemble: source is not UTF-8".to_owned());
+		return ptr::null_mut();
+	};
+
+	match crate::assemble(source, &AssembleOptions::default()) {
+		Ok(bytecode) => {
+			*len = bytecode.len();
+			Box::into_raw(bytecode.into_boxed_slice()) as *mut u8
+		},
+		Err(e) => {
+			set_error(Diagnostic::from(&e).render_with_source(source));
+			ptr::null_mut()
+		}
+	}
+}
+
+// safety: `bytes` and `len` must come from one evmasm_assemble call, and be freed only once
+#[no_mangle]
+#[allow(clippy::missing_safety_doc)]
+pub unsafe extern "C" fn evmasm_free(bytes: *mut u8, len: usize) {
+	if !bytes.is_null() {
+		drop(Box::from_raw(ptr::slice_from_raw_parts_mut(bytes, len)));
+	}
+}
+
+// the message of the last failed call on this thread, or NULL; it stays valid until the next failure
+#[no_mangle]
+pub extern "C" fn evmasm_last_error() -> *const c_char {
+	LAST_ERROR.with(|last| last.borrow().as_ref().map_or(ptr::null(), |message| message.as_ptr()))
+}
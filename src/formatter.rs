@@ -0,0 +1,386 @@
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use alloc::{format, vec};
+
+use crate::parser;
+use crate::tokenizer::{self, Comment, Loc, Token, TokenType};
+use crate::Error;
+
+// rewrites source in the canonical style: one command per line, function bodies on their own lines
+// indented with a tab per level, arrays on one line unless they hold a function body or a comment
+// or would not fit, single spaces between tokens and at most one blank line in a row. comments are
+// kept where they were, at the end of a line or on their own. works on tokens rather than the
+// parsed program, so numbers keep their spelling and comments survive
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Indent {
+	Tab,
+	Spaces(usize),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Functions {
+	Expanded, // the body on its own lines
+	Compact, // `{push 1 +}` on one line when it fits and holds no comment, expanded otherwise
+}
+
+#[derive(Clone, Debug)]
+pub struct Options {
+	pub indent: Indent,
+	pub max_width: usize, // columns, counting a tab as 4, before an array is split over lines
+	pub align_comments: bool, // comments ending consecutive lines start in the same column
+	pub functions: Functions,
+}
+
+impl Default for Options {
+	fn default() -> Options {
+		Options {indent: Indent::Tab, max_width: 100, align_comments: false, functions: Functions::Expanded}
+	}
+}
+
+impl Options {
+	// applies a setting by its name in the config file and on the command line
+	pub fn set(&mut self, key: &str, value: &str) -> Result<(), String> {
+		match key {
+			"indent" => self.indent = match value {
+				"tab" => Indent::Tab,
+				_ => Indent::Spaces(value.parse().map_err(|_| format!("Invalid indent, expected tab or a number of spaces: {}", value))?)
+			},
+			"max-width" => self.max_width = value.parse().map_err(|_| format!("Invalid max-width: {}", value))?,
+			"align-comments" => self.align_comments = value.parse().map_err(|_| format!("Invalid align-comments, expected true or false: {}", value))?,
+			"functions" => self.functions = match value {
+				"expanded" => Functions::Expanded,
+				"compact" => Functions::Compact,
+				_ => return Err(format!("Invalid functions, expected expanded or compact: {}", value))
+			},
+			_ => return Err(format!("Unknown formatter option: {}", key))
+		}
+
+		Ok(())
+	}
+}
+
+// an output line, and where its comment starts if it ends with one
+struct Line {
+	text: String,
+	comment: Option<usize>,
+}
+
+struct Formatter<'a> {
+	tokens: &'a [Token],
+	pos: usize,
+	comments: &'a [Comment],
+	next_comment: usize,
+	options: &'a Options,
+	lines: Vec<Line>,
+	line: Line,
+	depth: usize,
+	last_line: u64, // source line of the last token or comment written
+	opened: bool, // nothing written since a `{` or `[` ended a line, so no blank line is kept
+}
+
+fn before(comment: &Comment, loc: &Loc) -> bool {
+	(comment.loc.line, comment.loc.col) < (loc.line, loc.col)
+}
+
+fn text(typ: &TokenType) -> String {
+	match typ {
+		TokenType::String(_) => typ.to_string(),
+		// the display form of the rest is the token in backticks
+		_ => typ.to_string().trim_matches('`').to_string()
+	}
+}
+
+impl Formatter<'_> {
+	fn peek(&self) -> &TokenType {
+		&self.tokens[self.pos].typ
+	}
+
+	fn end_line(&mut self) {
+		if !self.line.text.is_empty() {
+			let line = core::mem::replace(&mut self.line, Line {text: String::new(), comment: None});
+			self.lines.push(line);
+		}
+	}
+
+	// ends the current line and keeps one blank line if the source had any before `loc`
+	fn start_line(&mut self, loc: &Loc) {
+		self.end_line();
+
+		if loc.line > self.last_line + 1 && !self.lines.is_empty() && !self.opened {
+			self.lines.push(Line {text: String::new(), comment: None});
+		}
+	}
+
+	fn write(&mut self, text: &str, line: u64) {
+		if self.line.text.is_empty() {
+			let indent = match self.options.indent {
+				Indent::Tab => "\t".repeat(self.depth),
+				Indent::Spaces(width) => " ".repeat(width * self.depth)
+			};
+			self.line.text.push_str(&indent);
+		} else if !self.line.text.trim_start().is_empty() && !self.line.text.ends_with(['[', '{']) && text != "]" && text != "}" {
+			self.line.text.push(' ');
+		}
+
+		self.line.text.push_str(text);
+		self.last_line = line;
+		self.opened = false;
+	}
+
+	// a comment after code on the current line
+	fn write_comment(&mut self, comment: &Comment) {
+		self.line.comment = Some(self.line.text.len());
+		self.write(&comment.text, comment.loc.line);
+	}
+
+	// columns the current line takes up
+	fn width(&self) -> usize {
+		self.line.text.chars().map(|c| if c == '\t' { 4 } else { 1 }).sum()
+	}
+
+	// writes the comments before `loc`: on the end of the current line if they started on the
+	// line of what it ends with, otherwise each on its own line
+	fn comments(&mut self, loc: &Loc) {
+		while let Some(comment) = self.comments.get(self.next_comment).filter(|comment| before(comment, loc)) {
+			self.next_comment += 1;
+
+			if comment.loc.line != self.last_line || self.line.text.trim().is_empty() {
+				self.start_line(&comment.loc);
+				self.write(&comment.text, comment.loc.line);
+			} else {
+				self.write_comment(comment);
+			}
+
+			self.end_line();
+		}
+	}
+
+	// the comments on the line of the last token written, before the next token
+	fn trailing_comments(&mut self) {
+		while let Some(comment) = self.comments.get(self.next_comment).filter(|comment| comment.loc.line == self.last_line && before(comment, &self.tokens[self.pos].loc)) {
+			self.next_comment += 1;
+			self.write_comment(comment);
+		}
+	}
+
+	fn token(&mut self) {
+		let token = &self.tokens[self.pos];
+		self.pos += 1;
+		self.comments(&token.loc);
+
+		// a string can span lines; the next token is on its last
+		let line = match &token.typ {
+			TokenType::String(val) => token.loc.line + val.matches('\n').count() as u64,
+			_ => token.loc.line
+		};
+
+		self.write(&text(&token.typ), line);
+	}
+
+	fn statement_start(&mut self) {
+		let loc = self.tokens[self.pos].loc.clone();
+		self.comments(&loc);
+		self.start_line(&loc);
+	}
+
+	// commands until a `}` or the end
+	fn statements(&mut self) {
+		while !matches!(self.peek(), TokenType::Eof | TokenType::RightCurly) {
+			self.statement_start();
+
+			match self.peek() {
+				TokenType::Directive(_) => self.test(),
+				TokenType::Push => {
+					self.token();
+					self.value();
+				},
+				TokenType::ILoad => {
+					self.token();
+					self.token();
+					self.value();
+				},
+				_ => self.token()
+			}
+		}
+	}
+
+	fn value(&mut self) {
+		match self.peek() {
+			TokenType::LeftCurly => self.function(),
+			TokenType::LeftSquare => self.array(),
+			_ => self.token()
+		}
+	}
+
+	fn function(&mut self) {
+		let empty = *self.peek_at(1) == TokenType::RightCurly && !self.comment_within(self.pos, self.pos + 1);
+
+		if empty || (self.options.functions == Functions::Compact && self.fits()) {
+			self.inline();
+			return;
+		}
+
+		self.token();
+		self.block(|f| f.statements());
+	}
+
+	fn peek_at(&self, offset: usize) -> &TokenType {
+		&self.tokens[self.pos + offset].typ
+	}
+
+	// the contents of a `{` or `[` just written, one line each, and the closing token on its own line
+	fn block(&mut self, contents: impl FnOnce(&mut Self)) {
+		self.trailing_comments();
+		self.end_line();
+		self.opened = true;
+		self.depth += 1;
+		contents(self);
+
+		let loc = self.tokens[self.pos].loc.clone();
+		self.comments(&loc);
+		self.depth -= 1;
+		self.end_line();
+		self.token();
+	}
+
+	// whether a comment is between the tokens at `start` and `end`
+	fn comment_within(&self, start: usize, end: usize) -> bool {
+		self.comments[self.next_comment..].iter().any(|comment| !before(comment, &self.tokens[start].loc) && before(comment, &self.tokens[end].loc))
+	}
+
+	// the index of the token closing the array or function at `start`
+	fn closing(&self, start: usize) -> usize {
+		let mut depth = 0;
+		let mut pos = start;
+
+		loop {
+			match &self.tokens[pos].typ {
+				TokenType::LeftSquare | TokenType::LeftCurly => depth += 1,
+				TokenType::RightSquare | TokenType::RightCurly => depth -= 1,
+				_ => {}
+			}
+
+			if depth == 0 {
+				return pos;
+			}
+
+			pos += 1;
+		}
+	}
+
+	// whether the array or function at the current token can be written on the current line: it
+	// holds no comment and, unless they are compact, no function body, and stays within max_width
+	fn fits(&self) -> bool {
+		let end = self.closing(self.pos);
+
+		if self.comment_within(self.pos, end) {
+			return false;
+		}
+
+		let mut width = self.width() + 1;
+
+		for pos in self.pos..=end {
+			let typ = &self.tokens[pos].typ;
+
+			if *typ == TokenType::LeftCurly && self.tokens[pos + 1].typ != TokenType::RightCurly && self.options.functions == Functions::Expanded {
+				return false;
+			}
+
+			if pos > self.pos && !matches!(self.tokens[pos - 1].typ, TokenType::LeftSquare | TokenType::LeftCurly) && !matches!(typ, TokenType::RightSquare | TokenType::RightCurly) {
+				width += 1;
+			}
+
+			width += text(typ).chars().count();
+		}
+
+		width <= self.options.max_width
+	}
+
+	// the array or function at the current token, on the current line
+	fn inline(&mut self) {
+		let end = self.closing(self.pos);
+
+		while self.pos <= end {
+			self.token();
+		}
+	}
+
+	fn array(&mut self) {
+		if self.fits() {
+			self.inline();
+			return;
+		}
+
+		self.token();
+		self.block(|f| while *f.peek() != TokenType::RightSquare {
+			f.statement_start();
+			f.value();
+		});
+	}
+
+	// `.test "name" { ... }`, with `.expect [ ... ]` after the closing brace
+	fn test(&mut self) {
+		self.token();
+		self.token();
+		self.function();
+
+		if *self.peek() == TokenType::Directive(".expect".to_string()) {
+			self.token();
+			self.value();
+		}
+	}
+}
+
+// the source in the canonical style; source that does not parse is an error, as it would be for
+// the assembler
+pub fn format(source: &str, filename: &str) -> Result<String, Error> {
+	format_with(source, filename, &Options::default())
+}
+
+pub fn format_with(source: &str, filename: &str, options: &Options) -> Result<String, Error> {
+	let (tokens, comments) = tokenizer::tokenize_with_comments(source, filename)?;
+	parser::parse_with_tests(tokens.clone())?;
+
+	let mut formatter = Formatter {
+		tokens: &tokens,
+		pos: 0,
+		comments: &comments,
+		next_comment: 0,
+		options,
+		lines: vec![],
+		line: Line {text: String::new(), comment: None},
+		depth: 0,
+		last_line: 0,
+		opened: true,
+	};
+
+	formatter.statements();
+
+	// the end-of-file token can be left of a comment on the last line, so this takes every comment
+	let eof = tokens[formatter.pos].loc.clone();
+	formatter.comments(&Loc {line: eof.line + 1, col: 0, ..eof});
+	formatter.end_line();
+
+	let mut lines = formatter.lines;
+
+	if options.align_comments {
+		align_comments(&mut lines);
+	}
+
+	Ok(lines.iter().map(|line| format!("{}\n", line.text)).collect())
+}
+
+// pads the code before each run of comments ending consecutive lines to the longest in the run
+fn align_comments(lines: &mut [Line]) {
+	for run in lines.chunk_by_mut(|a, b| a.comment.is_some() == b.comment.is_some()) {
+		let width = |line: &Line, start: usize| line.text[..start].chars().map(|c| if c == '\t' { 4 } else { 1 }).sum::<usize>();
+		let Some(column) = run.iter().filter_map(|line| line.comment.map(|start| width(line, start))).max() else { continue };
+
+		for line in run {
+			let Some(start) = line.comment else { continue };
+			let padding = " ".repeat(column - width(line, start));
+			line.text.insert_str(start, &padding);
+		}
+	}
+}
@@ -0,0 +1,129 @@
+use alloc::string::ToString;
+use alloc::vec::Vec;
+use alloc::vec;
+
+use crate::tokenizer::{self, Loc, TokenType, TokenizeError};
+
+// what each piece of the source is, for highlighting it: in --emit html, in the LSP's semantic
+// tokens and in editor plugins. the language has no constants, macros or labels besides nil, true
+// and false and the .test and .expect directives, which are Constant and Directive
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Kind {
+	Keyword, // a command
+	Number,
+	String,
+	Comment, // including a shebang
+	Constant, // nil, true and false
+	Directive,
+	Punctuation, // the brackets of arrays and functions
+}
+
+pub const KINDS: &[Kind] = &[Kind::Keyword, Kind::Number, Kind::String, Kind::Comment, Kind::Constant, Kind::Directive, Kind::Punctuation];
+
+impl Kind {
+	pub fn name(self) -> &'static str {
+		match self {
+			Kind::Keyword => "keyword",
+			Kind::Number => "number",
+			Kind::String => "string",
+			Kind::Comment => "comment",
+			Kind::Constant => "constant",
+			Kind::Directive => "directive",
+			Kind::Punctuation => "punctuation"
+		}
+	}
+}
+
+// `len` characters from `loc`, all on its line; a string over several lines is a piece per line
+#[derive(Clone, Debug)]
+pub struct Piece {
+	pub loc: Loc,
+	pub len: usize,
+	pub kind: Kind,
+}
+
+fn kind(typ: &TokenType) -> Option<Kind> {
+	Some(match typ {
+		TokenType::Eof => return None,
+		TokenType::Number(_) => Kind::Number,
+		TokenType::String(_) => Kind::String,
+		TokenType::Nil | TokenType::Boolean(_) => Kind::Constant,
+		TokenType::Directive(_) => Kind::Directive,
+		TokenType::LeftSquare | TokenType::RightSquare | TokenType::LeftCurly | TokenType::RightCurly => Kind::Punctuation,
+		_ => Kind::Keyword
+	})
+}
+
+// characters the token takes up in the source
+fn len(typ: &TokenType) -> usize {
+	match typ {
+		TokenType::String(val) => val.chars().count() + 2,
+		typ => typ.to_string().trim_matches('`').chars().count()
+	}
+}
+
+// the start and end of each line, in characters, as the tokenizer counts lines: \r\n and a lone
+// \r each end one
+pub(crate) fn lines(chars: &[char]) -> Vec<(usize, usize)> {
+	let mut lines = vec![];
+	let mut start = 0;
+	let mut i = 0;
+
+	while i < chars.len() {
+		if chars[i] == '\n' || chars[i] == '\r' {
+			lines.push((start, i));
+
+			if chars[i] == '\r' && chars.get(i + 1) == Some(&'\n') {
+				i += 1;
+			}
+
+			start = i + 1;
+		}
+
+		i += 1;
+	}
+
+	lines.push((start, chars.len()));
+	lines
+}
+
+// every token and comment in `source`, in order
+pub fn classify(source: &str, filename: &str) -> Result<Vec<Piece>, TokenizeError> {
+	let (tokens, comments) = tokenizer::tokenize_with_comments(source, filename)?;
+	let chars: Vec<char> = source.chars().collect();
+	let lines = lines(&chars);
+
+	let mut spans: Vec<(Loc, usize, Kind)> = tokens.into_iter().filter_map(|token| Some((token.loc, len(&token.typ), kind(&token.typ)?)))
+		.chain(comments.into_iter().map(|comment| (comment.loc, comment.text.chars().count(), Kind::Comment))).collect();
+	spans.sort_by_key(|(loc, ..)| (loc.line, loc.col));
+
+	let mut pieces = vec![];
+
+	for (loc, mut len, kind) in spans {
+		let mut line = loc.line;
+		let mut col = loc.col;
+
+		// split where the span runs past the end of its line
+		loop {
+			let (start, end) = lines[line as usize - 1];
+			let on_line = len.min(end - (start + col as usize - 1));
+
+			// a blank line inside a string has nothing to highlight
+			if on_line > 0 {
+				pieces.push(Piece {loc: Loc {line, col, filename: loc.filename.clone()}, len: on_line, kind});
+			}
+
+			if on_line == len || line as usize == lines.len() {
+				break;
+			}
+
+			// the line break is one or two characters
+			len -= on_line + lines[line as usize].0 - end;
+			line += 1;
+			col = 1;
+		}
+	}
+
+	Ok(pieces)
+}
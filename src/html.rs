@@ -0,0 +1,102 @@
+use alloc::string::String;
+use alloc::vec::Vec;
+use alloc::vec;
+use core::fmt::Write;
+
+use crate::highlight::{self, Kind};
+use crate::tokenizer::TokenizeError;
+
+// the source as a standalone HTML page: each token in a span whose class says what it is, and
+// each line with an id, L1 and up, and a link to itself, so a review can point at #L12
+
+const STYLE: &str = "body { margin: 0; background: #fff; color: #24292f; }
+pre { margin: 0; padding: 1em 0; font: 14px/1.5 ui-monospace, monospace; tab-size: 4; }
+.line { display: block; padding-right: 1em; }
+.line:target { background: #fff8c5; }
+.ln { display: inline-block; width: 4em; padding-right: 1em; text-align: right; color: #8c959f; text-decoration: none; user-select: none; }
+.op { color: #0550ae; font-weight: bold; }
+.num { color: #0a3069; }
+.str { color: #116329; }
+.lit { color: #8250df; }
+.dir { color: #953800; }
+.punct { color: #57606a; }
+.com { color: #6e7781; font-style: italic; }
+";
+
+fn class(kind: Kind) -> &'static str {
+	match kind {
+		Kind::Keyword => "op",
+		Kind::Number => "num",
+		Kind::String => "str",
+		Kind::Comment => "com",
+		Kind::Constant => "lit",
+		Kind::Directive => "dir",
+		Kind::Punctuation => "punct"
+	}
+}
+
+pub(crate) fn escape(out: &mut String, c: char) {
+	match c {
+		'&' => out.push_str("&amp;"),
+		'<' => out.push_str("&lt;"),
+		'>' => out.push_str("&gt;"),
+		'"' => out.push_str("&quot;"),
+		c => out.push(c)
+	}
+}
+
+pub fn html(source: &str, filename: &str) -> Result<String, TokenizeError> {
+	let pieces = highlight::classify(source, filename)?;
+	let chars: Vec<char> = source.chars().collect();
+	let lines = highlight::lines(&chars);
+
+	// the class of every character
+	let mut classes: Vec<Option<&str>> = vec![None; chars.len()];
+
+	for piece in pieces {
+		let start = lines[piece.loc.line as usize - 1].0 + piece.loc.col as usize - 1;
+
+		for slot in &mut classes[start..start + piece.len] {
+			*slot = Some(class(piece.kind));
+		}
+	}
+
+	let mut out = String::from("<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>");
+	filename.chars().for_each(|c| escape(&mut out, c));
+	let _ = write!(out, "</title>\n<style>\n{}</style>\n</head>\n<body>\n<pre><code>", STYLE);
+
+	for (n, (start, end)) in lines.iter().enumerate() {
+		// the line after the last line break is only a line if it has something on it
+		if n + 1 == lines.len() && start == end && n > 0 {
+			break;
+		}
+
+		let _ = write!(out, "<span class=\"line\" id=\"L{0}\"><a class=\"ln\" href=\"#L{0}\">{0}</a>", n + 1);
+		let mut open = None;
+
+		for i in *start..*end {
+			if classes[i] != open {
+				if open.is_some() {
+					out.push_str("</span>");
+				}
+
+				if let Some(class) = classes[i] {
+					let _ = write!(out, "<span class=\"{}\">", class);
+				}
+
+				open = classes[i];
+			}
+
+			escape(&mut out, chars[i]);
+		}
+
+		if open.is_some() {
+			out.push_str("</span>");
+		}
+
+		out.push_str("</span>");
+	}
+
+	out.push_str("</code></pre>\n</body>\n</html>\n");
+	Ok(out)
+}
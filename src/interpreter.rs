@@ -0,0 +1,1119 @@
+use regex::Regex;
+use serde_json::{json, Value as Json};
+use std::collections::{HashMap, VecDeque};
+use std::fmt;
+use std::rc::Rc;
+use thiserror::Error;
+
+use crate::codegen;
+use crate::parser::{self, Command};
+use crate::tokenizer::Loc;
+
+#[derive(Clone)]
+pub enum Value {
+	Nil,
+	Number(f64),
+	String(String),
+	Boolean(bool),
+	Function(Rc<Function>),
+	Array(Vec<Value>)
+}
+
+pub struct Function {
+	pub start: usize, // id the first command got when lowered, which identifies the function in snapshots
+	pub code: Vec<Instr>,
+}
+
+pub struct Instr {
+	pub id: usize, // position of the command in a pre-order walk of the program
+	pub command: Command,
+	pub operand: Value, // lowered operand of push and iload, nil otherwise
+}
+
+impl PartialEq for Value {
+	fn eq(&self, other: &Value) -> bool {
+		match (self, other) {
+			(Value::Nil, Value::Nil) => true,
+			(Value::Number(a), Value::Number(b)) => a == b,
+			(Value::String(a), Value::String(b)) => a == b,
+			(Value::Boolean(a), Value::Boolean(b)) => a == b,
+			(Value::Function(a), Value::Function(b)) => Rc::ptr_eq(a, b),
+			(Value::Array(a), Value::Array(b)) => a == b,
+			_ => false
+		}
+	}
+}
+
+impl Value {
+	pub fn from_ast(value: &parser::Value, next_id: &mut usize) -> Value {
+		match value {
+			parser::Value::Nil => Value::Nil,
+			parser::Value::Number(val) => Value::Number(*val),
+			parser::Value::String(val) => Value::String(val.clone()),
+			parser::Value::Boolean(val) => Value::Boolean(*val),
+			parser::Value::Function(commands) => Value::Function(lower_from(commands, next_id)),
+			parser::Value::Array(values) => Value::Array(values.iter().map(|value| Value::from_ast(value, next_id)).collect())
+		}
+	}
+
+	pub fn to_ast(&self) -> parser::Value {
+		match self {
+			Value::Nil => parser::Value::Nil,
+			Value::Number(val) => parser::Value::Number(*val),
+			Value::String(val) => parser::Value::String(val.clone()),
+			Value::Boolean(val) => parser::Value::Boolean(*val),
+			Value::Function(function) => parser::Value::Function(function.code.iter().map(|instr| instr.command.clone()).collect()),
+			Value::Array(values) => parser::Value::Array(values.iter().map(Value::to_ast).collect())
+		}
+	}
+
+	pub fn type_name(&self) -> &'static str {
+		match self {
+			Value::Nil         => "nil",
+			Value::Number(_)   => "number",
+			Value::String(_)   => "string",
+			Value::Boolean(_)  => "boolean",
+			Value::Function(_) => "function",
+			Value::Array(_)    => "array"
+		}
+	}
+
+	// nil and false are false, everything else is true
+	pub fn truthy(&self) -> bool {
+		!matches!(self, Value::Nil | Value::Boolean(false))
+	}
+}
+
+impl fmt::Display for Value {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		write!(f, "{}", self.to_ast())
+	}
+}
+
+// conversions for hosts and embedders building values to push or return
+impl From<f64> for Value {
+	fn from(val: f64) -> Value {
+		Value::Number(val)
+	}
+}
+
+impl From<bool> for Value {
+	fn from(val: bool) -> Value {
+		Value::Boolean(val)
+	}
+}
+
+impl From<&str> for Value {
+	fn from(val: &str) -> Value {
+		Value::String(val.to_owned())
+	}
+}
+
+impl From<String> for Value {
+	fn from(val: String) -> Value {
+		Value::String(val)
+	}
+}
+
+impl<T: Into<Value>> From<Vec<T>> for Value {
+	fn from(vals: Vec<T>) -> Value {
+		Value::Array(vals.into_iter().map(Into::into).collect())
+	}
+}
+
+impl<T: Into<Value>> From<Option<T>> for Value {
+	fn from(val: Option<T>) -> Value {
+		val.map_or(Value::Nil, Into::into)
+	}
+}
+
+fn lower_from(commands: &[Command], next_id: &mut usize) -> Rc<Function> {
+	let start = *next_id;
+	let mut code = vec![];
+
+	for command in commands {
+		let id = *next_id;
+		*next_id += 1;
+
+		let operand = match command {
+			Command::Push(value) | Command::ILoad(_, value) => Value::from_ast(value, next_id),
+			_ => Value::Nil
+		};
+
+		code.push(Instr {id, command: command.clone(), operand});
+	}
+
+	Rc::new(Function {start, code})
+}
+
+// prepares a program for execution, numbering every command (including those in nested
+// function values) in the order they appear in the source
+pub fn lower(commands: &[Command]) -> Rc<Function> {
+	lower_from(commands, &mut 0)
+}
+
+#[derive(Debug, Error)]
+#[error("{message} in command {command}")]
+pub struct RuntimeError {
+	pub message: String,
+	pub command: usize,
+	pub backtrace: Vec<usize>, // commands that made the calls and loops enclosing `command`, innermost first
+}
+
+impl RuntimeError {
+	// the error and its backtrace as a diagnostic, with snippets of `source` when known
+	pub fn report(&self, locs: &[Loc], source: Option<&str>) -> String {
+		let diagnostic = self.diagnostic(locs);
+
+		match source {
+			Some(source) => diagnostic.render_with_source(source),
+			None => diagnostic.render()
+		}
+	}
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum LoopKind {
+	Each,
+	Map,
+	Filter,
+	Reduce
+}
+
+#[derive(Clone)]
+enum Frame {
+	Code {function: Rc<Function>, ip: usize},
+	// an each/map/filter/reduce in progress; `index` items have been handed to the function so far
+	Loop {kind: LoopKind, id: usize, function: Rc<Function>, items: Vec<Value>, index: usize, results: Vec<Value>}
+}
+
+// bytes of string and array storage a value holds
+pub fn value_bytes(value: &Value) -> usize {
+	match value {
+		Value::String(val) => val.len(),
+		Value::Array(vals) => values_bytes(vals),
+		_ => 0
+	}
+}
+
+fn values_bytes(vals: &[Value]) -> usize {
+	std::mem::size_of_val(vals) + vals.iter().map(value_bytes).sum::<usize>()
+}
+
+// the environment `query` and `info` read from
+pub trait Host {
+	// result of `query` for a selector string, usually an array of items
+	fn query(&mut self, selector: &str) -> Result<Value, String>;
+
+	// result of `info`: the property `key` of an item previously returned by query
+	fn info(&mut self, item: &Value, key: &str) -> Result<Value, String>;
+
+	// writes a line for `print`
+	fn print(&mut self, _text: &str) -> Result<(), String> {
+		Err("Console output is not available on this host".to_owned())
+	}
+
+	// reads a line for `readln`, None at the end of input
+	fn read_line(&mut self) -> Result<Option<String>, String> {
+		Err("Console input is not available on this host".to_owned())
+	}
+}
+
+// host with no data: every query finds nothing and every property is nil
+pub struct StubHost;
+
+impl Host for StubHost {
+	fn query(&mut self, _selector: &str) -> Result<Value, String> {
+		Ok(Value::Array(vec![]))
+	}
+
+	fn info(&mut self, _item: &Value, _key: &str) -> Result<Value, String> {
+		Ok(Value::Nil)
+	}
+}
+
+// stub host that also provides print and readln through stdin and stdout
+pub struct ConsoleHost;
+
+impl Host for ConsoleHost {
+	fn query(&mut self, selector: &str) -> Result<Value, String> {
+		StubHost.query(selector)
+	}
+
+	fn info(&mut self, item: &Value, key: &str) -> Result<Value, String> {
+		StubHost.info(item, key)
+	}
+
+	fn print(&mut self, text: &str) -> Result<(), String> {
+		println!("{}", text);
+		Ok(())
+	}
+
+	fn read_line(&mut self) -> Result<Option<String>, String> {
+		let mut line = String::new();
+
+		match std::io::stdin().read_line(&mut line) {
+			Ok(0) => Ok(None),
+			Ok(_) => Ok(Some(line.trim_end_matches(['\r', '\n']).to_owned())),
+			Err(e) => Err(format!("Cannot read input: {}", e))
+		}
+	}
+}
+
+// host operations a sandboxed program may use
+#[derive(Clone, Copy, Default)]
+pub struct Capabilities {
+	pub query: bool, // query and info
+	pub io: bool, // print and readln
+}
+
+impl Capabilities {
+	pub const NAMES: &'static [&'static str] = &["query", "io"];
+
+	// enables a capability by name, or every capability for "all"
+	pub fn allow(&mut self, name: &str) -> Result<(), String> {
+		match name {
+			"query" => self.query = true,
+			"io" => self.io = true,
+			"all" => *self = Capabilities {query: true, io: true},
+			_ => return Err(format!("Unknown capability: {} (expected {} or all)", name, Capabilities::NAMES.join(", ")))
+		}
+
+		Ok(())
+	}
+}
+
+// passes host operations through to `host` only when their capability is allowed
+pub struct Sandbox {
+	pub host: Box<dyn Host>,
+	pub allow: Capabilities,
+}
+
+fn denied(capability: &str, command: &str) -> String {
+	format!("`{}` needs the {} capability, which this program was not given (--allow {})", command, capability, capability)
+}
+
+impl Host for Sandbox {
+	fn query(&mut self, selector: &str) -> Result<Value, String> {
+		if !self.allow.query {
+			return Err(denied("query", "query"));
+		}
+
+		self.host.query(selector)
+	}
+
+	fn info(&mut self, item: &Value, key: &str) -> Result<Value, String> {
+		if !self.allow.query {
+			return Err(denied("query", "info"));
+		}
+
+		self.host.info(item, key)
+	}
+
+	fn print(&mut self, text: &str) -> Result<(), String> {
+		if !self.allow.io {
+			return Err(denied("io", "print"));
+		}
+
+		self.host.print(text)
+	}
+
+	fn read_line(&mut self) -> Result<Option<String>, String> {
+		if !self.allow.io {
+			return Err(denied("io", "readln"));
+		}
+
+		self.host.read_line()
+	}
+}
+
+#[derive(Clone, Copy, Default)]
+pub struct Limits {
+	pub max_stack: Option<usize>, // values on the stack
+	pub max_memory: Option<usize>, // bytes of string and array storage on the stack, in registers and in running loops
+	pub max_depth: Option<usize>, // nested function calls and loops
+}
+
+// what a step changed, enough to put the state back as it was before it: the stack is cut back to
+// `low` values and the values popped from below that are pushed back
+struct Undo {
+	low: usize,
+	popped: Vec<Value>,
+	registers: Vec<(usize, Value)>,
+	frames: Vec<FrameOp>, // undone last first, so a step costs what it changed rather than a copy of every frame
+	memory: usize,
+	steps: u64,
+	fuel: Option<u64>,
+}
+
+// a change to the frames, as Undo records it
+enum FrameOp {
+	Pushed,
+	Loaded, // a program queued under the frames
+	Popped(Frame),
+	Advanced, // the top frame's ip
+	Indexed, // the top loop's index
+	Collected, // a result added to the top loop's
+}
+
+pub struct Vm {
+	pub stack: Vec<Value>,
+	pub registers: Vec<Value>,
+	pub limits: Limits,
+	pub host: Box<dyn Host>,
+	memory: usize,
+	pub fuel: Option<u64>, // commands left to execute before the program is stopped, None for no limit
+	pub steps: u64, // commands executed so far
+	frames: Vec<Frame>,
+	pub history_limit: usize, // steps step_back can undo, 0 to keep no history
+	history: VecDeque<Undo>,
+}
+
+impl Default for Vm {
+	fn default() -> Vm {
+		Vm::new(StubHost)
+	}
+}
+
+impl Vm {
+	// a VM with an empty stack and registers; programs are queued with load and started with run:
+	// `Vm::new(host).load(lower(&commands)).run()`
+	pub fn new(host: impl Host + 'static) -> Vm {
+		Vm {
+			stack: vec![],
+			registers: vec![Value::Nil; codegen::REGISTER_COUNT as usize],
+			limits: Limits::default(),
+			host: Box::new(host),
+			memory: 0,
+			fuel: None,
+			steps: 0,
+			frames: vec![],
+			history_limit: 0,
+			history: VecDeque::new()
+		}
+	}
+
+	// queues a program to run on the current stack and registers, after those loaded before it
+	pub fn load(&mut self, function: Rc<Function>) -> &mut Vm {
+		self.memory = self.measure();
+		self.frames.insert(0, Frame::Code {function, ip: 0});
+		self.log(FrameOp::Loaded);
+		self
+	}
+
+	// bytes of storage held on the stack, in registers and by loops, as `memory` counts them. the
+	// stack and registers are public, so an embedder may have changed them since the last step
+	fn measure(&self) -> usize {
+		let frames = self.frames.iter().map(|frame| match frame {
+			Frame::Loop {items, results, ..} => values_bytes(items) + values_bytes(results),
+			Frame::Code {..} => 0
+		});
+
+		self.stack.iter().chain(&self.registers).map(value_bytes).chain(frames).sum()
+	}
+
+	fn push(&mut self, val: Value) {
+		self.memory += value_bytes(&val);
+		self.stack.push(val);
+	}
+
+	fn pop(&mut self) -> Result<Value, String> {
+		let val = self.stack.pop().ok_or("Stack underflow".to_owned())?;
+		self.memory = self.memory.saturating_sub(value_bytes(&val));
+
+		if let Some(undo) = self.history.back_mut() {
+			if self.stack.len() < undo.low {
+				undo.low = self.stack.len();
+				undo.popped.push(val.clone());
+			}
+		}
+
+		Ok(val)
+	}
+
+	fn set_register(&mut self, reg: usize, val: Value) {
+		let old = std::mem::replace(&mut self.registers[reg], val);
+		self.memory = self.memory.saturating_sub(value_bytes(&old)) + value_bytes(&self.registers[reg]);
+
+		if let Some(undo) = self.history.back_mut() {
+			undo.registers.push((reg, old));
+		}
+	}
+
+	// starts recording what the next step changes
+	fn record(&mut self) {
+		if self.history_limit == 0 {
+			return;
+		}
+
+		if self.history.len() == self.history_limit {
+			self.history.pop_front();
+		}
+
+		self.history.push_back(Undo {
+			low: self.stack.len(),
+			popped: vec![],
+			registers: vec![],
+			frames: vec![],
+			memory: self.memory,
+			steps: self.steps,
+			fuel: self.fuel
+		});
+	}
+
+	fn log(&mut self, op: FrameOp) {
+		if let Some(undo) = self.history.back_mut() {
+			undo.frames.push(op);
+		}
+	}
+
+	// puts the state back as it was before the last step; host effects such as printed output stay.
+	// Returns false when there is no history left
+	pub fn step_back(&mut self) -> bool {
+		let Some(undo) = self.history.pop_back() else {
+			return false;
+		};
+
+		self.stack.truncate(undo.low);
+		self.stack.extend(undo.popped.into_iter().rev());
+
+		for (reg, val) in undo.registers.into_iter().rev() {
+			self.registers[reg] = val;
+		}
+
+		for op in undo.frames.into_iter().rev() {
+			match (op, self.frames.last_mut()) {
+				(FrameOp::Pushed, _) => {
+					self.frames.pop();
+				},
+				(FrameOp::Loaded, _) => {
+					self.frames.remove(0);
+				},
+				(FrameOp::Popped(frame), _) => self.frames.push(frame),
+				(FrameOp::Advanced, Some(Frame::Code {ip, ..})) => *ip -= 1,
+				(FrameOp::Indexed, Some(Frame::Loop {index, ..})) => *index -= 1,
+				(FrameOp::Collected, Some(Frame::Loop {results, ..})) => {
+					results.pop();
+				},
+				_ => unreachable!()
+			}
+		}
+
+		self.memory = undo.memory;
+		self.steps = undo.steps;
+		self.fuel = undo.fuel;
+		true
+	}
+
+	fn pop_number(&mut self) -> Result<f64, String> {
+		match self.pop()? {
+			Value::Number(val) => Ok(val),
+			x => Err(format!("Expected number, got {}", x.type_name()))
+		}
+	}
+
+	fn pop_string(&mut self) -> Result<String, String> {
+		match self.pop()? {
+			Value::String(val) => Ok(val),
+			x => Err(format!("Expected string, got {}", x.type_name()))
+		}
+	}
+
+	fn pop_boolean(&mut self) -> Result<bool, String> {
+		match self.pop()? {
+			Value::Boolean(val) => Ok(val),
+			x => Err(format!("Expected boolean, got {}", x.type_name()))
+		}
+	}
+
+	fn pop_array(&mut self) -> Result<Vec<Value>, String> {
+		match self.pop()? {
+			Value::Array(val) => Ok(val),
+			x => Err(format!("Expected array, got {}", x.type_name()))
+		}
+	}
+
+	fn pop_register(&mut self) -> Result<usize, String> {
+		let reg = self.pop_number()?;
+
+		if reg.fract() != 0.0 || !(0.0..codegen::REGISTER_COUNT as f64).contains(&reg) {
+			return Err(format!("Register must be between 0-{}: {}", codegen::REGISTER_COUNT - 1, reg));
+		}
+
+		Ok(reg as usize)
+	}
+
+	// pops a register index and returns the function stored in that register
+	fn pop_function(&mut self) -> Result<Rc<Function>, String> {
+		let reg = self.pop_register()?;
+
+		match &self.registers[reg] {
+			Value::Function(function) => Ok(function.clone()),
+			x => Err(format!("Register {} holds {}, not a function", reg, x.type_name()))
+		}
+	}
+
+	fn call(&mut self, function: Rc<Function>) {
+		event!(TRACE, function = function.start, depth = self.frames.len(), "call");
+		self.frames.push(Frame::Code {function, ip: 0});
+		self.log(FrameOp::Pushed);
+	}
+
+	fn start_loop(&mut self, kind: LoopKind, id: usize) -> Result<(), String> {
+		let initial = if kind == LoopKind::Reduce {Some(self.pop()?)} else {None};
+		let function = self.pop_function()?;
+		let items = self.pop_array()?;
+
+		if let Some(initial) = initial {
+			self.push(initial);
+		}
+
+		// the items and results of a loop are held until it finishes
+		self.memory += values_bytes(&items);
+		self.frames.push(Frame::Loop {kind, id, function, items, index: 0, results: vec![]});
+		self.log(FrameOp::Pushed);
+		self.continue_loop(false)
+	}
+
+	// runs when a loop frame is on top: collects the result of the last call (if any) and
+	// starts the next one, or finishes the loop
+	fn continue_loop(&mut self, returned: bool) -> Result<(), String> {
+		let result = match self.frames.last() {
+			Some(Frame::Loop {kind: LoopKind::Map | LoopKind::Filter, ..}) if returned => Some(self.pop()?),
+			_ => None
+		};
+
+		let Some(Frame::Loop {kind, items, index, results, ..}) = self.frames.last_mut() else {unreachable!()};
+		let kind = *kind;
+
+		let kept = result.and_then(|result| match kind {
+			LoopKind::Map => Some(result),
+			_ => result.truthy().then(|| items[*index - 1].clone())
+		});
+
+		if let Some(kept) = kept {
+			self.memory += std::mem::size_of::<Value>() + value_bytes(&kept);
+			results.push(kept);
+			self.log(FrameOp::Collected);
+		}
+
+		let Some(Frame::Loop {function, items, index, ..}) = self.frames.last_mut() else {unreachable!()};
+
+		if *index < items.len() {
+			let item = items[*index].clone();
+			let function = function.clone();
+			*index += 1;
+
+			self.log(FrameOp::Indexed);
+			self.push(item);
+			self.call(function);
+		} else {
+			let Some(mut frame) = self.frames.pop() else {unreachable!()};
+			let Frame::Loop {items, results, ..} = &mut frame else {unreachable!()};
+			self.memory = self.memory.saturating_sub(values_bytes(items) + values_bytes(results));
+
+			// the results stay with the frame while step_back may need it
+			let results = if self.history.is_empty() { std::mem::take(results) } else { results.clone() };
+			self.log(FrameOp::Popped(frame));
+
+			if kind == LoopKind::Map || kind == LoopKind::Filter {
+				self.push(Value::Array(results));
+			}
+		}
+
+		Ok(())
+	}
+
+	fn binary_number(&mut self, op: fn(f64, f64) -> f64) -> Result<(), String> {
+		let b = self.pop_number()?;
+		let a = self.pop_number()?;
+		self.push(Value::Number(op(a, b)));
+		Ok(())
+	}
+
+	fn compare(&mut self, op: fn(std::cmp::Ordering) -> bool) -> Result<(), String> {
+		let b = self.pop()?;
+		let a = self.pop()?;
+
+		let ordering = match (&a, &b) {
+			(Value::Number(a), Value::Number(b)) => a.partial_cmp(b),
+			(Value::String(a), Value::String(b)) => Some(a.cmp(b)),
+			_ => return Err(format!("Cannot compare {} with {}", a.type_name(), b.type_name()))
+		};
+
+		self.push(Value::Boolean(ordering.is_some_and(op)));
+		Ok(())
+	}
+
+	fn execute(&mut self, instr: &Instr) -> Result<(), String> {
+		match &instr.command {
+			Command::Push(_) => self.push(instr.operand.clone()),
+			Command::ILoad(reg, _) => {
+				self.set_register(*reg as usize, instr.operand.clone());
+			},
+			Command::Dup => {
+				let val = self.pop()?;
+				self.push(val.clone());
+				self.push(val);
+			},
+			Command::Swap => {
+				let b = self.pop()?;
+				let a = self.pop()?;
+				self.push(b);
+				self.push(a);
+			},
+			Command::Load => {
+				let reg = self.pop_register()?;
+				let val = self.registers[reg].clone();
+				self.push(val);
+			},
+			Command::Drop => {
+				self.pop()?;
+			},
+			Command::Query => {
+				let selector = self.pop_string()?;
+				let result = self.host.query(&selector)?;
+				self.push(result);
+			},
+			Command::Info => {
+				let key = self.pop_string()?;
+				let item = self.pop()?;
+				let result = self.host.info(&item, &key)?;
+				self.push(result);
+			},
+			Command::If => {
+				let function = self.pop_function()?;
+
+				if self.pop()?.truthy() {
+					self.call(function);
+				}
+			},
+			Command::Call => {
+				let function = self.pop_function()?;
+				self.call(function);
+			},
+			Command::Each => self.start_loop(LoopKind::Each, instr.id)?,
+			Command::Map => self.start_loop(LoopKind::Map, instr.id)?,
+			Command::Filter => self.start_loop(LoopKind::Filter, instr.id)?,
+			Command::Reduce => self.start_loop(LoopKind::Reduce, instr.id)?,
+			Command::Reverse => {
+				let reversed = match self.pop()? {
+					Value::Array(mut vals) => {
+						vals.reverse();
+						Value::Array(vals)
+					},
+					Value::String(val) => Value::String(val.chars().rev().collect()),
+					x => return Err(format!("Cannot reverse {}", x.type_name()))
+				};
+				self.push(reversed);
+			},
+			Command::ToStr => {
+				let string = match self.pop()? {
+					Value::String(val) => val,
+					x => x.to_string()
+				};
+				self.push(Value::String(string));
+			},
+			Command::ToNum => {
+				let number = match self.pop()? {
+					Value::Number(val) => Value::Number(val),
+					Value::String(val) => val.trim().parse().map(Value::Number).unwrap_or(Value::Nil),
+					Value::Boolean(val) => Value::Number(val as u8 as f64),
+					_ => Value::Nil
+				};
+				self.push(number);
+			},
+			Command::Add => self.binary_number(|a, b| a + b)?,
+			Command::Sub => self.binary_number(|a, b| a - b)?,
+			Command::Mul => self.binary_number(|a, b| a * b)?,
+			Command::Div | Command::Mod => {
+				let b = self.pop_number()?;
+				let a = self.pop_number()?;
+
+				if b == 0.0 {
+					return Err("Division by zero".to_owned());
+				}
+
+				self.push(Value::Number(if instr.command == Command::Div {a / b} else {a % b}));
+			},
+			Command::Eq | Command::NotEq => {
+				let b = self.pop()?;
+				let a = self.pop()?;
+				self.push(Value::Boolean((a == b) == (instr.command == Command::Eq)));
+			},
+			Command::Greater => self.compare(|o| o.is_gt())?,
+			Command::GreaterEq => self.compare(|o| o.is_ge())?,
+			Command::Less => self.compare(|o| o.is_lt())?,
+			Command::LessEq => self.compare(|o| o.is_le())?,
+			Command::And | Command::Or => {
+				let b = self.pop_boolean()?;
+				let a = self.pop_boolean()?;
+				self.push(Value::Boolean(if instr.command == Command::And {a && b} else {a || b}));
+			},
+			Command::Not => {
+				let val = self.pop_boolean()?;
+				self.push(Value::Boolean(!val));
+			},
+			Command::Concat => {
+				let b = self.pop()?;
+				let a = self.pop()?;
+
+				let joined = match (a, b) {
+					(Value::String(a), Value::String(b)) => Value::String(a + &b),
+					(Value::Array(mut a), Value::Array(b)) => {
+						a.extend(b);
+						Value::Array(a)
+					},
+					(a, b) => return Err(format!("Cannot concat {} with {}", a.type_name(), b.type_name()))
+				};
+				self.push(joined);
+			},
+			Command::Match => {
+				let pattern = self.pop_string()?;
+				let string = self.pop_string()?;
+				let regex = Regex::new(&pattern).map_err(|e| format!("Invalid pattern: {}", e))?;
+				self.push(Value::Boolean(regex.is_match(&string)));
+			},
+			Command::Split => {
+				let separator = self.pop_string()?;
+				let string = self.pop_string()?;
+				self.push(Value::Array(string.split(separator.as_str()).map(|s| Value::String(s.to_owned())).collect()));
+			},
+			Command::Print => {
+				let text = match self.pop()? {
+					Value::String(val) => val,
+					x => x.to_string()
+				};
+				self.host.print(&text)?;
+			},
+			Command::ReadLine => {
+				let line = self.host.read_line()?.map(Value::String).unwrap_or(Value::Nil);
+				self.push(line);
+			},
+			Command::Assert => {
+				let value = self.pop()?;
+
+				if !value.truthy() {
+					return Err(format!("Assertion failed: got {}", value));
+				}
+			},
+			Command::AssertEq => {
+				let expected = self.pop()?;
+				let actual = self.pop()?;
+
+				if actual != expected {
+					return Err(format!("Assertion failed: expected {}, got {}", expected, actual));
+				}
+			},
+			Command::Iota => {
+				let n = self.pop_number()?;
+
+				if n.fract() != 0.0 || n < 0.0 {
+					return Err(format!("Iota needs a non-negative integer: {}", n));
+				}
+
+				// refuse before allocating rather than after
+				if let Some(max) = self.limits.max_memory {
+					if self.memory as f64 + n * std::mem::size_of::<Value>() as f64 > max as f64 {
+						return Err(format!("Memory limit of {} bytes exceeded", max));
+					}
+				}
+
+				self.push(Value::Array((0..n as u64).map(|i| Value::Number(i as f64)).collect()));
+			}
+		}
+
+		Ok(())
+	}
+
+	fn check_limits(&self) -> Result<(), String> {
+		if self.limits.max_stack.is_some_and(|max| self.stack.len() > max) {
+			return Err(format!("Stack limit of {} values exceeded", self.limits.max_stack.unwrap()));
+		}
+
+		if self.limits.max_memory.is_some_and(|max| self.memory > max) {
+			return Err(format!("Memory limit of {} bytes exceeded", self.limits.max_memory.unwrap()));
+		}
+
+		if self.limits.max_depth.is_some_and(|max| self.frames.len() > max) {
+			return Err(format!("Nesting limit of {} calls exceeded", self.limits.max_depth.unwrap()));
+		}
+
+		Ok(())
+	}
+
+	// finishes returned calls and advances loops until the next command is ready to execute
+	fn settle(&mut self) -> Result<(), RuntimeError> {
+		while let Some(Frame::Code {function, ip}) = self.frames.last() {
+			if *ip < function.code.len() {
+				break;
+			}
+
+			if let Some(frame) = self.frames.pop() {
+				self.log(FrameOp::Popped(frame));
+			}
+
+			if let Some(Frame::Loop {id, ..}) = self.frames.last() {
+				let id = *id;
+				self.continue_loop(true).and_then(|_| self.check_limits()).map_err(|message| self.error(message, id))?;
+			}
+		}
+
+		Ok(())
+	}
+
+	// the commands that called into the active frames, innermost first, leaving out `command` itself
+	fn backtrace(&self, command: usize) -> Vec<usize> {
+		self.frames.iter().rev().filter_map(|frame| match frame {
+			Frame::Code {function, ip} if *ip > 0 => Some(function.code[*ip - 1].id),
+			_ => None
+		}).skip_while(|id| *id == command).collect()
+	}
+
+	// while paused before a command, the commands that called into the frames running it, innermost first
+	pub fn callers(&self) -> Vec<usize> {
+		self.frames.iter().rev().skip(1).filter_map(|frame| match frame {
+			Frame::Code {function, ip} if *ip > 0 => Some(function.code[*ip - 1].id),
+			_ => None
+		}).collect()
+	}
+
+	fn error(&self, message: String, command: usize) -> RuntimeError {
+		event!(DEBUG, %message, command, "runtime error");
+		RuntimeError {message, command, backtrace: self.backtrace(command)}
+	}
+
+	// the command that will execute next, or None once the program has finished
+	pub fn peek(&mut self) -> Result<Option<&Instr>, RuntimeError> {
+		self.settle()?;
+
+		match self.frames.last() {
+			Some(Frame::Code {function, ip}) => Ok(Some(&function.code[*ip])),
+			_ => Ok(None)
+		}
+	}
+
+	// number of active calls and loops; a command that calls a function returns to this depth
+	pub fn depth(&self) -> usize {
+		self.frames.len()
+	}
+
+	// executes the next command; returns false once the program has finished
+	pub fn step(&mut self) -> Result<bool, RuntimeError> {
+		self.step_with(&mut |_, _| {})
+	}
+
+	// like step, then calls `after` with the executed command and the state it left behind
+	pub fn step_with(&mut self, after: &mut dyn FnMut(&Instr, &Vm)) -> Result<bool, RuntimeError> {
+		self.settle()?;
+
+		let Some(Frame::Code {function, ip}) = self.frames.last() else {
+			return Ok(false);
+		};
+
+		let function = function.clone();
+		let instr = &function.code[*ip];
+
+		if self.fuel == Some(0) {
+			return Err(self.error(format!("Fuel exhausted after {} steps", self.steps), instr.id));
+		}
+
+		self.record();
+
+		if let Some(Frame::Code {ip, ..}) = self.frames.last_mut() {
+			*ip += 1;
+		}
+
+		self.log(FrameOp::Advanced);
+
+		self.fuel = self.fuel.map(|fuel| fuel - 1);
+		self.steps += 1;
+
+		self.execute(instr).and_then(|_| self.check_limits()).map_err(|message| self.error(message, instr.id))?;
+		after(instr, self);
+		Ok(true)
+	}
+
+	// runs the loaded programs to completion and returns the stack they leave, bottom first
+	#[cfg_attr(feature = "tracing", tracing::instrument(name = "run", level = "debug", skip_all))]
+	pub fn run(&mut self) -> Result<&[Value], RuntimeError> {
+		self.memory = self.measure();
+		while self.step()? {}
+		Ok(&self.stack)
+	}
+
+	// like run, calling `after` after every command as step_with does
+	#[cfg_attr(feature = "tracing", tracing::instrument(name = "run", level = "debug", skip_all))]
+	pub fn run_with(&mut self, after: &mut dyn FnMut(&Instr, &Vm)) -> Result<(), RuntimeError> {
+		self.memory = self.measure();
+		while self.step_with(after)? {}
+		Ok(())
+	}
+
+	// abandons the running program, keeping the stack and registers
+	pub fn abort(&mut self) {
+		while let Some(frame) = self.frames.pop() {
+			self.log(FrameOp::Popped(frame));
+		}
+	}
+
+	// clears the stack, registers, step count and history; the host, fuel, limits and history limit are kept
+	pub fn reset(&mut self) {
+		let host = std::mem::replace(&mut self.host, Box::new(StubHost));
+		*self = Vm {host, fuel: self.fuel, limits: self.limits, history_limit: self.history_limit, ..Vm::default()};
+	}
+
+	// the stack, registers, step count and position in the program as JSON; functions are stored by
+	// their start id, so the snapshot can only be restored against the same program
+	pub fn snapshot(&self) -> Json {
+		let values = |values: &[Value]| values.iter().map(value_to_snapshot).collect::<Vec<_>>();
+
+		let frames: Vec<_> = self.frames.iter().map(|frame| match frame {
+			Frame::Code {function, ip} => json!({"function": function.start, "ip": ip}),
+			Frame::Loop {kind, id, function, items, index, results} => json!({
+				"loop": loop_name(*kind),
+				"command": id,
+				"function": function.start,
+				"items": values(items),
+				"index": index,
+				"results": values(results)
+			})
+		}).collect();
+
+		json!({
+			"version": SNAPSHOT_VERSION,
+			"steps": self.steps,
+			"stack": values(&self.stack),
+			"registers": values(&self.registers),
+			"frames": frames
+		})
+	}
+
+	// replaces the stack, registers, step count and position with those of a snapshot taken while
+	// running `program`; the host, fuel and limits are kept
+	pub fn restore(&mut self, program: &Rc<Function>, snapshot: &Json) -> Result<(), String> {
+		if snapshot["version"] != SNAPSHOT_VERSION {
+			return Err(format!("Unsupported snapshot version: {}", snapshot["version"]));
+		}
+
+		let mut functions = HashMap::new();
+		collect_functions(program, &mut functions);
+
+		let function = |json: &Json| json.as_u64().and_then(|start| functions.get(&(start as usize)).cloned())
+			.ok_or(format!("Snapshot refers to a function not in this program: {}", json));
+		let number = |json: &Json, what: &str| json.as_u64().map(|val| val as usize).ok_or(format!("Invalid {} in snapshot: {}", what, json));
+		let values = |json: &Json, what: &str| json.as_array().ok_or(format!("Invalid {} in snapshot", what))?
+			.iter().map(|value| value_from_snapshot(value, &functions)).collect::<Result<Vec<_>, _>>();
+
+		let stack = values(&snapshot["stack"], "stack")?;
+		let registers = values(&snapshot["registers"], "registers")?;
+
+		if registers.len() != codegen::REGISTER_COUNT as usize {
+			return Err(format!("Snapshot has {} registers, expected {}", registers.len(), codegen::REGISTER_COUNT));
+		}
+
+		let mut frames = vec![];
+
+		for frame in snapshot["frames"].as_array().ok_or("Invalid frames in snapshot")? {
+			let code = function(&frame["function"])?;
+
+			frames.push(match frame["loop"].as_str() {
+				None => {
+					let ip = number(&frame["ip"], "ip")?;
+
+					if ip > code.code.len() {
+						return Err(format!("Snapshot ip {} is past the end of its function", ip));
+					}
+
+					Frame::Code {function: code, ip}
+				},
+				Some(name) => {
+					let kind = [LoopKind::Each, LoopKind::Map, LoopKind::Filter, LoopKind::Reduce].into_iter()
+						.find(|kind| loop_name(*kind) == name).ok_or(format!("Unknown loop in snapshot: {}", name))?;
+					let items = values(&frame["items"], "loop items")?;
+					let index = number(&frame["index"], "loop index")?;
+
+					if index > items.len() {
+						return Err(format!("Snapshot loop index {} is past its {} items", index, items.len()));
+					}
+
+					let id = number(&frame["command"], "loop command")?;
+					let results = values(&frame["results"], "loop results")?;
+
+					Frame::Loop {kind, id, function: code, items, index, results}
+				}
+			});
+		}
+
+		self.steps = number(&snapshot["steps"], "steps")? as u64;
+		self.stack = stack;
+		self.registers = registers;
+		self.frames = frames;
+		self.memory = self.measure();
+		self.history.clear();
+		Ok(())
+	}
+}
+
+const SNAPSHOT_VERSION: u64 = 1;
+
+fn loop_name(kind: LoopKind) -> &'static str {
+	match kind {
+		LoopKind::Each   => "each",
+		LoopKind::Map    => "map",
+		LoopKind::Filter => "filter",
+		LoopKind::Reduce => "reduce"
+	}
+}
+
+// every function in a program by start id
+fn collect_functions(function: &Rc<Function>, functions: &mut HashMap<usize, Rc<Function>>) {
+	fn in_value(value: &Value, functions: &mut HashMap<usize, Rc<Function>>) {
+		match value {
+			Value::Function(function) => collect_functions(function, functions),
+			Value::Array(values) => values.iter().for_each(|value| in_value(value, functions)),
+			_ => {}
+		}
+	}
+
+	functions.insert(function.start, function.clone());
+
+	for instr in &function.code {
+		in_value(&instr.operand, functions);
+	}
+}
+
+// like ast_json::value_to_json, but functions are stored by start id and numbers JSON cannot hold
+// (infinities and NaN) as strings
+fn value_to_snapshot(value: &Value) -> Json {
+	match value {
+		Value::Nil => json!({"type": "nil"}),
+		Value::Number(val) if val.is_finite() => json!({"type": "number", "value": val}),
+		Value::Number(val) => json!({"type": "number", "value": val.to_string()}),
+		Value::String(val) => json!({"type": "string", "value": val}),
+		Value::Boolean(val) => json!({"type": "boolean", "value": val}),
+		Value::Function(function) => json!({"type": "function", "start": function.start}),
+		Value::Array(values) => json!({"type": "array", "values": values.iter().map(value_to_snapshot).collect::<Vec<_>>()})
+	}
+}
+
+fn value_from_snapshot(json: &Json, functions: &HashMap<usize, Rc<Function>>) -> Result<Value, String> {
+	let invalid = || format!("Invalid value in snapshot: {}", json);
+
+	Ok(match json["type"].as_str().ok_or_else(invalid)? {
+		"nil" => Value::Nil,
+		"number" => match &json["value"] {
+			Json::String(val) => Value::Number(val.parse().map_err(|_| invalid())?),
+			val => Value::Number(val.as_f64().ok_or_else(invalid)?)
+		},
+		"string" => Value::String(json["value"].as_str().ok_or_else(invalid)?.to_owned()),
+		"boolean" => Value::Boolean(json["value"].as_bool().ok_or_else(invalid)?),
+		"function" => {
+			let start = json["start"].as_u64().ok_or_else(invalid)? as usize;
+			Value::Function(functions.get(&start).cloned().ok_or(format!("Snapshot refers to a function not in this program: {}", start))?)
+		},
+		"array" => Value::Array(json["values"].as_array().ok_or_else(invalid)?.iter()
+			.map(|value| value_from_snapshot(value, functions)).collect::<Result<_, _>>()?),
+		_ => return Err(invalid())
+	})
+}
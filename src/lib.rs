@@ -0,0 +1,423 @@
+// the assembler, disassembler and interpreter as a library; main.rs is the command-line front end.
+// without the std feature only the assembly pipeline is built, on alloc alone
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
+use alloc::string::String;
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use alloc::vec;
+use core::fmt;
+use thiserror::Error;
+
+// the public surface, which follows semver: lexer, ast, optimizer, codegen, disasm, vm,
+// diagnostics, build, cache and prelude, and the items defined in this file. the modules below
+// them hold the implementation and the command line's tools; they are public so main.rs can use
+// them, but are hidden from the docs and may change in any release
+
+pub mod lexer {
+	pub use crate::tokenizer::{tokenize, tokens, tokenize_limited, tokenize_legacy, tokenize_with_comments, tokenize_with_progress, Comment, Loc, Token, TokenType, TokenizeError, Tokens};
+	pub use crate::highlight::{classify, Kind, Piece, KINDS};
+}
+
+pub mod ast {
+	pub use crate::builder::ProgramBuilder;
+	pub use crate::parser::{get_command_name, parse, parse_with_locs, parse_each, parse_limited, parse_outline, parse_with_progress, parse_with_tests, stack_effect, Body, Command, Outline, ParseError, Parsed, Test, Value};
+	pub use crate::printer::{print, print_minified};
+
+	#[cfg(feature = "std")]
+	pub mod json {
+		pub use crate::ast_json::{program_from_json, program_from_str, program_to_json, AstJsonError, SCHEMA_VERSION};
+	}
+}
+
+#[cfg(feature = "std")]
+pub mod vm {
+	pub use crate::interpreter::{lower, value_bytes, Capabilities, ConsoleHost, Function, Host, Instr, Limits, RuntimeError, Sandbox, StubHost, Value, Vm};
+}
+
+#[cfg(feature = "std")]
+pub mod diagnostics {
+	pub use crate::diagnostic::{emit, explain, sarif, Diagnostic, Explanation, Format, Label, Level, Lints, Severity, Span, EXPLANATIONS, WARNINGS};
+	pub use crate::messages::{Arg, Locale, Message};
+}
+
+// the types most programs need: `use evm_asm::prelude::*;`
+pub mod prelude {
+	pub use crate::ast::{Command, ProgramBuilder, Value};
+	pub use crate::lexer::Loc;
+	pub use crate::{assemble, AssembleOptions, Error, InputLimits, Phase};
+
+	#[cfg(feature = "std")]
+	pub use crate::diagnostics::Diagnostic;
+	#[cfg(feature = "std")]
+	pub use crate::vm::{Host, Vm};
+}
+
+// a tracing event that compiles to nothing without the tracing feature:
+// event!(DEBUG, field = value, "message")
+macro_rules! event {
+	($level:ident, $($arg:tt)*) => {
+		#[cfg(feature = "tracing")]
+		tracing::event!(tracing::Level::$level, $($arg)*);
+	}
+}
+
+pub mod codegen;
+pub mod disasm;
+pub mod optimizer;
+
+#[doc(hidden)]
+#[cfg(feature = "std")]
+pub mod ast_json;
+#[cfg(feature = "std")]
+pub mod build;
+#[doc(hidden)]
+pub mod builder;
+#[cfg(feature = "std")]
+pub mod cache;
+#[doc(hidden)]
+#[cfg(feature = "std")]
+pub mod callgraph;
+#[doc(hidden)]
+pub mod cfg;
+#[doc(hidden)]
+#[cfg(feature = "std")]
+pub mod config;
+#[doc(hidden)]
+#[cfg(feature = "std")]
+pub mod coverage;
+#[doc(hidden)]
+#[cfg(feature = "std")]
+pub mod dap;
+#[doc(hidden)]
+#[cfg(feature = "std")]
+pub mod debugger;
+#[doc(hidden)]
+#[cfg(feature = "std")]
+pub mod depth;
+#[doc(hidden)]
+#[cfg(feature = "std")]
+pub mod diagnostic;
+#[doc(hidden)]
+#[cfg(feature = "std")]
+pub mod doc;
+#[doc(hidden)]
+#[cfg(feature = "std")]
+pub mod explore;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+#[doc(hidden)]
+pub mod formatter;
+#[doc(hidden)]
+pub mod highlight;
+#[doc(hidden)]
+pub mod html;
+#[doc(hidden)]
+#[cfg(feature = "std")]
+pub mod interpreter;
+#[doc(hidden)]
+#[cfg(feature = "std")]
+pub mod lint;
+#[doc(hidden)]
+#[cfg(feature = "std")]
+pub mod lsp;
+#[doc(hidden)]
+pub mod listing;
+#[doc(hidden)]
+pub mod opcode;
+#[doc(hidden)]
+pub mod parser;
+#[doc(hidden)]
+pub mod printer;
+#[doc(hidden)]
+#[cfg(feature = "std")]
+pub mod messages;
+#[doc(hidden)]
+#[cfg(feature = "std")]
+pub mod memory;
+#[doc(hidden)]
+#[cfg(feature = "std")]
+pub mod mmap;
+#[doc(hidden)]
+#[cfg(feature = "std")]
+pub mod profile;
+#[cfg(feature = "python")]
+pub mod python;
+#[doc(hidden)]
+#[cfg(feature = "std")]
+pub mod rename;
+#[doc(hidden)]
+#[cfg(all(feature = "std", not(target_arch = "wasm32")))]
+pub mod repl;
+#[doc(hidden)]
+#[cfg(feature = "std")]
+pub mod search;
+#[doc(hidden)]
+#[cfg(feature = "std")]
+pub mod stats;
+#[doc(hidden)]
+#[cfg(feature = "std")]
+pub mod stream;
+#[doc(hidden)]
+#[cfg(feature = "std")]
+pub mod testing;
+#[doc(hidden)]
+pub mod tokenizer;
+#[doc(hidden)]
+#[cfg(feature = "std")]
+pub mod typecheck;
+#[cfg(feature = "wasm")]
+pub mod wasm;
+
+pub use ast::{parse, ProgramBuilder};
+pub use codegen::generate;
+pub use lexer::tokenize;
+
+// an error from any phase, keeping its kind and, where the phase has one, its source location
+#[derive(Debug, Error)]
+pub enum Error {
+	#[error("Tokenizer error: {0}")]
+	Tokenize(#[from] tokenizer::TokenizeError),
+	#[error("Parser error: {0}")]
+	Parse(#[from] parser::ParseError),
+	#[cfg(feature = "std")]
+	#[error("ast-json error: {0}")]
+	AstJson(#[from] ast_json::AstJsonError),
+	#[error("Codegen error: {0}")]
+	Codegen(#[from] codegen::CodegenError),
+	#[error("Disassembler error: {0}")]
+	Disasm(#[from] disasm::DisasmError),
+	#[cfg(feature = "std")]
+	#[error("Runtime error: {0}")]
+	Runtime(#[from] interpreter::RuntimeError),
+	#[error("Bytecode is {size} bytes, exceeding the maximum of {max} bytes")]
+	TooLarge {size: usize, max: usize},
+	#[error("Source is {size} bytes, exceeding the limit of {max} bytes; raise it with --max-file-size")]
+	FileTooLarge {size: usize, max: usize},
+	#[error("Bytecode has checksum {actual:08x}, expected {expected:08x}")]
+	ChecksumMismatch {expected: u32, actual: u32},
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Phase {
+	Tokenize,
+	Parse,
+	Optimize,
+	Codegen,
+	Disasm,
+	Runtime,
+}
+
+impl Error {
+	pub fn phase(&self) -> Phase {
+		match self {
+			Error::Tokenize(_) | Error::FileTooLarge {..} => Phase::Tokenize,
+			Error::Parse(_) => Phase::Parse,
+			#[cfg(feature = "std")]
+			Error::AstJson(_) => Phase::Parse,
+			Error::Codegen(_) | Error::TooLarge {..} => Phase::Codegen,
+			Error::Disasm(_) | Error::ChecksumMismatch {..} => Phase::Disasm,
+			#[cfg(feature = "std")]
+			Error::Runtime(_) => Phase::Runtime
+		}
+	}
+
+	// the source location, for errors found while reading the source
+	pub fn loc(&self) -> Option<&lexer::Loc> {
+		match self {
+			Error::Tokenize(e) => Some(e.loc()),
+			Error::Parse(e) => Some(e.loc()),
+			_ => None
+		}
+	}
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct AssembleOptions {
+	pub optimizer: optimizer::Options,
+	pub max_size: Option<usize>, // bytes
+	pub limits: InputLimits,
+	pub target: codegen::TargetLimits, // checked on the optimized program; with any set, the cache is not used
+	pub on_progress: Option<Progress>,
+	#[cfg(feature = "std")]
+	pub cache: Option<cache::Cache>, // a program read from the cache reports no progress
+	pub legacy_numbers: bool, // numbers as read before they were strict; such a source is not cached
+	pub input: InputFormat,
+	pub keep: Keep,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum InputFormat {
+	#[default]
+	Asm,
+	#[cfg(feature = "std")]
+	AstJson, // the schema of ast_json::program_to_json; not cached
+}
+
+// how much of the program assemble_file hands back besides the bytecode, each keeping what the one
+// before it does
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Keep {
+	#[default]
+	Bytecode,
+	Optimized, // disassembled from the bytecode when it is read from the cache
+	Written, // the program before optimization, with its locations; the cache is not read for it
+}
+
+// a program assembled by assemble_file, with as much of it as AssembleOptions::keep asks for
+#[derive(Clone, Debug, Default)]
+pub struct Assembly {
+	pub bytecode: Vec<u8>,
+	pub commands: Vec<parser::Command>, // optimized
+	pub written: Option<(Vec<parser::Command>, Vec<lexer::Loc>)>,
+	pub report: Vec<String>, // what the optimizer did, a line each; empty when read from the cache
+}
+
+// guard rails for inputs that may be too large, as a service gets: each is checked as the input is
+// read, so a huge one fails with an error naming the limit rather than taking all the memory.
+// none by default
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct InputLimits {
+	pub max_file_size: Option<usize>, // bytes of source
+	pub max_tokens: Option<usize>,
+	pub max_nodes: Option<usize>, // commands and values, the nodes of the tree
+}
+
+// called by assemble with each phase and how far through it it is, 0 to 100, whenever that
+// changes; every phase ends with 100. for progress bars on large inputs
+#[derive(Clone)]
+pub struct Progress(Arc<dyn Fn(Phase, u8) + Send + Sync>);
+
+impl Progress {
+	pub fn new(on_progress: impl Fn(Phase, u8) + Send + Sync + 'static) -> Progress {
+		Progress(Arc::new(on_progress))
+	}
+}
+
+impl fmt::Debug for Progress {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		f.write_str("Progress")
+	}
+}
+
+// the assembler keeps no global state, so its inputs, outputs and errors can go to other threads and
+// assemble can run on many at once; this stops compiling if one of them stops being Send or Sync
+const _: fn() = || {
+	fn send_sync<T: Send + Sync>() {}
+
+	send_sync::<AssembleOptions>();
+	send_sync::<Error>();
+	send_sync::<parser::Command>();
+	send_sync::<parser::Parsed>();
+	send_sync::<tokenizer::Token>();
+	#[cfg(feature = "std")]
+	send_sync::<diagnostic::Diagnostic>();
+};
+
+// source text to bytecode, as the command line does with its default output
+pub fn assemble(source: &str, options: &AssembleOptions) -> Result<Vec<u8>, Error> {
+	Ok(assemble_file(source, "<source>", options)?.bytecode)
+}
+
+// each (filename, source) pair, on a thread per core, or one after another in a browser; the results
+// are in the order of the inputs, whichever finishes first
+#[cfg(feature = "std")]
+pub fn assemble_all(inputs: &[(&str, &str)], options: &AssembleOptions) -> Vec<Result<Vec<u8>, Error>> {
+	#[cfg(not(target_arch = "wasm32"))]
+	use rayon::prelude::*;
+	#[cfg(not(target_arch = "wasm32"))]
+	let inputs = inputs.par_iter();
+	#[cfg(target_arch = "wasm32")]
+	let inputs = inputs.iter();
+
+	inputs.map(|(filename, source)| Ok(assemble_file(source, filename, options)?.bytecode)).collect()
+}
+
+// with `filename` in the locations of errors; the whole pipeline the command line runs on a file
+#[cfg_attr(feature = "tracing", tracing::instrument(name = "assemble", level = "info", skip(source, options)))]
+pub fn assemble_file(source: &str, filename: &str, options: &AssembleOptions) -> Result<Assembly, Error> {
+	let progress = |phase| move |percent| if let Some(Progress(on_progress)) = &options.on_progress {
+		on_progress(phase, percent)
+	};
+
+	if let Some(max) = options.limits.max_file_size.filter(|max| source.len() > *max) {
+		return Err(Error::FileTooLarge {size: source.len(), max});
+	}
+
+	let generate = || -> Result<Assembly, Error> {
+		let limits = &options.limits;
+
+		let (commands, locs) = match options.input {
+			InputFormat::Asm => {
+				let tokenize = if options.legacy_numbers { lexer::tokenize_legacy } else { lexer::tokenize_limited };
+				let tokens = tokenize(source, filename, limits.max_tokens.unwrap_or(usize::MAX), &mut progress(Phase::Tokenize))?;
+				let parsed = parser::parse_limited(tokens, limits.max_nodes.unwrap_or(usize::MAX), &mut progress(Phase::Parse))?;
+				(parsed.commands, parsed.locs)
+			},
+			#[cfg(feature = "std")]
+			InputFormat::AstJson => ast_json::program_from_str(source, filename)?
+		};
+
+		// kept from before optimization, which inlines calls
+		let written = (options.keep == Keep::Written).then(|| (commands.clone(), locs));
+
+		let mut report = vec![];
+		let commands = optimizer::optimize(commands, &options.optimizer, &mut report);
+		progress(Phase::Optimize)(100);
+		codegen::validate_limited(&commands, &options.target)?;
+
+		let kept = match options.keep {
+			Keep::Bytecode => vec![],
+			_ => commands.clone()
+		};
+
+		// the threads finish in any order, so progress is reported a command at a time on one
+		#[cfg(feature = "std")]
+		if options.on_progress.is_none() {
+			let bytecode = codegen::generate_parallel(commands).to_vec();
+			return Ok(Assembly {bytecode, commands: kept, written, report});
+		}
+
+		let bytecode = codegen::generate_with_progress(commands, &mut progress(Phase::Codegen)).to_vec();
+		Ok(Assembly {bytecode, commands: kept, written, report})
+	};
+
+	#[cfg(feature = "std")]
+	// a source may assemble with legacy numbers and fail without them, and the cache does not know the
+	// input or target limits a program was checked against, so any of them keeps it out
+	let assembly = match options.cache.as_ref().filter(|_| options.input == InputFormat::Asm && !options.legacy_numbers && options.limits == InputLimits::default() && options.target == codegen::TargetLimits::default()) {
+		Some(cache) => {
+			let key = cache::Cache::key(source, &options.optimizer);
+
+			// bytecode that does not disassemble is assembled again, as if it was not there
+			let cached = cache.get(&key).filter(|_| options.keep < Keep::Written).and_then(|bytecode| {
+				let commands = match options.keep {
+					Keep::Bytecode => vec![],
+					_ => disasm::disassemble(&bytecode).ok()?
+				};
+
+				event!(DEBUG, key = %key, "cached");
+				Some(Assembly {bytecode, commands, written: None, report: vec![]})
+			});
+
+			match cached {
+				Some(assembly) => assembly,
+				None => {
+					let assembly = generate()?;
+					let _ = cache.put(&key, &assembly.bytecode); // a cache that cannot be written to is skipped
+					assembly
+				}
+			}
+		},
+		None => generate()?
+	};
+	#[cfg(not(feature = "std"))]
+	let assembly = generate()?;
+
+	match options.max_size {
+		Some(max) if assembly.bytecode.len() > max => Err(Error::TooLarge {size: assembly.bytecode.len(), max}),
+		_ => Ok(assembly)
+	}
+}
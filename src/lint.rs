@@ -0,0 +1,152 @@
+use std::collections::BTreeMap;
+
+use crate::cfg::{divergence, functions};
+use crate::diagnostic::{Diagnostic, Span};
+use crate::messages::Message;
+use crate::parser::{get_command_name, Command, Value};
+use crate::tokenizer::Loc;
+
+// style and correctness checks on a parsed program, for `evm-asm lint`. each finding is a warning
+// with a name in diagnostic::WARNINGS, so -W, --allow and --deny and the [lint] table of the config
+// file turn them on and off like any other warning
+
+#[derive(Clone, Debug)]
+pub struct Options {
+	pub max_nesting: usize, // function literals inside each other before deep-nesting warns
+}
+
+impl Default for Options {
+	fn default() -> Options {
+		Options {max_nesting: 3}
+	}
+}
+
+impl Options {
+	pub fn set(&mut self, key: &str, value: &str) -> Result<(), String> {
+		match key {
+			"max-nesting" => self.max_nesting = value.parse().map_err(|_| format!("Invalid max-nesting: {}", value))?,
+			_ => return Err(format!("Unknown lint option: {}", key))
+		}
+
+		Ok(())
+	}
+}
+
+// numbers common enough to need no name, besides register numbers
+const PLAIN_NUMBERS: &[f64] = &[-1.0, 0.0, 1.0, 2.0];
+
+struct Linter<'a> {
+	locs: &'a [Loc],
+	next: usize, // index in `locs` of the next command visited
+	options: &'a Options,
+	pushed: Vec<f64>, // every number the program pushes, which includes the registers it reads
+	functions: BTreeMap<u8, &'a [Command]>,
+	diagnostics: Vec<Diagnostic>,
+}
+
+fn span(command: &Command, loc: &Loc) -> Span {
+	Span::new(loc, get_command_name(command).len() as u64)
+}
+
+// whether `command`, given the commands after it, pushes a register number rather than a quantity
+pub(crate) fn is_register(commands: &[Command], i: usize) -> bool {
+	matches!(commands.get(i + 1), Some(Command::Load | Command::Call | Command::If | Command::Each | Command::Map | Command::Filter))
+		|| matches!(commands.get(i + 2), Some(Command::Reduce))
+}
+
+fn pushed_numbers(commands: &[Command], pushed: &mut Vec<f64>) {
+	fn in_value(value: &Value, pushed: &mut Vec<f64>) {
+		match value {
+			Value::Function(commands) => pushed_numbers(commands, pushed),
+			Value::Array(values) => values.iter().for_each(|value| in_value(value, pushed)),
+			_ => {}
+		}
+	}
+
+	for command in commands {
+		match command {
+			Command::Push(Value::Number(val)) => pushed.push(*val),
+			Command::Push(value) | Command::ILoad(_, value) => in_value(value, pushed),
+			_ => {}
+		}
+	}
+}
+
+impl Linter<'_> {
+	// visits commands in the order parse_with_locs numbers them: each command, then the commands of
+	// the functions in its operand
+	fn commands(&mut self, commands: &[Command], depth: usize) {
+		let failing = divergence(commands, &self.functions);
+		let mut failing_loc = None;
+
+		for (i, command) in commands.iter().enumerate() {
+			let loc = self.locs[self.next].clone();
+			self.next += 1;
+
+			// reported at the first command that never runs, once for the rest
+			match (failing, &failing_loc) {
+				(Some(failing), _) if failing == i => failing_loc = Some(loc.clone()),
+				(Some(failing), Some(failing_loc)) if failing + 1 == i => {
+					let fails = &commands[failing];
+					self.diagnostics.push(Diagnostic::warning("W006", Message::new("W006")).with_span(span(command, &loc))
+						.with_label(span(fails, failing_loc), Message::new("W006.fails").arg("command", get_command_name(fails)))
+						.with_note(Message::new("W006.note")));
+				},
+				_ => {}
+			}
+
+			match command {
+				Command::Push(Value::Function(_)) => {
+					self.diagnostics.push(Diagnostic::warning("W003", Message::new("W003")).with_span(span(command, &loc)).with_note(Message::new("W003.note")));
+				},
+				Command::ILoad(reg, Value::Function(_)) if !self.pushed.contains(&(*reg as f64)) => {
+					self.diagnostics.push(Diagnostic::warning("W003", Message::new("W003.register").arg("reg", reg)).with_span(span(command, &loc))
+						.with_note(Message::new("W003.register-note").arg("reg", reg)));
+				},
+				Command::Push(Value::Number(val)) if !PLAIN_NUMBERS.contains(val) && !is_register(commands, i) => {
+					self.diagnostics.push(Diagnostic::warning("W004", Message::new("W004").arg("number", val)).with_span(span(command, &loc)).with_note(Message::new("W004.note")));
+				},
+				_ => {}
+			}
+
+			if let Command::Push(value) | Command::ILoad(_, value) = command {
+				self.value(value, command, &loc, depth);
+			}
+
+			// the drop's location comes after those of the commands in the pushed value
+			if let (Command::Push(_), Some(Command::Drop)) = (command, commands.get(i + 1)) {
+				let drop = &self.locs[self.next];
+				self.diagnostics.push(Diagnostic::warning("W002", Message::new("W002")).with_span(span(command, &loc)).with_label(Span::new(drop, 4), Message::new("W002.drop")));
+			}
+		}
+	}
+
+	// `depth` counts the functions around `value`; the first function past max_nesting is reported
+	// at the command that pushes it, and not again for those inside it
+	fn value(&mut self, value: &Value, command: &Command, loc: &Loc, depth: usize) {
+		match value {
+			Value::Function(commands) => {
+				if depth == self.options.max_nesting {
+					self.diagnostics.push(Diagnostic::warning("W005", Message::new("W005").arg("depth", depth + 1).arg("max", self.options.max_nesting))
+						.with_span(span(command, loc)).with_note(Message::new("W005.note")));
+				}
+
+				self.commands(commands, depth + 1);
+			},
+			Value::Array(values) => values.iter().for_each(|value| self.value(value, command, loc, depth)),
+			_ => {}
+		}
+	}
+}
+
+// the warnings for `commands`, whose locations are `locs` as parse_with_locs returns them, in
+// program order
+pub fn lint(commands: &[Command], locs: &[Loc], options: &Options) -> Vec<Diagnostic> {
+	let mut pushed = vec![];
+	pushed_numbers(commands, &mut pushed);
+
+	let mut linter = Linter {locs, next: 0, options, pushed, functions: functions(commands), diagnostics: vec![]};
+	linter.commands(commands, 0);
+	linter.diagnostics.sort_by_key(|diagnostic| diagnostic.primary_span.as_ref().map(|span| (span.loc.line, span.loc.col)));
+	linter.diagnostics
+}
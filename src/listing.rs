@@ -0,0 +1,125 @@
+use alloc::borrow::ToOwned;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use alloc::{format, vec};
+use bytes::BytesMut;
+use core::fmt::Write;
+
+use crate::codegen;
+use crate::optimizer;
+use crate::parser::*;
+
+const HEX_BYTES: usize = 10;
+
+pub fn hex(bytes: &[u8]) -> String {
+	let mut text = bytes.iter().take(HEX_BYTES).map(|b| format!("{:02x}", b)).collect::<Vec<_>>().join(" ");
+
+	if bytes.len() > HEX_BYTES {
+		text.push_str(" ..");
+	}
+
+	text
+}
+
+pub fn write_line(out: &mut String, offset: Option<usize>, bytes: &[u8], depth: usize, text: &str) {
+	let offset = offset.map(|o| format!("{:08x}", o)).unwrap_or_default();
+	let _ = writeln!(out, "{:8}  {:32}  {}{}", offset, hex(bytes), "\t".repeat(depth), text);
+}
+
+fn list_commands(out: &mut String, commands: &[Command], mut offset: usize, depth: usize) -> usize {
+	for command in commands {
+		match command {
+			Command::Push(Value::Function(body)) | Command::ILoad(_, Value::Function(body)) => {
+				// header: opcode, register, value tag and body length
+				let header_len = codegen::command_size(command) - body.iter().map(codegen::command_size).sum::<usize>();
+				let encoded = codegen::generate(vec![command.clone()]);
+				let text = match command {
+					Command::ILoad(reg, _) => format!("iload {} {{", reg),
+					_ => "push {".to_owned()
+				};
+
+				write_line(out, Some(offset), &encoded[..header_len], depth, &text);
+				offset = list_commands(out, body, offset + header_len, depth + 1);
+				write_line(out, None, &[], depth, "}");
+			},
+			_ => {
+				let encoded: BytesMut = codegen::generate(vec![command.clone()]);
+
+				write_line(out, Some(offset), &encoded, depth, &command.to_string());
+				offset += encoded.len();
+			}
+		}
+	}
+
+	offset
+}
+
+pub struct FunctionStats {
+	pub offset: usize, // offset of the command pushing or loading the function
+	pub bytes: usize,
+	pub commands: usize,
+	pub max_constant: usize,
+	pub depth: usize,
+	pub preview: String,
+}
+
+fn max_constant(commands: &[Command]) -> usize {
+	commands.iter().map(|command| match command {
+		Command::Push(Value::Function(body)) | Command::ILoad(_, Value::Function(body)) => max_constant(body),
+		Command::Push(value) | Command::ILoad(_, value) => codegen::value_size(value),
+		_ => 0
+	}).max().unwrap_or(0)
+}
+
+fn collect_stats(stats: &mut Vec<FunctionStats>, commands: &[Command], mut offset: usize, depth: usize) {
+	for command in commands {
+		if let Command::Push(value @ Value::Function(body)) | Command::ILoad(_, value @ Value::Function(body)) = command {
+			let mut preview = value.to_string();
+
+			if preview.chars().count() > 40 {
+				preview = format!("{}...", preview.chars().take(40).collect::<String>());
+			}
+
+			stats.push(FunctionStats {
+				offset,
+				bytes: codegen::value_size(value),
+				commands: optimizer::count_commands(body),
+				max_constant: max_constant(body),
+				depth: depth + 1,
+				preview
+			});
+
+			let header_len = codegen::command_size(command) - body.iter().map(codegen::command_size).sum::<usize>();
+			collect_stats(stats, body, offset + header_len, depth + 1);
+		}
+
+		offset += codegen::command_size(command);
+	}
+}
+
+// size breakdown of every function literal in the program, heaviest first
+pub fn function_stats(commands: &[Command]) -> Vec<FunctionStats> {
+	let mut stats = vec![];
+	collect_stats(&mut stats, commands, 0, 0);
+	stats.sort_by_key(|s| core::cmp::Reverse(s.bytes));
+	stats
+}
+
+// human-readable listing of the generated bytecode: offset, encoded bytes and command per line,
+// followed by per-function statistics
+pub fn listing(commands: &[Command]) -> String {
+	let mut out = String::new();
+	list_commands(&mut out, commands, 0, 0);
+
+	let stats = function_stats(commands);
+
+	if !stats.is_empty() {
+		let _ = writeln!(out, "\n{:8}  {:>8}  {:>8}  {:>9}  {:>5}  function", "offset", "bytes", "commands", "max const", "depth");
+
+		for s in stats {
+			let _ = writeln!(out, "{:08x}  {:>8}  {:>8}  {:>9}  {:>5}  {}", s.offset, s.bytes, s.commands, s.max_constant, s.depth, s.preview);
+		}
+	}
+
+	out
+}
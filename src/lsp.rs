@@ -0,0 +1,410 @@
+use serde_json::{json, Value as Json};
+use std::collections::HashMap;
+use std::io::{self, Write};
+use std::path::Path;
+
+use crate::config::{self, Config};
+use crate::dap::read_message;
+use crate::diagnostic::{Diagnostic, Severity};
+use crate::highlight::{self, Kind, KINDS};
+use crate::parser::{self, get_command_name, stack_effect, Command, Value};
+use crate::tokenizer::{self, Loc, Token, TokenType};
+use crate::{formatter, lint, Error};
+
+// a Language Server Protocol server for editors: diagnostics as a document changes, hover with
+// the stack effect of a command, go to the iload of a register a command reads, formatting,
+// semantic tokens and an outline of the registers iloaded at the top level.
+// documents are sent whole on every change. the parser stops at the first error, so a document
+// that does not parse gets that one error and no warnings
+
+// every command with its location and whether it pushes a register number
+type Commands = Vec<(Loc, Command, bool)>;
+
+struct At {
+	uri: String,
+	token: Token,
+	command: Option<(Command, bool)>,
+	operand_of: Option<(Command, bool)>,
+	commands: Commands,
+}
+
+pub struct Server {
+	documents: HashMap<String, String>, // by URI
+	utf16: bool, // positions count UTF-16 code units, the protocol's default, rather than characters
+}
+
+// the protocol's standard token type for each kind, so themes color them
+fn token_type(kind: Kind) -> &'static str {
+	match kind {
+		Kind::Keyword => "keyword",
+		Kind::Number => "number",
+		Kind::String => "string",
+		Kind::Comment => "comment",
+		Kind::Constant => "enumMember",
+		Kind::Directive => "macro",
+		Kind::Punctuation => "operator"
+	}
+}
+
+// LSP error codes
+const METHOD_NOT_FOUND: i64 = -32601;
+const REQUEST_FAILED: i64 = -32803;
+
+fn path(uri: &str) -> &str {
+	uri.strip_prefix("file://").unwrap_or(uri)
+}
+
+// the config file that applies to the document
+fn config(uri: &str) -> Config {
+	Path::new(path(uri)).parent().and_then(config::find).and_then(|path| config::load(&path).ok()).unwrap_or_default()
+}
+
+// the length of a token in the source
+fn token_len(token: &Token) -> usize {
+	match &token.typ {
+		TokenType::String(val) => val.chars().count() + 2,
+		TokenType::Eof => 0,
+		typ => typ.to_string().trim_matches('`').chars().count()
+	}
+}
+
+// every command in the order parse_with_locs gives their locations, and whether it pushes a
+// register number for the command after it
+fn flatten<'a>(commands: &'a [Command], out: &mut Vec<(&'a Command, bool)>) {
+	fn in_value<'a>(value: &'a Value, out: &mut Vec<(&'a Command, bool)>) {
+		match value {
+			Value::Function(commands) => flatten(commands, out),
+			Value::Array(values) => values.iter().for_each(|value| in_value(value, out)),
+			_ => {}
+		}
+	}
+
+	for (i, command) in commands.iter().enumerate() {
+		out.push((command, lint::is_register(commands, i)));
+
+		if let Command::Push(value) | Command::ILoad(_, value) = command {
+			in_value(value, out);
+		}
+	}
+}
+
+// the function literals in a value
+fn functions(value: &Value) -> usize {
+	match value {
+		Value::Function(_) => 1,
+		Value::Array(values) => values.iter().map(functions).sum(),
+		_ => 0
+	}
+}
+
+fn hover_text(command: &Command) -> String {
+	let name = get_command_name(command);
+
+	match stack_effect(command) {
+		Some((0, 0)) => format!("`{}` leaves the stack as it is", name),
+		Some((pops, pushes)) => format!("`{}` pops {} and pushes {}", name, values(pops), values(pushes)),
+		None => format!("`{}` pops a register number, and what else it pops and pushes depends on the function in that register", name)
+	}
+}
+
+fn values(count: usize) -> String {
+	match count {
+		1 => "1 value".to_owned(),
+		count => format!("{} values", count)
+	}
+}
+
+impl Default for Server {
+	fn default() -> Server {
+		Server::new()
+	}
+}
+
+impl Server {
+	pub fn new() -> Server {
+		Server {documents: HashMap::new(), utf16: true}
+	}
+
+	// the protocol's column of character `col` (from 1, as in Loc) on `line` (from 1)
+	fn column(&self, text: &str, line: u64, col: u64) -> usize {
+		let chars = (col as usize).saturating_sub(1);
+
+		match self.utf16 {
+			true => text.lines().nth(line as usize - 1).unwrap_or("").chars().take(chars).map(char::len_utf16).sum(),
+			false => chars
+		}
+	}
+
+	fn range(&self, text: &str, loc: &Loc, len: usize) -> Json {
+		let start = self.column(text, loc.line, loc.col);
+		let end = self.column(text, loc.line, loc.col + len as u64);
+		json!({"start": {"line": loc.line - 1, "character": start}, "end": {"line": loc.line - 1, "character": end}})
+	}
+
+	fn to_lsp(&self, text: &str, diagnostic: &Diagnostic) -> Json {
+		let mut message = diagnostic.message.to_string();
+
+		for note in &diagnostic.notes {
+			message.push_str(&format!("\n{}", note));
+		}
+
+		let range = match &diagnostic.primary_span {
+			Some(span) => self.range(text, &span.loc, span.len as usize),
+			None => json!({"start": {"line": 0, "character": 0}, "end": {"line": 0, "character": 0}})
+		};
+
+		let severity = match diagnostic.severity {
+			Severity::Error => 1,
+			Severity::Warning => 2,
+			Severity::Note => 3
+		};
+
+		json!({"range": range, "severity": severity, "code": diagnostic.code, "source": "evm-asm", "message": message})
+	}
+
+	// the errors or lint warnings for a document, as a publishDiagnostics notification
+	fn diagnostics(&self, uri: &str) -> Json {
+		let text = &self.documents[uri];
+		let parsed = tokenizer::tokenize(text, path(uri)).map_err(Error::from).and_then(|tokens| Ok(parser::parse_with_locs(tokens)?));
+
+		let diagnostics: Vec<Json> = match parsed {
+			Err(e) => vec![self.to_lsp(text, &Diagnostic::from(&e))],
+			Ok((commands, locs)) => {
+				let config = config(uri);
+				lint::lint(&commands, &locs, &config.lint).into_iter().filter_map(|warning| config.lints.apply(warning))
+					.map(|warning| self.to_lsp(text, &warning)).collect()
+			}
+		};
+
+		json!({"jsonrpc": "2.0", "method": "textDocument/publishDiagnostics", "params": {"uri": uri, "diagnostics": diagnostics}})
+	}
+
+	// the document's tokens and commands; no commands if it does not parse
+	fn parse(&self, uri: &str) -> Option<(Vec<Token>, Commands)> {
+		let tokens = tokenizer::tokenize(self.documents.get(uri)?, path(uri)).ok()?;
+		let commands = parser::parse_with_locs(tokens.clone()).map(|(commands, locs)| {
+			let mut flat = vec![];
+			flatten(&commands, &mut flat);
+			locs.into_iter().zip(flat).map(|(loc, (command, register))| (loc, command.clone(), register)).collect()
+		}).unwrap_or_default();
+
+		Some((tokens, commands))
+	}
+
+	// the token at a protocol position, the command it starts and the command the token before it
+	// starts, and every command
+	fn at(&self, params: &Json) -> Option<At> {
+		let uri = params["textDocument"]["uri"].as_str()?;
+		let text = self.documents.get(uri)?;
+		let line = params["position"]["line"].as_u64()? + 1;
+		let character = params["position"]["character"].as_u64()? as usize;
+		let (tokens, commands) = self.parse(uri)?;
+
+		let index = tokens.iter().position(|token| {
+			token.loc.line == line && (self.column(text, line, token.loc.col)..self.column(text, line, token.loc.col + token_len(token) as u64)).contains(&character)
+		})?;
+
+		let starting = |token: &Token| commands.iter().find(|(loc, ..)| loc.line == token.loc.line && loc.col == token.loc.col).map(|(_, command, register)| (command.clone(), *register));
+		let command = starting(&tokens[index]);
+		let operand_of = index.checked_sub(1).and_then(|previous| starting(&tokens[previous]));
+
+		Some(At {uri: uri.to_owned(), token: tokens[index].clone(), command, operand_of, commands})
+	}
+
+	fn hover(&self, params: &Json) -> Json {
+		match self.at(params) {
+			Some(At {uri, token, command: Some((command, _)), ..}) => json!({
+				"contents": {"kind": "markdown", "value": hover_text(&command)},
+				"range": self.range(&self.documents[&uri], &token.loc, token_len(&token))
+			}),
+			_ => Json::Null
+		}
+	}
+
+	// from a register number pushed for call, load and the loops, the iloads of that register
+	fn definition(&self, params: &Json) -> Json {
+		let Some(At {uri, operand_of: Some((Command::Push(Value::Number(reg)), true)), commands, ..}) = self.at(params) else {
+			return Json::Null;
+		};
+
+		let text = &self.documents[&uri];
+		let definitions: Vec<Json> = commands.iter().filter(|(_, command, _)| matches!(command, Command::ILoad(other, _) if *other as f64 == reg))
+			.map(|(loc, ..)| json!({"uri": uri, "range": self.range(text, loc, "iload".len())})).collect();
+
+		json!(definitions)
+	}
+
+	// every token and comment, each as five numbers: its line and start relative to the previous
+	// one, its length, its index in the legend and no modifiers
+	fn semantic_tokens(&self, params: &Json) -> Json {
+		let Some((uri, text)) = params["textDocument"]["uri"].as_str().and_then(|uri| Some((uri, self.documents.get(uri)?))) else {
+			return Json::Null;
+		};
+
+		let Ok(pieces) = highlight::classify(text, path(uri)) else {
+			return Json::Null;
+		};
+
+		let mut data = vec![];
+		let (mut line, mut start) = (0, 0);
+
+		for piece in pieces {
+			let col = self.column(text, piece.loc.line, piece.loc.col);
+			let end = self.column(text, piece.loc.line, piece.loc.col + piece.len as u64);
+
+			if piece.loc.line - 1 != line {
+				start = 0;
+			}
+
+			let kind = KINDS.iter().position(|kind| *kind == piece.kind).unwrap_or_default();
+			data.extend([(piece.loc.line - 1 - line) as usize, col - start, end - col, kind, 0]);
+			line = piece.loc.line - 1;
+			start = col;
+		}
+
+		json!({"data": data})
+	}
+
+	// the registers iloaded at the top level, as an outline; function bodies are skipped rather than
+	// parsed, so a huge literal costs only a scan for its closing brace
+	fn document_symbols(&self, params: &Json) -> Json {
+		let Some((uri, text)) = params["textDocument"]["uri"].as_str().and_then(|uri| Some((uri, self.documents.get(uri)?))) else {
+			return Json::Null;
+		};
+
+		let Some(outline) = tokenizer::tokenize(text, path(uri)).ok().and_then(|tokens| parser::parse_outline(tokens).ok()) else {
+			return json!([]);
+		};
+
+		let mut bodies = outline.bodies.iter();
+		let mut symbols = vec![];
+
+		for (command, loc) in outline.commands.iter().zip(&outline.locs) {
+			let (reg, value) = match command {
+				Command::ILoad(reg, value) => (Some(*reg), value),
+				Command::Push(value) => (None, value),
+				_ => continue
+			};
+
+			// the bodies skipped in the value, of which an iloaded function is the only one
+			let skipped: Vec<&parser::Body> = bodies.by_ref().take(functions(value)).collect();
+
+			let Some(reg) = reg else {
+				continue;
+			};
+
+			let selection = self.range(text, loc, "iload".len());
+			let (kind, detail, range) = match (value, skipped.first()) {
+				(Value::Function(_), Some(body)) => {
+					let end = outline.end(body);
+					(12, "function".to_owned(), json!({"start": selection["start"], "end": self.range(text, end, 1)["end"]}))
+				},
+				_ => (13, value.to_string(), selection.clone())
+			};
+
+			symbols.push(json!({"name": format!("register {}", reg), "detail": detail, "kind": kind, "range": range, "selectionRange": selection}));
+		}
+
+		json!(symbols)
+	}
+
+	fn format(&self, params: &Json) -> Result<Json, String> {
+		let uri = params["textDocument"]["uri"].as_str().ok_or("formatting needs a document")?;
+		let text = self.documents.get(uri).ok_or("Unknown document")?;
+		let formatted = formatter::format_with(text, path(uri), &config(uri).fmt).map_err(|e| e.to_string())?;
+
+		if formatted == *text {
+			return Ok(json!([]));
+		}
+
+		// one edit replacing the whole document
+		let lines = text.split('\n').count();
+		Ok(json!([{"range": {"start": {"line": 0, "character": 0}, "end": {"line": lines, "character": 0}}, "newText": formatted}]))
+	}
+
+	// the messages to send for one from the editor: a response to a request, and diagnostics after
+	// a document changes
+	pub fn handle(&mut self, message: &Json) -> Vec<Json> {
+		let params = &message["params"];
+		let method = message["method"].as_str().unwrap_or_default();
+
+		let result = match method {
+			"initialize" => {
+				// characters are simpler to count than UTF-16 code units, so they are used if the
+				// editor allows
+				let encodings = params["capabilities"]["general"]["positionEncodings"].as_array();
+				self.utf16 = !encodings.is_some_and(|encodings| encodings.iter().any(|encoding| encoding == "utf-32"));
+
+				Ok(json!({
+					"capabilities": {
+						"positionEncoding": if self.utf16 {"utf-16"} else {"utf-32"},
+						"textDocumentSync": 1,
+						"hoverProvider": true,
+						"definitionProvider": true,
+						"documentFormattingProvider": true,
+						"documentSymbolProvider": true,
+						"semanticTokensProvider": {
+							"legend": {"tokenTypes": KINDS.iter().map(|kind| token_type(*kind)).collect::<Vec<_>>(), "tokenModifiers": []},
+							"full": true
+						}
+					},
+					"serverInfo": {"name": "evm-asm", "version": env!("CARGO_PKG_VERSION")}
+				}))
+			},
+			"textDocument/didOpen" | "textDocument/didChange" => {
+				let uri = params["textDocument"]["uri"].as_str().unwrap_or_default().to_owned();
+				let text = match method {
+					"textDocument/didOpen" => params["textDocument"]["text"].as_str(),
+					_ => params["contentChanges"].as_array().and_then(|changes| changes.last()).and_then(|change| change["text"].as_str())
+				};
+
+				self.documents.insert(uri.clone(), text.unwrap_or_default().to_owned());
+				return vec![self.diagnostics(&uri)];
+			},
+			"textDocument/didClose" => {
+				let uri = params["textDocument"]["uri"].as_str().unwrap_or_default();
+				self.documents.remove(uri);
+				return vec![json!({"jsonrpc": "2.0", "method": "textDocument/publishDiagnostics", "params": {"uri": uri, "diagnostics": []}})];
+			},
+			"textDocument/hover" => Ok(self.hover(params)),
+			"textDocument/definition" => Ok(self.definition(params)),
+			"textDocument/semanticTokens/full" => Ok(self.semantic_tokens(params)),
+			"textDocument/documentSymbol" => Ok(self.document_symbols(params)),
+			"textDocument/formatting" => self.format(params).map_err(|message| (REQUEST_FAILED, message)),
+			"shutdown" => Ok(Json::Null),
+			method => Err((METHOD_NOT_FOUND, format!("Unsupported request: {}", method)))
+		};
+
+		// notifications have no id and get no response
+		if message.get("id").is_none() {
+			return vec![];
+		}
+
+		match result {
+			Ok(result) => vec![json!({"jsonrpc": "2.0", "id": message["id"], "result": result})],
+			Err((code, message_text)) => vec![json!({"jsonrpc": "2.0", "id": message["id"], "error": {"code": code, "message": message_text}})]
+		}
+	}
+}
+
+// serves the Language Server Protocol on stdin and stdout until the editor sends exit
+pub fn serve() -> io::Result<()> {
+	let stdin = io::stdin();
+	let mut input = stdin.lock();
+	let mut server = Server::new();
+
+	while let Some(message) = read_message(&mut input)? {
+		if message["method"] == "exit" {
+			break;
+		}
+
+		for reply in server.handle(&message) {
+			let body = reply.to_string();
+			let mut out = io::stdout().lock();
+			write!(out, "Content-Length: {}\r\n\r\n{}", body.len(), body)?;
+			out.flush()?;
+		}
+	}
+
+	Ok(())
+}
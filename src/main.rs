@@ -1,16 +1,1233 @@
 use base64::{Engine as _, engine::general_purpose};
 use getopts::Options;
+use serde_json::json;
 use std::env;
 use std::fs;
+use std::io::{self, Write};
+use std::mem;
+use std::path::{Path, PathBuf};
 use std::process;
+use std::sync::{Arc, Mutex, OnceLock};
+use std::thread;
+use std::time::{Duration, SystemTime};
 
-mod codegen;
-mod parser;
-mod tokenizer;
+use evm_asm::{ast_json, callgraph, cfg, codegen, config, coverage, dap, debugger, depth, diagnostic, disasm, doc, explore, formatter, html, interpreter, lint, listing, lsp, memory, mmap, optimizer, parser, printer, profile, rename, repl, search, stats, stream, testing, tokenizer, typecheck};
+use evm_asm::stream::StreamError;
+use evm_asm::cache::Cache;
+use evm_asm::diagnostic::{Diagnostic, Format, Level, Lints, Severity};
+use evm_asm::messages::{Locale, Message};
+use evm_asm::{Assembly, InputFormat, InputLimits, Keep, Progress};
 
-fn print_usage(pname: &str, opts: Options) {
-	let brief = format!("Usage: {} [options] [FILE]", pname);
-	print!("{}", opts.usage(&brief));
+// counts allocations for --report-memory
+#[cfg(feature = "memory")]
+#[global_allocator]
+static ALLOCATOR: memory::Tracking = memory::Tracking;
+
+#[derive(Clone, Copy, PartialEq)]
+enum Emit {
+	Bytecode,
+	Listing,
+	AstJson,
+	Html,
+	CfgDot,
+	CallGraphDot,
+	CallGraphJson,
+	MinAsm
+}
+
+impl Emit {
+	fn from_name(name: &str) -> Option<Emit> {
+		match name {
+			"bytecode" => Some(Emit::Bytecode),
+			"listing"  => Some(Emit::Listing),
+			"ast-json" => Some(Emit::AstJson),
+			"html"     => Some(Emit::Html),
+			"cfg-dot"  => Some(Emit::CfgDot),
+			"call-graph-dot"  => Some(Emit::CallGraphDot),
+			"call-graph-json" => Some(Emit::CallGraphJson),
+			"min-asm"  => Some(Emit::MinAsm),
+			_ => None
+		}
+	}
+
+	fn extension(&self) -> &str {
+		match self {
+			Emit::Bytecode => "bin",
+			Emit::Listing  => "lst",
+			Emit::AstJson  => "json",
+			Emit::Html     => "html",
+			Emit::CfgDot   => "dot",
+			Emit::CallGraphDot  => "calls.dot",
+			Emit::CallGraphJson => "calls.json",
+			Emit::MinAsm   => "min.asm"
+		}
+	}
+}
+
+// where an artifact goes: the -o path when emitting one artifact (stdout without -o),
+// otherwise the -o path or input path with the artifact's extension. `-o -` is stdout for all
+fn output_path(emit: Emit, emit_count: usize, output: Option<&str>, input: &str) -> Option<PathBuf> {
+	if output == Some("-") {
+		None
+	} else if emit_count == 1 {
+		output.map(PathBuf::from)
+	} else {
+		Some(Path::new(output.unwrap_or(input)).with_extension(emit.extension()))
+	}
+}
+
+fn print_usage(brief: &str, opts: &Options) {
+	print!("{}", opts.usage(brief));
+}
+
+fn parse_options(brief: &str, opts: &Options, args: &[String]) -> getopts::Matches {
+	match opts.parse(args) {
+		Ok(opt) => {opt}
+		Err(e) => {
+			eprintln!("{}.", e);
+			print_usage(brief, opts);
+			process::exit(exitcode::USAGE);
+		}
+	}
+}
+
+fn read_source(filename: &str) -> String {
+	let Ok(input) = fs::read_to_string(filename) else {
+		eprintln!("File cannot be read: {}", filename);
+		process::exit(exitcode::NOINPUT);
+	};
+
+	input
+}
+
+// the language of diagnostics, from --locale or the environment; set by error_format
+static LOCALE: OnceLock<Locale> = OnceLock::new();
+
+// the format and locale of diagnostics
+fn error_format(matches: &getopts::Matches) -> Format {
+	let name = matches.opt_str("message-format").or(matches.opt_str("error-format")).unwrap_or("text".to_owned());
+	let Some(format) = Format::from_name(&name) else {
+		eprintln!("Unknown error format: {}", name);
+		process::exit(exitcode::USAGE);
+	};
+
+	let locale = match matches.opt_str("locale") {
+		Some(name) => Locale::from_name(&name).unwrap_or_else(|| {
+			eprintln!("Unknown locale: {}", name);
+			process::exit(exitcode::USAGE);
+		}),
+		// as gettext looks for it; unknown languages get English
+		None => ["LC_ALL", "LC_MESSAGES", "LANG"].iter().filter_map(|var| env::var(var).ok()).find(|val| !val.is_empty())
+			.and_then(|val| Locale::from_name(&val)).unwrap_or_default()
+	};
+
+	let _ = LOCALE.set(locale);
+	format
+}
+
+fn locale() -> Locale {
+	LOCALE.get().copied().unwrap_or_default()
+}
+
+// diagnostics waiting to be printed as one JSON or SARIF document, which a tool reading stderr
+// expects rather than one per diagnostic
+static DEFERRED: Mutex<Vec<Diagnostic>> = Mutex::new(vec![]);
+
+// text is printed at once; other formats wait for flush_diagnostics
+fn emit(diagnostic: Diagnostic, format: Format, source: Option<&str>) {
+	let diagnostic = diagnostic.localize(locale());
+
+	match format {
+		Format::Text => eprintln!("{}", diagnostic::emit(&[diagnostic], format, source)),
+		_ => DEFERRED.lock().unwrap().push(diagnostic)
+	}
+}
+
+// -W, -A and -D in the order given, so a later flag overrides an earlier one, on top of `lints`
+fn lints(matches: &getopts::Matches, mut lints: Lints) -> Lints {
+	let mut flags: Vec<(usize, String, Level)> = vec![];
+
+	for (name, level) in [("W", Level::Warn), ("A", Level::Allow), ("D", Level::Deny)] {
+		flags.extend(matches.opt_strs_pos(name).into_iter().map(|(pos, warning)| (pos, warning, level)));
+	}
+
+	flags.sort_by_key(|(pos, _, _)| *pos);
+
+	for (_, warning, level) in flags {
+		if let Err(e) = lints.set(&warning, level) {
+			eprintln!("{}", e);
+			process::exit(exitcode::USAGE);
+		}
+	}
+
+	lints
+}
+
+// a warning as the lint flags make it: dropped, printed, or failing the run as an error
+fn warn(diagnostic: Diagnostic, lints: &Lints, format: Format, source: Option<&str>) {
+	match lints.apply(diagnostic) {
+		Some(diagnostic) if diagnostic.severity == Severity::Error => fail(diagnostic, format, source, exitcode::DATAERR),
+		Some(diagnostic) => emit(diagnostic, format, source),
+		None => {}
+	}
+}
+
+fn flush_diagnostics(format: Format) {
+	let deferred = mem::take(&mut *DEFERRED.lock().unwrap());
+
+	if !deferred.is_empty() {
+		eprintln!("{}", diagnostic::emit(&deferred, format, None));
+	}
+}
+
+fn fail(diagnostic: Diagnostic, format: Format, source: Option<&str>, code: i32) -> ! {
+	let explained = diagnostic::explain(diagnostic.code).is_some();
+	let code_name = diagnostic.code;
+	emit(diagnostic, format, source);
+
+	if format == Format::Text && explained {
+		eprintln!("\n{}", Message::new("explain").arg("code", code_name).localize(locale()));
+	}
+
+	flush_diagnostics(format);
+	process::exit(code);
+}
+
+fn parse_source(input: &str, filename: &str, format: Format) -> (Vec<parser::Command>, Vec<tokenizer::Loc>) {
+	let parsed = parse_source_with_tests(input, filename, format);
+	(parsed.commands, parsed.locs)
+}
+
+fn parse_source_with_tests(input: &str, filename: &str, format: Format) -> parser::Parsed {
+	let tokens = match tokenizer::tokenize(input, filename) {
+		Ok(tokens) => tokens,
+		Err(e) => fail(Diagnostic::from(&e), format, Some(input), exitcode::DATAERR)
+	};
+
+	match parser::parse_with_tests(tokens) {
+		Ok(parsed) => parsed,
+		Err(e) => fail(Diagnostic::from(&e), format, Some(input), exitcode::DATAERR)
+	}
+}
+
+fn load_source(filename: &str, format: Format) -> Vec<parser::Command> {
+	parse_source(&read_source(filename), filename, format).0
+}
+
+// bytecode files hold raw bytes, or the base64 text printed to stdout when they are named .b64
+fn read_bytecode(filename: &str) -> Vec<u8> {
+	let Ok(input) = fs::read(filename) else {
+		eprintln!("File cannot be read: {}", filename);
+		process::exit(exitcode::NOINPUT);
+	};
+
+	if Path::new(filename).extension().and_then(|e| e.to_str()) != Some("b64") {
+		return input;
+	}
+
+	match general_purpose::URL_SAFE_NO_PAD.decode(input.trim_ascii()) {
+		Ok(bytes) => bytes,
+		Err(e) => {
+			eprintln!("File is not base64: {}: {}", filename, e);
+			process::exit(exitcode::DATAERR);
+		}
+	}
+}
+
+fn is_bytecode(filename: &str, bytecode: bool) -> bool {
+	bytecode || matches!(Path::new(filename).extension().and_then(|e| e.to_str()), Some("bin" | "b64"))
+}
+
+// a program to run: bytecode when asked for or when the file is named .bin or .b64, source otherwise
+fn load_program(filename: &str, bytecode: bool, format: Format) -> Vec<parser::Command> {
+	if !is_bytecode(filename, bytecode) {
+		return load_source(filename, format);
+	}
+
+	match disasm::disassemble(&read_bytecode(filename)) {
+		Ok(commands) => commands,
+		Err(e) => fail(Diagnostic::from(&e), format, None, exitcode::DATAERR)
+	}
+}
+
+fn run_main(pname: &str, args: &[String]) {
+	let brief = format!("Usage: {} run [options] FILE...", pname);
+	let mut opts = Options::new();
+
+	opts.optflag("h", "help", "Prints this help menu.");
+	opts.optflag("b", "bytecode", "Treats FILE as bytecode regardless of its extension.");
+	opts.optflag("d", "debug", "Runs the program in the step debugger.");
+	opts.optflag("", "shared", "Runs several programs one after another on the same stack and registers.");
+	opts.optflag("", "watch", "Runs the program again whenever FILE changes, restarting it if it is still running.");
+	opts.optmulti("", "allow", "Lets the program use a host capability: query (query, info), io (print, readln) or all. Repeat or separate with commas; none are allowed by default.", "CAPS");
+	opts.optopt("", "max-steps", "Stops the program with an error after N commands.", "N");
+	opts.optopt("", "max-stack", "Stops the program with an error when the stack holds more than N values.", "N");
+	opts.optopt("", "max-memory", "Stops the program with an error when strings and arrays take more than BYTES.", "BYTES");
+	opts.optopt("", "max-depth", "Stops the program with an error when calls nest more than N deep.", "N");
+	opts.optflag("t", "trace", "Prints every executed command with its location and the top of the stack.");
+	opts.optopt("", "trace-depth", "Number of stack values shown per trace line (default 3).", "N");
+	opts.optopt("", "coverage", "Writes which commands ran to FILE: source annotated with run counts, or JSON if FILE ends in .json.", "FILE");
+	opts.optflag("", "profile", "Prints execution counts and time per opcode and per function to stderr.");
+	opts.optopt("", "snapshot", "Writes the stack, registers and position to FILE if the program stops with an error.", "FILE");
+	opts.optopt("", "resume", "Continues from a snapshot written by --snapshot for the same program.", "FILE");
+	opts.optopt("", "output", "Result format: text (default, one stack value per line) or json.", "FORMAT");
+	opts.optflag("", "registers", "Includes the registers in --output json.");
+	opts.optopt("", "trace-file", "Writes the trace to FILE instead of stderr.", "FILE");
+	opts.optopt("", "message-format", "Format of errors and warnings on stderr: text (default), json or sarif (SARIF 2.1.0, for code-scanning tools).", "FORMAT");
+	opts.optopt("", "error-format", "Same as --message-format.", "FORMAT");
+	opts.optopt("", "locale", "Language of errors and warnings: en or de. Defaults to the language of LC_ALL, LC_MESSAGES or LANG.", "LOCALE");
+
+	let matches = parse_options(&brief, &opts, args);
+
+	if matches.opt_present("h") {
+		print_usage(&brief, &opts);
+		return;
+	}
+
+	let Some(filename) = matches.free.first() else {
+		eprintln!("Must pass file to run.");
+		process::exit(exitcode::USAGE);
+	};
+
+	if matches.free.len() > 1 {
+		if !matches.opt_present("shared") {
+			eprintln!("Pass --shared to run several programs.");
+			process::exit(exitcode::USAGE);
+		}
+
+		if let Some(opt) = ["debug", "coverage", "profile", "snapshot", "resume", "watch"].iter().find(|opt| matches.opt_present(opt)) {
+			eprintln!("--{} works with a single program.", opt);
+			process::exit(exitcode::USAGE);
+		}
+	}
+
+	if matches.opt_present("watch") {
+		let args: Vec<&String> = args.iter().filter(|arg| *arg != "--watch").collect();
+		watch(filename, &args);
+	}
+
+	let format = error_format(&matches);
+	let json = match matches.opt_str("output").as_deref() {
+		None | Some("text") => false,
+		Some("json") => true,
+		Some(format) => {
+			eprintln!("Unknown output format: {}", format);
+			process::exit(exitcode::USAGE);
+		}
+	};
+
+	let mut allow = interpreter::Capabilities::default();
+
+	for name in matches.opt_strs("allow").iter().flat_map(|names| names.split(',')) {
+		if let Err(e) = allow.allow(name.trim()) {
+			eprintln!("{}", e);
+			process::exit(exitcode::USAGE);
+		}
+	}
+
+	let host = || interpreter::Sandbox {host: Box::new(interpreter::ConsoleHost), allow};
+
+	if matches.opt_present("d") {
+		let (mut debugger, source) = if is_bytecode(filename, matches.opt_present("b")) {
+			(debugger::Debugger::new(&load_program(filename, true, format), vec![]), None)
+		} else {
+			let source = read_source(filename);
+			let (commands, locs) = parse_source(&source, filename, format);
+			(debugger::Debugger::new(&commands, locs), Some(source))
+		};
+
+		debugger.vm.host = Box::new(host());
+
+		if let Err(e) = debugger.debug(source.as_deref()) {
+			eprintln!("Cannot read input: {}", e);
+			process::exit(exitcode::IOERR);
+		}
+
+		return;
+	}
+
+	// every program is loaded up front, so a missing or malformed file stops the run before it starts
+	let programs: Vec<_> = matches.free.iter().map(|filename| {
+		if is_bytecode(filename, matches.opt_present("b")) {
+			(load_program(filename, true, format), vec![], None)
+		} else {
+			let source = read_source(filename);
+			let (commands, locs) = parse_source(&source, filename, format);
+			(commands, locs, Some(source))
+		}
+	}).collect();
+
+	let mut vm = interpreter::Vm::new(host());
+	let (commands, _, _) = &programs[0];
+	let program = interpreter::lower(commands);
+	vm.load(program.clone());
+
+	if let Some(path) = matches.opt_str("resume") {
+		let snapshot = fs::read_to_string(&path).map_err(|e| e.to_string())
+			.and_then(|text| serde_json::from_str(&text).map_err(|e| e.to_string()))
+			.and_then(|snapshot| vm.restore(&program, &snapshot));
+
+		if let Err(e) = snapshot {
+			eprintln!("Cannot resume from {}: {}", path, e);
+			process::exit(exitcode::DATAERR);
+		}
+	}
+
+	let limit = |name: &str| match matches.opt_str(name).map(|val| val.parse::<u64>()) {
+		None => None,
+		Some(Ok(val)) => Some(val),
+		Some(Err(_)) => {
+			eprintln!("Invalid --{}: {}", name, matches.opt_str(name).unwrap());
+			process::exit(exitcode::USAGE);
+		}
+	};
+
+	vm.fuel = limit("max-steps");
+	vm.limits = interpreter::Limits {
+		max_stack: limit("max-stack").map(|val| val as usize),
+		max_memory: limit("max-memory").map(|val| val as usize),
+		max_depth: limit("max-depth").map(|val| val as usize)
+	};
+
+	let mut trace = if matches.opt_present("t") || matches.opt_present("trace-file") {
+		let depth = match matches.opt_str("trace-depth").map(|val| val.parse::<usize>()) {
+			None => 3,
+			Some(Ok(val)) => val,
+			Some(Err(_)) => {
+				eprintln!("Invalid trace depth: {}", matches.opt_str("trace-depth").unwrap());
+				process::exit(exitcode::USAGE);
+			}
+		};
+
+		let out: Box<dyn Write> = match matches.opt_str("trace-file") {
+			Some(path) => match fs::File::create(&path) {
+				Ok(file) => Box::new(io::BufWriter::new(file)),
+				Err(e) => {
+					eprintln!("Cannot write trace: {}", e);
+					process::exit(exitcode::CANTCREAT);
+				}
+			},
+			None => Box::new(io::stderr())
+		};
+
+		Some((depth, out))
+	} else {
+		None
+	};
+
+	let mut coverage = matches.opt_str("coverage").map(|_| coverage::Coverage::new(optimizer::count_commands(commands)));
+
+	let mut profile = matches.opt_present("profile").then(|| profile::Profile::new(commands));
+
+	let mut result = Ok(());
+	let mut current = 0; // the program that is running, or that failed
+
+	for (index, (commands, locs, _)) in programs.iter().enumerate() {
+		current = index;
+
+		if index > 0 {
+			vm.load(interpreter::lower(commands));
+		}
+
+		result = vm.run_with(&mut |instr, vm| {
+			if let Some(coverage) = &mut coverage {
+				coverage.record(instr.id);
+			}
+
+			if let Some(profile) = &mut profile {
+				profile.record(instr.id, &instr.command);
+			}
+
+			if let Some((depth, out)) = &mut trace {
+				let depth = *depth;
+				let loc = locs.get(instr.id).map(|loc| format!("{}:{}", loc.line, loc.col)).unwrap_or(format!("#{}", instr.id));
+				let top: Vec<_> = vm.stack.iter().rev().take(depth).rev().map(|value| preview(&value.to_ast(), 20)).collect();
+				let more = if vm.stack.len() > depth {"... "} else {""};
+
+				let _ = writeln!(out, "{:>8}  {:<24}  {}{}", loc, preview_command(&instr.command, 24), more, top.join(" "));
+			}
+		});
+
+		if result.is_err() {
+			break;
+		}
+	}
+
+	let (_, locs, source) = &programs[current];
+
+	if let (Err(_), Some(path)) = (&result, matches.opt_str("snapshot")) {
+		if let Err(e) = fs::write(&path, format!("{:#}\n", vm.snapshot())) {
+			eprintln!("Cannot write snapshot: {}", e);
+			process::exit(exitcode::CANTCREAT);
+		}
+	}
+
+	if let Some(profile) = &profile {
+		eprint!("{}", profile.report(locs));
+	}
+
+	if let (Some(coverage), Some(path)) = (&coverage, matches.opt_str("coverage")) {
+		let report = if path.ends_with(".json") {
+			format!("{:#}\n", coverage.to_json(locs))
+		} else {
+			coverage.annotate(locs, source.as_deref())
+		};
+
+		if let Err(e) = fs::write(&path, report) {
+			eprintln!("Cannot write coverage: {}", e);
+			process::exit(exitcode::CANTCREAT);
+		}
+	}
+
+	if json {
+		let values = |values: &[interpreter::Value]| values.iter().map(|value| ast_json::value_to_json(&value.to_ast())).collect::<Vec<_>>();
+		let mut out = json!({"stack": values(&vm.stack)});
+
+		if matches.opt_present("registers") {
+			out["registers"] = json!(values(&vm.registers));
+		}
+
+		if let Err(e) = &result {
+			out["error"] = json!({"message": e.message, "command": e.command, "backtrace": e.backtrace});
+
+			if programs.len() > 1 {
+				out["error"]["program"] = json!(matches.free[current]);
+			}
+
+			if let Some(loc) = locs.get(e.command) {
+				out["error"]["line"] = json!(loc.line);
+				out["error"]["column"] = json!(loc.col);
+			}
+		}
+
+		println!("{:#}", out);
+	}
+
+	if let Err(e) = result {
+		if !json {
+			fail(e.diagnostic(locs), format, source.as_deref(), exitcode::SOFTWARE);
+		}
+		process::exit(exitcode::SOFTWARE);
+	}
+
+	if !json {
+		for value in &vm.stack {
+			println!("{}", value);
+		}
+	}
+}
+
+// the long description of a diagnostic code, or the list of codes
+fn explain_main(pname: &str, args: &[String]) {
+	let brief = format!("Usage: {} explain [CODE]", pname);
+	let mut opts = Options::new();
+
+	opts.optflag("h", "help", "Prints this help menu.");
+
+	let matches = parse_options(&brief, &opts, args);
+
+	if matches.opt_present("h") {
+		print_usage(&brief, &opts);
+		return;
+	}
+
+	let Some(code) = matches.free.first() else {
+		for explanation in diagnostic::EXPLANATIONS {
+			println!("{}  {}", explanation.code, explanation.title);
+		}
+		return;
+	};
+
+	match diagnostic::explain(code) {
+		Some(explanation) => print!("{}", explanation.render()),
+		None => {
+			eprintln!("Unknown diagnostic code: {}", code);
+			process::exit(exitcode::USAGE);
+		}
+	}
+}
+
+// the file given with --config, or the evm-asm.toml that applies in the current directory
+fn load_config(matches: &getopts::Matches) -> config::Config {
+	match matches.opt_str("config").map(PathBuf::from).or_else(|| config::find(&env::current_dir().ok()?)) {
+		Some(path) => config::load(&path).unwrap_or_else(|e| {
+			eprintln!("{}", e);
+			process::exit(exitcode::CONFIG);
+		}),
+		None => config::Config::default()
+	}
+}
+
+// rewrites files in the canonical style, or with --check lists those that are not in it
+fn fmt_main(pname: &str, args: &[String]) {
+	let brief = format!("Usage: {} fmt [options] [FILE...]", pname);
+	let mut opts = Options::new();
+
+	opts.optflag("h", "help", "Prints this help menu.");
+	opts.optflag("", "check", "Changes nothing; lists the files that are not formatted and fails if there are any.");
+	opts.optopt("", "config", "Reads settings from FILE instead of the evm-asm.toml in the current directory or the nearest parent.", "FILE");
+	opts.optopt("", "indent", "Indents with tabs (default) or N spaces per level.", "tab|N");
+	opts.optopt("", "max-width", "Splits arrays that would make a line wider than N columns, counting a tab as 4. Defaults to 100.", "N");
+	opts.optflag("", "align-comments", "Starts comments that end consecutive lines in the same column.");
+	opts.optopt("", "functions", "Writes function bodies on their own lines (expanded, the default) or on one line when they fit (compact).", "STYLE");
+	opts.optopt("", "message-format", "Format of errors on stderr: text (default), json or sarif (SARIF 2.1.0, for code-scanning tools).", "FORMAT");
+	opts.optopt("", "error-format", "Same as --message-format.", "FORMAT");
+	opts.optopt("", "locale", "Language of errors: en or de. Defaults to the language of LC_ALL, LC_MESSAGES or LANG.", "LOCALE");
+
+	let matches = parse_options(&brief, &opts, args);
+
+	if matches.opt_present("h") {
+		print_usage(&brief, &opts);
+		return;
+	}
+
+	let format = error_format(&matches);
+	let check = matches.opt_present("check");
+	let mut options = load_config(&matches).fmt;
+	let mut settings: Vec<(&str, String)> = ["indent", "max-width", "functions"].iter().filter_map(|key| Some((*key, matches.opt_str(key)?))).collect();
+
+	if matches.opt_present("align-comments") {
+		settings.push(("align-comments", "true".to_owned()));
+	}
+
+	for (key, value) in settings {
+		if let Err(e) = options.set(key, &value) {
+			eprintln!("{}", e);
+			process::exit(exitcode::USAGE);
+		}
+	}
+
+	let format_source = |source: &str, filename: &str| match formatter::format_with(source, filename, &options) {
+		Ok(formatted) => formatted,
+		Err(e) => fail(Diagnostic::from(&e), format, Some(source), exitcode::DATAERR)
+	};
+
+	// without files, stdin to stdout
+	if matches.free.is_empty() {
+		let source = io::read_to_string(io::stdin()).unwrap_or_else(|e| {
+			eprintln!("Cannot read input: {}", e);
+			process::exit(exitcode::IOERR);
+		});
+		let formatted = format_source(&source, "<stdin>");
+
+		if !check {
+			print!("{}", formatted);
+		} else if formatted != source {
+			println!("<stdin>");
+			process::exit(exitcode::DATAERR);
+		}
+
+		return;
+	}
+
+	let mut unformatted = false;
+
+	for filename in &matches.free {
+		let source = read_source(filename);
+		let formatted = format_source(&source, filename);
+
+		if formatted == source {
+			continue;
+		}
+
+		if check {
+			println!("{}", filename);
+			unformatted = true;
+		} else if let Err(e) = fs::write(filename, formatted) {
+			eprintln!("Cannot write {}: {}", filename, e);
+			process::exit(exitcode::CANTCREAT);
+		}
+	}
+
+	if unformatted {
+		process::exit(exitcode::DATAERR);
+	}
+}
+
+// checks programs for the warnings in lint.rs, as set by the [lint] table of the config file and
+// the flags
+fn lint_main(pname: &str, args: &[String]) {
+	let brief = format!("Usage: {} lint [options] FILE...", pname);
+	let mut opts = Options::new();
+
+	opts.optflag("h", "help", "Prints this help menu.");
+	opts.optmulti("W", "warn", "Reports the warning NAME (dropped-push, uncalled-function, magic-number, deep-nesting, or its code), or all of them for `warnings`. The default for all but magic-number.", "NAME");
+	opts.optmulti("A", "allow", "Silences the warning NAME, or all of them for `warnings`.", "NAME");
+	opts.optmulti("D", "deny", "Turns the warning NAME into an error, or all of them for `warnings`.", "NAME");
+	opts.optopt("", "max-nesting", "Warns about functions inside more than N others. Defaults to 3.", "N");
+	opts.optopt("", "config", "Reads settings from FILE instead of the evm-asm.toml in the current directory or the nearest parent.", "FILE");
+	opts.optopt("", "message-format", "Format of errors and warnings on stderr: text (default), json or sarif (SARIF 2.1.0, for code-scanning tools).", "FORMAT");
+	opts.optopt("", "error-format", "Same as --message-format.", "FORMAT");
+	opts.optopt("", "locale", "Language of errors and warnings: en or de. Defaults to the language of LC_ALL, LC_MESSAGES or LANG.", "LOCALE");
+
+	let matches = parse_options(&brief, &opts, args);
+
+	if matches.opt_present("h") {
+		print_usage(&brief, &opts);
+		return;
+	}
+
+	if matches.free.is_empty() {
+		eprintln!("Must pass file to lint.");
+		process::exit(exitcode::USAGE);
+	}
+
+	let format = error_format(&matches);
+	let config = load_config(&matches);
+	let lints = lints(&matches, config.lints);
+	let mut options = config.lint;
+
+	if let Some(max) = matches.opt_str("max-nesting") {
+		if let Err(e) = options.set("max-nesting", &max) {
+			eprintln!("{}", e);
+			process::exit(exitcode::USAGE);
+		}
+	}
+
+	let mut denied = false;
+
+	for filename in &matches.free {
+		let source = read_source(filename);
+		let (commands, locs) = parse_source(&source, filename, format);
+
+		for warning in lint::lint(&commands, &locs, &options).into_iter().filter_map(|warning| lints.apply(warning)) {
+			denied |= warning.severity == Severity::Error;
+			emit(warning, format, Some(&source));
+		}
+	}
+
+	flush_diagnostics(format);
+
+	if denied {
+		process::exit(exitcode::DATAERR);
+	}
+}
+
+// a reference of the registers the files set, from their `;;;` doc comments
+fn doc_main(pname: &str, args: &[String]) {
+	let brief = format!("Usage: {} doc [options] FILE...", pname);
+	let mut opts = Options::new();
+
+	opts.optflag("h", "help", "Prints this help menu.");
+	opts.optopt("f", "format", "Writes markdown (default) or html.", "FORMAT");
+	opts.optopt("o", "output", "Writes the reference to FILE instead of stdout (-).", "FILE");
+	opts.optopt("", "message-format", "Format of errors on stderr: text (default), json or sarif (SARIF 2.1.0, for code-scanning tools).", "FORMAT");
+	opts.optopt("", "error-format", "Same as --message-format.", "FORMAT");
+	opts.optopt("", "locale", "Language of errors: en or de. Defaults to the language of LC_ALL, LC_MESSAGES or LANG.", "LOCALE");
+
+	let matches = parse_options(&brief, &opts, args);
+
+	if matches.opt_present("h") {
+		print_usage(&brief, &opts);
+		return;
+	}
+
+	if matches.free.is_empty() {
+		eprintln!("Must pass file to document.");
+		process::exit(exitcode::USAGE);
+	}
+
+	let render = match matches.opt_str("format").as_deref() {
+		None | Some("markdown") => doc::markdown,
+		Some("html") => doc::html,
+		Some(other) => {
+			eprintln!("Unknown doc format: {}", other);
+			process::exit(exitcode::USAGE);
+		}
+	};
+
+	let format = error_format(&matches);
+	let files: Vec<doc::File> = matches.free.iter().map(|filename| {
+		let source = read_source(filename);
+
+		match doc::document(&source, filename) {
+			Ok(file) => file,
+			Err(e) => fail(Diagnostic::from(&e), format, Some(&source), exitcode::DATAERR)
+		}
+	}).collect();
+
+	let reference = render(&files);
+
+	match matches.opt_str("o").filter(|path| path != "-") {
+		Some(path) => if let Err(e) = fs::write(&path, reference) {
+			eprintln!("Cannot write {}: {}", path, e);
+			process::exit(exitcode::CANTCREAT);
+		},
+		None => print!("{}", reference)
+	}
+}
+
+// moves what register OLD holds to register NEW in each file, rewriting the files
+fn rename_main(pname: &str, args: &[String]) {
+	let brief = format!("Usage: {} rename [options] OLD NEW FILE...\n\nRenumbers register OLD to NEW: its iloads and the pushed numbers that load, call, if and the loops take.", pname);
+	let mut opts = Options::new();
+
+	opts.optflag("h", "help", "Prints this help menu.");
+	opts.optflag("", "dry-run", "Changes nothing; prints the number of edits in each file.");
+
+	let matches = parse_options(&brief, &opts, args);
+
+	if matches.opt_present("h") {
+		print_usage(&brief, &opts);
+		return;
+	}
+
+	let [old, new, files @ ..] = matches.free.as_slice() else {
+		eprintln!("Must pass the old and new register.");
+		process::exit(exitcode::USAGE);
+	};
+
+	let (Some(old), Some(new)) = (old.parse::<u8>().ok().filter(|reg| *reg < 16), new.parse::<u8>().ok().filter(|reg| *reg < 16)) else {
+		eprintln!("Registers are numbers from 0 to 15.");
+		process::exit(exitcode::USAGE);
+	};
+
+	if files.is_empty() {
+		eprintln!("Must pass file to rename in.");
+		process::exit(exitcode::USAGE);
+	}
+
+	// every file is checked before any is written
+	let renamed: Vec<(&String, rename::Renamed)> = files.iter().map(|filename| match rename::rename(&read_source(filename), filename, old, new) {
+		Ok(renamed) => (filename, renamed),
+		Err(e) => {
+			eprintln!("{}: {}", filename, e);
+			process::exit(exitcode::DATAERR);
+		}
+	}).collect();
+
+	for (filename, renamed) in renamed {
+		for loc in &renamed.computed {
+			eprintln!("{}:{}:{}: the register is computed, so it may be {} and is not renamed", filename, loc.line, loc.col, old);
+		}
+
+		if matches.opt_present("dry-run") {
+			println!("{}: {} edits", filename, renamed.edits);
+		} else if renamed.edits > 0 {
+			if let Err(e) = fs::write(filename, renamed.source) {
+				eprintln!("Cannot write {}: {}", filename, e);
+				process::exit(exitcode::CANTCREAT);
+			}
+		}
+	}
+}
+
+// the runs of commands matching a pattern, in every source file in the paths
+fn grep_main(pname: &str, args: &[String]) {
+	let brief = format!("Usage: {} grep [options] PATTERN PATH...\n\nPATTERN is commands as in source, with _ for any command, value or register: 'push _ drop'. Exits with 1 if nothing matches.", pname);
+	let mut opts = Options::new();
+
+	opts.optflag("h", "help", "Prints this help menu.");
+	opts.optflag("c", "count", "Prints the number of matches in each file instead of the matches.");
+
+	let matches = parse_options(&brief, &opts, args);
+
+	if matches.opt_present("h") {
+		print_usage(&brief, &opts);
+		return;
+	}
+
+	let [pattern, paths @ ..] = matches.free.as_slice() else {
+		eprintln!("Must pass pattern to search for.");
+		process::exit(exitcode::USAGE);
+	};
+
+	let pattern = match search::Pattern::parse(pattern) {
+		Ok(pattern) => pattern,
+		Err(e) => {
+			eprintln!("Invalid pattern: {}", e);
+			process::exit(exitcode::USAGE);
+		}
+	};
+
+	let mut files = vec![];
+
+	for path in paths {
+		if let Err(e) = corpus_files(Path::new(path), &mut files) {
+			eprintln!("Cannot read {}: {}", path, e);
+			process::exit(exitcode::NOINPUT);
+		}
+	}
+
+	let mut found = false;
+
+	for file in files.iter().map(|file| file.to_string_lossy()).filter(|file| !is_bytecode(file, false)) {
+		let parsed = tokenizer::tokenize(&read_source(&file), &file).map_err(evm_asm::Error::from).and_then(|tokens| Ok(parser::parse_with_locs(tokens)?));
+
+		let (commands, locs) = match parsed {
+			Ok(parsed) => parsed,
+			Err(e) => {
+				eprintln!("Skipping {}: {}", file, e);
+				continue;
+			}
+		};
+
+		let matched = pattern.find(&commands, &locs);
+		found |= !matched.is_empty();
+
+		if matches.opt_present("c") {
+			println!("{}:{}", file, matched.len());
+			continue;
+		}
+
+		for found in matched {
+			let text: Vec<String> = found.commands.iter().map(|command| command.to_string()).collect();
+			println!("{}:{}:{}: {}", file, found.loc.line, found.loc.col, text.join(" "));
+		}
+	}
+
+	// as grep does
+	if !found {
+		process::exit(1);
+	}
+}
+
+fn explore_main(pname: &str, args: &[String]) {
+	let brief = format!("Usage: {} explore FILE\n\nPages through bytecode, raw or base64 if FILE ends in .b64, with the decoded commands beside their bytes.", pname);
+	let mut opts = Options::new();
+
+	opts.optflag("h", "help", "Prints this help menu.");
+
+	let matches = parse_options(&brief, &opts, args);
+
+	if matches.opt_present("h") {
+		print_usage(&brief, &opts);
+		return;
+	}
+
+	let Some(filename) = matches.free.first() else {
+		eprintln!("Must pass file to explore.");
+		process::exit(exitcode::USAGE);
+	};
+
+	if let Err(e) = explore::explore(read_bytecode(filename)) {
+		eprintln!("Cannot explore {}: {}", filename, e);
+		process::exit(exitcode::IOERR);
+	}
+}
+
+// the programs in `path`, a file or a directory searched recursively for source and bytecode
+fn corpus_files(path: &Path, files: &mut Vec<PathBuf>) -> io::Result<()> {
+	if !path.is_dir() {
+		files.push(path.to_owned());
+		return Ok(());
+	}
+
+	let mut entries: Vec<PathBuf> = fs::read_dir(path)?.map(|entry| entry.map(|entry| entry.path())).collect::<io::Result<_>>()?;
+	entries.sort();
+
+	for entry in entries {
+		if entry.is_dir() {
+			corpus_files(&entry, files)?;
+		} else if matches!(entry.extension().and_then(|e| e.to_str()), Some("asm" | "evmasm" | "bin" | "b64")) {
+			files.push(entry);
+		}
+	}
+
+	Ok(())
+}
+
+// opcode frequency, program sizes and repeated constants over every program in the paths
+fn stats_main(pname: &str, args: &[String]) {
+	let brief = format!("Usage: {} stats [options] PATH...", pname);
+	let mut opts = Options::new();
+
+	opts.optflag("h", "help", "Prints this help menu.");
+	opts.optopt("f", "format", "Writes text (default) or json.", "FORMAT");
+	opts.optopt("", "top", "Lists the N constants whose repeats take the most bytes. Defaults to 10.", "N");
+
+	let matches = parse_options(&brief, &opts, args);
+
+	if matches.opt_present("h") {
+		print_usage(&brief, &opts);
+		return;
+	}
+
+	if matches.free.is_empty() {
+		eprintln!("Must pass file or directory to scan.");
+		process::exit(exitcode::USAGE);
+	}
+
+	let json = match matches.opt_str("format").as_deref() {
+		None | Some("text") => false,
+		Some("json") => true,
+		Some(other) => {
+			eprintln!("Unknown stats format: {}", other);
+			process::exit(exitcode::USAGE);
+		}
+	};
+
+	let top = match matches.opt_str("top").map(|val| val.parse::<usize>()) {
+		None => 10,
+		Some(Ok(val)) => val,
+		Some(Err(_)) => {
+			eprintln!("Invalid --top.");
+			process::exit(exitcode::USAGE);
+		}
+	};
+
+	let mut files = vec![];
+
+	for path in &matches.free {
+		if let Err(e) = corpus_files(Path::new(path), &mut files) {
+			eprintln!("Cannot read {}: {}", path, e);
+			process::exit(exitcode::NOINPUT);
+		}
+	}
+
+	let mut stats = stats::Stats::default();
+
+	// a file that does not assemble is left out, so one broken program does not hide the rest
+	for file in &files {
+		let filename = file.to_string_lossy();
+		let result = match is_bytecode(&filename, false) {
+			true => stats.add(&read_bytecode(&filename)).map_err(evm_asm::Error::from),
+			false => stats.add_source(&read_source(&filename), &filename)
+		};
+
+		if let Err(e) = result {
+			eprintln!("Skipping {}: {}", filename, e);
+		}
+	}
+
+	match json {
+		true => println!("{:#}", stats.to_json(top)),
+		false => print!("{}", stats.text(top))
+	}
+}
+
+fn test_main(pname: &str, args: &[String]) {
+	let brief = format!("Usage: {} test [options] FILE", pname);
+	let mut opts = Options::new();
+
+	opts.optflag("h", "help", "Prints this help menu.");
+	opts.optopt("", "filter", "Runs only the tests whose name contains TEXT.", "TEXT");
+	opts.optopt("", "max-steps", "Fails a test after it executes N commands.", "N");
+
+	let matches = parse_options(&brief, &opts, args);
+
+	if matches.opt_present("h") {
+		print_usage(&brief, &opts);
+		return;
+	}
+
+	let Some(filename) = matches.free.first() else {
+		eprintln!("Must pass file to test.");
+		process::exit(exitcode::USAGE);
+	};
+
+	let fuel = match matches.opt_str("max-steps").map(|val| val.parse::<u64>()) {
+		None => None,
+		Some(Ok(val)) => Some(val),
+		Some(Err(_)) => {
+			eprintln!("Invalid --max-steps: {}", matches.opt_str("max-steps").unwrap());
+			process::exit(exitcode::USAGE);
+		}
+	};
+
+	let source = read_source(filename);
+	let parsed = parse_source_with_tests(&source, filename, Format::Text);
+	let filter = matches.opt_str("filter").unwrap_or_default();
+	let (mut passed, mut failed) = (0, 0);
+
+	for test in parsed.tests.iter().filter(|test| test.name.contains(&filter)) {
+		match testing::run_test(test, &parsed.commands, &parsed.locs, &source, fuel) {
+			testing::Outcome::Pass => {
+				println!("PASS {}", test.name);
+				passed += 1;
+			},
+			testing::Outcome::Fail(reason) => {
+				println!("FAIL {} (line {})", test.name, test.loc.line);
+
+				for line in reason.lines() {
+					println!("  {}", line);
+				}
+
+				failed += 1;
+			}
+		}
+	}
+
+	println!("{} passed, {} failed", passed, failed);
+
+	if failed > 0 {
+		process::exit(exitcode::SOFTWARE);
+	}
+}
+
+fn modified(filename: &str) -> Option<SystemTime> {
+	fs::metadata(filename).and_then(|meta| meta.modified()).ok()
+}
+
+// runs `run` with `args` in a child process, starting it over each time `filename` changes
+fn watch(filename: &str, args: &[&String]) -> ! {
+	let exe = env::current_exe().unwrap_or_else(|e| {
+		eprintln!("Cannot find the running executable: {}", e);
+		process::exit(exitcode::OSERR);
+	});
+
+	loop {
+		let started = modified(filename);
+		eprintln!("[watch] running {}", filename);
+
+		let mut child = process::Command::new(&exe).arg("run").args(args).spawn().unwrap_or_else(|e| {
+			eprintln!("Cannot start {}: {}", exe.display(), e);
+			process::exit(exitcode::OSERR);
+		});
+
+		let mut finished = false;
+
+		while modified(filename) == started {
+			if !finished {
+				if let Ok(Some(status)) = child.try_wait() {
+					eprintln!("[watch] exited with {}, waiting for changes", status);
+					finished = true;
+				}
+			}
+
+			thread::sleep(Duration::from_millis(200));
+		}
+
+		// killing a program that already exited does nothing
+		let _ = child.kill();
+		let _ = child.wait();
+	}
+}
+
+fn disasm_main(pname: &str, args: &[String]) {
+	let brief = format!("Usage: {} disasm [options] FILE", pname);
+	let mut opts = Options::new();
+
+	opts.optflag("h", "help", "Prints this help menu.");
+	opts.optflag("a", "annotate", "Shows byte offsets, raw bytes and previews of decoded constants.");
+	opts.optflag("", "best-effort", "Keeps decoding past corrupt regions, marking them with comments.");
+	opts.optopt("", "offset", "Starts decoding at byte N, which must begin a command.", "N");
+	opts.optopt("", "len", "Decodes only M bytes.", "M");
+	opts.optopt("f", "format", "Output format: asm (default) or ast-json, the schema of --emit ast-json.", "FORMAT");
+	opts.optopt("", "message-format", "Format of errors and warnings on stderr: text (default), json or sarif (SARIF 2.1.0, for code-scanning tools).", "FORMAT");
+	opts.optopt("", "error-format", "Same as --message-format.", "FORMAT");
+	opts.optopt("", "locale", "Language of errors and warnings: en or de. Defaults to the language of LC_ALL, LC_MESSAGES or LANG.", "LOCALE");
+
+	let matches = parse_options(&brief, &opts, args);
+
+	if matches.opt_present("h") {
+		print_usage(&brief, &opts);
+		return;
+	}
+
+	let Some(filename) = matches.free.first() else {
+		eprintln!("Must pass file to disassemble.");
+		process::exit(exitcode::USAGE);
+	};
+
+	let bytecode = read_bytecode(filename);
+
+	let parse_num = |name: &str, default: usize| match matches.opt_str(name).map(|val| val.parse::<usize>()) {
+		None => default,
+		Some(Ok(val)) => val,
+		Some(Err(_)) => {
+			eprintln!("Invalid --{}: {}", name, matches.opt_str(name).unwrap());
+			process::exit(exitcode::USAGE);
+		}
+	};
+
+	let format = error_format(&matches);
+	let offset = parse_num("offset", 0);
+	let len = parse_num("len", bytecode.len().saturating_sub(offset));
+
+	if matches.opt_present("best-effort") {
+		let decoded = match disasm::disassemble_best_effort(&bytecode, offset, len) {
+			Ok(decoded) => decoded,
+			Err(e) => fail(Diagnostic::from(&e), format, None, exitcode::DATAERR)
+		};
+
+		for decoded in decoded {
+			match decoded {
+				disasm::Decoded::Command(command) => print!("{}", printer::print(&[command])),
+				disasm::Decoded::Undecodable {offset, len, error} => println!("; {} undecodable bytes at offset {}: {}", len, offset, error)
+			}
+		}
+
+		return;
+	}
+
+	let json = match matches.opt_str("f").as_deref() {
+		None | Some("asm") => false,
+		Some("ast-json") => true,
+		Some(format) => {
+			eprintln!("Unknown disassembly format: {}", format);
+			process::exit(exitcode::USAGE);
+		}
+	};
+
+	let result = if matches.opt_present("a") {
+		disasm::annotate(&bytecode, offset, len)
+	} else if json {
+		disasm::disassemble_range(&bytecode, offset, len).map(|commands| format!("{:#}\n", ast_json::program_to_json(&commands, &[])))
+	} else {
+		disasm::disassemble_range(&bytecode, offset, len).map(|commands| printer::print(&commands))
+	};
+
+	match result {
+		Ok(text) => print!("{}", text),
+		Err(e) => fail(Diagnostic::from(&e), format, None, exitcode::DATAERR)
+	}
+}
+
+fn verify_main(pname: &str, args: &[String]) {
+	let brief = format!("Usage: {} verify [options] FILE", pname);
+	let mut opts = Options::new();
+
+	opts.optflag("h", "help", "Prints this help menu.");
+	opts.optopt("", "checksum", "Fails unless the CRC-32 of FILE is HEX, as printed for the program when it was verified before.", "HEX");
+	opts.optopt("", "message-format", "Format of errors and warnings on stderr: text (default), json or sarif (SARIF 2.1.0, for code-scanning tools).", "FORMAT");
+	opts.optopt("", "error-format", "Same as --message-format.", "FORMAT");
+	opts.optopt("", "locale", "Language of errors and warnings: en or de. Defaults to the language of LC_ALL, LC_MESSAGES or LANG.", "LOCALE");
+
+	let matches = parse_options(&brief, &opts, args);
+
+	if matches.opt_present("h") {
+		print_usage(&brief, &opts);
+		return;
+	}
+
+	let Some(filename) = matches.free.first() else {
+		eprintln!("Must pass file to verify.");
+		process::exit(exitcode::USAGE);
+	};
+
+	let checksum = match matches.opt_str("checksum").map(|hex| u32::from_str_radix(hex.trim_start_matches("0x"), 16)) {
+		None => None,
+		Some(Ok(checksum)) => Some(checksum),
+		Some(Err(_)) => {
+			eprintln!("Invalid --checksum: {}", matches.opt_str("checksum").unwrap());
+			process::exit(exitcode::USAGE);
+		}
+	};
+
+	let format = error_format(&matches);
+
+	match disasm::verify(&read_bytecode(filename), checksum) {
+		Ok(verified) => println!("{}: valid, {} bytes, {} commands, checksum {:08x}", filename, verified.size, verified.commands, verified.checksum),
+		Err(e) => fail(Diagnostic::from(&e), format, None, exitcode::DATAERR)
+	}
+}
+
+// display form of a value, cut off at `width` characters
+fn preview(value: &parser::Value, width: usize) -> String {
+	truncate(value.to_string(), width)
+}
+
+fn preview_command(command: &parser::Command, width: usize) -> String {
+	truncate(command.to_string(), width)
+}
+
+fn truncate(text: String, width: usize) -> String {
+	if text.chars().count() <= width {
+		text
+	} else {
+		format!("{}...", text.chars().take(width).collect::<String>())
+	}
 }
 
 fn main() {
@@ -18,24 +1235,224 @@ fn main() {
 	let args: Vec<String> = env::args().collect();
 	let pname = args[0].clone();
 
+	match args.get(1).map(String::as_str) {
+		Some("disasm") => disasm_main(&pname, &args[2..]),
+		Some("verify") => verify_main(&pname, &args[2..]),
+		Some("run") => run_main(&pname, &args[2..]),
+		Some("test") => test_main(&pname, &args[2..]),
+		Some("explain") => explain_main(&pname, &args[2..]),
+		Some("fmt") => fmt_main(&pname, &args[2..]),
+		Some("lint") => lint_main(&pname, &args[2..]),
+		Some("doc") => doc_main(&pname, &args[2..]),
+		Some("stats") => stats_main(&pname, &args[2..]),
+		Some("explore") => explore_main(&pname, &args[2..]),
+		Some("grep") => grep_main(&pname, &args[2..]),
+		Some("rename") => rename_main(&pname, &args[2..]),
+		Some("lsp") => {
+			if let Err(e) = lsp::serve() {
+				eprintln!("Language server error: {}", e);
+				process::exit(exitcode::IOERR);
+			}
+		},
+		Some("dap") => {
+			if let Err(e) = dap::serve() {
+				eprintln!("Debug adapter error: {}", e);
+				process::exit(exitcode::IOERR);
+			}
+		},
+		Some("repl") => {
+			if let Err(e) = repl::repl() {
+				eprintln!("Cannot read input: {}", e);
+				process::exit(exitcode::IOERR);
+			}
+		},
+		_ => assemble_main(&pname, &args[1..])
+	}
+}
+
+// several files at once, each to FILE.bin, on as many threads as there are cores; their diagnostics
+// are reported in the order of the files
+fn assemble_files(filenames: &[String], options: &evm_asm::AssembleOptions, host_io: bool, format: Format, lints: &Lints) {
+	let sources: Vec<String> = filenames.iter().map(|filename| read_source(filename)).collect();
+	let inputs: Vec<(&str, &str)> = filenames.iter().map(String::as_str).zip(sources.iter().map(String::as_str)).collect();
+	let mut failed = false;
+
+	for ((filename, source), result) in inputs.iter().zip(evm_asm::assemble_all(&inputs, options)) {
+		let bytecode = match result {
+			Ok(bytecode) => bytecode,
+			Err(e) => {
+				emit(Diagnostic::from(&e), format, Some(source));
+				failed = true;
+				continue;
+			}
+		};
+
+		if !host_io {
+			if let Some(command) = disasm::disassemble(&bytecode).ok().as_deref().and_then(codegen::find_console_io) {
+				let warning = Diagnostic::warning("W001", Message::new("W001").arg("command", command))
+					.with_note(Message::new("W001.note"))
+					.with_note(format!("in {}", filename));
+				warn(warning, lints, format, None);
+			}
+		}
+
+		if let Err(e) = fs::write(Path::new(filename).with_extension("bin"), bytecode) {
+			eprintln!("Cannot write output: {}", e);
+			process::exit(exitcode::CANTCREAT);
+		}
+	}
+
+	flush_diagnostics(format);
+
+	if failed {
+		process::exit(exitcode::DATAERR);
+	}
+}
+
+// --report-memory: the peak allocated in each phase, each counted from what was allocated when it
+// started
+struct MemoryReport {
+	on: bool,
+	phases: Mutex<Vec<(String, usize)>>,
+}
+
+impl MemoryReport {
+	fn phase(&self, name: &str) {
+		if self.on {
+			self.phases.lock().unwrap().push((name.to_owned(), memory::peak()));
+			memory::reset_peak();
+		}
+	}
+
+	fn print(&self) {
+		if !self.on {
+			return;
+		}
+
+		let phases = self.phases.lock().unwrap();
+
+		if cfg!(feature = "memory") {
+			eprintln!("Peak memory allocated:");
+
+			for (name, peak) in phases.iter() {
+				eprintln!("  {:<10}{:>12}", name, memory::human(*peak));
+			}
+		}
+
+		match memory::peak_rss() {
+			Some(rss) => eprintln!("Peak resident: {}", memory::human(rss)),
+			None => eprintln!("Peak resident: unknown on this system")
+		}
+
+		if !cfg!(feature = "memory") {
+			eprintln!("Build with --features memory for the memory of each phase.");
+			return;
+		}
+
+		// what would have saved the most
+		match phases.iter().max_by_key(|(_, peak)| *peak).map(|(name, _)| name.as_str()) {
+			Some("read") => eprintln!("Reading the source took the most; --mmap maps it instead of copying it."),
+			Some("tokenize" | "parse" | "optimize") => eprintln!("Holding the program took the most; --stream assembles it a command at a time, unoptimized."),
+			_ => {}
+		}
+	}
+}
+
+// --stream: raw bytes to a file, or base64 to stdout as it is encoded
+fn stream_bytecode(source: &str, filename: &str, max_size: Option<usize>, target: &codegen::TargetLimits, legacy_numbers: bool, output: Option<String>, format: Format) {
+	let result = match &output {
+		Some(path) => fs::File::create(path).map_err(StreamError::from).and_then(|file| {
+			let mut out = io::BufWriter::new(file);
+			stream::assemble_stream(source, filename, max_size, target, legacy_numbers, &mut out)?;
+			Ok(out.flush()?)
+		}),
+		None => {
+			let stdout = io::stdout();
+			let mut out = base64::write::EncoderWriter::new(stdout.lock(), &general_purpose::URL_SAFE_NO_PAD);
+
+			stream::assemble_stream(source, filename, max_size, target, legacy_numbers, &mut out).and_then(|_| {
+				writeln!(out.finish()?)?;
+				Ok(())
+			})
+		}
+	};
+
+	match result {
+		Ok(()) => {},
+		Err(StreamError::Assemble(e)) => fail(Diagnostic::from(&e), format, Some(source), exitcode::DATAERR),
+		Err(StreamError::Io(e)) => {
+			eprintln!("Cannot write output: {}", e);
+			process::exit(exitcode::CANTCREAT);
+		}
+	}
+}
+
+// the program as written, so errors point at the source rather than at what the optimizer made of it
+fn check_stack(commands: &[parser::Command], locs: &[tokenizer::Loc], matches: &getopts::Matches, source: Option<&str>, format: Format) {
+	let max_stack = match matches.opt_str("check-stack").map(|val| val.parse::<usize>()) {
+		None => None,
+		Some(Ok(max)) => Some(max),
+		Some(Err(_)) => {
+			eprintln!("Invalid --check-stack: {}", matches.opt_str("check-stack").unwrap());
+			process::exit(exitcode::USAGE);
+		}
+	};
+
+	match depth::analyze(commands, locs, max_stack) {
+		Ok(analysis) => if matches.opt_present("v") {
+			match analysis.peak {
+				Some(peak) => eprintln!("Stack depth: at most {} values", peak),
+				None => eprintln!("Stack depth: unbounded")
+			}
+		},
+		Err(e) => fail(Diagnostic::from(&e), format, source, exitcode::DATAERR)
+	}
+}
+
+fn assemble_main(pname: &str, args: &[String]) {
+	let brief = format!("Usage: {0} [options] [FILE...]\n       {0} disasm [options] FILE\n       {0} verify [options] FILE\n       {0} run [options] FILE\n       {0} test [options] FILE\n       {0} fmt [options] [FILE...]\n       {0} lint [options] FILE...\n       {0} doc [options] FILE...\n       {0} stats [options] PATH...\n       {0} explore FILE\n       {0} grep PATTERN PATH...\n       {0} rename OLD NEW FILE...\n       {0} explain [CODE]\n       {0} repl\n       {0} lsp\n       {0} dap", pname);
+
 	// setup options
 	let mut opts = Options::new();
 
 	opts.optflag("h", "help", "Prints this help menu.");
+	opts.optflag("v", "verbose", "Prints optimization statistics.");
+	opts.optopt("o", "output", "Writes output to FILE, or to stdout for -; with several --emit kinds, FILE's extension is replaced per kind.", "FILE");
+	opts.optopt("", "emit", "Comma-separated outputs to produce: bytecode (raw in files, base64 on stdout), listing, ast-json (the program before optimization, with locations), html (the source, highlighted, with an anchor per line), cfg-dot (the control flow as a Graphviz digraph), call-graph-dot and call-graph-json (which registers' functions call which, before optimization, with recursion cycles), min-asm (the optimized program as the shortest source text). Defaults to bytecode.", "KINDS");
+	opts.optopt("", "input-format", "Reads FILE as asm (default) or ast-json, the schema of --emit ast-json.", "FORMAT");
+	opts.optflag("", "host-io", "Declares that the target host provides console I/O (print, readln).");
+	opts.optopt("", "cache", "Keeps bytecode in DIR by a hash of the source and optimizer options, and reads it back when an unchanged source is assembled again.", "DIR");
+	opts.optflag("", "mmap", "Maps FILE into memory instead of reading it, for generated sources of several gigabytes.");
+	opts.optflag("", "stream", "Assembles FILE a top-level command at a time without holding the whole program, for generated sources too large to parse at once. Only writes bytecode, and runs no optimizer, as with -O0.");
+	opts.optflagopt("", "check-stack", "Fails if a command takes more values than the stack can hold on every path, or, given MAX, if the stack holds more than MAX values on every path. Assumes the stack starts empty; -v prints the deepest it can get.", "MAX");
+	opts.optflag("", "typecheck", "Fails if a command gets a value of a kind it always fails on, such as a string for +, following kinds through the stack, registers and calls. Values of unknown kind pass.");
+	opts.optflag("", "verify-roundtrip", "Disassembles the bytecode and fails if it differs from the assembled program.");
+	opts.optflag("", "progress", "Shows the progress of each phase on stderr.");
+	opts.optflag("", "report-memory", "Prints the peak memory of each phase on stderr, to find the one to blame when a large input runs out of memory. The figures per phase need a build with the memory feature; otherwise only the process's peak is shown.");
+	opts.optmulti("W", "warn", "Reports the warning NAME (console-io, or its code), or all of them for `warnings`. The default for all but magic-number.", "NAME");
+	opts.optmulti("A", "allow", "Silences the warning NAME, or all of them for `warnings`.", "NAME");
+	opts.optmulti("D", "deny", "Turns the warning NAME into an error, or all of them for `warnings`; -D warnings for strict builds.", "NAME");
+	opts.optflag("", "legacy-numbers", "Reads numbers as before they were strict: a number ends where its digits do, so 1.2.3 is 1.2 and .3, and 1-2 is 1 and -2. Not cached.");
+	opts.optopt("", "max-size", "Fails if the bytecode is larger than BYTES.", "BYTES");
+	opts.optopt("", "max-file-size", "Fails without reading FILE if it is larger than BYTES.", "BYTES");
+	opts.optopt("", "max-tokens", "Fails at the token after the first N, before the rest take memory.", "N");
+	opts.optopt("", "max-nodes", "Fails at the command or value after the first N, before the rest take memory.", "N");
+	opts.optopt("", "max-array-length", "Fails if an array of the optimized program has more than N values.", "N");
+	opts.optopt("", "max-function-length", "Fails if a function literal of the optimized program has more than N commands.", "N");
+	opts.optopt("", "max-string-size", "Fails if a string of the optimized program is larger than BYTES.", "BYTES");
+	opts.optopt("", "max-nesting-depth", "Fails if arrays and functions of the optimized program nest more than N deep.", "N");
+	opts.optmulti("O", "", "Sets the optimization level (0-2, default 2) or an optimizer option: inline-threshold=N, or closed, for a program whose registers nothing reads after it ends, so loads whose calls were all inlined are dropped.", "LEVEL");
+	opts.optmulti("", "opt", "Enables or disables (no-PASS) an optimization pass: inline, fold, peephole, dce.", "PASS");
+	opts.optopt("", "message-format", "Format of errors and warnings on stderr: text (default), json or sarif (SARIF 2.1.0, for code-scanning tools).", "FORMAT");
+	opts.optopt("", "error-format", "Same as --message-format.", "FORMAT");
+	opts.optopt("", "locale", "Language of errors and warnings: en or de. Defaults to the language of LC_ALL, LC_MESSAGES or LANG.", "LOCALE");
 
 	// parse options
-	let mut matches = match opts.parse(&args[1..]) {
-		Ok(opt) => {opt}
-		Err(e) => {
-			eprintln!("{}.", e);
-			print_usage(&pname, opts);
-			process::exit(exitcode::USAGE);
-		}
-	};
+	let mut matches = parse_options(&brief, &opts, args);
 
 	// validate and/or execute options
 	if matches.opt_present("h") {
-		print_usage(&pname, opts);
+		print_usage(&brief, &opts);
 		return;
 	}
 
@@ -44,25 +1461,262 @@ fn main() {
 		process::exit(exitcode::USAGE);
 	} else {
 		let filename = matches.free.remove(0);
-		let Ok(input) = fs::read_to_string(&filename) else {
-			eprintln!("File cannot be read: {}", filename);
-			process::exit(exitcode::NOINPUT);
+
+		let mut emits = vec![];
+
+		for name in matches.opt_str("emit").unwrap_or("bytecode".to_owned()).split(',') {
+			let Some(emit) = Emit::from_name(name.trim()) else {
+				eprintln!("Unknown emit kind: {}", name);
+				process::exit(exitcode::USAGE);
+			};
+
+			if !emits.contains(&emit) {
+				emits.push(emit);
+			}
+		}
+
+		let format = error_format(&matches);
+		let lints = lints(&matches, Lints::default());
+
+		// a line per phase on stderr, rewritten as the phase advances
+		let show_progress = matches.opt_present("progress");
+		let memory_report = Arc::new(MemoryReport {on: matches.opt_present("report-memory"), phases: Mutex::new(vec![])});
+		memory::reset_peak();
+
+		let on_progress = (show_progress || memory_report.on).then(|| Progress::new({
+			let memory_report = memory_report.clone();
+
+			move |phase, percent| {
+				if show_progress {
+					eprint!("\r{:<10}{:>3}%", format!("{:?}", phase).to_lowercase(), percent);
+
+					if percent == 100 {
+						eprintln!();
+					}
+				}
+
+				if percent == 100 {
+					memory_report.phase(&format!("{:?}", phase).to_lowercase());
+				}
+			}
+		}));
+
+		if emits.contains(&Emit::Html) && !matches!(matches.opt_str("input-format").as_deref(), None | Some("asm")) {
+			eprintln!("--emit html needs asm input.");
+			process::exit(exitcode::USAGE);
+		}
+
+		let mut optimizer_options = optimizer::Options::default();
+
+		for opt in matches.opt_strs("O") {
+			if let Err(e) = optimizer_options.set(&opt) {
+				eprintln!("{}", e);
+				process::exit(exitcode::USAGE);
+			}
+		}
+
+		for opt in matches.opt_strs("opt") {
+			if let Err(e) = optimizer_options.toggle(&opt) {
+				eprintln!("{}", e);
+				process::exit(exitcode::USAGE);
+			}
+		}
+
+		let max_size = match matches.opt_str("max-size").map(|val| val.parse::<usize>()) {
+			Some(Ok(val)) => Some(val),
+			Some(Err(_)) => {
+				eprintln!("Invalid maximum size: {}", matches.opt_str("max-size").unwrap());
+				process::exit(exitcode::USAGE);
+			},
+			None => None
 		};
 
-		let tokenize_result = tokenizer::tokenize(&input, &filename);
-		let Ok(tokens) = tokenize_result else {
-			eprintln!("Tokenizer error: {}", tokenize_result.err().unwrap());
-			process::exit(exitcode::DATAERR);
+		let limit = |name: &str| match matches.opt_str(name).map(|val| val.parse::<usize>()) {
+			None => None,
+			Some(Ok(val)) => Some(val),
+			Some(Err(_)) => {
+				eprintln!("Invalid --{}: {}", name, matches.opt_str(name).unwrap());
+				process::exit(exitcode::USAGE);
+			}
 		};
 
-		let parse_result = parser::parse(tokens);
-		let Ok(commands) = parse_result else {
-			eprintln!("Parser error: {}", parse_result.err().unwrap());
-			process::exit(exitcode::DATAERR);
+		let limits = InputLimits {max_file_size: limit("max-file-size"), max_tokens: limit("max-tokens"), max_nodes: limit("max-nodes")};
+		let target = codegen::TargetLimits {
+			max_array_length: limit("max-array-length"),
+			max_function_length: limit("max-function-length"),
+			max_string_size: limit("max-string-size"),
+			max_nesting_depth: limit("max-nesting-depth")
+		};
+
+		if !matches.free.is_empty() {
+			if emits != [Emit::Bytecode] || matches.opt_present("o") || matches.opt_present("verify-roundtrip") || matches.opt_present("check-stack") || matches.opt_present("typecheck") || !matches!(matches.opt_str("input-format").as_deref(), None | Some("asm")) {
+				eprintln!("Several files can only be assembled to bytecode, each next to its source.");
+				process::exit(exitcode::USAGE);
+			}
+
+			let options = evm_asm::AssembleOptions {optimizer: optimizer_options, max_size, limits, target, on_progress: None, cache: matches.opt_str("cache").map(Cache::new), legacy_numbers: matches.opt_present("legacy-numbers"), ..Default::default()};
+			let filenames: Vec<String> = [filename].into_iter().chain(mem::take(&mut matches.free)).collect();
+			assemble_files(&filenames, &options, matches.opt_present("host-io"), format, &lints);
+			return;
+		}
+
+		// the size is checked before the file is read or mapped
+		if let (Some(max), Ok(metadata)) = (limits.max_file_size, fs::metadata(&filename)) {
+			if metadata.len() > max as u64 {
+				fail(Diagnostic::from(&evm_asm::Error::FileTooLarge {size: metadata.len() as usize, max}), format, None, exitcode::DATAERR);
+			}
+		}
+
+		// a mapped file is read as the tokenizer goes rather than copied into memory first
+		let mapped = matches.opt_present("mmap").then(|| mmap::Mmap::open(&filename));
+		let read;
+
+		let source = match &mapped {
+			Some(mapped) => match mapped.as_ref().ok().and_then(|mapped| mapped.text().ok()) {
+				Some(text) => text,
+				None => {
+					eprintln!("File cannot be read: {}", filename);
+					process::exit(exitcode::NOINPUT);
+				}
+			},
+			None => {
+				read = read_source(&filename);
+				read.as_str()
+			}
+		};
+
+		memory_report.phase("read");
+
+		if matches.opt_present("stream") {
+			if emits != [Emit::Bytecode] || matches.opt_present("cache") || matches.opt_present("verify-roundtrip") || matches.opt_present("check-stack") || matches.opt_present("typecheck") || !matches!(matches.opt_str("input-format").as_deref(), None | Some("asm")) {
+				eprintln!("--stream only writes bytecode from asm input, and cannot be cached, verified or checked.");
+				process::exit(exitcode::USAGE);
+			}
+
+			stream_bytecode(source, &filename, max_size, &target, matches.opt_present("legacy-numbers"), matches.opt_str("o").filter(|path| path != "-"), format);
+			memory_report.phase("stream");
+			memory_report.print();
+			flush_diagnostics(format);
+			return;
+		}
+
+		let input = match matches.opt_str("input-format").as_deref() {
+			None | Some("asm") => InputFormat::Asm,
+			Some("ast-json") => InputFormat::AstJson,
+			Some(name) => {
+				eprintln!("Unknown input format: {}", name);
+				process::exit(exitcode::USAGE);
+			}
+		};
+
+		// ast-json, the call graph, html and the checks need the program as written
+		let keep = match emits.iter().any(|emit| matches!(emit, Emit::AstJson | Emit::CallGraphDot | Emit::CallGraphJson | Emit::Html)) || matches.opt_present("check-stack") || matches.opt_present("typecheck") {
+			true => Keep::Written,
+			false => Keep::Optimized
+		};
+
+		// the size is checked below instead, to name the largest values
+		let options = evm_asm::AssembleOptions {optimizer: optimizer_options, max_size: None, limits, target, on_progress, cache: matches.opt_str("cache").map(Cache::new), legacy_numbers: matches.opt_present("legacy-numbers"), input, keep};
+
+		// the locations of ast-json input are in the source it was made from, which is not at hand
+		let written = (input == InputFormat::Asm).then_some(source);
+
+		let Assembly {bytecode, commands, written: program, report} = match evm_asm::assemble_file(source, &filename, &options) {
+			Ok(assembly) => assembly,
+			Err(e) => fail(Diagnostic::from(&e), format, written, exitcode::DATAERR)
 		};
 
-		let bytecode = codegen::generate(commands);
+		let (parsed, locs) = program.unzip();
+		let locs = locs.unwrap_or_default();
+
+		if matches.opt_present("check-stack") {
+			check_stack(parsed.as_deref().unwrap_or_default(), &locs, &matches, written, format);
+		}
+
+		if matches.opt_present("typecheck") {
+			if let Err(e) = typecheck::check(parsed.as_deref().unwrap_or_default(), &locs) {
+				fail(Diagnostic::from(&e), format, written, exitcode::DATAERR);
+			}
+		}
+
+		if matches.opt_present("v") {
+			for line in report {
+				eprintln!("{}", line);
+			}
+		}
+
+		if !matches.opt_present("host-io") {
+			if let Some(command) = codegen::find_console_io(&commands) {
+				let warning = Diagnostic::warning("W001", Message::new("W001").arg("command", command))
+					.with_note(Message::new("W001.note"));
+				warn(warning, &lints, format, None);
+			}
+		}
+
+		if let Some(max_size) = max_size {
+			if bytecode.len() > max_size {
+				let mut diagnostic = Diagnostic::from(&evm_asm::Error::TooLarge {size: bytecode.len(), max: max_size});
+
+				for (size, value) in codegen::largest_values(&commands, 10) {
+					diagnostic = diagnostic.with_note(format!("{:>10} bytes  {}", size, preview(value, 60)));
+				}
+
+				fail(diagnostic, format, None, exitcode::DATAERR);
+			}
+		}
+
+		if matches.opt_present("verify-roundtrip") {
+			let decoded = match disasm::disassemble(&bytecode) {
+				Ok(decoded) => decoded,
+				Err(e) => {
+					eprintln!("Roundtrip verification failed: {}", e);
+					process::exit(exitcode::SOFTWARE);
+				}
+			};
+
+			// compared as bytes, as a NaN constant, which folding can make, equals no number
+			let same = |a: &parser::Command, b: &parser::Command| codegen::generate(vec![a.clone()]) == codegen::generate(vec![b.clone()]);
+
+			if decoded.len() != commands.len() || !commands.iter().zip(&decoded).all(|(a, b)| same(a, b)) {
+				let index = commands.iter().zip(&decoded).position(|(a, b)| !same(a, b)).unwrap_or(commands.len().min(decoded.len()));
+				let show = |cmds: &[parser::Command]| cmds.get(index).map(|c| c.to_string()).unwrap_or("end of program".to_owned());
+
+				eprintln!("Roundtrip verification failed at command {}: assembled {}, decoded {}", index, show(&commands), show(&decoded));
+				process::exit(exitcode::SOFTWARE);
+			}
+		}
+
+		let output = matches.opt_str("o");
+
+		for &emit in &emits {
+			let path = output_path(emit, emits.len(), output.as_deref(), &filename);
+
+			let artifact = match emit {
+				Emit::Bytecode if path.is_none() => format!("{}\n", general_purpose::URL_SAFE_NO_PAD.encode(&bytecode)).into_bytes(),
+				Emit::Bytecode => bytecode.to_vec(),
+				Emit::Listing  => listing::listing(&commands).into_bytes(),
+				Emit::CfgDot   => cfg::dot(&commands).into_bytes(),
+				Emit::MinAsm   => printer::print_minified(&commands).into_bytes(),
+				Emit::CallGraphDot  => callgraph::call_graph(parsed.as_deref().unwrap_or_default()).dot().into_bytes(),
+				Emit::CallGraphJson => format!("{:#}\n", callgraph::call_graph(parsed.as_deref().unwrap_or_default()).to_json()).into_bytes(),
+				Emit::AstJson  => format!("{:#}\n", ast_json::program_to_json(parsed.as_deref().unwrap_or_default(), &locs)).into_bytes(),
+				// the source parsed above, so it tokenizes
+				Emit::Html     => html::html(source, &filename).unwrap_or_default().into_bytes()
+			};
+
+			let result = match &path {
+				Some(path) => fs::write(path, artifact),
+				None => io::stdout().write_all(&artifact)
+			};
+
+			if let Err(e) = result {
+				eprintln!("Cannot write output: {}", e);
+				process::exit(exitcode::CANTCREAT);
+			}
+		}
 
-		println!("{}", general_purpose::URL_SAFE_NO_PAD.encode(bytecode));
+		memory_report.phase("write");
+		memory_report.print();
+		flush_diagnostics(format);
 	}
 }
\ No newline at end of file
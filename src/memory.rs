@@ -0,0 +1,106 @@
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+// counts for `evm-asm --report-memory`: the bytes allocated now and the most since the last
+// reset_peak, kept by Tracking when the binary is built with the memory feature, which installs it
+// as the global allocator, and the process's peak resident set size, which the system keeps
+
+static CURRENT: AtomicUsize = AtomicUsize::new(0);
+static PEAK: AtomicUsize = AtomicUsize::new(0);
+
+pub struct Tracking;
+
+fn grow(size: usize) {
+	let now = CURRENT.fetch_add(size, Ordering::Relaxed) + size;
+	PEAK.fetch_max(now, Ordering::Relaxed);
+}
+
+fn shrink(size: usize) {
+	CURRENT.fetch_sub(size, Ordering::Relaxed);
+}
+
+unsafe impl GlobalAlloc for Tracking {
+	unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+		let ptr = unsafe { System.alloc(layout) };
+
+		if !ptr.is_null() {
+			grow(layout.size());
+		}
+
+		ptr
+	}
+
+	unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+		let ptr = unsafe { System.alloc_zeroed(layout) };
+
+		if !ptr.is_null() {
+			grow(layout.size());
+		}
+
+		ptr
+	}
+
+	unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+		unsafe { System.dealloc(ptr, layout) };
+		shrink(layout.size());
+	}
+
+	unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+		let new = unsafe { System.realloc(ptr, layout, new_size) };
+
+		if !new.is_null() {
+			grow(new_size);
+			shrink(layout.size());
+		}
+
+		new
+	}
+}
+
+pub fn current() -> usize {
+	CURRENT.load(Ordering::Relaxed)
+}
+
+pub fn peak() -> usize {
+	PEAK.load(Ordering::Relaxed)
+}
+
+// starts the next peak from what is allocated now
+pub fn reset_peak() {
+	PEAK.store(current(), Ordering::Relaxed);
+}
+
+// the most memory the process has had resident, in bytes; None where the system does not say
+pub fn peak_rss() -> Option<usize> {
+	#[cfg(unix)]
+	{
+		let mut usage: libc::rusage = unsafe { std::mem::zeroed() };
+
+		if unsafe { libc::getrusage(libc::RUSAGE_SELF, &mut usage) } != 0 {
+			return None;
+		}
+
+		// kilobytes, but bytes on macOS
+		let scale = if cfg!(target_os = "macos") { 1 } else { 1024 };
+		Some(usage.ru_maxrss as usize * scale)
+	}
+	#[cfg(not(unix))]
+	None
+}
+
+// bytes as KiB, MiB or GiB, to one decimal
+pub fn human(bytes: usize) -> String {
+	let units = ["B", "KiB", "MiB", "GiB", "TiB"];
+	let mut size = bytes as f64;
+	let mut unit = 0;
+
+	while size >= 1024.0 && unit < units.len() - 1 {
+		size /= 1024.0;
+		unit += 1;
+	}
+
+	match unit {
+		0 => format!("{} B", bytes),
+		_ => format!("{:.1} {}", size, units[unit])
+	}
+}
@@ -0,0 +1,273 @@
+use std::fmt;
+
+// the text of every diagnostic, by message id and locale. a diagnostic carries a Message, an id
+// and its arguments, and is only turned into text when printed, in the locale it was given with
+// Diagnostic::localize. ids are the diagnostic's code for its main message, or the code and a
+// suffix for its labels and notes; templates fill `{name}` with the argument of that name.
+// a locale missing a message falls back to English
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Locale {
+	#[default]
+	En,
+	De,
+}
+
+impl Locale {
+	// a locale name as given to --locale or in LANG: "de", "de_DE", "de_DE.UTF-8"
+	pub fn from_name(name: &str) -> Option<Locale> {
+		match name.split(['_', '-', '.']).next()? {
+			"en" | "C" | "POSIX" => Some(Locale::En),
+			"de" => Some(Locale::De),
+			_ => None
+		}
+	}
+
+	fn catalog(self) -> &'static [(&'static str, &'static str)] {
+		match self {
+			Locale::En => EN,
+			Locale::De => DE
+		}
+	}
+}
+
+const EN: &[(&str, &str)] = &[
+	("T001", "Unexpected identifier {ident}"),
+	("T002", "Unterminated string"),
+	("T002.start", "string started here"),
+	("T002.note", "strings end at the next `\"`"),
+	("T003", "Input has more than {max} tokens"),
+	("T003.note", "raise the limit with --max-tokens"),
+	("T004", "Source is {size} bytes, exceeding the limit of {max} bytes"),
+	("T004.note", "raise the limit with --max-file-size"),
+	("T005", "Malformed number {text}"),
+	("T005.note", "a number is an optional `-` and digits with at most one `.`, ending at whitespace, a bracket, a quote or `;`"),
+	("P001", "Unexpected token {token}"),
+	("P002", "Unexpected token {token}: expected {expected}"),
+	("P002.number", "number"),
+	("P002.test-name", "test name"),
+	("P002.test-body", "test body"),
+	("P003", "Failed to parse number {text}"),
+	("P004", "Register is not an integer: {reg}"),
+	("P005", "Register must be between 0-{max}: {reg}"),
+	("P006", "Expected an array of stack values after .expect"),
+	("P007", "Values nested more than {max} levels deep"),
+	("P008", "Input has more than {max} commands and values"),
+	("P008.note", "raise the limit with --max-nodes"),
+	("J001", "Invalid JSON: {error}"),
+	("J002", "Unsupported ast-json version {version}, this assembler reads version {supported}"),
+	("J003", "{message}"),
+	("J003.path", "at {path}"),
+	("C001", "{what} length {len} does not fit in 64 bits"),
+	("C002", "Values nested {depth} levels deep, maximum is {max}"),
+	("C002.note", "values may nest at most {max} levels"),
+	("C003", "Register must be between 0-{max}: {reg}"),
+	("C003.note", "there are {count} registers"),
+	("C004", "Bytecode is {size} bytes, exceeding the maximum of {max} bytes"),
+	("C005", "{what} {len} is over the limit of {max}"),
+	("C005.note", "raise the limit with --{limit}"),
+	("D001", "Unexpected end of bytecode at offset {offset}: expected {expected}"),
+	("D002", "Unknown opcode {opcode} at offset {offset}"),
+	("D003", "Unknown value tag {tag} at offset {offset}"),
+	("D004", "Invalid UTF-8 in string at offset {offset}"),
+	("D005", "Length {len} at offset {offset} exceeds the {remaining} bytes remaining"),
+	("D006", "Values nested more than {max} levels deep at offset {offset}"),
+	("D007", "Range of {len} bytes at offset {offset} is outside the {size} byte program"),
+	("D008", "Bytecode has checksum {actual}, expected {expected}"),
+	("D008.note", "`evm-asm verify` prints the checksum of a valid program"),
+	("R001", "{message}"),
+	("R001.command", "in command {command}"),
+	("R001.called", "called from here"),
+	("R001.called-command", "called from command {command}"),
+	("S001", "Stack underflow: `{command}` takes {takes} values, but there are at most {depth}"),
+	("S001.note", "the analysis assumes the stack starts empty"),
+	("S001.called", "called from here"),
+	("S002", "Stack overflow: there are at least {depth} values after `{command}`, more than {max}"),
+	("S002.note", "raise the limit with --check-stack"),
+	("S002.called", "called from here"),
+	("K001", "`{command}` expects {expected}, got {found}"),
+	("K001.called", "called from here"),
+	("K002", "Register {reg} holds {found}, not a function"),
+	("K002.called", "called from here"),
+	("W001", "`{command}` needs console I/O, which only run mode provides"),
+	("W001.note", "pass --host-io if the target host supports it"),
+	("W002", "Value pushed and dropped right away"),
+	("W002.drop", "dropped here"),
+	("W003", "Function pushed onto the stack is never called"),
+	("W003.note", "only functions in registers can be called; load it with `iload`"),
+	("W003.register", "Function in register {reg} is never called"),
+	("W003.register-note", "the program never pushes {reg}, so no command calls the register"),
+	("W004", "Magic number {number}"),
+	("W004.note", "load it into a register once with `iload` and read it with `load`"),
+	("W005", "Function nested {depth} levels deep, more than {max}"),
+	("W005.note", "load inner functions into registers of their own"),
+	("W006", "Unreachable command"),
+	("W006.fails", "`{command}` always fails here"),
+	("W006.note", "the commands after it never run"),
+	("deny", "`--deny {flag}` turns this warning into an error"),
+	("explain", "For more information about this error, run `evm-asm explain {code}`."),
+];
+
+const DE: &[(&str, &str)] = &[
+	("T001", "Unerwarteter Bezeichner {ident}"),
+	("T002", "Nicht abgeschlossene Zeichenkette"),
+	("T002.start", "Zeichenkette beginnt hier"),
+	("T002.note", "Zeichenketten enden am nächsten `\"`"),
+	("T003", "Die Eingabe hat mehr als {max} Token"),
+	("T003.note", "die Grenze lässt sich mit --max-tokens erhöhen"),
+	("T004", "Die Quelle ist {size} Bytes groß und überschreitet die Grenze von {max} Bytes"),
+	("T004.note", "die Grenze lässt sich mit --max-file-size erhöhen"),
+	("T005", "Fehlerhafte Zahl {text}"),
+	("T005.note", "eine Zahl ist ein optionales `-` und Ziffern mit höchstens einem `.`, bis zu Leerraum, einer Klammer, einem Anführungszeichen oder `;`"),
+	("P001", "Unerwartetes Token {token}"),
+	("P002", "Unerwartetes Token {token}: erwartet wurde {expected}"),
+	("P002.number", "eine Zahl"),
+	("P002.test-name", "ein Testname"),
+	("P002.test-body", "ein Testrumpf"),
+	("P003", "Zahl {text} kann nicht gelesen werden"),
+	("P004", "Register ist keine ganze Zahl: {reg}"),
+	("P005", "Register muss zwischen 0 und {max} liegen: {reg}"),
+	("P006", "Nach .expect wird ein Array der Stapelwerte erwartet"),
+	("P007", "Werte sind tiefer als {max} Ebenen verschachtelt"),
+	("P008", "Die Eingabe hat mehr als {max} Befehle und Werte"),
+	("P008.note", "die Grenze lässt sich mit --max-nodes erhöhen"),
+	("J001", "Ungültiges JSON: {error}"),
+	("J002", "Nicht unterstützte ast-json-Version {version}, dieser Assembler liest Version {supported}"),
+	("J003.path", "bei {path}"),
+	("C001", "Länge {len} von {what} passt nicht in 64 Bit"),
+	("C002", "Werte sind {depth} Ebenen tief verschachtelt, höchstens {max} sind erlaubt"),
+	("C002.note", "Werte dürfen höchstens {max} Ebenen tief verschachtelt sein"),
+	("C003", "Register muss zwischen 0 und {max} liegen: {reg}"),
+	("C003.note", "es gibt {count} Register"),
+	("C004", "Der Bytecode ist {size} Bytes groß und überschreitet das Maximum von {max} Bytes"),
+	("C005", "{what} {len} überschreitet die Grenze von {max}"),
+	("C005.note", "die Grenze lässt sich mit --{limit} erhöhen"),
+	("D001", "Unerwartetes Ende des Bytecodes bei Offset {offset}: erwartet wurde {expected}"),
+	("D002", "Unbekannter Opcode {opcode} bei Offset {offset}"),
+	("D003", "Unbekanntes Werte-Tag {tag} bei Offset {offset}"),
+	("D004", "Ungültiges UTF-8 in Zeichenkette bei Offset {offset}"),
+	("D005", "Länge {len} bei Offset {offset} übersteigt die verbleibenden {remaining} Bytes"),
+	("D006", "Werte sind bei Offset {offset} tiefer als {max} Ebenen verschachtelt"),
+	("D007", "Bereich von {len} Bytes bei Offset {offset} liegt außerhalb des {size} Bytes großen Programms"),
+	("D008", "Der Bytecode hat die Prüfsumme {actual}, erwartet war {expected}"),
+	("D008.note", "`evm-asm verify` gibt die Prüfsumme eines gültigen Programms aus"),
+	("R001.command", "in Befehl {command}"),
+	("R001.called", "von hier aufgerufen"),
+	("R001.called-command", "von Befehl {command} aufgerufen"),
+	("S001", "Stapelunterlauf: `{command}` nimmt {takes} Werte, aber es gibt höchstens {depth}"),
+	("S001.note", "die Analyse nimmt an, dass der Stapel leer beginnt"),
+	("S001.called", "von hier aufgerufen"),
+	("S002", "Stapelüberlauf: nach `{command}` gibt es mindestens {depth} Werte, mehr als {max}"),
+	("S002.note", "die Grenze lässt sich mit --check-stack erhöhen"),
+	("S002.called", "von hier aufgerufen"),
+	("K001", "`{command}` erwartet {expected}, bekommt {found}"),
+	("K001.called", "von hier aufgerufen"),
+	("K002", "Register {reg} enthält {found}, keine Funktion"),
+	("K002.called", "von hier aufgerufen"),
+	("W001", "`{command}` braucht Konsolen-E/A, die nur der run-Modus bereitstellt"),
+	("W001.note", "mit --host-io angeben, wenn der Zielhost sie unterstützt"),
+	("W002", "Wert wird gepusht und sofort verworfen"),
+	("W002.drop", "hier verworfen"),
+	("W003", "Auf den Stapel gelegte Funktion wird nie aufgerufen"),
+	("W003.note", "nur Funktionen in Registern können aufgerufen werden; mit `iload` laden"),
+	("W003.register", "Funktion in Register {reg} wird nie aufgerufen"),
+	("W003.register-note", "das Programm pusht {reg} nie, also ruft kein Befehl das Register auf"),
+	("W004", "Magische Zahl {number}"),
+	("W004.note", "einmal mit `iload` in ein Register laden und mit `load` lesen"),
+	("W005", "Funktion ist {depth} Ebenen tief verschachtelt, mehr als {max}"),
+	("W005.note", "innere Funktionen in eigene Register laden"),
+	("W006", "Unerreichbarer Befehl"),
+	("W006.fails", "`{command}` schlägt hier immer fehl"),
+	("W006.note", "die Befehle danach laufen nie"),
+	("deny", "`--deny {flag}` macht diese Warnung zu einem Fehler"),
+	("explain", "Mehr zu diesem Fehler zeigt `evm-asm explain {code}`."),
+];
+
+#[derive(Clone, Debug)]
+pub enum Arg {
+	Text(String),
+	Message(Message), // translated along with the message it is in
+}
+
+// a message id and its arguments, written out in `locale` when displayed
+#[derive(Clone, Debug)]
+pub struct Message {
+	pub id: &'static str,
+	pub args: Vec<(&'static str, Arg)>,
+	pub locale: Locale,
+}
+
+impl Message {
+	pub fn new(id: &'static str) -> Message {
+		Message {id, args: vec![], locale: Locale::En}
+	}
+
+	pub fn arg(mut self, name: &'static str, value: impl fmt::Display) -> Message {
+		self.args.push((name, Arg::Text(value.to_string())));
+		self
+	}
+
+	pub fn arg_message(mut self, name: &'static str, value: Message) -> Message {
+		self.args.push((name, Arg::Message(value)));
+		self
+	}
+
+	// text that is not in the catalog, shown as is in every locale
+	pub fn text(text: impl Into<String>) -> Message {
+		Message::new("").arg("text", text.into())
+	}
+
+	pub fn localize(mut self, locale: Locale) -> Message {
+		self.locale = locale;
+		self
+	}
+
+	// each argument as text, with those that are messages in this message's locale
+	pub fn rendered_args(&self) -> impl Iterator<Item = (&'static str, String)> + '_ {
+		self.args.iter().map(|(name, arg)| match arg {
+			Arg::Text(text) => (*name, text.clone()),
+			Arg::Message(message) => (*name, message.render(self.locale))
+		})
+	}
+
+	pub fn render(&self, locale: Locale) -> String {
+		let template = locale.catalog().iter().chain(EN).find(|(id, _)| *id == self.id).map_or("{text}", |(_, template)| template);
+		let mut out = String::new();
+		let mut rest = template;
+
+		while let Some(start) = rest.find('{') {
+			let Some(len) = rest[start..].find('}') else { break };
+			let name = &rest[start + 1..start + len];
+			out.push_str(&rest[..start]);
+
+			match self.args.iter().find(|(arg, _)| *arg == name) {
+				Some((_, Arg::Text(text))) => out.push_str(text),
+				Some((_, Arg::Message(message))) => out.push_str(&message.render(locale)),
+				None => out.push_str(&rest[start..=start + len])
+			}
+
+			rest = &rest[start + len + 1..];
+		}
+
+		out.push_str(rest);
+		out
+	}
+}
+
+impl fmt::Display for Message {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		f.write_str(&self.render(self.locale))
+	}
+}
+
+impl From<&str> for Message {
+	fn from(text: &str) -> Message {
+		Message::text(text)
+	}
+}
+
+impl From<String> for Message {
+	fn from(text: String) -> Message {
+		Message::text(text)
+	}
+}
@@ -0,0 +1,85 @@
+use std::fs::File;
+use std::io;
+use std::ops::Deref;
+use std::path::Path;
+use std::str;
+
+// a file mapped into memory read-only, for `evm-asm --mmap`: the kernel reads its pages as the
+// tokenizer reaches them and can drop them again, so a generated source of several gigabytes needs
+// no buffer of its size. the file must not change while it is mapped. on systems other than unix
+// it is read into memory as usual
+
+#[cfg(unix)]
+pub struct Mmap {
+	ptr: *mut libc::c_void,
+	len: usize,
+}
+
+#[cfg(not(unix))]
+pub struct Mmap {
+	bytes: Vec<u8>,
+}
+
+impl Mmap {
+	#[cfg(unix)]
+	pub fn open(path: impl AsRef<Path>) -> io::Result<Mmap> {
+		use std::os::fd::AsRawFd;
+
+		let file = File::open(path)?;
+		let len = usize::try_from(file.metadata()?.len()).map_err(io::Error::other)?;
+
+		// mmap refuses an empty mapping
+		if len == 0 {
+			return Ok(Mmap {ptr: std::ptr::null_mut(), len});
+		}
+
+		// private and read-only, so nothing is written back; the mapping outlives the file descriptor
+		let ptr = unsafe {libc::mmap(std::ptr::null_mut(), len, libc::PROT_READ, libc::MAP_PRIVATE, file.as_raw_fd(), 0)};
+
+		if ptr == libc::MAP_FAILED {
+			return Err(io::Error::last_os_error());
+		}
+
+		Ok(Mmap {ptr, len})
+	}
+
+	#[cfg(not(unix))]
+	pub fn open(path: impl AsRef<Path>) -> io::Result<Mmap> {
+		use std::io::Read;
+
+		let mut bytes = vec![];
+		File::open(path)?.read_to_end(&mut bytes)?;
+		Ok(Mmap {bytes})
+	}
+
+	// the contents as source text, checked to be UTF-8 but not copied
+	pub fn text(&self) -> io::Result<&str> {
+		str::from_utf8(self).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+	}
+}
+
+impl Deref for Mmap {
+	type Target = [u8];
+
+	#[cfg(unix)]
+	fn deref(&self) -> &[u8] {
+		match self.len {
+			0 => &[],
+			len => unsafe {std::slice::from_raw_parts(self.ptr.cast::<u8>(), len)} // safe as the mapping lives as long as self
+		}
+	}
+
+	#[cfg(not(unix))]
+	fn deref(&self) -> &[u8] {
+		&self.bytes
+	}
+}
+
+#[cfg(unix)]
+impl Drop for Mmap {
+	fn drop(&mut self) {
+		if self.len != 0 {
+			unsafe {libc::munmap(self.ptr, self.len)};
+		}
+	}
+}
@@ -0,0 +1,65 @@
+use crate::parser::*;
+
+// every command, with placeholder operands for push and iload; decoding looks commands up by
+// the same opcode() codegen emits, so this list only has to be complete, not ordered
+pub const COMMANDS: &[Command] = &[
+	Command::Push(Value::Nil),
+	Command::Dup,
+	Command::Swap,
+	Command::ILoad(0, Value::Nil),
+	Command::Load,
+	Command::Drop,
+	Command::Query,
+	Command::Info,
+	Command::If,
+	Command::Each,
+	Command::Reduce,
+	Command::Reverse,
+	Command::Map,
+	Command::Filter,
+	Command::Call,
+	Command::ToStr,
+	Command::ToNum,
+	Command::Add,
+	Command::Sub,
+	Command::Mul,
+	Command::Div,
+	Command::Mod,
+	Command::Eq,
+	Command::NotEq,
+	Command::Greater,
+	Command::GreaterEq,
+	Command::Less,
+	Command::LessEq,
+	Command::And,
+	Command::Or,
+	Command::Not,
+	Command::Concat,
+	Command::Match,
+	Command::Split,
+	Command::Iota,
+	Command::Print,
+	Command::ReadLine,
+	Command::Assert,
+	Command::AssertEq
+];
+
+pub const NIL: u8 = 0;
+pub const NUMBER: u8 = 1;
+pub const STRING: u8 = 2;
+pub const BOOLEAN: u8 = 3;
+pub const FUNCTION: u8 = 4;
+pub const ARRAY: u8 = 5;
+
+pub fn opcode(command: &Command) -> u8 {
+	unsafe {*<*const _>::from(command).cast::<u8>()} // safe because of repr(u8) on enum
+}
+
+pub fn value_tag(value: &Value) -> u8 {
+	unsafe {*<*const _>::from(value).cast::<u8>()} // safe because of repr(u8) on enum
+}
+
+// the command an opcode stands for; push and iload come back with placeholder operands
+pub fn command(opcode: u8) -> Option<&'static Command> {
+	COMMANDS.iter().find(|command| self::opcode(command) == opcode)
+}
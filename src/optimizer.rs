@@ -0,0 +1,428 @@
+use alloc::borrow::ToOwned;
+use alloc::string::String;
+use alloc::vec::Vec;
+use alloc::{format, vec};
+
+use crate::parser::*;
+
+#[derive(Clone, Debug, Hash)]
+pub struct Options {
+	pub level: u8,
+	pub enabled: Vec<String>,
+	pub disabled: Vec<String>,
+	pub inline_threshold: usize,
+	// nothing reads the program's registers after it ends, neither a program run after it nor the
+	// host, so a load is dropped once every call of it is inlined
+	pub closed: bool,
+}
+
+impl Default for Options {
+	fn default() -> Options {
+		Options {level: 2, enabled: vec![], disabled: vec![], inline_threshold: 8, closed: false}
+	}
+}
+
+impl Options {
+	// applies an `-O` argument: a level (`0`-`2`), `closed` or `key=value`
+	pub fn set(&mut self, opt: &str) -> Result<(), String> {
+		if opt == "closed" {
+			self.closed = true;
+			return Ok(());
+		}
+
+		if let Ok(level) = opt.parse::<u8>() {
+			if level > MAX_LEVEL {
+				return Err(format!("Optimization level must be between 0-{}: {}", MAX_LEVEL, level));
+			}
+			self.level = level;
+			return Ok(());
+		}
+
+		match opt.split_once('=') {
+			Some(("inline-threshold", val)) => {
+				self.inline_threshold = val.parse().map_err(|_| format!("Invalid inline threshold: {}", val))?;
+				Ok(())
+			},
+			_ => Err(format!("Unknown optimizer option: {}", opt))
+		}
+	}
+
+	// applies an `--opt` argument: a pass name, optionally prefixed with `no-`
+	pub fn toggle(&mut self, name: &str) -> Result<(), String> {
+		let (enable, pass) = match name.strip_prefix("no-") {
+			Some(pass) => (false, pass),
+			None => (true, name)
+		};
+
+		if !PASSES.iter().any(|p| p.name == pass) {
+			return Err(format!("Unknown optimization pass: {}", pass));
+		}
+
+		self.enabled.retain(|p| p != pass);
+		self.disabled.retain(|p| p != pass);
+
+		if enable {
+			self.enabled.push(pass.to_owned());
+		} else {
+			self.disabled.push(pass.to_owned());
+		}
+
+		Ok(())
+	}
+
+	fn runs(&self, pass: &Pass) -> bool {
+		if self.disabled.iter().any(|p| p == pass.name) {
+			return false;
+		}
+
+		self.level >= pass.level || self.enabled.iter().any(|p| p == pass.name)
+	}
+}
+
+pub struct Pass {
+	pub name: &'static str,
+	pub level: u8, // lowest -O level that enables the pass
+	pub run: fn(Vec<Command>, &Options, &mut Vec<String>) -> Vec<Command>,
+}
+
+pub const MAX_LEVEL: u8 = 2;
+
+// passes in the order they run
+pub const PASSES: &[Pass] = &[
+	Pass {name: "inline", level: 2, run: |commands, options, _| inline_functions(commands, options.inline_threshold, options.closed)},
+	Pass {name: "fold", level: 1, run: |commands, _, _| fold_constants(commands)},
+	Pass {name: "peephole", level: 1, run: |commands, _, _| peephole(commands)},
+	Pass {name: "dce", level: 1, run: |commands, _, notes| {
+		let mut removed = vec![];
+		let commands = eliminate_dead_code(commands, &mut removed);
+		notes.extend(removed.iter().map(|command| format!("removed {}", command)));
+		commands
+	}},
+];
+
+// runs every enabled pass, appending a summary line per pass and any pass notes to `report`
+#[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all, fields(commands = commands.len())))]
+pub fn optimize(mut commands: Vec<Command>, options: &Options, report: &mut Vec<String>) -> Vec<Command> {
+	for pass in PASSES.iter().filter(|pass| options.runs(pass)) {
+		let before = count_commands(&commands);
+		let mut notes = vec![];
+		commands = (pass.run)(commands, options, &mut notes);
+		event!(DEBUG, pass = pass.name, before, after = count_commands(&commands), "optimizer pass");
+
+		report.push(format!("{}: {} -> {} commands", pass.name, before, count_commands(&commands)));
+		report.extend(notes.into_iter().map(|note| format!("  {}", note)));
+	}
+
+	commands
+}
+
+// counts commands including those nested in function values
+pub fn count_commands(commands: &[Command]) -> usize {
+	commands.iter().map(|command| {
+		match command {
+			Command::Push(value) | Command::ILoad(_, value) => 1 + count_value(value),
+			_ => 1
+		}
+	}).sum()
+}
+
+fn count_value(value: &Value) -> usize {
+	match value {
+		Value::Function(commands) => count_commands(commands),
+		Value::Array(values) => values.iter().map(count_value).sum(),
+		_ => 0
+	}
+}
+
+// applies a pass to the bodies of any function values nested in a command
+fn map_nested(command: Command, pass: &mut dyn FnMut(Vec<Command>) -> Vec<Command>) -> Command {
+	fn map_value(value: Value, pass: &mut dyn FnMut(Vec<Command>) -> Vec<Command>) -> Value {
+		match value {
+			Value::Function(commands) => Value::Function(pass(commands)),
+			Value::Array(values) => Value::Array(values.into_iter().map(|v| map_value(v, pass)).collect()),
+			x => x
+		}
+	}
+
+	match command {
+		Command::Push(value) => Command::Push(map_value(value, pass)),
+		Command::ILoad(reg, value) => Command::ILoad(reg, map_value(value, pass)),
+		x => x
+	}
+}
+
+// removes command pairs that cancel out: `push x drop`, `dup drop`, `swap swap` and `not not`. a pair
+// that would fail on its own is kept, so the program fails as it did: `dup` and `swap` need the
+// values to be known to be there, and `not` a value known to be a boolean
+pub fn peephole(commands: Vec<Command>) -> Vec<Command> {
+	let mut out: Vec<Command> = vec![];
+
+	for command in commands {
+		let command = map_nested(command, &mut peephole);
+		let len = out.len();
+
+		let cancels = match (&out[len.saturating_sub(2)..], &command) {
+			([.., Command::Push(_)], Command::Drop) => true,
+			([.., Command::Dup], Command::Drop) => known_depth(&out[..len - 1]) >= 1,
+			([.., Command::Swap], Command::Swap) => known_depth(&out[..len - 1]) >= 2,
+			([before, Command::Not], Command::Not) => pushes_boolean(before),
+			_ => false
+		};
+
+		if cancels {
+			out.pop();
+		} else {
+			out.push(command);
+		}
+	}
+
+	out
+}
+
+// the fewest values there are after `commands` run without failing, counting only what they push:
+// a function body starts on its caller's stack, and a call or loop may pop anything
+fn known_depth(commands: &[Command]) -> usize {
+	commands.iter().fold(0, |depth, command| match stack_effect(command) {
+		Some((pops, pushes)) if !matches!(command, Command::Map | Command::Filter | Command::Reduce) => depth.saturating_sub(pops) + pushes,
+		_ => 0
+	})
+}
+
+// whether a command that succeeds always leaves a boolean on top
+fn pushes_boolean(command: &Command) -> bool {
+	matches!(command, Command::Push(Value::Boolean(_)) | Command::Not | Command::And | Command::Or | Command::Eq | Command::NotEq
+		| Command::Greater | Command::GreaterEq | Command::Less | Command::LessEq | Command::Match)
+}
+
+fn fold_binary(a: &Value, b: &Value, command: &Command) -> Option<Value> {
+	match (a, b) {
+		(Value::Number(a), Value::Number(b)) => match command {
+			Command::Add                    => Some(Value::Number(a + b)),
+			Command::Sub                    => Some(Value::Number(a - b)),
+			Command::Mul                    => Some(Value::Number(a * b)),
+			Command::Div if *b != 0.0       => Some(Value::Number(a / b)),
+			Command::Mod if *b != 0.0       => Some(Value::Number(a % b)),
+			Command::Greater                => Some(Value::Boolean(a > b)),
+			Command::GreaterEq              => Some(Value::Boolean(a >= b)),
+			Command::Less                   => Some(Value::Boolean(a < b)),
+			Command::LessEq                 => Some(Value::Boolean(a <= b)),
+			Command::Eq                     => Some(Value::Boolean(a == b)),
+			Command::NotEq                  => Some(Value::Boolean(a != b)),
+			_ => None
+		},
+		(Value::String(a), Value::String(b)) if *command == Command::Concat => Some(Value::String(format!("{}{}", a, b))),
+		(Value::Array(a), Value::Array(b)) if *command == Command::Concat => Some(Value::Array([a.as_slice(), b.as_slice()].concat())),
+		(Value::Boolean(a), Value::Boolean(b)) if *command == Command::And => Some(Value::Boolean(*a && *b)),
+		(Value::Boolean(a), Value::Boolean(b)) if *command == Command::Or => Some(Value::Boolean(*a || *b)),
+		(a, b) if has_function(a) || has_function(b) => None, // functions are equal only to themselves in the vm
+		(a, b) => match command {
+			Command::Eq    => Some(Value::Boolean(a == b)),
+			Command::NotEq => Some(Value::Boolean(a != b)),
+			_ => None
+		}
+	}
+}
+
+fn has_function(value: &Value) -> bool {
+	match value {
+		Value::Function(_) => true,
+		Value::Array(values) => values.iter().any(has_function),
+		_ => false
+	}
+}
+
+// evaluates operations whose operands are all pushed constants, e.g. `push 2 push 3 +` becomes `push 5`
+pub fn fold_constants(commands: Vec<Command>) -> Vec<Command> {
+	let mut out: Vec<Command> = vec![];
+
+	for command in commands {
+		let command = map_nested(command, &mut fold_constants);
+		let len = out.len();
+
+		let folded = match (&out[len.saturating_sub(2)..], &command) {
+			([.., Command::Push(Value::Boolean(a))], Command::Not) => Some((1, Value::Boolean(!a))),
+			([Command::Push(a), Command::Push(b)], command) => fold_binary(a, b, command).map(|value| (2, value)),
+			_ => None
+		};
+
+		match folded {
+			Some((operands, value)) => {
+				out.truncate(len - operands);
+				out.push(Command::Push(value));
+			},
+			None => out.push(command)
+		}
+	}
+
+	out
+}
+
+// whether a command only touches the stack and cannot fail once its operands are present
+fn is_pure(command: &Command) -> bool {
+	matches!(command, Command::Push(_) | Command::Dup | Command::Swap | Command::ToStr | Command::ToNum | Command::Eq | Command::NotEq)
+}
+
+// whether a command may read a register
+fn reads_registers(command: &Command) -> bool {
+	matches!(command, Command::Load | Command::If | Command::Each | Command::Reduce | Command::Map | Command::Filter | Command::Call)
+}
+
+// finds the shortest run of pure commands at the end of `commands` that nets exactly one value without consuming anything below it
+fn producer_start(commands: &[Command]) -> Option<usize> {
+	for start in (0..commands.len()).rev() {
+		if !is_pure(&commands[start]) {
+			return None;
+		}
+
+		let mut depth: isize = 0;
+		let mut valid = true;
+
+		for command in &commands[start..] {
+			let (pops, pushes) = stack_effect(command)?;
+			depth -= pops as isize;
+
+			if depth < 0 {
+				valid = false;
+				break;
+			}
+
+			depth += pushes as isize;
+		}
+
+		if valid && depth == 1 {
+			return Some(start);
+		}
+	}
+
+	None
+}
+
+// removes pure computations whose result is immediately dropped and register loads that are overwritten before any read
+pub fn eliminate_dead_code(commands: Vec<Command>, removed: &mut Vec<Command>) -> Vec<Command> {
+	let mut out: Vec<Command> = vec![];
+
+	for command in commands {
+		let command = map_nested(command, &mut |body| eliminate_dead_code(body, removed));
+
+		if command == Command::Drop {
+			if let Some(start) = producer_start(&out) {
+				removed.extend(out.drain(start..));
+				removed.push(command);
+				continue;
+			}
+		}
+
+		out.push(command);
+	}
+
+	let mut i = 0;
+
+	while i < out.len() {
+		if let Command::ILoad(reg, _) = out[i] {
+			let overwritten = out[i + 1..].iter()
+				.take_while(|command| !reads_registers(command))
+				.any(|command| matches!(command, Command::ILoad(r, _) if *r == reg));
+
+			if overwritten {
+				removed.push(out.remove(i));
+				continue;
+			}
+		}
+
+		i += 1;
+	}
+
+	out
+}
+
+fn register_operand(command: &Command) -> Option<u8> {
+	match command {
+		Command::Push(Value::Number(reg)) if (0.0..16.0).contains(reg) && *reg as u8 as f64 == *reg => Some(*reg as u8),
+		_ => None
+	}
+}
+
+// inlines `push r call` where the nearest preceding write to register r is a small function literal and nothing in between can run other code
+fn inline_sequence(commands: Vec<Command>, threshold: usize, inlined: &mut Vec<u8>) -> Vec<Command> {
+	let mut out: Vec<Command> = vec![];
+
+	for command in commands {
+		let command = map_nested(command, &mut |body| inline_sequence(body, threshold, inlined));
+
+		if command == Command::Call {
+			if let Some(reg) = out.last().and_then(register_operand) {
+				let body = out[..out.len() - 1].iter().rev()
+					.find(|command| reads_registers(command) || matches!(command, Command::ILoad(r, _) if *r == reg))
+					.and_then(|command| match command {
+						Command::ILoad(_, Value::Function(body)) if count_commands(body) <= threshold => Some(body.clone()),
+						_ => None
+					});
+
+				if let Some(body) = body {
+					out.pop();
+					out.extend(body);
+					inlined.push(reg);
+					continue;
+				}
+			}
+		}
+
+		out.push(command);
+	}
+
+	out
+}
+
+// collects the registers named by constant operands, and whether any register is read through a computed operand
+fn register_uses(commands: &[Command], regs: &mut Vec<u8>, dynamic: &mut bool) {
+	for (i, command) in commands.iter().enumerate() {
+		if let Some(reg) = register_operand(command) {
+			regs.push(reg);
+		}
+
+		if reads_registers(command) && (i == 0 || register_operand(&commands[i - 1]).is_none()) {
+			*dynamic = true;
+		}
+
+		if let Command::Push(value) | Command::ILoad(_, value) = command {
+			value_register_uses(value, regs, dynamic);
+		}
+	}
+}
+
+fn value_register_uses(value: &Value, regs: &mut Vec<u8>, dynamic: &mut bool) {
+	match value {
+		Value::Function(commands) => register_uses(commands, regs, dynamic),
+		Value::Array(values) => values.iter().for_each(|value| value_register_uses(value, regs, dynamic)),
+		_ => {}
+	}
+}
+
+fn remove_loads(commands: Vec<Command>, regs: &[u8]) -> Vec<Command> {
+	commands.into_iter()
+		.filter(|command| !matches!(command, Command::ILoad(r, _) if regs.contains(r)))
+		.map(|command| map_nested(command, &mut |body| remove_loads(body, regs)))
+		.collect()
+}
+
+// replaces calls of small register-bound function literals with their bodies. the loads stay, as
+// registers outlive the program, unless it is `closed` and nothing left in it reads them
+pub fn inline_functions(commands: Vec<Command>, threshold: usize, closed: bool) -> Vec<Command> {
+	let mut inlined = vec![];
+	let commands = inline_sequence(commands, threshold, &mut inlined);
+
+	if !closed {
+		return commands;
+	}
+
+	let mut regs = vec![];
+	let mut dynamic = false;
+	register_uses(&commands, &mut regs, &mut dynamic);
+
+	if dynamic {
+		return commands;
+	}
+
+	inlined.retain(|reg| !regs.contains(reg));
+	remove_loads(commands, &inlined)
+}
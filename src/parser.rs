@@ -1,15 +1,94 @@
-use std::cell::Cell;
-use std::fmt;
-
+use alloc::borrow::ToOwned;
+use alloc::string::String;
+use alloc::vec::Vec;
+use alloc::{format, vec};
+use core::cell::{Cell, RefCell};
+use core::fmt;
+use thiserror::Error;
+
+use crate::codegen;
 use crate::tokenizer::*;
 
 pub struct State {
 	ctok: Cell<usize>,
-	tokens: Vec<Token>
+	tokens: Vec<Token>,
+	locs: RefCell<Vec<Loc>>,
+	depth: Cell<usize>, // arrays and functions currently open
+	bodies: RefCell<Option<Vec<Body>>>, // function literals skipped rather than parsed, for parse_outline
+	nodes: Cell<usize>, // commands and values parsed
+	max_nodes: usize
+}
+
+#[derive(Debug, Error)]
+pub enum ParseError {
+	#[error("Unexpected token {token} on {loc}")]
+	UnexpectedToken {token: TokenType, loc: Loc},
+	#[error("Unexpected token {token} on {loc}: expected {expected}")]
+	Expected {expected: &'static str, token: TokenType, loc: Loc},
+	#[error("Failed to parse number {text} on {loc}")]
+	InvalidNumber {text: String, loc: Loc},
+	#[error("Register is not an integer: {reg} on {loc}")]
+	RegisterNotInteger {reg: f64, loc: Loc},
+	#[error("Register must be between 0-15: {reg} on {loc}")]
+	RegisterOutOfRange {reg: u64, loc: Loc},
+	#[error("Expected an array of stack values after .expect on {loc}")]
+	ExpectArray {loc: Loc},
+	#[error("Values nested more than {} levels deep on {loc}", codegen::MAX_NESTING)]
+	NestingTooDeep {loc: Loc},
+	#[error("Input has more than {max} commands and values, at {loc}; raise the limit with --max-nodes")]
+	TooManyNodes {max: usize, loc: Loc},
+}
+
+impl ParseError {
+	pub fn loc(&self) -> &Loc {
+		match self {
+			ParseError::UnexpectedToken {loc, ..} | ParseError::Expected {loc, ..} | ParseError::InvalidNumber {loc, ..}
+				| ParseError::RegisterNotInteger {loc, ..} | ParseError::RegisterOutOfRange {loc, ..} | ParseError::ExpectArray {loc} | ParseError::NestingTooDeep {loc}
+				| ParseError::TooManyNodes {loc, ..} => loc
+		}
+	}
+}
+
+pub struct Parsed {
+	pub commands: Vec<Command>,
+	pub locs: Vec<Loc>,
+	pub tests: Vec<Test>,
+}
+
+// the program with the body of each function literal skipped, for tools that only need its top
+// level, such as an outline. each skipped literal is an empty function in `commands`, and its body
+// is in `bodies`, in the order a pre-order walk meets them, to parse when it is needed. `locs` are
+// those of the commands outside the bodies. test blocks are parsed in full
+pub struct Outline {
+	pub commands: Vec<Command>,
+	pub locs: Vec<Loc>,
+	pub tests: Vec<Test>,
+	pub bodies: Vec<Body>,
+	tokens: Vec<Token>,
+}
+
+// the tokens of a skipped function body, from the one after `{` to its `}`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Body {
+	pub start: usize,
+	pub end: usize,
+	depth: usize, // of the literal, so nesting is limited as in a full parse
+}
+
+// a `.test "name" { ... } .expect [ ... ]` block; `expect` is the whole stack the body must leave,
+// or None to only require that it runs without an error
+pub struct Test {
+	pub name: String,
+	pub loc: Loc,
+	pub body: Vec<Command>,
+	pub locs: Vec<Loc>, // location of each command in the body, numbered as in parse_with_locs
+	pub expect: Option<Vec<Value>>,
 }
 
 #[repr(u8)]
-#[derive(Clone, PartialEq)]
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 pub enum Value {
 	Nil,
 	Number(f64),
@@ -20,7 +99,9 @@ pub enum Value {
 }
 
 #[repr(u8)]
-#[derive(Clone, PartialEq)]
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 pub enum Command {
 	Push(Value),
 	Dup,
@@ -56,7 +137,11 @@ pub enum Command {
 	Concat,
 	Match,
 	Split,
-	Iota
+	Iota,
+	Print,
+	ReadLine,
+	Assert,
+	AssertEq
 }
 
 impl fmt::Display for Value {
@@ -96,7 +181,7 @@ impl fmt::Display for Value {
 	}
 }
 
-fn get_command_name(cmd: &Command) -> &str {
+pub fn get_command_name(cmd: &Command) -> &'static str {
 	match cmd {
 		Command::Push(_)    => "push",
 		Command::Dup        => "dup",
@@ -132,7 +217,43 @@ fn get_command_name(cmd: &Command) -> &str {
 		Command::Concat     => "concat",
 		Command::Match      => "match",
 		Command::Split      => "split",
-		Command::Iota       => "iota"
+		Command::Iota       => "iota",
+		Command::Print      => "print",
+		Command::ReadLine   => "readln",
+		Command::Assert     => "assert",
+		Command::AssertEq   => "assert_eq"
+	}
+}
+
+// number of values a command pops and pushes, or None if it depends on a called function
+pub fn stack_effect(cmd: &Command) -> Option<(usize, usize)> {
+	match cmd {
+		Command::Push(_)    => Some((0, 1)),
+		Command::Dup        => Some((1, 2)),
+		Command::Swap       => Some((2, 2)),
+		Command::ILoad(_,_) => Some((0, 0)),
+		Command::Load       => Some((1, 1)),
+		Command::Drop       => Some((1, 0)),
+		Command::Query      => Some((1, 1)),
+		Command::Info       => Some((2, 1)),
+		Command::If         => None,
+		Command::Each       => None,
+		Command::Reduce     => Some((3, 1)),
+		Command::Reverse    => Some((1, 1)),
+		Command::Map        => Some((2, 1)),
+		Command::Filter     => Some((2, 1)),
+		Command::Call       => None,
+		Command::ToStr      => Some((1, 1)),
+		Command::ToNum      => Some((1, 1)),
+		Command::Not        => Some((1, 1)),
+		Command::Iota       => Some((1, 1)),
+		Command::Print      => Some((1, 0)),
+		Command::ReadLine   => Some((0, 1)),
+		Command::Assert     => Some((1, 0)),
+		Command::AssertEq   => Some((2, 0)),
+		Command::Add | Command::Sub | Command::Mul | Command::Div | Command::Mod |
+		Command::Eq | Command::NotEq | Command::Greater | Command::GreaterEq | Command::Less | Command::LessEq |
+		Command::And | Command::Or | Command::Concat | Command::Match | Command::Split => Some((2, 1))
 	}
 }
 
@@ -146,120 +267,166 @@ impl fmt::Display for Command {
 	}
 }
 
-fn next(state: &State) -> Token {
+// tokens are lent out rather than cloned; a value clones only the payload it is built from
+fn next(state: &State) -> &Token {
 	state.ctok.set(state.ctok.get() + 1);
-	state.tokens[state.ctok.get() - 1].clone()
+	&state.tokens[state.ctok.get() - 1]
 }
 
-fn last(state: &State) -> Token {
-	state.tokens[state.ctok.get() - 1].clone()
+fn last(state: &State) -> &Token {
+	&state.tokens[state.ctok.get() - 1]
 }
 
 fn rewind(state: &State, amount: usize) {
 	state.ctok.set(state.ctok.get() - amount);
 }
 
-fn accept(state: &State, typ: &TokenType) -> bool {
-	if &next(state).typ == typ {
+// moves past the next token if `matches` accepts it
+fn accept_if(state: &State, matches: impl Fn(&TokenType) -> bool) -> bool {
+	if matches(&next(state).typ) {
 		return true;
 	}
 	rewind(state, 1);
 	false
 }
 
+fn accept(state: &State, typ: &TokenType) -> bool {
+	accept_if(state, |next| next == typ)
+}
+
+fn accept_directive(state: &State, name: &str) -> bool {
+	accept_if(state, |next| matches!(next, TokenType::Directive(directive) if directive == name))
+}
+
 fn accept_str(state: &State) -> bool {
-	match next(state).typ {
-		TokenType::String(_) => {
-			true
-		},
-		_ => {
-			rewind(state, 1);
-			false
-		}
-	}
+	accept_if(state, |next| matches!(next, TokenType::String(_)))
 }
 
 fn accept_num(state: &State) -> bool {
-	match next(state).typ {
-		TokenType::Number(_) => {
-			true
-		},
-		_ => {
-			rewind(state, 1);
-			false
-		}
-	}
+	accept_if(state, |next| matches!(next, TokenType::Number(_)))
 }
 
 fn accept_bool(state: &State) -> bool {
-	match next(state).typ {
-		TokenType::Boolean(_) => {
-			true
-		},
-		_ => {
-			rewind(state, 1);
-			false
-		}
-	}
+	accept_if(state, |next| matches!(next, TokenType::Boolean(_)))
 }
 
-fn expect_num(state: &State) -> Result<f64, String> {
-	match next(state).typ {
+fn expect_num(state: &State) -> Result<f64, ParseError> {
+	let t = next(state);
+
+	match &t.typ {
 		TokenType::Number(val) => {
 			match val.parse::<f64>() {
 				Ok(parsed) => Ok(parsed),
-				Err(error) => Err(format!("Failed to parse number: {}", error))
+				Err(_) => Err(ParseError::InvalidNumber {text: val.clone(), loc: t.loc.clone()})
 			}
 		},
-		_ => {
+		token => {
 			rewind(state, 1);
-			Err(format!("Unexpected token: expected number, got {} on {}", last(state).typ, last(state).loc))
+			Err(ParseError::Expected {expected: "number", token: token.clone(), loc: t.loc.clone()})
 		}
 	}
 }
 
-fn parse_value(state: &State) -> Result<Value, String> {
+// the parser recurses into nested values, so deep nesting would overflow the stack; codegen
+// rejects anything past MAX_NESTING anyway
+fn open(state: &State) -> Result<(), ParseError> {
+	state.depth.set(state.depth.get() + 1);
+
+	if state.depth.get() > codegen::MAX_NESTING {
+		return Err(ParseError::NestingTooDeep {loc: last(state).loc.clone()});
+	}
+
+	Ok(())
+}
+
+// counts a command or value, the nodes of the tree, against the limit of parse_limited
+fn node(state: &State) -> Result<(), ParseError> {
+	state.nodes.set(state.nodes.get() + 1);
+
+	if state.nodes.get() > state.max_nodes {
+		return Err(ParseError::TooManyNodes {max: state.max_nodes, loc: state.tokens[state.ctok.get()].loc.clone()});
+	}
+
+	Ok(())
+}
+
+fn parse_value(state: &State) -> Result<Value, ParseError> {
+	node(state)?;
+
 	if accept_num(state) {
-		let TokenType::Number(val) = last(state).typ else {unreachable!()};
+		let TokenType::Number(val) = &last(state).typ else {unreachable!()};
 		match val.parse::<f64>() {
 			Ok(parsed) => Ok(Value::Number(parsed)),
-			Err(error) => Err(format!("Failed to parse number: {}", error))
+			Err(_) => Err(ParseError::InvalidNumber {text: val.clone(), loc: last(state).loc.clone()})
 		}
 	} else if accept_str(state) {
-		let TokenType::String(val) = last(state).typ else {unreachable!()};
-		Ok(Value::String(val))
+		let TokenType::String(val) = &last(state).typ else {unreachable!()};
+		Ok(Value::String(val.clone()))
 	} else if accept_bool(state) {
 		let TokenType::Boolean(val) = last(state).typ else {unreachable!()};
 		Ok(Value::Boolean(val))
 	} else if accept(state, &TokenType::LeftSquare) {
+		open(state)?;
 		let mut values = vec![];
 
 		while !accept(state, &TokenType::RightSquare) {
 			values.push(parse_value(state)?);
 		}
 
+		state.depth.set(state.depth.get() - 1);
 		Ok(Value::Array(values))
 	} else if accept(state, &TokenType::LeftCurly) {
+		open(state)?;
+
+		if state.bodies.borrow().is_some() {
+			return skip_body(state);
+		}
+
 		let mut commands = vec![];
 
 		while !accept(state, &TokenType::RightCurly) {
 			commands.push(parse_command(state)?);
 		}
 
+		state.depth.set(state.depth.get() - 1);
 		Ok(Value::Function(commands))
 	} else if accept(state, &TokenType::Nil) {
 		Ok(Value::Nil)
 	} else {
 		let t = next(state);
 
-		Err(format!("Unexpected token {} on {}", t.typ, t.loc))
+		Err(ParseError::UnexpectedToken {token: t.typ.clone(), loc: t.loc.clone()})
 	}
 }
 
-fn parse_command(state: &State) -> Result<Command, String> {
+// past the `}` that closes the function just opened, counting the braces between
+fn skip_body(state: &State) -> Result<Value, ParseError> {
+	let start = state.ctok.get();
+	let mut open = 1;
+
+	while open > 0 {
+		let t = next(state);
+
+		match t.typ {
+			TokenType::LeftCurly => open += 1,
+			TokenType::RightCurly => open -= 1,
+			TokenType::Eof => return Err(ParseError::UnexpectedToken {token: t.typ.clone(), loc: t.loc.clone()}),
+			_ => {}
+		}
+	}
+
+	let body = Body {start, end: state.ctok.get() - 1, depth: state.depth.get()};
+	state.bodies.borrow_mut().as_mut().unwrap().push(body);
+	state.depth.set(state.depth.get() - 1);
+	Ok(Value::Function(vec![]))
+}
+
+fn parse_command(state: &State) -> Result<Command, ParseError> {
+	node(state)?;
 	let t = next(state);
+	state.locs.borrow_mut().push(t.loc.clone());
 
-	match t.typ {
+	match &t.typ {
 		TokenType::Push => {
 			let value = parse_value(state)?;
 
@@ -268,14 +435,14 @@ fn parse_command(state: &State) -> Result<Command, String> {
 		TokenType::ILoad => {
 			let reg = expect_num(state)?;
 
-			if reg != reg.trunc() {
-				return Err(format!("Register is not an integer: {} on {}", reg, last(state).loc))
+			if reg % 1.0 != 0.0 {
+				return Err(ParseError::RegisterNotInteger {reg, loc: last(state).loc.clone()})
 			}
 
 			let reg = reg as u64;
 
 			if !(0..16).contains(&reg) {
-				return Err(format!("Register must be between 0-15: {} on {}", reg, last(state).loc))
+				return Err(ParseError::RegisterOutOfRange {reg, loc: last(state).loc.clone()})
 			}
 
 			let value = parse_value(state)?;
@@ -315,22 +482,256 @@ fn parse_command(state: &State) -> Result<Command, String> {
 		TokenType::Match => {Ok(Command::Match)},
 		TokenType::Split => {Ok(Command::Split)},
 		TokenType::Iota => {Ok(Command::Iota)},
-		_ => {
-			Err(format!("Unexpected token {} on {}", t.typ, t.loc))
+		TokenType::Print => {Ok(Command::Print)},
+		TokenType::ReadLine => {Ok(Command::ReadLine)},
+		TokenType::Assert => {Ok(Command::Assert)},
+		TokenType::AssertEq => {Ok(Command::AssertEq)},
+		token => {
+			Err(ParseError::UnexpectedToken {token: token.clone(), loc: t.loc.clone()})
 		}
 	}
 }
 
-pub fn parse(tokens: Vec<Token>) -> Result<Vec<Command>, String> {
+fn parse_test(state: &State) -> Result<Test, ParseError> {
+	let loc = last(state).loc.clone();
+
+	if !accept_str(state) {
+		let t = next(state);
+		return Err(ParseError::Expected {expected: "test name", token: t.typ.clone(), loc: t.loc.clone()});
+	}
+
+	let TokenType::String(name) = &last(state).typ else {unreachable!()};
+	let name = name.clone();
+	let bodies = state.bodies.take();
+	let test = parse_test_block(state, name, loc);
+	*state.bodies.borrow_mut() = bodies;
+	test
+}
+
+// the rest of the block, parsed in full even by parse_outline
+fn parse_test_block(state: &State, name: String, loc: Loc) -> Result<Test, ParseError> {
+	if !accept(state, &TokenType::LeftCurly) {
+		let t = next(state);
+		return Err(ParseError::Expected {expected: "test body", token: t.typ.clone(), loc: t.loc.clone()});
+	}
+
+	// the body is numbered on its own, so its locations are moved out of the program's
+	let start = state.locs.borrow().len();
+	let mut body = vec![];
+
+	while !accept(state, &TokenType::RightCurly) {
+		body.push(parse_command(state)?);
+	}
+
+	let locs = state.locs.borrow_mut().split_off(start);
+	let mut expect = None;
+
+	if accept_directive(state, ".expect") {
+		match parse_value(state)? {
+			Value::Array(values) => expect = Some(values),
+			_ => return Err(ParseError::ExpectArray {loc: last(state).loc.clone()})
+		}
+	}
+
+	Ok(Test {name, loc, body, locs, expect})
+}
+
+pub fn parse(tokens: Vec<Token>) -> Result<Vec<Command>, ParseError> {
+	parse_with_locs(tokens).map(|(commands, _)| commands)
+}
+
+// also returns the location of every command, in the order a pre-order walk of the program
+// (including commands in nested function values) visits them
+pub fn parse_with_locs(tokens: Vec<Token>) -> Result<(Vec<Command>, Vec<Loc>), ParseError> {
+	parse_with_tests(tokens).map(|parsed| (parsed.commands, parsed.locs))
+}
+
+// also returns the .test blocks, which are left out of the program
+pub fn parse_with_tests(tokens: Vec<Token>) -> Result<Parsed, ParseError> {
+	parse_with_progress(tokens, &mut |_| {})
+}
+
+// calls `progress` with the percentage of tokens read, after each top-level command or test when
+// it changes and with 100 at the end
+pub fn parse_with_progress(tokens: Vec<Token>, progress: &mut dyn FnMut(u8)) -> Result<Parsed, ParseError> {
+	parse_limited(tokens, usize::MAX, progress)
+}
+
+// fails on the command or value after the first `max_nodes`, before building the rest of a tree
+// that would take too much memory
+#[cfg_attr(feature = "tracing", tracing::instrument(name = "parse", level = "debug", skip_all, fields(tokens = tokens.len())))]
+pub fn parse_limited(tokens: Vec<Token>, max_nodes: usize, progress: &mut dyn FnMut(u8)) -> Result<Parsed, ParseError> {
+	let state = State {
+		ctok: Cell::new(0),
+		tokens,
+		locs: RefCell::new(vec![]),
+		depth: Cell::new(0),
+		bodies: RefCell::new(None),
+		nodes: Cell::new(0),
+		max_nodes
+	};
+
+	let (commands, tests) = parse_program(&state, progress)?;
+	event!(DEBUG, commands = commands.len(), tests = tests.len(), "parsed");
+	Ok(Parsed {commands, locs: state.locs.into_inner(), tests})
+}
+
+fn parse_program(state: &State, progress: &mut dyn FnMut(u8)) -> Result<(Vec<Command>, Vec<Test>), ParseError> {
 	let mut commands = vec![];
+	let mut percent = 0;
+	let mut tests = vec![];
+
+	while !accept(state, &TokenType::Eof) {
+		if accept_directive(state, ".test") {
+			tests.push(parse_test(state)?);
+		} else {
+			commands.push(parse_command(state)?);
+		}
+
+		if (state.ctok.get() * 100 / state.tokens.len()) as u8 != percent {
+			percent = (state.ctok.get() * 100 / state.tokens.len()) as u8;
+			progress(percent);
+		}
+	}
+
+	progress(100);
+	Ok((commands, tests))
+}
+
+pub fn parse_outline(tokens: Vec<Token>) -> Result<Outline, ParseError> {
 	let state = State {
 		ctok: Cell::new(0),
-		tokens
+		tokens,
+		locs: RefCell::new(vec![]),
+		depth: Cell::new(0),
+		bodies: RefCell::new(Some(vec![])),
+		nodes: Cell::new(0),
+		max_nodes: usize::MAX
 	};
 
-	while !accept(&state, &TokenType::Eof) {
-		commands.push(parse_command(&state)?);
+	let (commands, tests) = parse_program(&state, &mut |_| {})?;
+	event!(DEBUG, commands = commands.len(), bodies = state.bodies.borrow().as_ref().map_or(0, Vec::len), "outlined");
+	Ok(Outline {commands, locs: state.locs.into_inner(), tests, bodies: state.bodies.into_inner().unwrap_or_default(), tokens: state.tokens})
+}
+
+impl Outline {
+	// the commands of a skipped body, parsed in full, with their locations
+	pub fn body(&self, body: &Body) -> Result<(Vec<Command>, Vec<Loc>), ParseError> {
+		// the `}` ends the body; the Eof after it is never reached
+		let mut tokens = self.tokens[body.start..=body.end].to_vec();
+		tokens.push(Token::new(TokenType::Eof, self.tokens[body.end].loc.clone()));
+
+		let state = State {
+			ctok: Cell::new(0),
+			tokens,
+			locs: RefCell::new(vec![]),
+			depth: Cell::new(body.depth),
+			bodies: RefCell::new(None),
+			nodes: Cell::new(0),
+			max_nodes: usize::MAX
+		};
+
+		let mut commands = vec![];
+
+		while !accept(&state, &TokenType::RightCurly) {
+			commands.push(parse_command(&state)?);
+		}
+
+		Ok((commands, state.locs.into_inner()))
+	}
+
+	// the location of the `}` closing a body
+	pub fn end(&self, body: &Body) -> &Loc {
+		&self.tokens[body.end].loc
+	}
+
+	// the whole program, with every body parsed, as parse returns it
+	pub fn program(&self) -> Result<Vec<Command>, ParseError> {
+		let mut bodies = self.bodies.iter();
+		let mut commands = self.commands.clone();
+
+		for command in &mut commands {
+			if let Command::Push(value) | Command::ILoad(_, value) = command {
+				self.fill(value, &mut bodies)?;
+			}
+		}
+
+		Ok(commands)
+	}
+
+	fn fill<'a>(&self, value: &mut Value, bodies: &mut impl Iterator<Item = &'a Body>) -> Result<(), ParseError> {
+		match value {
+			Value::Function(commands) => *commands = self.body(bodies.next().unwrap())?.0,
+			Value::Array(values) => values.iter_mut().try_for_each(|value| self.fill(value, bodies))?,
+			_ => {}
+		}
+
+		Ok(())
 	}
+}
+
+// whether a token at the top level ends the command before it: a command, a test or the end
+fn starts_command(typ: &TokenType) -> bool {
+	!matches!(typ, TokenType::Nil | TokenType::Number(_) | TokenType::String(_) | TokenType::Boolean(_) | TokenType::LeftSquare | TokenType::RightSquare | TokenType::LeftCurly | TokenType::RightCurly)
+		&& !matches!(typ, TokenType::Directive(directive) if directive != ".test")
+}
+
+// parses the top-level commands one at a time as they are read from `tokens`, which end with Eof
+// as tokenizer::tokens does, calling `each` with a command before the next is read, so only the
+// tokens of one command are held at once. .test blocks are parsed and left out
+pub fn parse_each<E: From<ParseError>>(tokens: impl IntoIterator<Item = Result<Token, E>>, mut each: impl FnMut(Command) -> Result<(), E>) -> Result<(), E> {
+	let mut tokens = tokens.into_iter();
+	let mut chunk: Vec<Token> = vec![];
+
+	loop {
+		// to the first token after the command at the top level, which the parser leaves for the next.
+		// tokens left before it are ones the parser stopped at, so the next command fails on them
+		if chunk.len() <= 1 {
+			let mut depth = 0usize;
+
+			for token in tokens.by_ref() {
+				let token = token?;
+				let ends = matches!(token.typ, TokenType::Eof) || (!chunk.is_empty() && depth == 0 && starts_command(&token.typ));
+
+				match token.typ {
+					TokenType::LeftSquare | TokenType::LeftCurly => depth += 1,
+					TokenType::RightSquare | TokenType::RightCurly => depth = depth.saturating_sub(1),
+					_ => {}
+				}
 
-	Ok(commands)
+				chunk.push(token);
+
+				if ends {
+					break;
+				}
+			}
+		}
+
+		if chunk.is_empty() {
+			return Ok(());
+		}
+
+		let state = State {
+			ctok: Cell::new(0),
+			tokens: chunk,
+			locs: RefCell::new(vec![]),
+			depth: Cell::new(0),
+			bodies: RefCell::new(None),
+			nodes: Cell::new(0),
+			max_nodes: usize::MAX
+		};
+
+		if accept(&state, &TokenType::Eof) {
+			return Ok(());
+		}
+
+		if accept_directive(&state, ".test") {
+			parse_test(&state)?;
+		} else {
+			each(parse_command(&state)?)?;
+		}
+
+		let mut read = state.tokens;
+		chunk = read.split_off(state.ctok.get());
+	}
 }
@@ -0,0 +1,103 @@
+use alloc::format;
+use alloc::string::{String, ToString};
+
+use crate::parser::*;
+
+// whether a space must come between two pieces of text: when both touch at a word or number, as
+// brackets, braces and quotes end a token on their own
+fn needs_space(out: &str, text: &str) -> bool {
+	let joins = |c: char| !c.is_whitespace() && !"[]{}\"".contains(c);
+	out.chars().next_back().is_some_and(joins) && text.chars().next().is_some_and(joins)
+}
+
+fn push_compact(out: &mut String, text: &str) {
+	if needs_space(out, text) {
+		out.push(' ');
+	}
+
+	out.push_str(text);
+}
+
+// a number in its shortest spelling: 0.5 as .5
+fn compact_number(number: f64) -> String {
+	let text = number.to_string();
+
+	if let Some(rest) = text.strip_prefix("0.") {
+		format!(".{}", rest)
+	} else if let Some(rest) = text.strip_prefix("-0.") {
+		format!("-.{}", rest)
+	} else {
+		text
+	}
+}
+
+fn print_value_compact(out: &mut String, value: &Value) {
+	match value {
+		Value::Number(number) => push_compact(out, &compact_number(*number)),
+		Value::Array(values) => {
+			push_compact(out, "[");
+			values.iter().for_each(|value| print_value_compact(out, value));
+			push_compact(out, "]");
+		},
+		Value::Function(body) => {
+			push_compact(out, "{");
+			print_compact(out, body);
+			push_compact(out, "}");
+		},
+		value => push_compact(out, &value.to_string())
+	}
+}
+
+fn print_compact(out: &mut String, commands: &[Command]) {
+	for command in commands {
+		push_compact(out, get_command_name(command));
+
+		match command {
+			Command::Push(value) => print_value_compact(out, value),
+			Command::ILoad(reg, value) => {
+				push_compact(out, &reg.to_string());
+				print_value_compact(out, value);
+			},
+			_ => {}
+		}
+	}
+}
+
+fn print_commands(out: &mut String, commands: &[Command], depth: usize) {
+	for command in commands {
+		out.push_str(&"\t".repeat(depth));
+
+		match command {
+			Command::Push(Value::Function(body)) | Command::ILoad(_, Value::Function(body)) if !body.is_empty() => {
+				match command {
+					Command::ILoad(reg, _) => out.push_str(&format!("iload {} {{\n", reg)),
+					_ => out.push_str("push {\n")
+				}
+
+				print_commands(out, body, depth + 1);
+				out.push_str(&"\t".repeat(depth));
+				out.push_str("}\n");
+			},
+			_ => {
+				out.push_str(&command.to_string());
+				out.push('\n');
+			}
+		}
+	}
+}
+
+// canonical source text: one command per line, function bodies indented with tabs
+pub fn print(commands: &[Command]) -> String {
+	let mut out = String::new();
+	print_commands(&mut out, commands, 0);
+	out
+}
+
+// the shortest source text for --emit min-asm: no comments, and whitespace only between tokens that
+// would otherwise run together
+pub fn print_minified(commands: &[Command]) -> String {
+	let mut out = String::new();
+	print_compact(&mut out, commands);
+	out.push('\n');
+	out
+}
@@ -0,0 +1,104 @@
+use std::cmp::Reverse;
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use crate::parser::{get_command_name, Command, Value};
+use crate::tokenizer::Loc;
+
+// per-opcode and per-function execution counts and time for a run; a command is charged the
+// time since the previous command finished
+pub struct Profile {
+	owners: Vec<Option<usize>>, // command that pushes or loads the function each command is in, None at the top level
+	definitions: HashMap<usize, String>,
+	opcodes: HashMap<&'static str, (u64, Duration)>,
+	functions: HashMap<Option<usize>, (u64, Duration)>,
+	last: Instant,
+}
+
+// numbers commands in the same order as interpreter::lower
+fn collect_owners(commands: &[Command], owner: Option<usize>, owners: &mut Vec<Option<usize>>, definitions: &mut HashMap<usize, String>) {
+	fn in_value(value: &Value, id: usize, owners: &mut Vec<Option<usize>>, definitions: &mut HashMap<usize, String>) {
+		match value {
+			Value::Function(commands) => collect_owners(commands, Some(id), owners, definitions),
+			Value::Array(values) => values.iter().for_each(|value| in_value(value, id, owners, definitions)),
+			_ => {}
+		}
+	}
+
+	for command in commands {
+		let id = owners.len();
+		owners.push(owner);
+
+		match command {
+			Command::ILoad(reg, value) => {
+				definitions.insert(id, format!("iload {}", reg));
+				in_value(value, id, owners, definitions);
+			},
+			Command::Push(value) => {
+				definitions.insert(id, "push".to_owned());
+				in_value(value, id, owners, definitions);
+			},
+			_ => {}
+		}
+	}
+}
+
+impl Profile {
+	pub fn new(commands: &[Command]) -> Profile {
+		let mut owners = vec![];
+		let mut definitions = HashMap::new();
+		collect_owners(commands, None, &mut owners, &mut definitions);
+
+		Profile {owners, definitions, opcodes: HashMap::new(), functions: HashMap::new(), last: Instant::now()}
+	}
+
+	pub fn record(&mut self, id: usize, command: &Command) {
+		let now = Instant::now();
+		let elapsed = now - self.last;
+		self.last = now;
+
+		let opcode = self.opcodes.entry(get_command_name(command)).or_default();
+		opcode.0 += 1;
+		opcode.1 += elapsed;
+
+		let function = self.functions.entry(self.owners.get(id).copied().flatten()).or_default();
+		function.0 += 1;
+		function.1 += elapsed;
+	}
+
+	pub fn report(&self, locs: &[Loc]) -> String {
+		let total: Duration = self.opcodes.values().map(|(_, time)| *time).sum();
+		let percent = |time: &Duration| if total.is_zero() {0.0} else {time.as_secs_f64() * 100.0 / total.as_secs_f64()};
+		let mut out = format!("{:<12}  {:>10}  {:>12}  {:>6}\n", "opcode", "count", "time", "%");
+
+		let mut opcodes: Vec<_> = self.opcodes.iter().collect();
+		opcodes.sort_by_key(|(_, (_, time))| Reverse(*time));
+
+		for (name, (count, time)) in opcodes {
+			out.push_str(&format!("{:<12}  {:>10}  {:>12?}  {:>6.1}\n", name, count, time, percent(time)));
+		}
+
+		out.push_str(&format!("\n{:<40}  {:>10}  {:>12}  {:>6}\n", "function", "count", "time", "%"));
+
+		let mut functions: Vec<_> = self.functions.iter().collect();
+		functions.sort_by_key(|(_, (_, time))| Reverse(*time));
+
+		for (owner, (count, time)) in functions {
+			let name = match owner {
+				None => "(top level)".to_owned(),
+				Some(id) => {
+					let definition = self.definitions.get(id).map(String::as_str).unwrap_or("?");
+
+					match locs.get(*id) {
+						Some(loc) => format!("{} at {}:{}", definition, loc.line, loc.col),
+						None => format!("{} (command {})", definition, id)
+					}
+				}
+			};
+
+			out.push_str(&format!("{:<40}  {:>10}  {:>12?}  {:>6.1}\n", name, count, time, percent(time)));
+		}
+
+		out
+	}
+}
@@ -0,0 +1,71 @@
+use pyo3::create_exception;
+use pyo3::exceptions::PyException;
+use pyo3::prelude::*;
+use pyo3::types::{PyBytes, PyList};
+
+use crate::diagnostic::Diagnostic;
+use crate::interpreter::{self, Value, Vm};
+use crate::{disasm, parser, printer, tokenizer, AssembleOptions};
+
+// the `evm_asm` Python module, built with maturin (see pyproject.toml). every failure raises
+// EvmAsmError(message, code, line, column), where line and column are None without a location
+
+create_exception!(evm_asm, EvmAsmError, PyException);
+
+fn raise(diagnostic: Diagnostic, source: Option<&str>) -> PyErr {
+	let message = match source {
+		Some(source) => diagnostic.render_with_source(source),
+		None => diagnostic.render()
+	};
+	let loc = diagnostic.primary_span.as_ref().map(|span| (span.loc.line, span.loc.col));
+
+	EvmAsmError::new_err((message, diagnostic.code, loc.map(|(line, _)| line), loc.map(|(_, col)| col)))
+}
+
+fn to_py<'py>(py: Python<'py>, value: &Value) -> PyResult<Bound<'py, PyAny>> {
+	Ok(match value {
+		Value::Nil => py.None().into_bound(py),
+		Value::Number(val) => val.into_pyobject(py)?.into_any(),
+		Value::String(val) => val.into_pyobject(py)?.into_any(),
+		Value::Boolean(val) => val.into_pyobject(py)?.to_owned().into_any(),
+		Value::Array(vals) => PyList::new(py, vals.iter().map(|val| to_py(py, val)).collect::<PyResult<Vec<_>>>()?)?.into_any(),
+		// functions have no Python counterpart, so they come back as their source text
+		Value::Function(_) => value.to_string().into_pyobject(py)?.into_any()
+	})
+}
+
+#[pyfunction]
+fn assemble<'py>(py: Python<'py>, source: &str) -> PyResult<Bound<'py, PyBytes>> {
+	match crate::assemble(source, &AssembleOptions::default()) {
+		Ok(bytecode) => Ok(PyBytes::new(py, &bytecode)),
+		Err(e) => Err(raise(Diagnostic::from(&e), Some(source)))
+	}
+}
+
+#[pyfunction]
+fn disassemble(bytecode: &[u8]) -> PyResult<String> {
+	disasm::disassemble(bytecode).map(|commands| printer::print(&commands)).map_err(|e| raise(Diagnostic::from(&e), None))
+}
+
+// runs source without host capabilities and returns the stack, bottom first
+#[pyfunction]
+#[pyo3(signature = (source, max_steps=None))]
+fn run<'py>(py: Python<'py>, source: &str, max_steps: Option<u64>) -> PyResult<Bound<'py, PyList>> {
+	let tokens = tokenizer::tokenize(source, "<source>").map_err(|e| raise(Diagnostic::from(&e), Some(source)))?;
+	let (commands, locs) = parser::parse_with_locs(tokens).map_err(|e| raise(Diagnostic::from(&e), Some(source)))?;
+
+	let mut vm = Vm::default();
+	vm.fuel = max_steps;
+
+	let stack = vm.load(interpreter::lower(&commands)).run().map_err(|e| raise(e.diagnostic(&locs), Some(source)))?;
+	PyList::new(py, stack.iter().map(|value| to_py(py, value)).collect::<PyResult<Vec<_>>>()?)
+}
+
+#[pymodule]
+fn evm_asm(m: &Bound<'_, PyModule>) -> PyResult<()> {
+	m.add("EvmAsmError", m.py().get_type::<EvmAsmError>())?;
+	m.add_function(wrap_pyfunction!(assemble, m)?)?;
+	m.add_function(wrap_pyfunction!(disassemble, m)?)?;
+	m.add_function(wrap_pyfunction!(run, m)?)?;
+	Ok(())
+}
@@ -0,0 +1,115 @@
+use thiserror::Error;
+
+use crate::cfg::register;
+use crate::highlight::lines;
+use crate::lint::is_register;
+use crate::parser::{self, Command, Value};
+use crate::tokenizer::{self, Loc, Token, TokenType};
+
+// `evm-asm rename OLD NEW`: the language's names are register numbers, so this moves a function or
+// value from one register to another. it rewrites the register of each iload of OLD and each
+// pushed OLD that a load, call, if or loop takes, and leaves the rest of the source as it was. a
+// register computed at run time cannot be followed, so its location is returned for a person to
+// check
+
+#[derive(Debug, Error)]
+pub enum RenameError {
+	#[error(transparent)]
+	Parse(#[from] crate::Error),
+	#[error("register {register} is already used on line {}, column {}", loc.line, loc.col)]
+	InUse {register: u8, loc: Loc},
+}
+
+pub struct Renamed {
+	pub source: String,
+	pub edits: usize,
+	pub computed: Vec<Loc>, // commands taking a register that is not a pushed number
+}
+
+struct Renamer<'a> {
+	tokens: &'a [Token],
+	locs: &'a [Loc],
+	next: usize,
+	old: u8,
+	new: u8,
+	edits: Vec<Loc>, // of the number tokens to rewrite
+	computed: Vec<Loc>,
+}
+
+impl Renamer<'_> {
+	// the token after the one a command starts with: the register of an iload, the value of a push
+	fn operand(&self, loc: &Loc) -> &Token {
+		let i = self.tokens.iter().position(|token| token.loc.line == loc.line && token.loc.col == loc.col).unwrap_or_default();
+		&self.tokens[i + 1]
+	}
+
+	fn register(&mut self, reg: u8, loc: &Loc) -> Result<(), RenameError> {
+		if reg == self.new {
+			return Err(RenameError::InUse {register: reg, loc: loc.clone()});
+		}
+
+		if reg == self.old {
+			self.edits.push(self.operand(loc).loc.clone());
+		}
+
+		Ok(())
+	}
+
+	// in the pre-order of parse_with_locs
+	fn commands(&mut self, commands: &[Command]) -> Result<(), RenameError> {
+		for (i, command) in commands.iter().enumerate() {
+			let loc = self.locs[self.next].clone();
+			self.next += 1;
+
+			match command {
+				Command::ILoad(reg, _) => self.register(*reg, &loc)?,
+				Command::Push(Value::Number(reg)) if is_register(commands, i) && *reg as u8 as f64 == *reg => self.register(*reg as u8, &loc)?,
+				Command::Load | Command::Call | Command::If | Command::Each | Command::Map | Command::Filter | Command::Reduce if register(commands, i).is_none() => {
+					self.computed.push(loc.clone());
+				},
+				_ => {}
+			}
+
+			if let Command::Push(value) | Command::ILoad(_, value) = command {
+				self.value(value)?;
+			}
+		}
+
+		Ok(())
+	}
+
+	fn value(&mut self, value: &Value) -> Result<(), RenameError> {
+		match value {
+			Value::Function(commands) => self.commands(commands),
+			Value::Array(values) => values.iter().try_for_each(|value| self.value(value)),
+			_ => Ok(())
+		}
+	}
+}
+
+pub fn rename(source: &str, filename: &str, old: u8, new: u8) -> Result<Renamed, RenameError> {
+	let tokens = tokenizer::tokenize(source, filename).map_err(crate::Error::from)?;
+	let (commands, locs) = parser::parse_with_locs(tokens.clone()).map_err(crate::Error::from)?;
+
+	let mut renamer = Renamer {tokens: &tokens, locs: &locs, next: 0, old, new, edits: vec![], computed: vec![]};
+
+	if old != new {
+		renamer.commands(&commands)?;
+	}
+
+	// rewritten from the end, so the offsets of the edits before stay right
+	let mut chars: Vec<char> = source.chars().collect();
+	let starts = lines(&chars);
+
+	for loc in renamer.edits.iter().rev() {
+		let start = starts[loc.line as usize - 1].0 + loc.col as usize - 1;
+		let len = tokens.iter().find(|token| token.loc.line == loc.line && token.loc.col == loc.col).map_or(0, |token| match &token.typ {
+			TokenType::Number(spelling) => spelling.chars().count(),
+			_ => 0
+		});
+
+		chars.splice(start..start + len, new.to_string().chars());
+	}
+
+	Ok(Renamed {source: chars.into_iter().collect(), edits: renamer.edits.len(), computed: renamer.computed})
+}
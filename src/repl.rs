@@ -0,0 +1,198 @@
+use rustyline::completion::Completer;
+use rustyline::error::ReadlineError;
+use rustyline::highlight::Highlighter;
+use rustyline::hint::Hinter;
+use rustyline::history::DefaultHistory;
+use rustyline::validate::Validator;
+use rustyline::{Context, Editor, Helper};
+use std::env;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+
+use crate::diagnostic::Diagnostic;
+use crate::interpreter::{self, ConsoleHost, Vm};
+use crate::opcode;
+use crate::parser;
+use crate::tokenizer::{self, TokenType};
+use crate::Error;
+
+const HELP: &str = "\
+Enter commands to run them; the stack is printed after each line.
+  :stack       prints the stack, one value per line
+  :reset       clears the stack and registers
+  :load FILE   runs a source file
+  :help        prints this message
+  :quit        exits
+Tab completes mnemonics and constants; history is kept in ~/.evm_asm_history.";
+
+const META_COMMANDS: &[&str] = &[":stack", ":reset", ":load", ":help", ":quit"];
+
+// completes mnemonics and constants, or meta-commands at the start of a line
+struct ReplHelper;
+
+impl Completer for ReplHelper {
+	type Candidate = String;
+
+	fn complete(&self, line: &str, pos: usize, _ctx: &Context<'_>) -> rustyline::Result<(usize, Vec<String>)> {
+		let start = line[..pos].rfind(|c: char| c.is_whitespace() || "[]{}".contains(c)).map_or(0, |i| i + 1);
+		let word = &line[start..pos];
+
+		let words: Vec<&str> = if start == 0 && word.starts_with(':') {
+			META_COMMANDS.to_vec()
+		} else {
+			opcode::COMMANDS.iter().map(parser::get_command_name).chain(["true", "false", "nil"]).collect()
+		};
+
+		Ok((start, words.into_iter().filter(|w| w.starts_with(word)).map(str::to_owned).collect()))
+	}
+}
+
+impl Hinter for ReplHelper {
+	type Hint = String;
+}
+
+impl Highlighter for ReplHelper {}
+
+impl Validator for ReplHelper {}
+
+impl Helper for ReplHelper {}
+
+fn history_path() -> Option<PathBuf> {
+	env::var_os("HOME").map(|home| PathBuf::from(home).join(".evm_asm_history"))
+}
+
+fn print_stack(vm: &Vm) {
+	let values: Vec<_> = vm.stack.iter().map(|value| value.to_string()).collect();
+	println!("<{}> {}", values.len(), values.join(" "));
+}
+
+// whether the tokens leave a function or array literal open
+fn unclosed(tokens: &[tokenizer::Token]) -> bool {
+	let mut depth: isize = 0;
+
+	for token in tokens {
+		match token.typ {
+			TokenType::LeftSquare | TokenType::LeftCurly => depth += 1,
+			TokenType::RightSquare | TokenType::RightCurly => depth -= 1,
+			_ => {}
+		}
+	}
+
+	depth > 0
+}
+
+fn execute(vm: &mut Vm, source: &str, filename: &str) {
+	let result = tokenizer::tokenize(source, filename)
+		.map_err(Error::from)
+		.and_then(|tokens| parser::parse(tokens).map_err(Error::from));
+
+	let commands = match result {
+		Ok(commands) => commands,
+		Err(e) => {
+			eprintln!("{}", Diagnostic::from(&e).render());
+			return;
+		}
+	};
+
+	vm.load(interpreter::lower(&commands));
+
+	if let Err(e) = vm.run() {
+		eprintln!("{}", e.diagnostic(&[]).render());
+		vm.abort();
+	}
+}
+
+// a line from the editor, None at the end of input; Ctrl-C discards the line being edited
+fn read_line(editor: &mut Editor<ReplHelper, DefaultHistory>, prompt: &str) -> io::Result<Option<String>> {
+	match editor.readline(prompt) {
+		Ok(line) => Ok(Some(line)),
+		Err(ReadlineError::Interrupted) => Ok(Some(String::new())),
+		Err(ReadlineError::Eof) => Ok(None),
+		Err(e) => Err(io::Error::other(e))
+	}
+}
+
+// reads commands from stdin, running each line against a persistent stack and registers
+pub fn repl() -> io::Result<()> {
+	let mut editor = Editor::<ReplHelper, DefaultHistory>::new().map_err(io::Error::other)?;
+	editor.set_helper(Some(ReplHelper));
+
+	let history = history_path();
+
+	if let Some(path) = &history {
+		let _ = editor.load_history(path); // missing on first use
+	}
+
+	let result = repl_loop(&mut editor);
+
+	if let Some(path) = &history {
+		let _ = editor.save_history(path);
+	}
+
+	result
+}
+
+fn repl_loop(editor: &mut Editor<ReplHelper, DefaultHistory>) -> io::Result<()> {
+	let mut vm = Vm::new(ConsoleHost);
+
+	loop {
+		let Some(mut source) = read_line(editor, "> ")? else {
+			println!();
+			return Ok(());
+		};
+
+		if source.trim_start().starts_with(':') {
+			let _ = editor.add_history_entry(source.as_str());
+		}
+
+		match source.trim().split_once(' ').unwrap_or((source.trim(), "")) {
+			(":quit" | ":q", _) => return Ok(()),
+			(":help", _) => {
+				println!("{}", HELP);
+				continue;
+			},
+			(":reset", _) => {
+				vm.reset();
+				continue;
+			},
+			(":stack", _) => {
+				for (i, value) in vm.stack.iter().enumerate().rev() {
+					println!("{:>4}: {}", i, value);
+				}
+				continue;
+			},
+			(":load", filename) => {
+				match fs::read_to_string(filename.trim()) {
+					Ok(input) => execute(&mut vm, &input, filename.trim()),
+					Err(e) => eprintln!("File cannot be read: {}: {}", filename.trim(), e)
+				}
+				print_stack(&vm);
+				continue;
+			},
+			(meta, _) if meta.starts_with(':') => {
+				eprintln!("Unknown command {}, try :help", meta);
+				continue;
+			},
+			_ => {}
+		}
+
+		// keep reading while a function or array literal is open
+		while tokenizer::tokenize(&source, "<repl>").is_ok_and(|tokens| unclosed(&tokens)) {
+			match read_line(editor, "... ")? {
+				Some(line) => {
+					source.push('\n');
+					source.push_str(&line);
+				},
+				None => break
+			}
+		}
+
+		if !source.trim().is_empty() {
+			let _ = editor.add_history_entry(source.as_str());
+		}
+
+		execute(&mut vm, &source, "<repl>");
+		print_stack(&vm);
+	}
+}
@@ -0,0 +1,211 @@
+use crate::parser::{self, Command, Value};
+use crate::tokenizer::{self, Loc};
+use crate::Error;
+
+// structural search for `evm-asm grep`: a pattern is a run of commands written as source, where
+// `_` stands for any one command, or for any value or register where an operand goes:
+//
+//   push _ drop        a push of anything, dropped
+//   iload _ {_ +}      an iload of a function adding something to what it is called with
+//   push [_ 0]         a push of a two-value array ending in 0
+//
+// the pattern matches consecutive commands in the program or in one function body. a function in a
+// pattern matches a body of exactly its commands
+
+// what `_` becomes for the tokenizer and parser: strings no source would hold, pushed where `_` is
+// a command
+const ANY_VALUE: &str = "\u{0}_";
+const ANY_COMMAND: &str = "\u{0}*";
+
+#[derive(Clone, Debug)]
+enum Item {
+	Any,
+	Push(Operand),
+	ILoad(Option<u8>, Operand), // None for any register
+	Command(Command),
+}
+
+#[derive(Clone, Debug)]
+enum Operand {
+	Any,
+	Array(Vec<Operand>),
+	Function(Vec<Item>),
+	Value(Value),
+}
+
+#[derive(Clone, Debug)]
+pub struct Pattern {
+	items: Vec<Item>,
+}
+
+pub struct Match {
+	pub loc: Loc, // of the first command
+	pub commands: Vec<Command>,
+}
+
+// the pattern with each `_` outside a string replaced for the parser, and the line and column of
+// each iload whose register is `_`, which becomes 0. a `_` is an operand after push, after iload
+// or its register and inside an array, and a command anywhere else
+fn substitute(pattern: &str) -> (String, Vec<(u64, u64)>) {
+	let mut out = String::new();
+	let mut any_registers = vec![];
+	let mut words: Vec<(String, u64, u64)> = vec![]; // with where they start in `out`
+	let mut brackets = vec![];
+	let (mut line, mut col) = (1, 1);
+	let mut chars = pattern.chars().peekable();
+
+	while let Some(c) = chars.next() {
+		let mut word = c.to_string();
+
+		if c == '"' {
+			// to the closing quote
+			for c in chars.by_ref() {
+				word.push(c);
+
+				if c == '"' {
+					break;
+				}
+			}
+		} else if !c.is_whitespace() && !"[]{}".contains(c) {
+			while let Some(c) = chars.next_if(|c| !c.is_whitespace() && !"[]{}\"".contains(*c)) {
+				word.push(c);
+			}
+		}
+
+		let previous = |back: usize| words.len().checked_sub(back).map(|i| words[i].0.as_str());
+
+		if word == "_" {
+			word = match (previous(1), previous(2)) {
+				(Some("iload"), _) => {
+					let (_, line, col) = words[words.len() - 1];
+					any_registers.push((line, col));
+					"0".to_owned()
+				},
+				(Some("push"), _) | (_, Some("iload")) => format!("\"{}\"", ANY_VALUE),
+				_ if brackets.last() == Some(&'[') => format!("\"{}\"", ANY_VALUE),
+				_ => format!("push \"{}\"", ANY_COMMAND)
+			};
+		}
+
+		match c {
+			'[' | '{' => brackets.push(c),
+			']' | '}' => {
+				brackets.pop();
+			},
+			_ => {}
+		}
+
+		if !c.is_whitespace() {
+			words.push((word.clone(), line, col));
+		}
+
+		for c in word.chars() {
+			if c == '\n' {
+				line += 1;
+				col = 1;
+			} else {
+				col += 1;
+			}
+		}
+
+		out.push_str(&word);
+	}
+
+	(out, any_registers)
+}
+
+// the pattern's commands as items; `next` counts commands in the pre-order of parse_with_locs
+fn items(commands: &[Command], locs: &[Loc], next: &mut usize, any_registers: &[(u64, u64)]) -> Vec<Item> {
+	commands.iter().map(|command| {
+		let loc = &locs[*next];
+		*next += 1;
+
+		match command {
+			Command::Push(Value::String(text)) if text == ANY_COMMAND => Item::Any,
+			Command::Push(value) => Item::Push(operand(value, locs, next, any_registers)),
+			Command::ILoad(reg, value) => {
+				let reg = (!any_registers.contains(&(loc.line, loc.col))).then_some(*reg);
+				Item::ILoad(reg, operand(value, locs, next, any_registers))
+			},
+			command => Item::Command(command.clone())
+		}
+	}).collect()
+}
+
+fn operand(value: &Value, locs: &[Loc], next: &mut usize, any_registers: &[(u64, u64)]) -> Operand {
+	match value {
+		Value::String(text) if text == ANY_VALUE => Operand::Any,
+		Value::Array(values) => Operand::Array(values.iter().map(|value| operand(value, locs, next, any_registers)).collect()),
+		Value::Function(commands) => Operand::Function(items(commands, locs, next, any_registers)),
+		value => Operand::Value(value.clone())
+	}
+}
+
+impl Pattern {
+	pub fn parse(pattern: &str) -> Result<Pattern, Error> {
+		let (source, any_registers) = substitute(pattern);
+		let (commands, locs) = parser::parse_with_locs(tokenizer::tokenize(&source, "<pattern>")?)?;
+		Ok(Pattern {items: items(&commands, &locs, &mut 0, &any_registers)})
+	}
+
+	// every match in the program, in source order; `locs` are as parse_with_locs returns them
+	pub fn find(&self, commands: &[Command], locs: &[Loc]) -> Vec<Match> {
+		let mut matches = vec![];
+		let mut next = 0;
+		self.search(commands, locs, &mut next, &mut matches);
+		matches.sort_by_key(|found| (found.loc.line, found.loc.col));
+		matches
+	}
+
+	fn search(&self, commands: &[Command], locs: &[Loc], next: &mut usize, matches: &mut Vec<Match>) {
+		let mut indices = vec![];
+
+		// numbered in the pre-order of parse_with_locs: each command, then those in its operand
+		for command in commands {
+			indices.push(*next);
+			*next += 1;
+
+			if let Command::Push(value) | Command::ILoad(_, value) = command {
+				self.search_value(value, locs, next, matches);
+			}
+		}
+
+		if self.items.is_empty() {
+			return;
+		}
+
+		for (start, window) in commands.windows(self.items.len()).enumerate() {
+			if commands_match(&self.items, window) {
+				matches.push(Match {loc: locs[indices[start]].clone(), commands: window.to_vec()});
+			}
+		}
+	}
+
+	fn search_value(&self, value: &Value, locs: &[Loc], next: &mut usize, matches: &mut Vec<Match>) {
+		match value {
+			Value::Function(commands) => self.search(commands, locs, next, matches),
+			Value::Array(values) => values.iter().for_each(|value| self.search_value(value, locs, next, matches)),
+			_ => {}
+		}
+	}
+}
+
+fn commands_match(items: &[Item], commands: &[Command]) -> bool {
+	items.len() == commands.len() && items.iter().zip(commands).all(|(item, command)| match (item, command) {
+		(Item::Any, _) => true,
+		(Item::Push(pattern), Command::Push(value)) => value_matches(pattern, value),
+		(Item::ILoad(reg, pattern), Command::ILoad(other, value)) => reg.is_none_or(|reg| reg == *other) && value_matches(pattern, value),
+		(Item::Command(pattern), command) => pattern == command,
+		_ => false
+	})
+}
+
+fn value_matches(pattern: &Operand, value: &Value) -> bool {
+	match (pattern, value) {
+		(Operand::Any, _) => true,
+		(Operand::Array(patterns), Value::Array(values)) => patterns.len() == values.len() && patterns.iter().zip(values).all(|(pattern, value)| value_matches(pattern, value)),
+		(Operand::Function(items), Value::Function(commands)) => commands_match(items, commands),
+		(Operand::Value(pattern), value) => pattern == value,
+		_ => false
+	}
+}
@@ -0,0 +1,185 @@
+use serde_json::{json, Value as Json};
+use std::collections::BTreeMap;
+use std::fmt::Write;
+
+use crate::codegen;
+use crate::disasm::{self, DisasmError};
+use crate::parser::{get_command_name, Command, Value};
+use crate::{assemble_file, AssembleOptions, Error};
+
+// totals over many programs, for `evm-asm stats`: how often each command is used and the bytes it
+// takes, program sizes, and constants pushed more than once. programs are counted as bytecode,
+// after optimization, so sources are assembled first
+
+#[derive(Clone, Debug, Default)]
+pub struct Opcode {
+	pub count: usize,
+	pub bytes: usize, // without the bodies of functions, which are counted as their own commands
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct Constant {
+	pub count: usize,
+	pub programs: usize,
+	pub size: usize, // bytes of one encoding
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct Stats {
+	pub programs: usize,
+	pub bytes: usize,
+	pub smallest: Option<usize>,
+	pub largest: usize,
+	pub opcodes: BTreeMap<&'static str, Opcode>,
+	pub constants: BTreeMap<String, Constant>, // by their text
+}
+
+// the constants a program pushes or iloads: every value but a function, whose body is walked
+fn constants(commands: &[Command], out: &mut Vec<(String, usize)>) {
+	for command in commands {
+		match command {
+			Command::Push(Value::Function(body)) | Command::ILoad(_, Value::Function(body)) => constants(body, out),
+			Command::Push(value) | Command::ILoad(_, value) if !contains_function(value) => out.push((value.to_string(), codegen::value_size(value))),
+			Command::Push(Value::Array(values)) | Command::ILoad(_, Value::Array(values)) => values.iter().for_each(|value| constants(&[Command::Push(value.clone())], out)),
+			_ => {}
+		}
+	}
+}
+
+fn contains_function(value: &Value) -> bool {
+	match value {
+		Value::Function(_) => true,
+		Value::Array(values) => values.iter().any(contains_function),
+		_ => false
+	}
+}
+
+impl Stats {
+	fn commands(&mut self, commands: &[Command]) {
+		for command in commands {
+			let body: &[Command] = match command {
+				Command::Push(Value::Function(body)) | Command::ILoad(_, Value::Function(body)) => body,
+				_ => &[]
+			};
+
+			let opcode = self.opcodes.entry(get_command_name(command)).or_default();
+			opcode.count += 1;
+			opcode.bytes += codegen::command_size(command) - body.iter().map(codegen::command_size).sum::<usize>();
+			self.commands(body);
+
+			if let Command::Push(value) | Command::ILoad(_, value) = command {
+				self.values(value);
+			}
+		}
+	}
+
+	// the bodies of functions inside arrays
+	fn values(&mut self, value: &Value) {
+		if let Value::Array(values) = value {
+			for value in values {
+				match value {
+					Value::Function(body) => self.commands(body),
+					value => self.values(value)
+				}
+			}
+		}
+	}
+
+	pub fn add(&mut self, bytecode: &[u8]) -> Result<(), DisasmError> {
+		let commands = disasm::disassemble(bytecode)?;
+
+		self.programs += 1;
+		self.bytes += bytecode.len();
+		self.smallest = Some(self.smallest.map_or(bytecode.len(), |smallest| smallest.min(bytecode.len())));
+		self.largest = self.largest.max(bytecode.len());
+		self.commands(&commands);
+
+		let mut pushed = vec![];
+		constants(&commands, &mut pushed);
+
+		for (i, (text, size)) in pushed.iter().enumerate() {
+			let constant = self.constants.entry(text.clone()).or_default();
+			constant.count += 1;
+			constant.size = *size;
+
+			if !pushed[..i].iter().any(|(other, _)| other == text) {
+				constant.programs += 1;
+			}
+		}
+
+		Ok(())
+	}
+
+	// assembles the source with the default options and adds the bytecode
+	pub fn add_source(&mut self, source: &str, filename: &str) -> Result<(), Error> {
+		let bytecode = assemble_file(source, filename, &AssembleOptions::default())?.bytecode;
+		Ok(self.add(&bytecode)?)
+	}
+
+	pub fn average(&self) -> usize {
+		self.bytes.checked_div(self.programs).unwrap_or_default()
+	}
+
+	// bytes that constants take beyond their first encoding
+	pub fn repeated_bytes(&self) -> usize {
+		self.constants.values().map(|constant| (constant.count - 1) * constant.size).sum()
+	}
+
+	fn opcodes_by_count(&self) -> Vec<(&&'static str, &Opcode)> {
+		let mut opcodes: Vec<_> = self.opcodes.iter().collect();
+		opcodes.sort_by_key(|(_, opcode)| std::cmp::Reverse(opcode.count));
+		opcodes
+	}
+
+	// the `top` constants whose repeats take the most bytes
+	fn most_repeated(&self, top: usize) -> Vec<(&String, &Constant)> {
+		let mut constants: Vec<_> = self.constants.iter().filter(|(_, constant)| constant.count > 1).collect();
+		constants.sort_by_key(|(_, constant)| std::cmp::Reverse((constant.count - 1) * constant.size));
+		constants.truncate(top);
+		constants
+	}
+
+	pub fn text(&self, top: usize) -> String {
+		let mut out = String::new();
+		let commands: usize = self.opcodes.values().map(|opcode| opcode.count).sum();
+
+		writeln!(out, "Programs: {}", self.programs).unwrap();
+		writeln!(out, "Bytes: {} total, {} average, {} smallest, {} largest", self.bytes, self.average(), self.smallest.unwrap_or_default(), self.largest).unwrap();
+		writeln!(out, "\n{:<12} {:>8} {:>7} {:>8}", "Command", "Count", "%", "Bytes").unwrap();
+
+		for (name, opcode) in self.opcodes_by_count() {
+			writeln!(out, "{:<12} {:>8} {:>6.1}% {:>8}", name, opcode.count, opcode.count as f64 * 100.0 / commands as f64, opcode.bytes).unwrap();
+		}
+
+		let pushed: usize = self.constants.values().map(|constant| constant.count).sum();
+		writeln!(out, "\nConstants: {} pushed, {} distinct, {} bytes in repeats", pushed, self.constants.len(), self.repeated_bytes()).unwrap();
+
+		let repeated = self.most_repeated(top);
+
+		if !repeated.is_empty() {
+			writeln!(out, "\n{:>8} {:>8} {:>8}  Constant", "Count", "Programs", "Bytes").unwrap();
+		}
+
+		for (text, constant) in repeated {
+			writeln!(out, "{:>8} {:>8} {:>8}  {}", constant.count, constant.programs, (constant.count - 1) * constant.size, text).unwrap();
+		}
+
+		out
+	}
+
+	pub fn to_json(&self, top: usize) -> Json {
+		json!({
+			"programs": self.programs,
+			"bytes": {"total": self.bytes, "average": self.average(), "smallest": self.smallest.unwrap_or_default(), "largest": self.largest},
+			"commands": self.opcodes_by_count().iter().map(|(name, opcode)| json!({"name": name, "count": opcode.count, "bytes": opcode.bytes})).collect::<Vec<_>>(),
+			"constants": {
+				"pushed": self.constants.values().map(|constant| constant.count).sum::<usize>(),
+				"distinct": self.constants.len(),
+				"repeated_bytes": self.repeated_bytes(),
+				"most_repeated": self.most_repeated(top).iter().map(|(text, constant)| json!({
+					"value": text, "count": constant.count, "programs": constant.programs, "bytes": (constant.count - 1) * constant.size
+				})).collect::<Vec<_>>()
+			}
+		})
+	}
+}
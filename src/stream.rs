@@ -0,0 +1,47 @@
+use std::io::{self, Write};
+use std::slice;
+use thiserror::Error;
+
+use crate::codegen;
+use crate::parser;
+use crate::tokenizer;
+
+// `evm-asm --stream`: for machine-generated programs too large to hold as a tree, each top-level
+// command goes from the tokenizer through the parser to bytecode before the next is read, so memory
+// is bounded by the largest command rather than the input. the optimizer needs the whole program
+// and is not run, so the bytecode is what -O0 gives; .test blocks are skipped
+
+#[derive(Debug, Error)]
+pub enum StreamError {
+	#[error(transparent)]
+	Assemble(#[from] crate::Error),
+	#[error(transparent)]
+	Io(#[from] io::Error),
+}
+
+impl From<parser::ParseError> for StreamError {
+	fn from(e: parser::ParseError) -> StreamError {
+		StreamError::Assemble(e.into())
+	}
+}
+
+// writes the bytecode of `source` to `out` a command at a time; returns how many bytes it wrote.
+// on an error the bytes before it have been written
+pub fn assemble_stream(source: &str, filename: &str, max_size: Option<usize>, target: &codegen::TargetLimits, legacy_numbers: bool, out: &mut dyn Write) -> Result<usize, StreamError> {
+	let mut size = 0;
+	let tokens = tokenizer::tokens(source, filename).legacy_numbers(legacy_numbers).map(|token| token.map_err(|e| StreamError::from(crate::Error::from(e))));
+
+	parser::parse_each(tokens, |command| {
+		codegen::validate_limited(slice::from_ref(&command), target).map_err(crate::Error::from)?;
+
+		let bytes = codegen::generate(vec![command]);
+		size += bytes.len();
+
+		match max_size {
+			Some(max) if size > max => Err(crate::Error::TooLarge {size, max}.into()),
+			_ => Ok(out.write_all(&bytes)?)
+		}
+	})?;
+
+	Ok(size)
+}
@@ -0,0 +1,63 @@
+use crate::interpreter::{self, Value, Vm};
+use crate::optimizer;
+use crate::parser::{self, Command, Test};
+use crate::tokenizer::Loc;
+
+pub enum Outcome {
+	Pass,
+	Fail(String), // what went wrong, on one or more lines
+}
+
+// the values of `expect` that differ from `stack`, one line each
+fn diff(expect: &[Value], stack: &[Value]) -> Vec<String> {
+	let show = |value: Option<&Value>| value.map(Value::to_string).unwrap_or("nothing".to_owned());
+
+	(0..expect.len().max(stack.len())).filter(|i| expect.get(*i) != stack.get(*i)).map(|i| {
+		format!("at {}: expected {}, got {}", i, show(expect.get(i)), show(stack.get(i)))
+	}).collect()
+}
+
+// runs a test after the file's top-level iload commands, which is where the functions under test
+// are defined; `locs` are the locations of the file's commands
+pub fn run_test(test: &Test, program: &[Command], locs: &[Loc], source: &str, fuel: Option<u64>) -> Outcome {
+	let mut commands = vec![];
+	let mut test_locs = vec![];
+	let mut id = 0;
+
+	for command in program {
+		let count = optimizer::count_commands(std::slice::from_ref(command));
+
+		if let Command::ILoad(..) = command {
+			commands.push(command.clone());
+			test_locs.extend_from_slice(&locs[id..id + count]);
+		}
+
+		id += count;
+	}
+
+	commands.extend(test.body.iter().cloned());
+	test_locs.extend(test.locs.iter().cloned());
+
+	let mut vm = Vm::default();
+	vm.fuel = fuel;
+	vm.load(interpreter::lower(&commands));
+
+	if let Err(e) = vm.run() {
+		return Outcome::Fail(e.report(&test_locs, Some(source)));
+	}
+
+	let Some(expect) = &test.expect else {
+		return Outcome::Pass;
+	};
+
+	let mut next_id = 0;
+	let expect: Vec<Value> = expect.iter().map(|value| Value::from_ast(value, &mut next_id)).collect();
+	let differences = diff(&expect, &vm.stack);
+
+	if differences.is_empty() {
+		return Outcome::Pass;
+	}
+
+	let show = |values: &[Value]| parser::Value::Array(values.iter().map(Value::to_ast).collect()).to_string();
+	Outcome::Fail(format!("expected {}\n  actual {}\n{}", show(&expect), show(&vm.stack), differences.join("\n")))
+}
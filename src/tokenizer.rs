@@ -1,7 +1,14 @@
-use maplit::hashmap;
-use std::fmt;
-
-#[derive(Clone)]
+use alloc::borrow::ToOwned;
+use alloc::collections::BTreeMap;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use alloc::vec;
+use core::fmt;
+use memchr::{memchr, memchr2, memchr_iter};
+use thiserror::Error;
+
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Loc {
 	pub line: u64,
 	pub col: u64,
@@ -14,12 +21,14 @@ impl fmt::Display for Loc {
 	}
 }
 
-#[derive(Clone, Hash, Eq, PartialEq)]
+#[derive(Clone, Debug, Hash, Eq, PartialEq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 pub enum TokenType {
 	Eof,
 	Nil,
 	Number(String),
 	String(String),
+	Directive(String),
 	Boolean(bool),
 	LeftSquare,
 	RightSquare,
@@ -59,7 +68,11 @@ pub enum TokenType {
 	Concat,
 	Match,
 	Split,
-	Iota
+	Iota,
+	Print,
+	ReadLine,
+	Assert,
+	AssertEq
 }
 
 fn get_token_name(typ: &TokenType) -> &str {
@@ -67,6 +80,7 @@ fn get_token_name(typ: &TokenType) -> &str {
 		TokenType::Eof         => "end-of-file",
 		TokenType::Number(x)   => x,
 		TokenType::String(x)   => x,
+		TokenType::Directive(x) => x,
 		TokenType::Boolean(x)  => if *x {"true"} else {"false"},
 		TokenType::Nil         => "nil",
 		TokenType::LeftSquare  => "[",
@@ -107,7 +121,11 @@ fn get_token_name(typ: &TokenType) -> &str {
 		TokenType::Concat      => "concat",
 		TokenType::Match       => "match",
 		TokenType::Split       => "split",
-		TokenType::Iota        => "iota"
+		TokenType::Iota        => "iota",
+		TokenType::Print       => "print",
+		TokenType::ReadLine    => "readln",
+		TokenType::Assert      => "assert",
+		TokenType::AssertEq    => "assert_eq"
 	}
 }
 
@@ -132,254 +150,405 @@ impl fmt::Display for Token {
 	}
 }
 
+#[derive(Debug, Error)]
+pub enum TokenizeError {
+	#[error("Unexpected identifier {ident} on {loc}")]
+	UnexpectedIdentifier {ident: String, loc: Loc},
+	#[error("Unterminated string starting on {loc}")]
+	UnterminatedString {loc: Loc, end: Loc}, // where the string starts and where the input ran out
+	#[error("Input has more than {max} tokens, at {loc}; raise the limit with --max-tokens")]
+	TooManyTokens {max: usize, loc: Loc},
+	#[error("Malformed number {text} on {loc}")]
+	MalformedNumber {text: String, loc: Loc},
+}
+
+impl TokenizeError {
+	pub fn loc(&self) -> &Loc {
+		match self {
+			TokenizeError::UnexpectedIdentifier {loc, ..} | TokenizeError::UnterminatedString {loc, ..} | TokenizeError::TooManyTokens {loc, ..}
+				| TokenizeError::MalformedNumber {loc, ..} => loc
+		}
+	}
+}
+
 impl Token {
 	pub fn new(typ: TokenType, loc: Loc) -> Token {
 		Token {typ, loc}
 	}
 }
 
-pub fn tokenize(char_str: &str, filename: &str) -> Result<Vec<Token>, String> {
-	let mut tokens = vec![];
-	let chars: Vec<_> = char_str.chars().collect();
-
-	let mut i = 0;
-	let mut line: u64 = 1;
-	let mut col: u64 = 1;
-	let mut buffer = String::new();
-
-	let token_map = hashmap! {
-		'[' => TokenType::LeftSquare,
-		']' => TokenType::RightSquare,
-		'{' => TokenType::LeftCurly,
-		'}' => TokenType::RightCurly,
-	};
-
-	let op_map = hashmap! {
-		"push".to_owned() => TokenType::Push,
-		"dup".to_owned() => TokenType::Dup,
-		"swap".to_owned() => TokenType::Swap,
-		"iload".to_owned() => TokenType::ILoad,
-		"load".to_owned() => TokenType::Load,
-		"drop".to_owned() => TokenType::Drop,
-		"query".to_owned() => TokenType::Query,
-		"info".to_owned() => TokenType::Info,
-		"if".to_owned() => TokenType::If,
-		"each".to_owned() => TokenType::Each,
-		"reduce".to_owned() => TokenType::Reduce,
-		"reverse".to_owned() => TokenType::Reverse,
-		"map".to_owned() => TokenType::Map,
-		"filter".to_owned() => TokenType::Filter,
-		"call".to_owned() => TokenType::Call,
-		"tostr".to_owned() => TokenType::ToStr,
-		"tonum".to_owned() => TokenType::ToNum,
-		"+".to_owned() => TokenType::Add,
-		"-".to_owned() => TokenType::Sub,
-		"*".to_owned() => TokenType::Mul,
-		"/".to_owned() => TokenType::Div,
-		"%".to_owned() => TokenType::Mod,
-		"=".to_owned() => TokenType::Eq,
-		"!=".to_owned() => TokenType::NotEq,
-		">".to_owned() => TokenType::Greater,
-		">=".to_owned() => TokenType::GreaterEq,
-		"<".to_owned() => TokenType::Less,
-		"<=".to_owned() => TokenType::LessEq,
-		"and".to_owned() => TokenType::And,
-		"or".to_owned() => TokenType::Or,
-		"not".to_owned() => TokenType::Not,
-		"concat".to_owned() => TokenType::Concat,
-		"match".to_owned() => TokenType::Match,
-		"split".to_owned() => TokenType::Split,
-		"iota".to_owned() => TokenType::Iota,
-		"true".to_owned() => TokenType::Boolean(true),
-		"false".to_owned() => TokenType::Boolean(false),
-		"nil".to_owned() => TokenType::Nil
-	};
-
-	macro_rules! here {
-		() => {Loc {line, col, filename: filename.to_string()}}
-	}
+// a `; comment` or the shebang line, which the parser never sees; `text` starts with the `;` or `#!`
+#[derive(Clone, Debug)]
+pub struct Comment {
+	pub text: String,
+	pub loc: Loc,
+}
 
-	// shebang check
-	if (chars.len() >= 2) && (chars[0] == '#') && (chars[1] == '!') {
-		while i < chars.len() { // consume until newline
-			let c = chars[i];
+pub fn tokenize(char_str: &str, filename: &str) -> Result<Vec<Token>, TokenizeError> {
+	tokenize_with_progress(char_str, filename, &mut |_| {})
+}
 
-			if (c == '\r') || (c == '\n') {
-				break;
-			}
+// calls `progress` with the percentage of the source read, at line ends when it changes and with
+// 100 at the end
+pub fn tokenize_with_progress(char_str: &str, filename: &str, progress: &mut dyn FnMut(u8)) -> Result<Vec<Token>, TokenizeError> {
+	read_tokens(char_str, filename, progress, &mut vec![], usize::MAX, false)
+}
 
-			i += 1;
+// fails on the token after the first `max_tokens`, before reading the rest of a source that would
+// take too much memory as tokens
+pub fn tokenize_limited(char_str: &str, filename: &str, max_tokens: usize, progress: &mut dyn FnMut(u8)) -> Result<Vec<Token>, TokenizeError> {
+	read_tokens(char_str, filename, progress, &mut vec![], max_tokens, false)
+}
+
+// as tokenize_limited, with numbers read as they were before they were strict (see Tokens::legacy_numbers)
+pub fn tokenize_legacy(char_str: &str, filename: &str, max_tokens: usize, progress: &mut dyn FnMut(u8)) -> Result<Vec<Token>, TokenizeError> {
+	read_tokens(char_str, filename, progress, &mut vec![], max_tokens, true)
+}
+
+// also returns the comments, in source order, for tools that rewrite the source
+pub fn tokenize_with_comments(char_str: &str, filename: &str) -> Result<(Vec<Token>, Vec<Comment>), TokenizeError> {
+	let mut comments = vec![];
+	let tokens = read_tokens(char_str, filename, &mut |_| {}, &mut comments, usize::MAX, false)?;
+	Ok((tokens, comments))
+}
+
+// a position in the source, moved a char at a time or, with memchr, past a run of bytes at once
+struct Cursor<'a> {
+	source: &'a str,
+	pos: usize, // in bytes
+}
+
+impl<'a> Cursor<'a> {
+	fn peek(&self) -> Option<char> {
+		self.source[self.pos..].chars().next()
+	}
+
+	// the char and its byte offset
+	fn next(&mut self) -> Option<(usize, char)> {
+		let c = self.peek()?;
+		self.pos += c.len_utf8();
+		Some((self.pos - c.len_utf8(), c))
+	}
+
+	fn next_if(&mut self, accept: impl FnOnce(char) -> bool) -> bool {
+		match self.peek() {
+			Some(c) if accept(c) => {
+				self.pos += c.len_utf8();
+				true
+			},
+			_ => false
 		}
 	}
 
-	while i < chars.len() {
-		let c = chars[i];
+	// consumes the chars `more` accepts; returns how many
+	fn take_while(&mut self, mut more: impl FnMut(char) -> bool) -> u64 {
+		let mut count = 0;
 
-		if c == '\r' {
-			line += 1;
-			col = 0;
-			if (i + 1 < chars.len()) && chars[i + 1] == '\n' {i += 1}
-		} else if c == '\n' {
-			line += 1;
-			col = 0;
-		} else if c == ';' {
-			i += 1;
+		while self.next_if(&mut more) {
+			count += 1;
+		}
 
-			while i < chars.len() { // consume until newline
-				let nc = chars[i];
+		count
+	}
 
-				if (nc == '\r') || (nc == '\n') {
-					i -= 1;
+	// consumes spaces and tabs, the usual run of whitespace; returns how many
+	fn skip_blanks(&mut self) -> u64 {
+		let blanks = self.rest().iter().take_while(|b| matches!(b, b' ' | b'\t')).count();
+		self.pos += blanks;
+		blanks as u64
+	}
 
-					break;
-				}
+	// consumes the next `len` bytes, as memchr found them, or the rest of the source; returns them
+	fn skip(&mut self, len: Option<usize>) -> &'a str {
+		let start = self.pos;
+		self.pos = len.map_or(self.source.len(), |len| start + len);
+		&self.source[start..self.pos]
+	}
 
-				i += 1;
-			}
-		} else if c.is_whitespace() {
-			// do nothing
-		} else if token_map.contains_key(&c) {
-			tokens.push(Token::new(token_map[&c].clone(), here!()));
-		} else if c.is_ascii_digit() { // number takes precendence over identifier because it isolates ascii digits
-			buffer += &c.to_string();
-
-			let scol = col;
-
-			let mut found_dot: bool = false;
-
-			while i + 1 < chars.len() {
-				i += 1;
-				col += 1;
-
-				if chars[i].is_ascii_digit() {
-					buffer += &chars[i].to_string();
-				} else if (chars[i] == '.') && !found_dot {
-					found_dot = true;
-					buffer += ".";
-				} else {
-					i -= 1;
-					col -= 1;
-					break;
-				}
-			}
+	fn rest(&self) -> &'a [u8] {
+		&self.source.as_bytes()[self.pos..]
+	}
+}
+
+// a number: an optional minus, then digits with at most one dot among them, and at least one digit.
+// it ends at whitespace, a bracket or brace, a quote, a comment or the end of the source, so `1.2.3`,
+// `1-2` and `5abc` are each one malformed number
+fn is_number(text: &str) -> bool {
+	let digits = text.strip_prefix('-').unwrap_or(text);
 
-			tokens.push(Token::new(TokenType::Number(buffer.clone()), Loc {line, col: scol, filename: filename.to_string()}));
+	digits.bytes().any(|b| b.is_ascii_digit()) && digits.bytes().all(|b| b.is_ascii_digit() || b == b'.')
+		&& digits.bytes().filter(|b| *b == b'.').count() <= 1
+}
 
-			buffer.clear();
-		} else if c == '.' {
-			buffer += &c.to_string();
+// the digits of a legacy number, with one dot at most
+fn number_chars() -> impl FnMut(char) -> bool {
+	let mut found_dot = false;
 
-			let scol = col;
+	move |c| match c {
+		'.' if !found_dot => {
+			found_dot = true;
+			true
+		},
+		c => c.is_ascii_digit()
+	}
+}
 
-			while i + 1 < chars.len() {
-				i += 1;
-				col += 1;
+// the tokens of a source one at a time, ending with Eof, so a caller can use each before the next
+// is read. stops after an error
+pub struct Tokens<'a> {
+	source: &'a str,
+	filename: &'a str,
+	cursor: Cursor<'a>,
+	line: u64,
+	col: u64,
+	percent: u8,
+	progress: Option<&'a mut dyn FnMut(u8)>,
+	comments: Option<&'a mut Vec<Comment>>,
+	token_map: BTreeMap<char, TokenType>,
+	op_map: BTreeMap<&'static str, TokenType>,
+	max_tokens: usize, // not counting Eof
+	count: usize,
+	legacy_numbers: bool,
+	done: bool,
+}
 
-				if chars[i].is_ascii_digit() {
-					buffer += &chars[i].to_string();
-				} else {
-					i -= 1;
-					col -= 1;
-					break;
-				}
-			}
+pub fn tokens<'a>(char_str: &'a str, filename: &'a str) -> Tokens<'a> {
+	Tokens::new(char_str, filename, None, None)
+}
 
-			tokens.push(Token::new(TokenType::Number(buffer.clone()), Loc {line, col: scol, filename: filename.to_string()}));
+impl<'a> Tokens<'a> {
+	fn new(char_str: &'a str, filename: &'a str, progress: Option<&'a mut dyn FnMut(u8)>, comments: Option<&'a mut Vec<Comment>>) -> Tokens<'a> {
+		let token_map = BTreeMap::from([
+			('[', TokenType::LeftSquare),
+			(']', TokenType::RightSquare),
+			('{', TokenType::LeftCurly),
+			('}', TokenType::RightCurly),
+		]);
+
+		let op_map = BTreeMap::from([
+			("push", TokenType::Push),
+			("dup", TokenType::Dup),
+			("swap", TokenType::Swap),
+			("iload", TokenType::ILoad),
+			("load", TokenType::Load),
+			("drop", TokenType::Drop),
+			("query", TokenType::Query),
+			("info", TokenType::Info),
+			("if", TokenType::If),
+			("each", TokenType::Each),
+			("reduce", TokenType::Reduce),
+			("reverse", TokenType::Reverse),
+			("map", TokenType::Map),
+			("filter", TokenType::Filter),
+			("call", TokenType::Call),
+			("tostr", TokenType::ToStr),
+			("tonum", TokenType::ToNum),
+			("+", TokenType::Add),
+			("-", TokenType::Sub),
+			("*", TokenType::Mul),
+			("/", TokenType::Div),
+			("%", TokenType::Mod),
+			("=", TokenType::Eq),
+			("!=", TokenType::NotEq),
+			(">", TokenType::Greater),
+			(">=", TokenType::GreaterEq),
+			("<", TokenType::Less),
+			("<=", TokenType::LessEq),
+			("and", TokenType::And),
+			("or", TokenType::Or),
+			("not", TokenType::Not),
+			("concat", TokenType::Concat),
+			("match", TokenType::Match),
+			("split", TokenType::Split),
+			("iota", TokenType::Iota),
+			("print", TokenType::Print),
+			("readln", TokenType::ReadLine),
+			("assert", TokenType::Assert),
+			("assert_eq", TokenType::AssertEq),
+			("true", TokenType::Boolean(true)),
+			("false", TokenType::Boolean(false)),
+			("nil", TokenType::Nil)
+		]);
+
+		let mut tokens = Tokens {source: char_str, filename, cursor: Cursor {source: char_str, pos: 0}, line: 1, col: 1, percent: 0, progress, comments, token_map, op_map, max_tokens: usize::MAX, count: 0, legacy_numbers: false, done: false};
+
+		// shebang check
+		if char_str.starts_with("#!") {
+			let text = tokens.cursor.skip(memchr2(b'\r', b'\n', tokens.cursor.rest())); // consume until newline
+			tokens.comment(text);
+		}
 
-			buffer.clear();
-		} else if c == '-' {
-			if (i + 1 < chars.len()) && ((chars[i + 1] == '.') || chars[i + 1].is_ascii_digit()) {
-				buffer += &c.to_string();
+		tokens
+	}
 
-				let scol = col;
+	// numbers as they were read before they were strict: a number ends where its digits and first dot
+	// do, so `1.2.3` is 1.2 and .3, `1-2` is 1 and -2, and `5abc` is 5 and an identifier
+	pub fn legacy_numbers(mut self, on: bool) -> Tokens<'a> {
+		self.legacy_numbers = on;
+		self
+	}
 
-				let mut found_dot: bool = false;
+	// a strict number, running to the next delimiter
+	fn number(&mut self, start: usize) -> Result<Token, TokenizeError> {
+		let token_map = &self.token_map;
+		let count = self.cursor.take_while(|c| !c.is_whitespace() && c != '"' && c != ';' && !token_map.contains_key(&c));
+		let text = &self.source[start..self.cursor.pos];
 
-				while i + 1 < chars.len() {
-					i += 1;
-					col += 1;
+		if !is_number(text) {
+			return Err(TokenizeError::MalformedNumber {text: text.to_owned(), loc: self.here()});
+		}
 
-					if chars[i].is_ascii_digit() {
-						buffer += &chars[i].to_string();
-					} else if (chars[i] == '.') && !found_dot {
-						found_dot = true;
-						buffer += ".";
-					} else {
-						i -= 1;
-						col -= 1;
-						break;
-					}
-				}
+		let token = Token::new(TokenType::Number(text.to_owned()), self.here());
+		self.col += count;
+		Ok(token)
+	}
 
-				tokens.push(Token::new(TokenType::Number(buffer.clone()), Loc {line, col: scol, filename: filename.to_string()}));
+	fn here(&self) -> Loc {
+		Loc {line: self.line, col: self.col, filename: self.filename.to_string()}
+	}
 
-				buffer.clear();
-			} else {
-				buffer += &c.to_string();
+	fn comment(&mut self, text: &str) {
+		let loc = self.here();
 
-				let scol = col;
+		if let Some(comments) = self.comments.as_mut() {
+			comments.push(Comment {text: text.trim_end().to_owned(), loc});
+		}
+	}
 
-				while (i + 1 < chars.len()) && !chars[i + 1].is_whitespace() && (chars[i + 1] != '"') && !token_map.contains_key(&chars[i + 1]) {
-					i += 1;
-					col += 1;
+	// the next token before the end of the source
+	fn read(&mut self) -> Result<Option<Token>, TokenizeError> {
+		while let Some((i, c)) = self.cursor.next() {
+			if (c == '\r' || c == '\n') && (i * 100 / self.source.len()) as u8 != self.percent {
+				self.percent = (i * 100 / self.source.len()) as u8;
 
-					buffer += &chars[i].to_string();
+				if let Some(progress) = self.progress.as_mut() {
+					progress(self.percent);
 				}
+			}
 
-				if op_map.contains_key(&buffer) {
-					tokens.push(Token::new(op_map[&buffer].clone(), Loc {line, col: scol, filename: filename.to_string()}))
+			let next = self.cursor.peek();
+			let mut token = None;
+
+			if c == '\r' {
+				self.line += 1;
+				self.col = 0;
+				self.cursor.next_if(|c| c == '\n');
+			} else if c == '\n' {
+				self.line += 1;
+				self.col = 0;
+			} else if c == ';' {
+				self.cursor.skip(memchr2(b'\r', b'\n', self.cursor.rest())); // consume until newline
+				self.comment(&self.source[i..self.cursor.pos]);
+			} else if c.is_whitespace() {
+				self.col += self.cursor.skip_blanks();
+			} else if let Some(typ) = self.token_map.get(&c) {
+				token = Some(Token::new(typ.clone(), self.here()));
+			} else if c.is_ascii_digit() || (c == '-' && next.is_some_and(|c| (c == '.') || c.is_ascii_digit())) {
+				// number takes precendence over identifier because it isolates ascii digits
+				if self.legacy_numbers {
+					let count = self.cursor.take_while(number_chars());
+					token = Some(Token::new(TokenType::Number(self.source[i..self.cursor.pos].to_owned()), self.here()));
+					self.col += count;
 				} else {
-					return Err(format!("Unexpected identifier {} on {}", buffer, Loc {line: line, col: scol, filename: filename.to_string()}));
+					token = Some(self.number(i)?);
+				}
+			} else if c == '.' && next.is_some_and(|c| c.is_ascii_alphabetic()) {
+				let count = self.cursor.take_while(|c| c.is_ascii_alphanumeric() || c == '_');
+				token = Some(Token::new(TokenType::Directive(self.source[i..self.cursor.pos].to_owned()), self.here()));
+				self.col += count;
+			} else if c == '.' && !self.legacy_numbers {
+				token = Some(self.number(i)?);
+			} else if c == '.' {
+				let count = self.cursor.take_while(|c| c.is_ascii_digit());
+				token = Some(Token::new(TokenType::Number(self.source[i..self.cursor.pos].to_owned()), self.here()));
+				self.col += count;
+			} else if c == '"' {
+				let start = self.here();
+				let text = self.cursor.skip(memchr(b'"', self.cursor.rest()));
+
+				// the column goes on from the quote, or starts again after the last newline
+				match text.rfind('\n') {
+					Some(last) => {
+						self.line += memchr_iter(b'\n', text.as_bytes()).count() as u64;
+						self.col = text[last + 1..].chars().count() as u64;
+					},
+					None => self.col += text.chars().count() as u64
 				}
 
-				buffer.clear();
-			}
-		} else if c == '"' {
-			let sline = line;
-			let scol = col;
+				self.col += 1; // the final quote
+
+				if !self.cursor.next_if(|c| c == '"') {
+					return Err(TokenizeError::UnterminatedString {loc: start, end: self.here()});
+				}
+
+				token = Some(Token::new(TokenType::String(text.to_owned()), start));
+			} else {
+				let token_map = &self.token_map;
+				let count = self.cursor.take_while(|c| !c.is_whitespace() && (c != '"') && !token_map.contains_key(&c));
+				let word = &self.source[i..self.cursor.pos];
 
-			while (i + 1 < chars.len()) && (chars[i + 1] != '"') { //"
-				i += 1;
-				col += 1;
+				match self.op_map.get(word) {
+					Some(typ) => token = Some(Token::new(typ.clone(), self.here())),
+					None => return Err(TokenizeError::UnexpectedIdentifier {ident: word.to_owned(), loc: self.here()})
+				}
 
-				buffer += &chars[i].to_string();
+				self.col += count;
 			}
 
-			i += 1; // skip final quote
-			col += 1;
+			self.col += 1;
 
-			if i == chars.len() {
-				return Err(format!("Unterminated string starting on {}", Loc {line: sline, col: scol, filename: filename.to_string()}));
+			if token.is_some() {
+				return Ok(token);
 			}
+		}
 
-			tokens.push(Token::new(TokenType::String(buffer.clone()), Loc {line: sline, col: scol, filename: filename.to_string()}));
+		Ok(None)
+	}
+}
 
-			buffer.clear();
-		} else {
-			buffer += &c.to_string();
+impl Iterator for Tokens<'_> {
+	type Item = Result<Token, TokenizeError>;
 
-			let scol = col;
+	fn next(&mut self) -> Option<Self::Item> {
+		if self.done {
+			return None;
+		}
 
-			while (i + 1 < chars.len()) && !chars[i + 1].is_whitespace() && (chars[i + 1] != '"') && !token_map.contains_key(&chars[i + 1]) {
-				i += 1;
-				col += 1;
+		let token = self.read().transpose();
 
-				buffer += &chars[i].to_string();
-			}
+		match &token {
+			Some(Ok(token)) => {
+				self.count += 1;
 
-			if op_map.contains_key(&buffer) {
-				tokens.push(Token::new(op_map[&buffer].clone(), Loc {line, col: scol, filename: filename.to_string()}))
-			} else {
-				return Err(format!("Unexpected identifier {} on {}", buffer, Loc {line: line, col: scol, filename: filename.to_string()}));
-			}
+				if self.count > self.max_tokens {
+					self.done = true;
+					return Some(Err(TokenizeError::TooManyTokens {max: self.max_tokens, loc: token.loc.clone()}));
+				}
+			},
+			Some(Err(_)) => self.done = true,
+			None => {
+				self.done = true;
 
-			buffer.clear();
+				if let Some(progress) = self.progress.as_mut() {
+					progress(100);
+				}
+
+				return Some(Ok(Token::new(TokenType::Eof, self.here())));
+			}
 		}
 
-		i += 1;
+		token
 	}
+}
+
+// every tokenize function reads through here, so they share one span
+#[cfg_attr(feature = "tracing", tracing::instrument(name = "tokenize", level = "debug", skip_all, fields(filename, chars = char_str.len())))]
+fn read_tokens(char_str: &str, filename: &str, progress: &mut dyn FnMut(u8), comments: &mut Vec<Comment>, max_tokens: usize, legacy_numbers: bool) -> Result<Vec<Token>, TokenizeError> {
+	let mut tokens = Tokens::new(char_str, filename, Some(progress), Some(comments)).legacy_numbers(legacy_numbers);
+	tokens.max_tokens = max_tokens;
 
-	tokens.push(Token::new(TokenType::Eof, here!()));
+	let tokens: Vec<Token> = tokens.collect::<Result<_, _>>()?;
+	event!(DEBUG, tokens = tokens.len(), "tokenized");
 
 	Ok(tokens)
 }
@@ -0,0 +1,360 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use std::ptr;
+
+use thiserror::Error;
+
+use crate::cfg::register;
+use crate::depth::number;
+use crate::parser::{get_command_name, Command, Value};
+use crate::tokenizer::Loc;
+
+// value kinds, for `evm-asm --typecheck`: the kind of each value on the stack and in each register
+// where it is known, through the program from an empty stack. calls are followed as
+// --check-stack follows them, and a command given a kind it always fails on is an error where it
+// is sure to run. values the analysis cannot follow, as what the host returns or what a function it
+// cannot find leaves, have no kind, and pass any check
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Kind {
+	Nil,
+	Number,
+	String,
+	Boolean,
+	Function,
+	Array,
+}
+
+impl Kind {
+	fn of(value: &Value) -> Kind {
+		match value {
+			Value::Nil         => Kind::Nil,
+			Value::Number(_)   => Kind::Number,
+			Value::String(_)   => Kind::String,
+			Value::Boolean(_)  => Kind::Boolean,
+			Value::Function(_) => Kind::Function,
+			Value::Array(_)    => Kind::Array
+		}
+	}
+
+	// as the interpreter names them in its errors
+	pub fn name(self) -> &'static str {
+		match self {
+			Kind::Nil      => "nil",
+			Kind::Number   => "number",
+			Kind::String   => "string",
+			Kind::Boolean  => "boolean",
+			Kind::Function => "function",
+			Kind::Array    => "array"
+		}
+	}
+}
+
+impl fmt::Display for Kind {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		write!(f, "{}", self.name())
+	}
+}
+
+#[derive(Debug, Error)]
+pub enum TypeError {
+	#[error("Type error on {loc}: {command} expects {expected}, got {found}")]
+	Mismatch {command: &'static str, expected: &'static str, found: Kind, loc: Loc, called: Vec<Loc>},
+	#[error("Type error on {loc}: register {reg} holds {found}, not a function")]
+	NotAFunction {command: &'static str, reg: u8, found: Kind, loc: Loc, called: Vec<Loc>},
+}
+
+impl TypeError {
+	pub fn loc(&self) -> &Loc {
+		match self {
+			TypeError::Mismatch {loc, ..} | TypeError::NotAFunction {loc, ..} => loc
+		}
+	}
+
+	// the calls that led to the command, innermost first
+	pub fn called(&self) -> &[Loc] {
+		match self {
+			TypeError::Mismatch {called, ..} | TypeError::NotAFunction {called, ..} => called
+		}
+	}
+
+	pub fn command(&self) -> &'static str {
+		match self {
+			TypeError::Mismatch {command, ..} | TypeError::NotAFunction {command, ..} => command
+		}
+	}
+}
+
+#[derive(Clone, Default)]
+struct State<'a> {
+	stack: Vec<Option<Kind>>, // the top of the stack; values below it have no known kind
+	kinds: BTreeMap<u8, Kind>,
+	functions: BTreeMap<u8, &'a [Command]>,
+}
+
+impl<'a> State<'a> {
+	fn pop(&mut self) -> Option<Kind> {
+		self.stack.pop().flatten()
+	}
+
+	fn push(&mut self, kind: Option<Kind>) {
+		self.stack.push(kind);
+	}
+
+	// what holds on either of two paths: the stacks are matched from the top
+	fn join(&self, other: &State<'a>) -> State<'a> {
+		let len = self.stack.len().min(other.stack.len());
+		let (ours, theirs) = (&self.stack[self.stack.len() - len..], &other.stack[other.stack.len() - len..]);
+
+		State {
+			stack: ours.iter().zip(theirs).map(|(a, b)| a.filter(|_| a == b)).collect(),
+			kinds: self.kinds.iter().filter(|(reg, kind)| other.kinds.get(reg) == Some(kind)).map(|(reg, kind)| (*reg, *kind)).collect(),
+			functions: self.functions.iter().filter(|(reg, body)| other.functions.get(reg).is_some_and(|other| ptr::eq(*other, **body))).map(|(reg, body)| (*reg, *body)).collect()
+		}
+	}
+
+	fn same(&self, other: &State<'a>) -> bool {
+		self.stack == other.stack && self.kinds == other.kinds && self.functions.len() == other.functions.len()
+			&& self.functions.iter().zip(&other.functions).all(|((a, x), (b, y))| a == b && ptr::eq(*x, *y))
+	}
+}
+
+struct Checker<'a> {
+	locs: &'a [Loc],
+	ids: BTreeMap<*const Command, usize>, // index in `locs` of each command
+	calling: Vec<(&'a [Command], &'a Command)>, // functions being followed, so recursion is not, and the commands that called them
+}
+
+impl<'a> Checker<'a> {
+	fn loc(&self, command: &Command) -> Loc {
+		self.locs.get(self.ids[&(command as *const Command)]).cloned().unwrap_or_else(|| Loc {line: 0, col: 0, filename: String::new()})
+	}
+
+	fn called(&self) -> Vec<Loc> {
+		self.calling.iter().rev().map(|(_, command)| self.loc(command)).collect()
+	}
+
+	// `found` must be one of `kinds`, unless it is unknown or the command may not run
+	fn expect(&self, command: &Command, certain: bool, found: Option<Kind>, kinds: &[Kind], expected: &'static str) -> Result<(), TypeError> {
+		match found {
+			Some(found) if certain && !kinds.contains(&found) => Err(TypeError::Mismatch {command: get_command_name(command), expected, found, loc: self.loc(command), called: self.called()}),
+			_ => Ok(())
+		}
+	}
+
+	// the register a calling command pops, which must hold a function
+	fn function(&self, commands: &'a [Command], i: usize, state: &mut State<'a>, certain: bool) -> Result<Option<u8>, TypeError> {
+		let command = &commands[i];
+		let found = state.pop();
+		self.expect(command, certain, found, &[Kind::Number], "number")?;
+
+		let reg = register(commands, i);
+
+		match reg.and_then(|reg| Some((reg, *state.kinds.get(&reg)?))) {
+			Some((reg, found)) if certain && found != Kind::Function => Err(TypeError::NotAFunction {command: get_command_name(command), reg, found, loc: self.loc(command), called: self.called()}),
+			_ => Ok(reg)
+		}
+	}
+
+	fn run(&mut self, commands: &'a [Command], state: &mut State<'a>, certain: bool) -> Result<(), TypeError> {
+		for (i, command) in commands.iter().enumerate() {
+			match command {
+				Command::Push(value) => state.push(Some(Kind::of(value))),
+				Command::ILoad(reg, value) => {
+					// an iload that may not have run leaves either value in the register
+					if certain {
+						state.kinds.insert(*reg, Kind::of(value));
+					} else {
+						state.kinds.remove(reg);
+					}
+
+					match value {
+						Value::Function(body) if certain => state.functions.insert(*reg, body),
+						_ => state.functions.remove(reg)
+					};
+				},
+				Command::Dup => {
+					let kind = state.pop();
+					state.push(kind);
+					state.push(kind);
+				},
+				Command::Swap => {
+					let b = state.pop();
+					let a = state.pop();
+					state.push(b);
+					state.push(a);
+				},
+				Command::Load => {
+					let found = state.pop();
+					self.expect(command, certain, found, &[Kind::Number], "number")?;
+					let kind = register(commands, i).and_then(|reg| state.kinds.get(&reg).copied());
+					state.push(kind);
+				},
+				Command::Drop | Command::Print | Command::Assert => {
+					state.pop();
+				},
+				Command::AssertEq => {
+					state.pop();
+					state.pop();
+				},
+				Command::Query => {
+					let found = state.pop();
+					self.expect(command, certain, found, &[Kind::String], "string")?;
+					state.push(None);
+				},
+				Command::Info => {
+					let found = state.pop();
+					self.expect(command, certain, found, &[Kind::String], "string")?;
+					state.pop();
+					state.push(None);
+				},
+				Command::If => {
+					let reg = self.function(commands, i, state, certain)?;
+					state.pop();
+
+					let mut called = state.clone();
+					self.call(command, reg, &mut called, false)?;
+					*state = state.join(&called);
+				},
+				Command::Call => {
+					let reg = self.function(commands, i, state, certain)?;
+					self.call(command, reg, state, certain)?;
+				},
+				Command::Each | Command::Map | Command::Filter | Command::Reduce => {
+					let initial = matches!(command, Command::Reduce).then(|| state.pop());
+					let reg = self.function(commands, i, state, certain)?;
+					let found = state.pop();
+					self.expect(command, certain, found, &[Kind::Array], "array")?;
+
+					if let Some(initial) = initial {
+						state.push(initial);
+					}
+
+					// the body runs any number of times, on items of any kind, until nothing changes
+					loop {
+						let mut called = state.clone();
+						called.push(None);
+						self.call(command, reg, &mut called, false)?;
+
+						if matches!(command, Command::Map | Command::Filter) {
+							called.pop();
+						}
+
+						let joined = state.join(&called);
+
+						if joined.same(state) {
+							break;
+						}
+
+						*state = joined;
+					}
+
+					if matches!(command, Command::Map | Command::Filter) {
+						state.push(Some(Kind::Array));
+					}
+				},
+				Command::Reverse => {
+					let found = state.pop();
+					self.expect(command, certain, found, &[Kind::Array, Kind::String], "array or string")?;
+					state.push(found);
+				},
+				Command::ToStr => {
+					state.pop();
+					state.push(Some(Kind::String));
+				},
+				Command::ToNum => {
+					// a string that is not a number becomes nil
+					let kind = match state.pop() {
+						Some(Kind::Number | Kind::Boolean) => Some(Kind::Number),
+						Some(Kind::Nil | Kind::Function | Kind::Array) => Some(Kind::Nil),
+						Some(Kind::String) | None => None
+					};
+					state.push(kind);
+				},
+				Command::Add | Command::Sub | Command::Mul | Command::Div | Command::Mod => {
+					for _ in 0..2 {
+						let found = state.pop();
+						self.expect(command, certain, found, &[Kind::Number], "number")?;
+					}
+					state.push(Some(Kind::Number));
+				},
+				Command::Eq | Command::NotEq => {
+					state.pop();
+					state.pop();
+					state.push(Some(Kind::Boolean));
+				},
+				Command::Greater | Command::GreaterEq | Command::Less | Command::LessEq => {
+					self.pair(command, certain, state, &[Kind::Number, Kind::String], "number or string")?;
+					state.push(Some(Kind::Boolean));
+				},
+				Command::And | Command::Or | Command::Not => {
+					for _ in 0..if matches!(command, Command::Not) { 1 } else { 2 } {
+						let found = state.pop();
+						self.expect(command, certain, found, &[Kind::Boolean], "boolean")?;
+					}
+					state.push(Some(Kind::Boolean));
+				},
+				Command::Concat => {
+					let kind = self.pair(command, certain, state, &[Kind::String, Kind::Array], "string or array")?;
+					state.push(kind);
+				},
+				Command::Match | Command::Split => {
+					for _ in 0..2 {
+						let found = state.pop();
+						self.expect(command, certain, found, &[Kind::String], "string")?;
+					}
+					state.push(Some(if matches!(command, Command::Match) { Kind::Boolean } else { Kind::Array }));
+				},
+				Command::ReadLine => state.push(None),
+				Command::Iota => {
+					let found = state.pop();
+					self.expect(command, certain, found, &[Kind::Number], "number")?;
+					state.push(Some(Kind::Array));
+				}
+			}
+		}
+
+		Ok(())
+	}
+
+	// two operands of one of `kinds`, and the same one; returns it where known
+	fn pair(&self, command: &Command, certain: bool, state: &mut State<'a>, kinds: &[Kind], expected: &'static str) -> Result<Option<Kind>, TypeError> {
+		let b = state.pop();
+		self.expect(command, certain, b, kinds, expected)?;
+		let a = state.pop();
+		self.expect(command, certain, a, kinds, expected)?;
+
+		if let (Some(a), Some(b)) = (a, b) {
+			self.expect(command, certain, Some(b), &[a], a.name())?;
+		}
+
+		Ok(a.or(b))
+	}
+
+	// runs the function in `reg` on `state`
+	fn call(&mut self, command: &'a Command, reg: Option<u8>, state: &mut State<'a>, certain: bool) -> Result<(), TypeError> {
+		match reg.and_then(|reg| state.functions.get(&reg).copied()) {
+			Some(body) if !self.calling.iter().any(|(calling, _)| ptr::eq(*calling, body)) => {
+				self.calling.push((body, command));
+				let result = self.run(body, state, certain);
+				self.calling.pop();
+				result
+			},
+			_ => {
+				// a function that is not followed may leave and iload anything
+				*state = State::default();
+				Ok(())
+			}
+		}
+	}
+}
+
+// `locs` are the locations of the commands, as parse_with_locs gives them; returns the first error,
+// as the program would stop there
+pub fn check(commands: &[Command], locs: &[Loc]) -> Result<(), TypeError> {
+	let mut ids = BTreeMap::new();
+	number(commands, &mut ids);
+
+	let mut checker = Checker {locs, ids, calling: vec![]};
+	checker.run(commands, &mut State::default(), true)
+}
@@ -0,0 +1,24 @@
+use wasm_bindgen::prelude::*;
+
+use crate::diagnostic::{self, Diagnostic, Format};
+use crate::{disasm, printer, AssembleOptions};
+
+// the library's entry points for JavaScript, for the in-browser playground; errors are thrown as
+// rendered diagnostics, and `diagnostics` returns them as JSON for the editor to mark up
+
+#[wasm_bindgen]
+pub fn assemble(source: &str) -> Result<Vec<u8>, JsError> {
+	crate::assemble(source, &AssembleOptions::default()).map_err(|e| JsError::new(&Diagnostic::from(&e).render_with_source(source)))
+}
+
+#[wasm_bindgen]
+pub fn disassemble(bytecode: &[u8]) -> Result<String, JsError> {
+	disasm::disassemble(bytecode).map(|commands| printer::print(&commands)).map_err(|e| JsError::new(&Diagnostic::from(&e).render()))
+}
+
+// a JSON array in the format of --message-format json, empty when the source assembles
+#[wasm_bindgen]
+pub fn diagnostics(source: &str) -> String {
+	let found: Vec<Diagnostic> = crate::assemble(source, &AssembleOptions::default()).err().iter().map(Diagnostic::from).collect();
+	diagnostic::emit(&found, Format::Json, None)
+}
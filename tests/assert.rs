@@ -0,0 +1,34 @@
+use evm_asm::ast::Command;
+use evm_asm::vm::{lower, Value, Vm};
+use evm_asm::{codegen, disasm, parse, tokenize};
+
+fn run(source: &str) -> Result<Vec<String>, String> {
+	Vm::default().load(lower(&parse(tokenize(source, "<test>").unwrap()).unwrap())).run()
+		.map(|stack| stack.iter().map(Value::to_string).collect())
+		.map_err(|e| e.message)
+}
+
+#[test]
+fn assert_pops_a_truthy_value() {
+	assert_eq!(run("push 1 push true assert push 1 assert push \"\" assert").unwrap(), ["1"]);
+
+	assert_eq!(run("push false assert").unwrap_err(), "Assertion failed: got false");
+	assert_eq!(run("push nil assert").unwrap_err(), "Assertion failed: got nil");
+	assert_eq!(run("assert").unwrap_err(), "Stack underflow");
+}
+
+#[test]
+fn assert_eq_compares_two_values() {
+	assert_eq!(run("push [1 \"a\"] push [1 \"a\"] assert_eq push 2 push 1 push 1 + assert_eq").unwrap(), Vec::<String>::new());
+
+	assert_eq!(run("push 3 push 1 push 1 + assert_eq").unwrap_err(), "Assertion failed: expected 2, got 3");
+	assert_eq!(run("push 1 push \"1\" assert_eq").unwrap_err(), "Assertion failed: expected \"1\", got 1");
+}
+
+#[test]
+fn asserts_are_assembled() {
+	let commands = parse(tokenize("push true assert push 1 push 1 assert_eq", "<test>").unwrap()).unwrap();
+
+	assert!(commands[1] == Command::Assert && commands[4] == Command::AssertEq);
+	assert!(disasm::disassemble(&codegen::generate(commands.clone())).ok() == Some(commands));
+}
@@ -0,0 +1,75 @@
+use std::process::Command as Process;
+use std::{env, fs};
+
+use serde_json::json;
+
+use evm_asm::ast::json::{program_from_json, program_from_str, program_to_json, AstJsonError};
+use evm_asm::ast::parse_with_locs;
+use evm_asm::lexer::Loc;
+use evm_asm::tokenize;
+
+const PROGRAM: &str = "iload 3 {push [1 \"a\" nil] drop}\npush true push 3 call\n";
+
+fn positions(locs: &[Loc]) -> Vec<(u64, u64, &str)> {
+	locs.iter().map(|loc| (loc.line, loc.col, loc.filename.as_str())).collect()
+}
+
+fn error(json: serde_json::Value) -> String {
+	program_from_json(&json, "gen.json").err().unwrap().to_string()
+}
+
+#[test]
+fn programs_read_back_as_written() {
+	let (commands, locs) = parse_with_locs(tokenize(PROGRAM, "a.asm").unwrap()).unwrap();
+	let (read, read_locs) = program_from_str(&program_to_json(&commands, &locs).to_string(), "other.json").unwrap();
+
+	assert_eq!(read, commands);
+	assert_eq!(positions(&read_locs), positions(&locs));
+
+	// without spans, commands are placed at line 0 of the file being read
+	let (read, read_locs) = program_from_json(&program_to_json(&commands, &[]), "gen.json").unwrap();
+	assert_eq!(read, commands);
+	assert_eq!(positions(&read_locs)[..2], [(0, 0, "gen.json"), (0, 0, "gen.json")]);
+	assert_eq!(read_locs.len(), locs.len());
+}
+
+#[test]
+fn bare_arrays_are_version_one() {
+	let (commands, _) = program_from_json(&json!([{"op": "push", "value": {"type": "number", "value": 1}}, {"op": "drop"}]), "gen.json").unwrap();
+
+	assert_eq!(commands, parse_with_locs(tokenize("push 1 drop", "a.asm").unwrap()).unwrap().0);
+}
+
+#[test]
+fn errors_give_the_path_to_the_problem() {
+	assert_eq!(error(json!({"version": 2, "commands": []})), "Unsupported ast-json version 2, this assembler reads version 1");
+	assert_eq!(error(json!({"commands": []})), "Missing field `version` at program");
+	assert_eq!(error(json!([{"op": "jump"}])), "Unknown op `jump` at commands[0]");
+	assert_eq!(error(json!([{"op": "drop"}, {"op": "push"}])), "Missing field `value` at commands[1]");
+	assert_eq!(error(json!([{"op": "iload", "register": 300, "value": {"type": "nil"}}])), "`register` is not a register number at commands[0]");
+	assert_eq!(error(json!([{"op": "push", "value": {"type": "array", "values": [{"type": "set"}]}}])), "Unknown value type `set` at commands[0].value.values[0]");
+	assert_eq!(error(json!([{"op": "push", "value": {"type": "function", "body": [{"op": 1}]}}])), "`op` is not a string at commands[0].value.body[0]");
+
+	assert!(matches!(program_from_str("[", "gen.json"), Err(AstJsonError::Json(_))));
+}
+
+#[test]
+fn the_cli_assembles_ast_json() {
+	let dir = env::temp_dir().join(format!("evm-asm-ast-json-{}", std::process::id()));
+	fs::create_dir_all(&dir).unwrap();
+
+	let (commands, locs) = parse_with_locs(tokenize(PROGRAM, "a.asm").unwrap()).unwrap();
+	fs::write(dir.join("a.asm"), PROGRAM).unwrap();
+	fs::write(dir.join("a.json"), program_to_json(&commands, &locs).to_string()).unwrap();
+
+	// -O0, so both keep every command rather than optimizing the program down to nothing
+	let assemble = |args: &[&str]| Process::new(env!("CARGO_BIN_EXE_evm-asm")).arg("-O0").args(args).current_dir(&dir).output().unwrap();
+	let from_asm = assemble(&["a.asm"]);
+	let from_json = assemble(&["--input-format", "ast-json", "a.json"]);
+
+	assert!(from_json.status.success());
+	assert_eq!(from_json.stdout, from_asm.stdout);
+
+	let invalid = assemble(&["--input-format", "ast-json", "a.asm"]);
+	assert_eq!(invalid.status.code(), Some(65));
+}
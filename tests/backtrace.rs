@@ -0,0 +1,37 @@
+use evm_asm::ast::parse_with_locs;
+use evm_asm::vm::{lower, RuntimeError, Vm};
+use evm_asm::tokenize;
+
+const PROGRAM: &str = "\
+iload 0 {
+	drop drop
+}
+iload 1 {
+	push 0 call
+}
+push [1] push 1 map
+";
+
+fn fail(source: &str) -> RuntimeError {
+	let (commands, _) = parse_with_locs(tokenize(source, "<test>").unwrap()).unwrap();
+	Vm::default().load(lower(&commands)).run().err().unwrap()
+}
+
+#[test]
+fn backtraces_list_the_callers() {
+	let error = fail(PROGRAM);
+
+	assert_eq!((error.command, error.backtrace), (2, vec![5, 8]));
+	assert!(fail("push 1 drop drop").backtrace.is_empty());
+	assert_eq!(fail("iload 0 {drop drop} push [1 2] push 0 each").backtrace, [5]);
+}
+
+#[test]
+fn reports_point_at_every_caller() {
+	let (_, locs) = parse_with_locs(tokenize(PROGRAM, "<test>").unwrap()).unwrap();
+	let report = fail(PROGRAM).report(&locs, Some(PROGRAM));
+
+	assert!(report.contains("<test>:2:7"));
+	assert!(report.contains("5 | \tpush 0 call\n  | \t       - called from here"));
+	assert!(report.contains("7 | push [1] push 1 map\n  |                 - called from here"));
+}
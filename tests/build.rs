@@ -0,0 +1,47 @@
+use std::fs;
+use std::path::PathBuf;
+
+use evm_asm::build::{assemble_dir, BuildError, MODULE};
+use evm_asm::{assemble, AssembleOptions};
+
+fn dir(name: &str, files: &[(&str, &str)]) -> PathBuf {
+	let dir = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join(name);
+	let _ = fs::remove_dir_all(&dir);
+	fs::create_dir_all(&dir).unwrap();
+
+	for (file, source) in files {
+		fs::write(dir.join(file), source).unwrap();
+	}
+
+	dir
+}
+
+#[test]
+fn generates_a_const_per_file() {
+	let asm = dir("build-asm", &[("hello-world.evmasm", "push \"hello\" print\n"), ("2d.evmasm", "push 1 push 2 +\n"), ("notes.txt", "not assembled")]);
+	let out = assemble_dir(&asm, &asm).unwrap();
+	let module = fs::read_to_string(&out).unwrap();
+
+	assert_eq!(out, asm.join(MODULE));
+	assert!(module.contains("pub const HELLO_WORLD: &[u8] = &["));
+	assert!(module.contains("pub const _2D: &[u8] = &["));
+	assert!(!module.contains("NOTES"));
+
+	// the bytes are those assemble returns
+	let bytes = assemble("push 1 push 2 +\n", &AssembleOptions::default()).unwrap();
+	let listed: Vec<String> = bytes.iter().map(|byte| format!("0x{:02x},", byte)).collect();
+	assert!(module.contains(&listed.join(" ")));
+}
+
+#[test]
+fn reports_errors_with_the_file() {
+	let asm = dir("build-asm-error", &[("bad.evmasm", "push [1\n")]);
+
+	match assemble_dir(&asm, &asm) {
+		Err(BuildError::Assemble {path, message}) => {
+			assert_eq!(path, asm.join("bad.evmasm"));
+			assert!(message.contains("bad.evmasm:"), "{}", message);
+		},
+		other => panic!("{:?}", other)
+	}
+}
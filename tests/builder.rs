@@ -0,0 +1,55 @@
+use evm_asm::ast::{Command, ProgramBuilder, Value};
+use evm_asm::codegen::{CodegenError, MAX_NESTING};
+use evm_asm::{parse, tokenize};
+
+fn parsed(source: &str) -> Vec<Command> {
+	parse(tokenize(source, "<test>").unwrap()).unwrap()
+}
+
+#[test]
+fn builds_what_the_source_would_parse_to() {
+	let built = ProgramBuilder::new()
+		.iload_function(0, |f| f.push(Value::Number(1.0)).add())
+		.push(Value::Number(2.0))
+		.push(Value::Number(0.0))
+		.call()
+		.function(|f| f.dup().drop())
+		.iload(1, Value::String("x".to_owned()))
+		.build()
+		.unwrap();
+
+	assert_eq!(built, parsed("iload 0 {push 1 +} push 2 push 0 call push {dup drop} iload 1 \"x\""));
+}
+
+#[test]
+fn every_command_has_a_method() {
+	let built = ProgramBuilder::new()
+		.dup().swap().load().drop().query().info().if_().each().reduce().reverse().map().filter().call()
+		.to_str().to_num().add().sub().mul().div().modulo().eq().not_eq().greater().greater_eq().less().less_eq()
+		.and().or().not().concat().match_().split().iota().print().read_line().assert().assert_eq()
+		.command(Command::Push(Value::Nil))
+		.build()
+		.unwrap();
+
+	let source = "dup swap load drop query info if each reduce reverse map filter call tostr tonum + - * / % = != > >= < <= \
+		and or not concat match split iota print readln assert assert_eq push nil";
+
+	assert_eq!(built, parsed(source));
+}
+
+#[test]
+fn programs_are_checked_like_parsed_source() {
+	let register = ProgramBuilder::new().iload(16, Value::Nil).build();
+	assert!(matches!(register, Err(CodegenError::InvalidRegister(16))));
+
+	// the same checks reach into function bodies
+	let nested = ProgramBuilder::new().function(|f| f.iload(20, Value::Nil)).build();
+	assert!(matches!(nested, Err(CodegenError::InvalidRegister(20))));
+
+	let mut deep = Value::Nil;
+	for _ in 0..=MAX_NESTING {
+		deep = Value::Array(vec![deep]);
+	}
+
+	assert!(matches!(ProgramBuilder::new().push(deep).build(), Err(CodegenError::NestingTooDeep {..})));
+}
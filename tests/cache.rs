@@ -0,0 +1,83 @@
+use std::fs;
+use std::path::PathBuf;
+
+use evm_asm::cache::Cache;
+use evm_asm::lexer::TokenizeError;
+use evm_asm::{assemble, assemble_file, parse, tokenize, AssembleOptions, Error, InputLimits, Keep};
+
+fn cache(name: &str) -> (PathBuf, AssembleOptions) {
+	let dir = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join(name);
+	let _ = fs::remove_dir_all(&dir);
+	(dir.clone(), AssembleOptions {cache: Some(Cache::new(dir)), ..AssembleOptions::default()})
+}
+
+#[test]
+fn reads_back_unchanged_sources() {
+	let (dir, options) = cache("cache-hit");
+	let source = "push 1 push 2 +\n";
+	let bytecode = assemble(source, &options).unwrap();
+
+	assert_eq!(bytecode, assemble(source, &AssembleOptions::default()).unwrap());
+
+	// what is in the cache is what comes back, without assembling
+	let key = Cache::key(source, &options.optimizer);
+	fs::write(dir.join(&key).with_extension("bin"), [0xff]).unwrap();
+	assert_eq!(assemble(source, &options).unwrap(), [0xff]);
+
+	// another source or other optimizer options are assembled again
+	assert_eq!(assemble("push 3\n", &options).unwrap(), assemble("push 3\n", &AssembleOptions::default()).unwrap());
+	let mut unoptimized = options.clone();
+	unoptimized.optimizer.set("0").unwrap();
+	assert_ne!(Cache::key(source, &unoptimized.optimizer), key);
+	assert_eq!(fs::read_dir(&dir).unwrap().count(), 2);
+}
+
+#[test]
+fn keeps_only_programs_that_assemble() {
+	let (dir, options) = cache("cache-error");
+
+	assert!(assemble("push [", &options).is_err());
+	assert!(assemble("push [", &options).is_err());
+	assert!(!dir.exists());
+
+	let options = AssembleOptions {max_size: Some(2), ..options};
+	assert!(assemble("push 1\n", &options).is_err());
+	assert!(assemble("push 1\n", &options).is_err());
+}
+
+#[test]
+fn limits_keep_programs_out() {
+	let (dir, options) = cache("cache-limits");
+	let source = "push 1 push 2 +\n";
+	assert!(assemble(source, &options).is_ok());
+
+	// a program cached without limits must still be checked against them
+	let limited = AssembleOptions {limits: InputLimits {max_tokens: Some(2), ..InputLimits::default()}, ..options.clone()};
+	assert!(matches!(assemble(source, &limited), Err(Error::Tokenize(TokenizeError::TooManyTokens {..}))));
+
+	// and what assembles within them is not kept
+	let limited = AssembleOptions {limits: InputLimits {max_nodes: Some(10), ..InputLimits::default()}, ..options};
+	assert!(assemble("push 3\n", &limited).is_ok());
+	assert_eq!(fs::read_dir(&dir).unwrap().count(), 1);
+}
+
+#[test]
+fn cached_programs_are_kept_as_asked() {
+	let (_, options) = cache("cache-keep");
+	let source = "iload 0 {push 1 +} push 2 push 0 call\n";
+	let optimized = AssembleOptions {keep: Keep::Optimized, ..options.clone()};
+	let first = assemble_file(source, "a.asm", &optimized).unwrap();
+
+	assert!(first.written.is_none());
+	assert!(!first.report.is_empty());
+
+	// read back, the optimized program comes from the bytecode
+	let cached = assemble_file(source, "a.asm", &optimized).unwrap();
+	assert_eq!(cached.commands, first.commands);
+	assert!(cached.report.is_empty());
+
+	// the program as written is not in the cache, so it is assembled again
+	let written = assemble_file(source, "a.asm", &AssembleOptions {keep: Keep::Written, ..options}).unwrap();
+	assert_eq!(written.written.unwrap().0, parse(tokenize(source, "a.asm").unwrap()).unwrap());
+	assert_eq!(written.bytecode, first.bytecode);
+}
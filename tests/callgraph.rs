@@ -0,0 +1,64 @@
+use serde_json::json;
+
+use evm_asm::ast::parse;
+use evm_asm::callgraph::{call_graph, CallGraph};
+use evm_asm::lexer::tokenize;
+
+fn graph(source: &str) -> CallGraph {
+	call_graph(&parse(tokenize(source, "a.asm").unwrap()).unwrap())
+}
+
+const PROGRAM: &str = "iload 0 {push 1 call}
+iload 1 {dup push 0 if}
+iload 2 {push 2 call push 2 call}
+iload 3 {push [{push 2 call}]}
+push 0 call
+push 1 push 1 + call
+push 5 call
+";
+
+#[test]
+fn edges_and_cycles() {
+	let graph = graph(PROGRAM);
+
+	assert_eq!(graph.functions, [0, 1, 2, 3]);
+	assert_eq!(graph.cycles(), [vec![0, 1], vec![2]]);
+	assert_eq!(graph.to_json(), json!({
+		"nodes": [
+			{"id": "program", "unresolved": 2},
+			{"id": "r0", "register": 0, "unresolved": 0},
+			{"id": "r1", "register": 1, "unresolved": 0},
+			{"id": "r2", "register": 2, "unresolved": 0},
+			{"id": "r3", "register": 3, "unresolved": 0}
+		],
+		"edges": [
+			{"from": "r0", "to": "r1", "via": "call"},
+			{"from": "r1", "to": "r0", "via": "if"},
+			{"from": "r2", "to": "r2", "via": "call"},
+			{"from": "r3", "to": "r2", "via": "call"},
+			{"from": "program", "to": "r0", "via": "call"}
+		],
+		"cycles": [["r0", "r1"], ["r2"]]
+	}));
+}
+
+#[test]
+fn dot_marks_cycles() {
+	let dot = graph(PROGRAM).dot();
+
+	assert!(dot.starts_with("digraph calls {\n"));
+	assert!(dot.contains("\tr0 [label=\"register 0\", color=red];\n"));
+	assert!(dot.contains("\tr3 [label=\"register 3\"];\n"));
+	assert!(dot.contains("\tr1 -> r0 [label=\"if\", color=red];\n"));
+	assert!(dot.contains("\tr3 -> r2 [label=\"call\"];\n"));
+	assert!(dot.contains("\tprogram -> r0 [label=\"call\"];\n"));
+}
+
+#[test]
+fn reduce_takes_the_register_below_the_initial_value() {
+	let graph = graph("iload 4 {+}\npush [1 2] push 4 push 0 reduce\n");
+
+	assert_eq!(graph.calls.len(), 1);
+	assert_eq!(graph.calls[0].to, 4);
+	assert!(graph.cycles().is_empty());
+}
@@ -0,0 +1,46 @@
+use evm_asm::ast::parse;
+use evm_asm::cfg::dot;
+use evm_asm::lexer::tokenize;
+
+fn graph(source: &str) -> String {
+	dot(&parse(tokenize(source, "a.asm").unwrap()).unwrap())
+}
+
+#[test]
+fn blocks_end_at_calls() {
+	let graph = graph("iload 0 {push 1 +}\npush 2 push 0 call\npush 1 push 0 push 0 reduce\nprint\n");
+
+	assert!(graph.starts_with("digraph cfg {\n"));
+	assert!(graph.contains("\t\tsubgraph cluster_1 {\n\t\t\tlabel=\"register 0\";\n\t\t\tb1 [label=\"push 1\\l+\\l\"];\n\t\t}\n"));
+	assert!(graph.contains("\t\tb0 [label=\"iload 0 {...}\\lpush 2\\lpush 0\\lcall\\l\"];\n"));
+	assert!(graph.contains("\t\tb2 [label=\"push 1\\lpush 0\\lpush 0\\lreduce\\l\"];\n"));
+	assert!(graph.contains("\t\tb3 [label=\"print\\l\"];\n"));
+	assert!(graph.contains("\tb0 -> b2;\n\tb2 -> b3;\n"));
+	assert!(graph.contains("\tb0 -> b1 [style=dashed, label=\"call\"];\n"));
+	assert!(graph.contains("\tb2 -> b1 [style=dashed, label=\"reduce\"];\n"));
+}
+
+#[test]
+fn nested_and_anonymous_functions() {
+	let graph = graph("iload 0 {\n\tiload 1 {}\n\tpush 1 call\n}\npush {push \"a\nb\"}\n");
+
+	assert!(graph.contains("label=\"register 1\";\n\t\t\t\tb2 [label=\"(empty)\\l\"];"));
+	assert!(graph.contains("label=\"function\";"));
+	assert!(graph.contains("push \\\"a\\nb\\\"\\l"));
+	assert!(graph.contains("\tb1 -> b2 [style=dashed, label=\"call\"];\n"));
+}
+
+#[test]
+fn computed_registers_have_no_edge() {
+	let graph = graph("iload 0 {}\npush 1 push 1 - call\n");
+
+	assert!(!graph.contains("style=dashed"));
+}
+
+#[test]
+fn blocks_after_a_failing_call_are_grey() {
+	let graph = graph("iload 0 {push false assert}\npush 0 call\nprint\n");
+
+	assert!(graph.contains("b2 [label=\"print\\l\", color=grey, fontcolor=grey];\n"));
+	assert!(!graph.contains("b0 -> b2"));
+}
@@ -0,0 +1,105 @@
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::io::Write;
+use std::process::{Command as Process, Stdio};
+use std::rc::Rc;
+
+use evm_asm::ast::{Command, Value as AstValue};
+use evm_asm::vm::{lower, Host, StubHost, Value, Vm};
+use evm_asm::{codegen, disasm, parse, tokenize};
+
+// reads from `input` and keeps what is printed in `output`
+#[derive(Default)]
+struct Console {
+	input: VecDeque<String>,
+	output: Rc<RefCell<Vec<String>>>,
+}
+
+impl Host for Console {
+	fn query(&mut self, selector: &str) -> Result<Value, String> {
+		StubHost.query(selector)
+	}
+
+	fn info(&mut self, item: &Value, key: &str) -> Result<Value, String> {
+		StubHost.info(item, key)
+	}
+
+	fn print(&mut self, text: &str) -> Result<(), String> {
+		self.output.borrow_mut().push(text.to_owned());
+		Ok(())
+	}
+
+	fn read_line(&mut self) -> Result<Option<String>, String> {
+		Ok(self.input.pop_front())
+	}
+}
+
+fn run(host: impl Host + 'static, source: &str) -> Result<Vec<String>, String> {
+	Vm::new(host).load(lower(&parse(tokenize(source, "<test>").unwrap()).unwrap())).run()
+		.map(|stack| stack.iter().map(Value::to_string).collect())
+		.map_err(|e| e.message)
+}
+
+#[test]
+fn print_and_readln_use_the_host() {
+	let output = Rc::default();
+	let console = Console {input: ["a".into(), "b".into()].into(), output: Rc::clone(&output)};
+
+	let stack = run(console, "readln print push \"x\" print push [1 \"y\"] print readln readln").unwrap();
+
+	assert_eq!(*output.borrow(), ["a", "x", "[1 \"y\"]"]);
+	assert_eq!(stack, ["\"b\"", "nil"]);
+}
+
+#[test]
+fn hosts_without_a_console_refuse() {
+	assert_eq!(run(StubHost, "push 1 print").unwrap_err(), "Console output is not available on this host");
+	assert_eq!(run(StubHost, "readln").unwrap_err(), "Console input is not available on this host");
+}
+
+#[test]
+fn console_io_is_found_anywhere_in_the_program() {
+	let find = |source: &str| codegen::find_console_io(&parse(tokenize(source, "<test>").unwrap()).unwrap()).cloned();
+
+	assert!(find("push 1 drop").is_none());
+	assert!(find("push 1 print") == Some(Command::Print));
+	assert!(find("push [{push 1 drop} {readln}]") == Some(Command::ReadLine));
+	assert!(find("iload 0 {iload 1 {push 1 print}}") == Some(Command::Print));
+
+	// and it survives assembly
+	let commands = vec![Command::Push(AstValue::Function(vec![Command::ReadLine, Command::Print]))];
+	assert!(disasm::disassemble(&codegen::generate(commands.clone())).ok() == Some(commands));
+}
+
+#[test]
+fn run_mode_uses_stdin_and_stdout() {
+	let path = std::env::temp_dir().join(format!("evm-asm-console-{}.evm", std::process::id()));
+	std::fs::write(&path, "readln readln concat print readln").unwrap();
+
+	let mut child = Process::new(env!("CARGO_BIN_EXE_evm-asm"))
+		.args(["run", "--allow", "io"])
+		.arg(&path)
+		.stdin(Stdio::piped())
+		.stdout(Stdio::piped())
+		.spawn()
+		.unwrap();
+
+	child.stdin.take().unwrap().write_all(b"ab\r\ncd\n").unwrap();
+	let output = child.wait_with_output().unwrap();
+
+	assert!(output.status.success());
+	assert_eq!(String::from_utf8(output.stdout).unwrap(), "abcd\nnil\n");
+}
+
+#[test]
+fn programs_run_in_the_order_they_are_loaded() {
+	let output = Rc::new(RefCell::new(vec![]));
+	let mut vm = Vm::new(Console {output: output.clone(), ..Console::default()});
+	let program = |source| lower(&parse(tokenize(source, "<test>").unwrap()).unwrap());
+
+	vm.load(program("push \"first\" print"));
+	vm.load(program("push \"second\" print"));
+	assert!(vm.run().is_ok());
+
+	assert_eq!(*output.borrow(), ["first", "second"]);
+}
@@ -0,0 +1 @@
+push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push 
@@ -0,0 +1 @@
+push .test
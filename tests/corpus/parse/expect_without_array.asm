@@ -0,0 +1 @@
+.test "t" {push 1} .expect 1
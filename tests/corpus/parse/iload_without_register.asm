@@ -0,0 +1 @@
+iload
@@ -0,0 +1 @@
+iload 1.5 nil
@@ -0,0 +1 @@
+iload 1e300 nil
@@ -0,0 +1 @@
+push [1 2
@@ -0,0 +1 @@
+iload 0 {dup
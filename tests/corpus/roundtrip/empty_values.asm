@@ -0,0 +1,4 @@
+push []
+push {}
+push ""
+iload 15 {}
@@ -0,0 +1,2 @@
+push [[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]
+push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {push {}}}}}}}}}}}}}}}}}}}}}}}}}}}}}}}}}}}}}}}}}}}}}}}}}}}}}}}}}}}}}}}}}}}}}}}}}}}}}}}}}}}}}}}}}}}}}}}}}}}}}}}}}}}}}}}}}}}}}}}}}}}}}}}}}}}}}}}}}}}}}}}}}}}}}}}}}}}}}}}}}}}}}}}}}}}}}}}}}}}}}}}}}}}}}}}}}}}}}}}}}}}}}}}}}}}}}}}}}}}}}}}}}}}}}}}}}}}}}}}}}}}}}}}}}}}}}}}}
@@ -0,0 +1,65 @@
+use serde_json::json;
+
+use evm_asm::ast::parse_with_locs;
+use evm_asm::coverage::Coverage;
+use evm_asm::lexer::Loc;
+use evm_asm::optimizer::count_commands;
+use evm_asm::vm::{lower, Vm};
+use evm_asm::tokenize;
+
+const PROGRAM: &str = "\
+iload 0 {push 1 +}
+iload 1 {push 0}
+
+push [1 2 3] push 0 map
+push false push 1 if
+";
+
+fn cover(source: &str) -> (Coverage, Vec<Loc>) {
+	let (commands, locs) = parse_with_locs(tokenize(source, "<test>").unwrap()).unwrap();
+	let mut coverage = Coverage::new(count_commands(&commands));
+
+	Vm::default().load(lower(&commands)).run_with(&mut |instr, _| coverage.record(instr.id)).unwrap();
+	(coverage, locs)
+}
+
+#[test]
+fn commands_are_counted_each_time_they_run() {
+	let (coverage, _) = cover(PROGRAM);
+
+	assert_eq!(coverage.hits, [1, 3, 3, 1, 0, 1, 1, 1, 1, 1, 1]);
+	assert_eq!(coverage.covered(), 10);
+
+	// ids outside the program are ignored
+	let mut empty = Coverage::new(0);
+	empty.record(3);
+	assert!(empty.hits.is_empty());
+}
+
+#[test]
+fn source_is_annotated_per_line() {
+	let (coverage, locs) = cover(PROGRAM);
+
+	assert_eq!(coverage.annotate(&locs, Some(PROGRAM)).lines().collect::<Vec<_>>(), [
+		"        3:     1: iload 0 {push 1 +}",
+		"       1*:     2: iload 1 {push 0}",
+		"        -:     3: ",
+		"        1:     4: push [1 2 3] push 0 map",
+		"        1:     5: push false push 1 if",
+		"covered 10 of 11 commands (90.9%)"
+	]);
+
+	assert_eq!(coverage.annotate(&locs, None), "command 4 never ran\ncovered 10 of 11 commands (90.9%)\n");
+}
+
+#[test]
+fn coverage_is_written_as_json() {
+	let (coverage, locs) = cover("push 1\npush 2");
+
+	assert_eq!(coverage.to_json(&locs), json!({
+		"commands": 2,
+		"covered": 2,
+		"hits": [1, 1],
+		"lines": [{"line": 1, "commands": 1, "covered": 1, "hits": 1}, {"line": 2, "commands": 1, "covered": 1, "hits": 1}]
+	}));
+}
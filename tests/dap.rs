@@ -0,0 +1,116 @@
+use std::io::Write;
+use std::process::{Command, Stdio};
+use std::{env, fs};
+
+use serde_json::{json, Value as Json};
+
+const PROGRAM: &str = "\
+iload 0 {
+	push 2 *
+	print
+}
+push 21 push 0 call
+push 1 drop drop
+";
+
+// the messages the adapter sends back for `requests`, sent in one go
+fn session(name: &str, requests: &[Json]) -> Vec<Json> {
+	let path = env::temp_dir().join(format!("evm-asm-dap-{}-{}.evm", name, std::process::id()));
+	fs::write(&path, PROGRAM).unwrap();
+
+	let mut input = vec![];
+	let launch = json!({"command": "launch", "arguments": {"program": path, "stopOnEntry": name == "entry"}});
+
+	for (seq, request) in [json!({"command": "initialize"}), launch].iter().chain(requests).enumerate() {
+		let mut request = request.clone();
+		request["seq"] = json!(seq + 1);
+		request["type"] = json!("request");
+
+		let body = request.to_string();
+		write!(input, "Content-Length: {}\r\n\r\n{}", body.len(), body).unwrap();
+	}
+
+	let mut child = Command::new(env!("CARGO_BIN_EXE_evm-asm")).arg("dap").stdin(Stdio::piped()).stdout(Stdio::piped()).spawn().unwrap();
+	child.stdin.take().unwrap().write_all(&input).unwrap();
+	let output = child.wait_with_output().unwrap();
+	assert!(output.status.success());
+
+	let mut messages = vec![];
+	let mut rest = &output.stdout[..];
+
+	while let Some(start) = rest.windows(4).position(|window| window == b"\r\n\r\n") {
+		let header = std::str::from_utf8(&rest[..start]).unwrap();
+		let len: usize = header.strip_prefix("Content-Length: ").unwrap().parse().unwrap();
+
+		messages.push(serde_json::from_slice(&rest[start + 4..start + 4 + len]).unwrap());
+		rest = &rest[start + 4 + len..];
+	}
+
+	messages
+}
+
+fn events(messages: &[Json]) -> Vec<String> {
+	messages.iter().filter(|message| message["type"] == "event").map(|message| match message["body"]["reason"].as_str() {
+		Some(reason) => format!("{} {}", message["event"].as_str().unwrap(), reason),
+		None => message["event"].as_str().unwrap().to_owned()
+	}).collect()
+}
+
+fn response<'a>(messages: &'a [Json], command: &str) -> &'a Json {
+	messages.iter().rev().find(|message| message["type"] == "response" && message["command"] == command).unwrap()
+}
+
+#[test]
+fn breakpoints_stop_the_program() {
+	let messages = session("breakpoints", &[
+		json!({"command": "setBreakpoints", "arguments": {"breakpoints": [{"line": 2}, {"line": 4}]}}),
+		json!({"command": "configurationDone"}),
+		json!({"command": "stackTrace"}),
+		json!({"command": "variables", "arguments": {"variablesReference": 1}}),
+		json!({"command": "disconnect"})
+	]);
+
+	assert_eq!(response(&messages, "initialize")["body"]["supportsStepBack"], true);
+	assert_eq!(response(&messages, "setBreakpoints")["body"]["breakpoints"], json!([{"verified": true, "line": 2}, {"verified": false, "line": 4}]));
+	assert_eq!(events(&messages), ["initialized", "stopped breakpoint"]);
+
+	let frames = &response(&messages, "stackTrace")["body"]["stackFrames"];
+	assert_eq!(frames.as_array().unwrap().len(), 2);
+	assert_eq!((&frames[0]["name"], &frames[0]["line"], &frames[0]["column"]), (&json!("push 2 *"), &json!(2), &json!(2)));
+	assert_eq!((&frames[1]["name"], &frames[1]["line"]), (&json!("push 21 push 0 call"), &json!(5)));
+
+	assert_eq!(response(&messages, "variables")["body"]["variables"], json!([{"name": "0", "value": "21", "type": "number", "variablesReference": 0}]));
+}
+
+#[test]
+fn output_and_errors_are_reported() {
+	let messages = session("errors", &[
+		json!({"command": "configurationDone"}),
+		json!({"command": "stackTrace"}),
+		json!({"command": "continue"}),
+		json!({"command": "bogus"})
+	]);
+
+	let output: Vec<_> = messages.iter().filter(|message| message["event"] == "output").map(|message| &message["body"]).collect();
+	assert_eq!(output[0], &json!({"category": "stdout", "output": "42\n"}));
+	assert_eq!(output[1]["category"], "stderr");
+
+	assert_eq!(events(&messages), ["initialized", "output", "output", "stopped exception", "terminated"]);
+	assert_eq!(response(&messages, "stackTrace")["body"]["stackFrames"][0]["line"], 6);
+	assert_eq!(response(&messages, "bogus")["message"], "Unsupported request: bogus");
+}
+
+#[test]
+fn stepping_both_ways() {
+	let messages = session("entry", &[
+		json!({"command": "configurationDone"}),
+		json!({"command": "next"}),
+		json!({"command": "stepIn"}),
+		json!({"command": "stepBack"}),
+		json!({"command": "stackTrace"}),
+		json!({"command": "disconnect"})
+	]);
+
+	assert_eq!(events(&messages), ["initialized", "stopped entry", "stopped step", "stopped step", "stopped step"]);
+	assert_eq!(response(&messages, "stackTrace")["body"]["stackFrames"][0]["line"], 5);
+}
@@ -0,0 +1,99 @@
+use evm_asm::ast::parse_with_locs;
+use evm_asm::debugger::{Breakpoint, Debugger, Resume, Stop};
+use evm_asm::tokenize;
+
+const PROGRAM: &str = "\
+push 1
+iload 0 {
+	push 2
+	*
+}
+push 0 call
+push 3
+";
+
+fn debugger() -> Debugger {
+	let (commands, locs) = parse_with_locs(tokenize(PROGRAM, "<test>").unwrap()).unwrap();
+	Debugger::new(&commands, locs)
+}
+
+// the id of the command about to run, None once the program has finished
+fn next(debugger: &mut Debugger) -> Option<usize> {
+	debugger.vm.peek().ok().flatten().map(|instr| instr.id)
+}
+
+fn stack(debugger: &Debugger) -> Vec<String> {
+	debugger.vm.stack.iter().map(ToString::to_string).collect()
+}
+
+#[test]
+fn stepping_enters_functions() {
+	let mut debugger = debugger();
+	let mut ids = vec![next(&mut debugger).unwrap()];
+
+	while debugger.resume(Resume::Step).ok() == Some(Stop::Step) {
+		ids.push(next(&mut debugger).unwrap());
+	}
+
+	assert_eq!(ids, [0, 1, 4, 5, 2, 3, 6]);
+	assert_eq!(stack(&debugger), ["2", "3"]);
+}
+
+#[test]
+fn stepping_over_and_out() {
+	let mut debugger = debugger();
+
+	for _ in 0..3 {
+		debugger.resume(Resume::Step).ok().unwrap();
+	}
+	assert_eq!(next(&mut debugger), Some(5));
+
+	// next runs the whole call
+	assert!(debugger.resume(Resume::StepOver).ok() == Some(Stop::Step));
+	assert_eq!(next(&mut debugger), Some(6));
+	assert_eq!(stack(&debugger), ["2"]);
+
+	// back into the call, then out of it
+	assert!(debugger.step_back(1));
+	assert_eq!(next(&mut debugger), Some(3));
+	assert!(debugger.resume(Resume::StepOut).ok() == Some(Stop::Step));
+	assert_eq!(next(&mut debugger), Some(6));
+
+	assert!(debugger.resume(Resume::Continue).ok() == Some(Stop::Finished));
+	assert!(debugger.resume(Resume::Step).ok() == Some(Stop::Finished));
+}
+
+#[test]
+fn breakpoints_stop_both_ways() {
+	let mut debugger = debugger();
+	debugger.breakpoints = vec![Breakpoint::Line(4), Breakpoint::Command(6)];
+
+	assert!(debugger.at_breakpoint(3));
+	assert!(!debugger.at_breakpoint(2));
+	assert_eq!(debugger.loc(3).unwrap().line, 4);
+
+	assert!(debugger.resume(Resume::Continue).ok() == Some(Stop::Breakpoint));
+	assert_eq!(next(&mut debugger), Some(3));
+	assert!(debugger.resume(Resume::Continue).ok() == Some(Stop::Breakpoint));
+	assert_eq!(next(&mut debugger), Some(6));
+
+	assert!(debugger.reverse_continue() == Stop::Breakpoint);
+	assert_eq!(next(&mut debugger), Some(3));
+	assert_eq!(stack(&debugger), ["1", "2"]);
+
+	// no breakpoint before the start, so the history runs out
+	assert!(debugger.reverse_continue() == Stop::Step);
+	assert_eq!(next(&mut debugger), Some(0));
+	assert!(debugger.vm.stack.is_empty());
+	assert!(!debugger.step_back(1));
+}
+
+#[test]
+fn errors_stop_the_program() {
+	let (commands, locs) = parse_with_locs(tokenize("push 1\ndrop\ndrop", "<test>").unwrap()).unwrap();
+	let mut debugger = Debugger::new(&commands, locs);
+
+	let error = debugger.resume(Resume::Continue).err().unwrap();
+	assert_eq!(error.message, "Stack underflow");
+	assert_eq!(error.command, 2);
+}
@@ -0,0 +1,65 @@
+use proptest::prelude::*;
+
+use evm_asm::ast::{Command, Value};
+use evm_asm::codegen::generate;
+use evm_asm::disasm::{disassemble, disassemble_best_effort, Decoded, DisasmError};
+use evm_asm::{opcode, parse, tokenize};
+
+fn bytecode(source: &str) -> Vec<u8> {
+	generate(parse(tokenize(source, "<test>").unwrap()).unwrap()).to_vec()
+}
+
+#[test]
+fn every_truncation_is_an_error() {
+	let bytes = bytecode("push \"text\" iload 0 {push [1 nil true] +} push 0 call");
+
+	for len in 0..bytes.len() {
+		// a cut between commands leaves a shorter program, and anywhere else is the end of the bytecode
+		match disassemble(&bytes[..len]) {
+			Ok(_) | Err(DisasmError::UnexpectedEnd {..} | DisasmError::LengthTooLarge {..}) => {},
+			Err(e) => panic!("{}: {}", len, e)
+		}
+	}
+
+	assert!(matches!(disassemble(&bytes[..3]), Err(DisasmError::UnexpectedEnd {offset: 2, ..} | DisasmError::LengthTooLarge {offset: 2, ..})));
+}
+
+#[test]
+fn corrupt_values_are_reported_where_they_are() {
+	let push = opcode::opcode(&Command::Push(Value::Nil));
+	let string = generate(vec![Command::Push(Value::String("a".to_owned()))]).to_vec();
+
+	// a length that would need more memory than the file has, checked before allocating
+	let mut huge = string[..2].to_vec();
+	huge.extend(u64::MAX.to_le_bytes());
+	assert!(matches!(disassemble(&huge), Err(DisasmError::LengthTooLarge {offset: 2, len: u64::MAX, remaining: 0})));
+
+	let mut invalid = string.clone();
+	*invalid.last_mut().unwrap() = 0xff;
+	assert!(matches!(disassemble(&invalid), Err(DisasmError::InvalidUtf8 {offset: 1})));
+
+	assert!(matches!(disassemble(&[push, 0xee]), Err(DisasmError::UnknownValueTag {offset: 1, tag: 0xee})));
+	assert!(matches!(disassemble(&[0xff]), Err(DisasmError::UnknownOpcode {offset: 0, opcode: 0xff})));
+}
+
+#[test]
+fn best_effort_skips_corrupt_regions() {
+	let mut bytes = bytecode("push 1");
+	bytes.extend([0xff, 0xfe]);
+	bytes.extend(bytecode("push 2"));
+
+	let decoded = disassemble_best_effort(&bytes, 0, bytes.len()).unwrap();
+
+	assert_eq!(decoded.len(), 3);
+	assert!(matches!(&decoded[0], Decoded::Command(Command::Push(Value::Number(val))) if *val == 1.0));
+	assert!(matches!(&decoded[1], Decoded::Undecodable {offset: 10, len: 2, error: DisasmError::UnknownOpcode {..}}));
+	assert!(matches!(&decoded[2], Decoded::Command(Command::Push(Value::Number(val))) if *val == 2.0));
+}
+
+proptest! {
+	#[test]
+	fn any_bytes_decode_or_fail(bytes in prop::collection::vec(any::<u8>(), 0..64)) {
+		let _ = disassemble(&bytes);
+		let _ = disassemble_best_effort(&bytes, 0, bytes.len());
+	}
+}
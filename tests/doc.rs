@@ -0,0 +1,59 @@
+use evm_asm::doc::{document, html, markdown};
+
+const LIBRARY: &str = ";;; ( a b -- sum )
+;;; adds two numbers
+iload 0 {+}
+
+; not a doc comment
+iload 1 [1 2]
+iload 2 {
+	;;; ( -- x )
+	iload 3 {push 5}
+	iload 4 {}
+}
+push 1 ;;; not on its own line
+iload 5 {}
+";
+
+#[test]
+fn attaches_doc_comments() {
+	let file = document(LIBRARY, "lib.asm").unwrap();
+	let registers: Vec<u8> = file.entries.iter().map(|entry| entry.register).collect();
+
+	// 4 is inside a function and undocumented
+	assert_eq!(registers, [0, 1, 2, 3, 5]);
+
+	let add = &file.entries[0];
+	assert_eq!(add.signature.as_deref(), Some("( a b -- sum )"));
+	assert_eq!(add.text, "adds two numbers");
+	assert_eq!(add.loc.line, 3);
+	assert!(add.function);
+
+	assert!(!file.entries[1].function);
+	assert_eq!(file.entries[1].text, "");
+	assert_eq!(file.entries[3].signature.as_deref(), Some("( -- x )"));
+	assert_eq!(file.entries[4].text, "");
+}
+
+#[test]
+fn renders_markdown() {
+	let reference = markdown(&[document(LIBRARY, "lib.asm").unwrap()]);
+
+	assert!(reference.contains("  - [register 0](#lib-asm-r0)\n"));
+	assert!(reference.contains("<a id=\"lib-asm-r0\"></a>\n### register 0\n\n`( a b -- sum )`\n\nadds two numbers\n\nDefined on line 3.\n"));
+	assert!(reference.contains("### register 1 (value)\n"));
+}
+
+#[test]
+fn renders_html() {
+	let reference = html(&[document(";;; x < y\niload 0 {<}\n", "a&b.asm").unwrap()]);
+
+	assert!(reference.contains("<h2><code>a&amp;b.asm</code></h2>"));
+	assert!(reference.contains("<li><a href=\"#a-b-asm-r0\">register 0</a></li>"));
+	assert!(reference.contains("<h3 id=\"a-b-asm-r0\">register 0</h3>\n<p>x &lt; y</p>\n"));
+}
+
+#[test]
+fn parse_errors() {
+	assert!(document(";;; broken\niload 0 {", "a.asm").is_err());
+}
@@ -0,0 +1,80 @@
+use evm_asm::ast::json::program_from_str;
+use evm_asm::ast::ParseError;
+use evm_asm::codegen::CodegenError;
+use evm_asm::depth::StackError;
+use evm_asm::diagnostics::{explain, Diagnostic, EXPLANATIONS};
+use evm_asm::disasm::DisasmError;
+use evm_asm::lexer::{Loc, TokenType, TokenizeError};
+use evm_asm::typecheck::{Kind, TypeError};
+use evm_asm::vm::RuntimeError;
+use evm_asm::Error;
+
+// one error of every kind, so a new code without an explanation fails here
+fn diagnostics() -> Vec<Diagnostic> {
+	let loc = Loc {line: 1, col: 1, filename: "<test>".to_owned()};
+	let json = |text| match program_from_str(text, "<test>") {
+		Err(e) => e,
+		Ok(_) => panic!("{} should not read", text)
+	};
+
+	let errors: Vec<Error> = vec![
+		TokenizeError::UnexpectedIdentifier {ident: "x".to_owned(), loc: loc.clone()}.into(),
+		TokenizeError::UnterminatedString {loc: loc.clone(), end: loc.clone()}.into(),
+		TokenizeError::TooManyTokens {max: 1, loc: loc.clone()}.into(),
+		TokenizeError::MalformedNumber {text: "1.2.3".to_owned(), loc: loc.clone()}.into(),
+		Error::FileTooLarge {size: 2, max: 1},
+		ParseError::UnexpectedToken {token: TokenType::Eof, loc: loc.clone()}.into(),
+		ParseError::Expected {expected: "number", token: TokenType::Eof, loc: loc.clone()}.into(),
+		ParseError::InvalidNumber {text: ".".to_owned(), loc: loc.clone()}.into(),
+		ParseError::RegisterNotInteger {reg: 1.5, loc: loc.clone()}.into(),
+		ParseError::RegisterOutOfRange {reg: 16, loc: loc.clone()}.into(),
+		ParseError::ExpectArray {loc: loc.clone()}.into(),
+		ParseError::NestingTooDeep {loc: loc.clone()}.into(),
+		ParseError::TooManyNodes {max: 1, loc: loc.clone()}.into(),
+		json("{").into(),
+		json("{\"version\": 2, \"commands\": []}").into(),
+		json("{\"version\": 1}").into(),
+		CodegenError::LengthOutOfRange {what: "String", len: 0}.into(),
+		CodegenError::NestingTooDeep {depth: 257}.into(),
+		CodegenError::InvalidRegister(16).into(),
+		CodegenError::OverLimit {what: "Array length", limit: "max-array-length", len: 3, max: 2}.into(),
+		Error::TooLarge {size: 2, max: 1},
+		DisasmError::UnexpectedEnd {offset: 0, expected: "opcode"}.into(),
+		DisasmError::UnknownOpcode {offset: 0, opcode: 0xff}.into(),
+		DisasmError::UnknownValueTag {offset: 0, tag: 9}.into(),
+		DisasmError::InvalidUtf8 {offset: 0}.into(),
+		DisasmError::LengthTooLarge {offset: 0, len: 1, remaining: 0}.into(),
+		DisasmError::NestingTooDeep {offset: 0}.into(),
+		DisasmError::RangeOutOfBounds {offset: 0, len: 1, size: 0}.into(),
+		Error::ChecksumMismatch {expected: 1, actual: 0},
+		RuntimeError {message: "x".to_owned(), command: 0, backtrace: vec![]}.into(),
+	];
+
+	let mut diagnostics: Vec<Diagnostic> = errors.iter().map(Diagnostic::from).collect();
+	diagnostics.push(Diagnostic::from(&StackError::Underflow {command: "+", takes: 2, depth: 1, loc: loc.clone(), called: vec![]}));
+	diagnostics.push(Diagnostic::from(&StackError::Overflow {command: "push", depth: 2, max: 1, loc: loc.clone(), called: vec![]}));
+	diagnostics.push(Diagnostic::from(&TypeError::Mismatch {command: "+", expected: "number", found: Kind::String, loc: loc.clone(), called: vec![]}));
+	diagnostics.push(Diagnostic::from(&TypeError::NotAFunction {command: "call", reg: 0, found: Kind::Number, loc: loc.clone(), called: vec![]}));
+	diagnostics.push(Diagnostic::warning("W001", "console I/O"));
+	diagnostics
+}
+
+#[test]
+fn every_code_is_explained() {
+	for diagnostic in diagnostics() {
+		assert!(explain(diagnostic.code).is_some(), "{} has no explanation", diagnostic.code);
+	}
+}
+
+#[test]
+fn codes_are_unique() {
+	for (i, explanation) in EXPLANATIONS.iter().enumerate() {
+		assert!(EXPLANATIONS[i + 1..].iter().all(|other| other.code != explanation.code), "{} is explained twice", explanation.code);
+	}
+}
+
+#[test]
+fn lookup_ignores_case() {
+	assert_eq!(explain("p005").map(|explanation| explanation.code), Some("P005"));
+	assert!(explain("X999").is_none());
+}
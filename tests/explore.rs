@@ -0,0 +1,59 @@
+use evm_asm::explore::Explorer;
+use evm_asm::{assemble, AssembleOptions};
+
+fn explorer(source: &str) -> Explorer {
+	Explorer::new(assemble(source, &AssembleOptions::default()).unwrap()).unwrap()
+}
+
+#[test]
+fn rows_have_offsets_and_nesting() {
+	let explorer = explorer("iload 0 {query}\npush \"a\"\npush 0 map\n");
+	let rows: Vec<(usize, usize, usize, &str)> = explorer.rows.iter().map(|row| (row.offset, row.len, row.depth, row.text.as_str())).collect();
+
+	assert_eq!(rows, [(0, 11, 0, "iload 0 {"), (11, 1, 1, "query"), (12, 11, 0, "push \"a\""), (23, 10, 0, "push 0"), (33, 1, 0, "map")]);
+}
+
+#[test]
+fn view_marks_the_selected_row_and_dumps_its_bytes() {
+	let mut explorer = explorer("push \"hello\" print\n");
+	let view = explorer.command("j").unwrap();
+
+	assert!(view.starts_with("  00000000  00 02 05"));
+	assert!(view.contains("> 0000000f  "));
+	assert!(view.contains("  1 byte at 0000000f:\n"));
+
+	let view = explorer.command("k").unwrap();
+	assert!(view.contains("  00000000  00 02 05 00 00 00 00 00 00 00 68 65 6c 6c 6f     ..........hello\n"));
+}
+
+#[test]
+fn jump_and_search() {
+	let mut explorer = explorer("iload 0 {query}\npush 1 push 0 map\npush 2 push 0 map\n");
+
+	explorer.command("g 0xb");
+	assert_eq!(explorer.rows[explorer.selected].text, "query");
+	explorer.command("g 3");
+	assert_eq!(explorer.selected, 0);
+	assert!(explorer.command("g 1000").unwrap().starts_with("offset 0x3e8 is past the end"));
+
+	explorer.command("/MAP");
+	assert_eq!(explorer.selected, 4);
+	explorer.command("/");
+	assert_eq!(explorer.selected, 7);
+
+	explorer.command("x 00 01 00 00 00 00 00 00 00 40");
+	assert_eq!(explorer.rows[explorer.selected].text, "push 2");
+	assert_eq!(explorer.command("/nothing").unwrap(), "not found\n");
+	assert!(explorer.command("q").is_none());
+}
+
+#[test]
+fn undecodable_bytes() {
+	let mut bytes = assemble("push 1 print\n", &AssembleOptions::default()).unwrap();
+	bytes.insert(0, 0xff);
+
+	let explorer = Explorer::new(bytes).unwrap();
+	assert!(explorer.rows[0].text.starts_with("?? "));
+	assert_eq!((explorer.rows[0].offset, explorer.rows[0].len), (0, 1));
+	assert_eq!(explorer.rows[1].offset, 1);
+}
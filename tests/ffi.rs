@@ -0,0 +1,45 @@
+#![cfg(feature = "ffi")]
+
+use std::ffi::{CStr, CString};
+use std::{ptr, slice, thread};
+
+use evm_asm::ffi::{evmasm_assemble, evmasm_free, evmasm_last_error};
+use evm_asm::{assemble, AssembleOptions};
+
+fn last_error() -> Option<String> {
+	let message = evmasm_last_error();
+	(!message.is_null()).then(|| unsafe {CStr::from_ptr(message)}.to_str().unwrap().to_owned())
+}
+
+#[test]
+fn bytecode_is_handed_out_and_freed() {
+	let source = CString::new("push 1 push 2 +").unwrap();
+	let mut len = 0;
+
+	let bytes = unsafe {evmasm_assemble(source.as_ptr(), &mut len)};
+	assert!(!bytes.is_null());
+	assert_eq!(unsafe {slice::from_raw_parts(bytes, len)}, &assemble("push 1 push 2 +", &AssembleOptions::default()).unwrap()[..]);
+
+	unsafe {evmasm_free(bytes, len)};
+	unsafe {evmasm_free(ptr::null_mut(), 0)};
+}
+
+#[test]
+fn failures_leave_a_message_per_thread() {
+	let source = CString::new("push [1").unwrap();
+	let mut len = 7;
+
+	assert!(unsafe {evmasm_assemble(source.as_ptr(), &mut len)}.is_null());
+	assert_eq!(len, 7);
+	assert!(last_error().unwrap().starts_with("error[P001]"));
+
+	// other threads have their own last error
+	assert_eq!(thread::spawn(last_error).join().unwrap(), None);
+
+	assert!(unsafe {evmasm_assemble(ptr::null(), &mut len)}.is_null());
+	assert_eq!(last_error().unwrap(), "evmasm_assemble: source and len must not be NULL");
+
+	let invalid = CString::new(vec![b'p', 0xff]).unwrap();
+	assert!(unsafe {evmasm_assemble(invalid.as_ptr(), &mut len)}.is_null());
+	assert_eq!(last_error().unwrap(), "evmasm_assemble: source is not UTF-8");
+}
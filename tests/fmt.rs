@@ -0,0 +1,77 @@
+use evm_asm::formatter::format;
+use evm_asm::{assemble, AssembleOptions};
+
+const MESSY: &str = "#!/usr/bin/env evm-asm
+; header
+push   1 push 2 +   ; add
+
+
+iload 0 {tonum}
+push [1 2  [3 4] {} ]
+push [ { push 1 } 2 ]
+push {
+  ; inner
+  push \"a;b\"   print
+
+}
+.test \"adds\" { push 1 push 2 + } .expect [3]
+push [1 ; one
+ 2]
+; trailing
+";
+
+const FORMATTED: &str = "#!/usr/bin/env evm-asm
+; header
+push 1
+push 2
++ ; add
+
+iload 0 {
+	tonum
+}
+push [1 2 [3 4] {}]
+push [
+	{
+		push 1
+	}
+	2
+]
+push {
+	; inner
+	push \"a;b\"
+	print
+}
+.test \"adds\" {
+	push 1
+	push 2
+	+
+} .expect [3]
+push [
+	1 ; one
+	2
+]
+; trailing
+";
+
+#[test]
+fn canonical_style() {
+	assert_eq!(format(MESSY, "<test>").unwrap(), FORMATTED);
+}
+
+#[test]
+fn formatted_source_is_unchanged() {
+	assert_eq!(format(FORMATTED, "<test>").unwrap(), FORMATTED);
+	assert_eq!(format(include_str!("../test.asm"), "test.asm").unwrap(), format(&format(include_str!("../test.asm"), "test.asm").unwrap(), "test.asm").unwrap());
+}
+
+#[test]
+fn same_program() {
+	let options = AssembleOptions::default();
+	assert_eq!(assemble(MESSY, &options).unwrap(), assemble(FORMATTED, &options).unwrap());
+}
+
+#[test]
+fn source_that_does_not_parse() {
+	assert!(format("push [1 2", "<test>").is_err());
+	assert!(format("push \"a", "<test>").is_err());
+}
@@ -0,0 +1,85 @@
+use std::fs;
+use std::path::PathBuf;
+
+use evm_asm::config::{self, ConfigError};
+use evm_asm::formatter::{format_with, Functions, Indent, Options};
+
+fn options(settings: &[(&str, &str)]) -> Options {
+	let mut options = Options::default();
+
+	for (key, value) in settings {
+		options.set(key, value).unwrap();
+	}
+
+	options
+}
+
+#[test]
+fn indent_with_spaces() {
+	let formatted = format_with("push {push {push 1}}", "<test>", &options(&[("indent", "2")])).unwrap();
+	assert_eq!(formatted, "push {\n  push {\n    push 1\n  }\n}\n");
+}
+
+#[test]
+fn compact_functions_fit_on_one_line() {
+	let options = options(&[("functions", "compact"), ("max-width", "20")]);
+
+	assert_eq!(format_with("iload 0 { push 1 + }", "<test>", &options).unwrap(), "iload 0 {push 1 +}\n");
+	assert_eq!(format_with("push {push 1 push 2 push 3 +}", "<test>", &options).unwrap(), "push {\n\tpush 1\n\tpush 2\n\tpush 3\n\t+\n}\n");
+	// a comment inside keeps the body expanded
+	assert_eq!(format_with("push {push 1 ; one\n}", "<test>", &options).unwrap(), "push {\n\tpush 1 ; one\n}\n");
+}
+
+#[test]
+fn arrays_wider_than_max_width_are_split() {
+	let options = options(&[("max-width", "16")]);
+
+	assert_eq!(format_with("push [1 2 3]", "<test>", &options).unwrap(), "push [1 2 3]\n");
+	assert_eq!(format_with("push [1 2 3 4 5 6]", "<test>", &options).unwrap(), "push [\n\t1\n\t2\n\t3\n\t4\n\t5\n\t6\n]\n");
+}
+
+#[test]
+fn aligned_comments() {
+	let source = "push 1 ; one\npush 22 ; two\n\npush 333 ; three\n";
+	let formatted = format_with(source, "<test>", &options(&[("align-comments", "true")])).unwrap();
+
+	assert_eq!(formatted, "push 1  ; one\npush 22 ; two\n\npush 333 ; three\n");
+}
+
+#[test]
+fn invalid_settings() {
+	let mut options = Options::default();
+
+	assert!(options.set("indent", "wide").is_err());
+	assert!(options.set("functions", "folded").is_err());
+	assert!(options.set("tabs", "4").is_err());
+	assert_eq!(options.indent, Indent::Tab);
+}
+
+fn config_file(name: &str, text: &str) -> PathBuf {
+	let dir = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join(name);
+	fs::create_dir_all(dir.join("src")).unwrap();
+	fs::write(dir.join(config::FILENAME), text).unwrap();
+	dir
+}
+
+#[test]
+fn config_file_in_a_parent() {
+	let dir = config_file("config-fmt", "[fmt]\nindent = 4\nmax-width = 80\nalign-comments = true\nfunctions = \"compact\"\n");
+	let path = config::find(&dir.join("src")).unwrap();
+	let options = config::load(&path).unwrap().fmt;
+
+	assert_eq!(path, dir.join(config::FILENAME));
+	assert_eq!(options.indent, Indent::Spaces(4));
+	assert_eq!(options.max_width, 80);
+	assert!(options.align_comments);
+	assert_eq!(options.functions, Functions::Compact);
+}
+
+#[test]
+fn invalid_config_file() {
+	for (name, text) in [("config-syntax", "[fmt"), ("config-value", "[fmt]\nindent = \"wide\"\n"), ("config-section", "[format]\n")] {
+		let dir = config_file(name, text);
+		assert!(matches!(config::load(&dir.join(config::FILENAME)), Err(ConfigError::Invalid {..})), "{}", text);
+	}
+}
@@ -0,0 +1,48 @@
+use evm_asm::vm::{lower, Value, Vm};
+use evm_asm::{parse, tokenize};
+
+fn vm(source: &str, fuel: Option<u64>) -> Vm {
+	let mut vm = Vm::default();
+	vm.fuel = fuel;
+	vm.load(lower(&parse(tokenize(source, "<test>").unwrap()).unwrap()));
+	vm
+}
+
+#[test]
+fn fuel_counts_commands() {
+	let mut exact = vm("push 1 push 2 +", Some(3));
+	assert_eq!(exact.run().ok().unwrap().iter().map(Value::to_string).collect::<Vec<_>>(), ["3"]);
+	assert_eq!((exact.fuel, exact.steps), (Some(0), 3));
+
+	let mut short = vm("push 1 push 2 +", Some(2));
+	let error = short.run().err().unwrap();
+	assert_eq!(error.message, "Fuel exhausted after 2 steps");
+	assert_eq!(error.command, 2);
+
+	// function bodies and loop bodies are commands too
+	let mut loops = vm("iload 0 {push 1 +} push [1 2 3] push 0 map", None);
+	loops.run().ok().unwrap();
+	assert_eq!((loops.fuel, loops.steps), (None, 10));
+}
+
+#[test]
+fn fuel_stops_endless_programs() {
+	let mut endless = vm("iload 0 {push 0 call} push 0 call", Some(1000));
+	assert_eq!(endless.run().err().unwrap().message, "Fuel exhausted after 1000 steps");
+
+	// more fuel lets a stopped program carry on
+	let mut stopped = vm("push 1 push 2 +", Some(1));
+	assert!(stopped.run().is_err());
+	stopped.fuel = Some(5);
+	assert_eq!(stopped.run().ok().unwrap().len(), 1);
+	assert_eq!(stopped.steps, 3);
+}
+
+#[test]
+fn reset_keeps_the_fuel() {
+	let mut vm = vm("push 1", Some(10));
+	vm.run().ok().unwrap();
+	vm.reset();
+
+	assert_eq!((vm.fuel, vm.steps), (Some(9), 0));
+}
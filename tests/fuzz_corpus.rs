@@ -0,0 +1,52 @@
+use std::fs;
+use std::path::PathBuf;
+
+use evm_asm::ast::parse_with_tests;
+use evm_asm::{codegen, disasm, tokenize};
+
+// inputs that once crashed a fuzz target (fuzz/), or sit on a limit one found; each test runs
+// its directory through the same checks as the target of the same name
+
+fn corpus(name: &str) -> Vec<(PathBuf, Vec<u8>)> {
+	let dir = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/corpus").join(name);
+	let mut files: Vec<_> = fs::read_dir(&dir).unwrap().map(|entry| entry.unwrap().path()).collect();
+	files.sort();
+	files.into_iter().map(|path| {
+		let bytes = fs::read(&path).unwrap();
+		(path, bytes)
+	}).collect()
+}
+
+#[test]
+fn parse() {
+	for (path, bytes) in corpus("parse") {
+		let source = String::from_utf8(bytes).unwrap();
+
+		if let Ok(tokens) = tokenize(&source, &path.to_string_lossy()) {
+			assert!(parse_with_tests(tokens).is_err(), "{} should not parse", path.display());
+		}
+	}
+}
+
+#[test]
+fn disasm() {
+	for (_, bytes) in corpus("disasm") {
+		let _ = disasm::disassemble(&bytes);
+		let _ = disasm::disassemble_best_effort(&bytes, 0, bytes.len());
+		let _ = disasm::annotate(&bytes, 0, bytes.len());
+	}
+}
+
+#[test]
+fn roundtrip() {
+	for (path, bytes) in corpus("roundtrip") {
+		let source = String::from_utf8(bytes).unwrap();
+		let commands = evm_asm::parse(tokenize(&source, &path.to_string_lossy()).unwrap()).unwrap();
+		codegen::validate(&commands).unwrap_or_else(|e| panic!("{}: {}", path.display(), e));
+
+		let bytecode = codegen::generate(commands);
+		let decoded = disasm::disassemble(&bytecode).unwrap_or_else(|e| panic!("{}: {}", path.display(), e));
+
+		assert_eq!(codegen::generate(decoded), bytecode, "{}", path.display());
+	}
+}
@@ -0,0 +1,54 @@
+use evm_asm::ast::parse_with_locs;
+use evm_asm::lexer::tokenize;
+use evm_asm::search::Pattern;
+
+const PROGRAM: &str = "push 1 drop
+iload 3 {
+	push [1 2] drop
+	push \"_\" print
+}
+iload 4 {+}
+push [1 {push 2}] drop
+";
+
+fn find(pattern: &str) -> Vec<(u64, u64, String)> {
+	let (commands, locs) = parse_with_locs(tokenize(PROGRAM, "a.asm").unwrap()).unwrap();
+	let pattern = Pattern::parse(pattern).unwrap();
+
+	pattern.find(&commands, &locs).into_iter().map(|found| {
+		let text: Vec<String> = found.commands.iter().map(|command| command.to_string()).collect();
+		(found.loc.line, found.loc.col, text.join(" "))
+	}).collect()
+}
+
+#[test]
+fn operand_wildcards() {
+	assert_eq!(find("push _ drop"), [
+		(1, 1, "push 1 drop".to_owned()),
+		(3, 2, "push [1 2] drop".to_owned()),
+		(7, 1, "push [1 {push 2}] drop".to_owned())
+	]);
+	assert_eq!(find("push [1 _] drop").len(), 2);
+	assert_eq!(find("push [_ {push _}] _").len(), 1);
+}
+
+#[test]
+fn command_and_register_wildcards() {
+	assert_eq!(find("iload _ {_}"), [(6, 1, "iload 4 {+}".to_owned())]);
+	assert_eq!(find("iload 4 {_}").len(), 1);
+	assert!(find("iload 5 {_}").is_empty());
+	assert_eq!(find("iload _ {_ _ push _ _}").len(), 1);
+	assert_eq!(find("_ print"), [(4, 2, "push \"_\" print".to_owned())]);
+}
+
+#[test]
+fn strings_are_not_wildcards() {
+	assert_eq!(find("push \"_\"").len(), 1);
+	assert!(find("push \"x\"").is_empty());
+}
+
+#[test]
+fn invalid_patterns() {
+	assert!(Pattern::parse("push").is_err());
+	assert!(Pattern::parse("iload 99 {}").is_err());
+}
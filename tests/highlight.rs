@@ -0,0 +1,37 @@
+use evm_asm::lexer::{classify, Kind};
+
+fn pieces(source: &str) -> Vec<(u64, u64, usize, Kind)> {
+	classify(source, "a.asm").unwrap().into_iter().map(|piece| (piece.loc.line, piece.loc.col, piece.len, piece.kind)).collect()
+}
+
+#[test]
+fn classifies_tokens_and_comments() {
+	assert_eq!(pieces("#!/bin/evm-asm\npush [true 1.5] ; x\n.test \"t\" {}\n"), vec![
+		(1, 1, 14, Kind::Comment),
+		(2, 1, 4, Kind::Keyword),
+		(2, 6, 1, Kind::Punctuation),
+		(2, 7, 4, Kind::Constant),
+		(2, 12, 3, Kind::Number),
+		(2, 15, 1, Kind::Punctuation),
+		(2, 17, 3, Kind::Comment),
+		(3, 1, 5, Kind::Directive),
+		(3, 7, 3, Kind::String),
+		(3, 11, 1, Kind::Punctuation),
+		(3, 12, 1, Kind::Punctuation),
+	]);
+}
+
+#[test]
+fn splits_strings_at_line_breaks() {
+	assert_eq!(pieces("push \"a\r\n\nbc\" print"), vec![
+		(1, 1, 4, Kind::Keyword),
+		(1, 6, 2, Kind::String),
+		(3, 1, 3, Kind::String),
+		(3, 5, 5, Kind::Keyword),
+	]);
+}
+
+#[test]
+fn tokenize_errors() {
+	assert!(classify("push \"open", "a.asm").is_err());
+}
@@ -0,0 +1,43 @@
+use evm_asm::vm::{lower, Host, StubHost, Value, Vm};
+use evm_asm::{parse, tokenize};
+
+// items are the words of the selector; their only property is their length
+struct Words;
+
+impl Host for Words {
+	fn query(&mut self, selector: &str) -> Result<Value, String> {
+		Ok(selector.split_whitespace().collect::<Vec<_>>().into())
+	}
+
+	fn info(&mut self, item: &Value, key: &str) -> Result<Value, String> {
+		match (item, key) {
+			(Value::String(word), "length") => Ok((word.len() as f64).into()),
+			(_, "length") => Err(format!("Not a word: {}", item)),
+			_ => Ok(Value::Nil)
+		}
+	}
+}
+
+fn run(host: impl Host + 'static, source: &str) -> Result<Vec<String>, String> {
+	Vm::new(host).load(lower(&parse(tokenize(source, "<test>").unwrap()).unwrap())).run()
+		.map(|stack| stack.iter().map(Value::to_string).collect())
+		.map_err(|e| e.message)
+}
+
+#[test]
+fn query_and_info_go_to_the_host() {
+	assert_eq!(run(Words, "push \"a bc\" query").unwrap(), ["[\"a\" \"bc\"]"]);
+	assert_eq!(run(Words, "push \"abc\" push \"length\" info push 1 push \"colour\" info").unwrap(), ["3", "nil"]);
+	assert_eq!(run(Words, "iload 0 {push \"length\" info} push \"a bc def\" query push 0 map").unwrap(), ["[1 2 3]"]);
+}
+
+#[test]
+fn host_errors_stop_the_program() {
+	assert_eq!(run(Words, "push 1 push \"length\" info").unwrap_err(), "Not a word: 1");
+	assert_eq!(run(Words, "push 1 query").unwrap_err(), "Expected string, got number");
+}
+
+#[test]
+fn the_stub_host_has_no_data() {
+	assert_eq!(run(StubHost, "push \"anything\" query push 1 push \"key\" info").unwrap(), ["[]", "nil"]);
+}
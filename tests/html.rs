@@ -0,0 +1,48 @@
+use evm_asm::html::html;
+
+fn code(page: &str) -> &str {
+	let start = page.find("<code>").unwrap() + "<code>".len();
+	&page[start..page.find("</code>").unwrap()]
+}
+
+#[test]
+fn highlights_tokens() {
+	let page = html("push 1 ; one\npush nil\niload 0 {+}\n", "a.asm").unwrap();
+	let code = code(&page);
+
+	assert!(code.contains("<span class=\"op\">push</span> <span class=\"num\">1</span> <span class=\"com\">; one</span>"));
+	assert!(code.contains("<span class=\"lit\">nil</span>"));
+	assert!(code.contains("<span class=\"op\">iload</span> <span class=\"num\">0</span> <span class=\"punct\">{</span><span class=\"op\">+</span><span class=\"punct\">}</span>"));
+}
+
+#[test]
+fn anchors_every_line() {
+	let page = html("push 1\n\npush 2\n", "a.asm").unwrap();
+
+	for n in 1..=3 {
+		assert!(page.contains(&format!("<span class=\"line\" id=\"L{0}\"><a class=\"ln\" href=\"#L{0}\">{0}</a>", n)));
+	}
+
+	assert!(!page.contains("id=\"L4\""));
+}
+
+#[test]
+fn escapes_source_and_title() {
+	let page = html("push \"<a&b>\"\n", "<x>.asm").unwrap();
+
+	assert!(page.contains("<title>&lt;x&gt;.asm</title>"));
+	assert!(page.contains("<span class=\"str\">&quot;&lt;a&amp;b&gt;&quot;</span>"));
+}
+
+#[test]
+fn strings_span_lines() {
+	let page = html("push \"a\nb\" print\n", "a.asm").unwrap();
+
+	assert!(page.contains("id=\"L1\"><a class=\"ln\" href=\"#L1\">1</a><span class=\"op\">push</span> <span class=\"str\">&quot;a</span></span>"));
+	assert!(page.contains("id=\"L2\"><a class=\"ln\" href=\"#L2\">2</a><span class=\"str\">b&quot;</span> <span class=\"op\">print</span></span>"));
+}
+
+#[test]
+fn tokenize_errors() {
+	assert!(html("push \"open\n", "a.asm").is_err());
+}
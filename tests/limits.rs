@@ -0,0 +1,28 @@
+use evm_asm::ast::ParseError;
+use evm_asm::lexer::TokenizeError;
+use evm_asm::{assemble, AssembleOptions, Error, InputLimits};
+
+fn with(limits: InputLimits) -> AssembleOptions {
+	AssembleOptions {limits, ..AssembleOptions::default()}
+}
+
+#[test]
+fn each_limit_stops_a_larger_input() {
+	// 16 bytes, 6 tokens, 5 commands and values
+	let source = "push [1 2] print";
+
+	assert!(matches!(assemble(source, &with(InputLimits {max_file_size: Some(10), ..InputLimits::default()})), Err(Error::FileTooLarge {size: 16, max: 10})));
+	assert!(matches!(assemble(source, &with(InputLimits {max_tokens: Some(5), ..InputLimits::default()})), Err(Error::Tokenize(TokenizeError::TooManyTokens {max: 5, ..}))));
+	assert!(matches!(assemble(source, &with(InputLimits {max_nodes: Some(3), ..InputLimits::default()})), Err(Error::Parse(ParseError::TooManyNodes {max: 3, ..}))));
+
+	let error = assemble(source, &with(InputLimits {max_nodes: Some(3), ..InputLimits::default()})).unwrap_err();
+	assert!(error.to_string().contains("--max-nodes"), "{}", error);
+}
+
+#[test]
+fn inputs_at_the_limits_pass() {
+	let source = "push [1 2] print";
+	let limits = InputLimits {max_file_size: Some(16), max_tokens: Some(6), max_nodes: Some(5)};
+
+	assert_eq!(assemble(source, &with(limits)).unwrap(), assemble(source, &AssembleOptions::default()).unwrap());
+}
@@ -0,0 +1,132 @@
+use std::fs;
+use std::path::PathBuf;
+
+use evm_asm::ast::parse_with_locs;
+use evm_asm::config;
+use evm_asm::diagnostics::{Diagnostic, Level, Lints, Severity};
+use evm_asm::lint::{lint, Options};
+use evm_asm::tokenize;
+
+fn warnings(source: &str, options: &Options) -> Vec<Diagnostic> {
+	let (commands, locs) = parse_with_locs(tokenize(source, "<test>").unwrap()).unwrap();
+	lint(&commands, &locs, options)
+}
+
+fn codes(source: &str) -> Vec<(&'static str, u64)> {
+	warnings(source, &Options::default()).iter().map(|warning| (warning.code, warning.primary_span.as_ref().unwrap().loc.line)).collect()
+}
+
+fn console_io() -> Diagnostic {
+	Diagnostic::warning("W001", "`print` needs console I/O")
+}
+
+#[test]
+fn dropped_push() {
+	assert_eq!(codes("push 1\ndrop"), [("W002", 1)]);
+	assert_eq!(codes("push 1 push 2 + drop"), []);
+
+	// the label is on the drop, after the commands of the pushed function
+	let warnings = warnings("push [{ push 1 }]\ndrop", &Options::default());
+	assert_eq!(warnings[0].labels[0].span.loc.line, 2);
+}
+
+#[test]
+fn uncalled_functions() {
+	assert_eq!(codes("push { push 1 }"), [("W003", 1)]);
+	assert_eq!(codes("iload 4 { push 1 }"), [("W003", 1)]);
+	assert_eq!(codes("iload 4 { push 1 }\npush 4 call"), []);
+	// a register read inside another function counts
+	assert_eq!(codes("iload 4 { push 1 }\niload 5 { push 4 call }\npush 5 call"), []);
+}
+
+#[test]
+fn magic_numbers() {
+	let numbers = |source| warnings(source, &Options::default()).iter().filter(|warning| warning.code == "W004").count();
+
+	assert_eq!(numbers("push 86400 *"), 1);
+	assert_eq!(numbers("push 0 push 1 push 2 push -1"), 0);
+	// register numbers are not magic
+	assert_eq!(numbers("iload 7 { push 1 }\npush [1] push 7 map push [1] push 7 push 0 reduce"), 0);
+}
+
+#[test]
+fn deep_nesting() {
+	let source = "iload 0 { iload 1 { iload 2 { iload 3 { iload 4 { push 1 } } } } }\npush 0 call push 1 call push 2 call push 3 call push 4 call";
+
+	assert_eq!(codes(source), [("W005", 1)]);
+	assert!(warnings(source, &Options {max_nesting: 5}).is_empty());
+}
+
+#[test]
+fn unreachable_commands() {
+	assert_eq!(codes("push false assert\npush 1 print\npush 2 print"), [("W006", 2)]);
+	assert_eq!(codes("push 1 push 2 assert_eq\npush 1 print"), [("W006", 2)]);
+	assert_eq!(codes("iload 0 { push 1 push 0 / }\npush 0 call\npush 1 print"), [("W006", 3)]);
+
+	// the function may not run, or the divisor may not be zero
+	assert_eq!(codes("iload 0 { push 1 push 0 / }\npush 1 = push 0 if\npush 1 print"), []);
+	assert_eq!(codes("push 1 dup /\npush 1 print"), []);
+
+	let warnings = warnings("push nil assert drop", &Options::default());
+	assert_eq!(warnings[0].labels[0].span.loc.col, 10);
+}
+
+#[test]
+fn magic_number_is_allowed_by_default() {
+	let lints = Lints::default();
+	let warning = warnings("push 86400", &Options::default()).remove(0);
+
+	assert_eq!(lints.level("W004"), Level::Allow);
+	assert_eq!(lints.level("W002"), Level::Warn);
+	assert!(lints.apply(warning).is_none());
+}
+
+#[test]
+fn warnings_are_reported_by_default() {
+	assert_eq!(Lints::default().apply(console_io()).map(|d| d.severity), Some(Severity::Warning));
+}
+
+#[test]
+fn later_flags_win() {
+	let mut lints = Lints::default();
+	lints.set("warnings", Level::Deny).unwrap();
+	lints.set("console-io", Level::Allow).unwrap();
+	assert!(lints.apply(console_io()).is_none());
+
+	lints.set("warnings", Level::Deny).unwrap();
+	let denied = lints.apply(console_io()).unwrap();
+	assert_eq!(denied.severity, Severity::Error);
+	assert!(denied.notes.iter().any(|note| note.to_string().contains("--deny warnings")));
+}
+
+#[test]
+fn codes_name_warnings_too() {
+	let mut lints = Lints::default();
+	lints.set("w001", Level::Deny).unwrap();
+	assert_eq!(lints.level("W001"), Level::Deny);
+	assert!(lints.set("no-such-warning", Level::Allow).is_err());
+}
+
+#[test]
+fn errors_are_never_changed() {
+	let mut lints = Lints::default();
+	lints.set("warnings", Level::Allow).unwrap();
+	assert!(lints.apply(Diagnostic::error("P005", "Register must be between 0-15: 16")).is_some());
+}
+
+#[test]
+fn config_file_levels() {
+	let dir = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("config-lint");
+	fs::create_dir_all(&dir).unwrap();
+	fs::write(dir.join(config::FILENAME), "[lint]\nmagic-number = \"warn\"\nW002 = \"deny\"\nmax-nesting = 5\n").unwrap();
+
+	let config = config::load(&dir.join(config::FILENAME)).unwrap();
+	let dropped = warnings("push 1 drop", &Options::default()).remove(0);
+
+	assert_eq!(config.lints.level("W004"), Level::Warn);
+	assert_eq!(config.lints.apply(dropped).unwrap().severity, Severity::Error);
+	assert_eq!(config.lint.max_nesting, 5);
+
+	fs::write(dir.join(config::FILENAME), "[lint]\ndropped-push = \"loud\"\n").unwrap();
+	assert!(config::load(&dir.join(config::FILENAME)).is_err());
+}
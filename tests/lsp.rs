@@ -0,0 +1,118 @@
+use serde_json::{json, Value as Json};
+
+use evm_asm::lsp::Server;
+
+const URI: &str = "file:///nonexistent/main.asm";
+
+fn open(text: &str) -> (Server, Vec<Json>) {
+	let mut server = Server::new();
+	server.handle(&json!({"jsonrpc": "2.0", "id": 1, "method": "initialize", "params": {"capabilities": {}}}));
+	let published = server.handle(&json!({"jsonrpc": "2.0", "method": "textDocument/didOpen", "params": {"textDocument": {"uri": URI, "languageId": "evm-asm", "version": 1, "text": text}}}));
+	(server, published)
+}
+
+fn request(server: &mut Server, method: &str, line: u64, character: u64) -> Json {
+	let params = json!({"textDocument": {"uri": URI}, "position": {"line": line, "character": character}});
+	server.handle(&json!({"jsonrpc": "2.0", "id": 2, "method": method, "params": params})).remove(0)["result"].clone()
+}
+
+#[test]
+fn capabilities() {
+	let mut server = Server::new();
+	let response = server.handle(&json!({"jsonrpc": "2.0", "id": 1, "method": "initialize", "params": {"capabilities": {}}})).remove(0);
+	let capabilities = &response["result"]["capabilities"];
+
+	assert_eq!(response["id"], 1);
+	assert_eq!(capabilities["hoverProvider"], true);
+	assert_eq!(capabilities["definitionProvider"], true);
+	assert_eq!(capabilities["documentFormattingProvider"], true);
+	assert_eq!(capabilities["documentSymbolProvider"], true);
+	assert_eq!(capabilities["semanticTokensProvider"]["legend"]["tokenTypes"][0], "keyword");
+}
+
+#[test]
+fn diagnostics_as_the_document_changes() {
+	let (mut server, published) = open("push 1 drop\n");
+	let diagnostics = &published[0]["params"]["diagnostics"];
+
+	assert_eq!(published[0]["method"], "textDocument/publishDiagnostics");
+	assert_eq!(diagnostics[0]["code"], "W002");
+	assert_eq!(diagnostics[0]["severity"], 2);
+	assert_eq!(diagnostics[0]["range"]["start"], json!({"line": 0, "character": 0}));
+
+	let change = json!({"jsonrpc": "2.0", "method": "textDocument/didChange", "params": {"textDocument": {"uri": URI, "version": 2}, "contentChanges": [{"text": "push 1\npush [1"}]}});
+	let published = server.handle(&change);
+	let diagnostics = &published[0]["params"]["diagnostics"];
+
+	assert_eq!(diagnostics.as_array().unwrap().len(), 1);
+	assert_eq!(diagnostics[0]["code"], "P001");
+	assert_eq!(diagnostics[0]["severity"], 1);
+}
+
+#[test]
+fn hover_shows_the_stack_effect() {
+	let (mut server, _) = open("push 1 push 2\n+\n");
+	let hover = request(&mut server, "textDocument/hover", 1, 0);
+
+	assert_eq!(hover["contents"]["value"], "`+` pops 2 values and pushes 1 value");
+	assert!(request(&mut server, "textDocument/hover", 0, 6).is_null());
+}
+
+#[test]
+fn definition_of_a_register() {
+	let (mut server, _) = open("iload 3 { push 1 }\npush 3 call\n");
+	let definitions = request(&mut server, "textDocument/definition", 1, 5);
+
+	assert_eq!(definitions, json!([{"uri": URI, "range": {"start": {"line": 0, "character": 0}, "end": {"line": 0, "character": 5}}}]));
+	// a number that is not a register
+	assert!(request(&mut server, "textDocument/definition", 0, 15).is_null());
+}
+
+#[test]
+fn formatting() {
+	let (mut server, _) = open("push   1 push 2 +\n");
+	let params = json!({"textDocument": {"uri": URI}, "options": {"tabSize": 4, "insertSpaces": false}});
+	let edits = server.handle(&json!({"jsonrpc": "2.0", "id": 3, "method": "textDocument/formatting", "params": params})).remove(0);
+
+	assert_eq!(edits["result"][0]["newText"], "push 1\npush 2\n+\n");
+}
+
+#[test]
+fn unknown_requests_fail_and_notifications_get_no_reply() {
+	let mut server = Server::new();
+	let response = server.handle(&json!({"jsonrpc": "2.0", "id": 4, "method": "workspace/symbol", "params": {}})).remove(0);
+
+	assert_eq!(response["error"]["code"], -32601);
+	assert!(server.handle(&json!({"jsonrpc": "2.0", "method": "$/cancelRequest", "params": {"id": 4}})).is_empty());
+}
+
+#[test]
+fn semantic_tokens() {
+	let (mut server, _) = open("push 1 ; one\n  iload 0 {nil}\n");
+	let params = json!({"textDocument": {"uri": URI}});
+	let result = server.handle(&json!({"jsonrpc": "2.0", "id": 2, "method": "textDocument/semanticTokens/full", "params": params})).remove(0)["result"].clone();
+
+	// keyword, number, comment, keyword, number, punctuation, constant, punctuation
+	assert_eq!(result["data"], json!([
+		0, 0, 4, 0, 0,
+		0, 5, 1, 1, 0,
+		0, 2, 5, 3, 0,
+		1, 2, 5, 0, 0,
+		0, 6, 1, 1, 0,
+		0, 2, 1, 6, 0,
+		0, 1, 3, 4, 0,
+		0, 3, 1, 6, 0
+	]));
+}
+
+#[test]
+fn outline_of_the_registers() {
+	let (mut server, _) = open("iload 0 {\n\tpush [1 {dup}] drop\n}\npush {nil} drop\niload 1 \"one\"\n");
+	let params = json!({"textDocument": {"uri": URI}});
+	let result = server.handle(&json!({"jsonrpc": "2.0", "id": 2, "method": "textDocument/documentSymbol", "params": params})).remove(0)["result"].clone();
+
+	assert_eq!(result, json!([
+		{"name": "register 0", "detail": "function", "kind": 12, "range": {"start": {"line": 0, "character": 0}, "end": {"line": 2, "character": 1}}, "selectionRange": {"start": {"line": 0, "character": 0}, "end": {"line": 0, "character": 5}}},
+		{"name": "register 1", "detail": "\"one\"", "kind": 13, "range": {"start": {"line": 4, "character": 0}, "end": {"line": 4, "character": 5}}, "selectionRange": {"start": {"line": 4, "character": 0}, "end": {"line": 4, "character": 5}}}
+	]));
+}
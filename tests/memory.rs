@@ -0,0 +1,27 @@
+use evm_asm::memory::{self, human, Tracking};
+
+#[global_allocator]
+static ALLOCATOR: Tracking = Tracking;
+
+#[test]
+fn counts_the_peak_since_a_reset() {
+	memory::reset_peak();
+	let before = memory::current();
+
+	let big = vec![0u8; 1 << 20];
+	assert!(memory::current() >= before + (1 << 20));
+	drop(big);
+
+	assert!(memory::peak() >= before + (1 << 20));
+	memory::reset_peak();
+	assert!(memory::peak() < before + (1 << 20));
+
+	assert!(memory::peak_rss().unwrap() >= 1 << 20);
+}
+
+#[test]
+fn human_sizes() {
+	assert_eq!(human(512), "512 B");
+	assert_eq!(human(1536), "1.5 KiB");
+	assert_eq!(human(3 << 30), "3.0 GiB");
+}
@@ -0,0 +1,41 @@
+use evm_asm::ast::ParseError;
+use evm_asm::diagnostics::{Diagnostic, Locale, Message};
+use evm_asm::lexer::{Loc, TokenizeError};
+
+fn loc() -> Loc {
+	Loc {line: 1, col: 7, filename: "<test>".to_owned()}
+}
+
+#[test]
+fn english_is_the_default() {
+	let diagnostic = Diagnostic::from(&ParseError::RegisterOutOfRange {reg: 16, loc: loc()});
+	assert_eq!(diagnostic.message.to_string(), "Register must be between 0-15: 16");
+	assert_eq!(diagnostic.message.id, "P005");
+}
+
+#[test]
+fn localize_translates_messages_labels_and_notes() {
+	let diagnostic = Diagnostic::from(&TokenizeError::UnterminatedString {loc: loc(), end: loc()}).localize(Locale::De);
+	assert_eq!(diagnostic.message.to_string(), "Nicht abgeschlossene Zeichenkette");
+	assert_eq!(diagnostic.labels[0].message.to_string(), "Zeichenkette beginnt hier");
+	assert!(diagnostic.notes[0].to_string().starts_with("Zeichenketten enden"));
+}
+
+#[test]
+fn arguments_that_are_messages_are_translated_too() {
+	let diagnostic = Diagnostic::from(&ParseError::Expected {expected: "number", token: evm_asm::lexer::TokenType::Nil, loc: loc()});
+	assert_eq!(diagnostic.clone().localize(Locale::De).message.to_string(), "Unerwartetes Token `nil`: erwartet wurde eine Zahl");
+
+	let json = diagnostic.to_json();
+	assert_eq!(json["messageId"], "P002");
+	assert_eq!(json["arguments"]["expected"], "number");
+}
+
+#[test]
+fn text_outside_the_catalog_is_kept() {
+	let message = Message::from("{not a placeholder}").localize(Locale::De);
+	assert_eq!(message.to_string(), "{not a placeholder}");
+	assert_eq!(Locale::from_name("de_DE.UTF-8"), Some(Locale::De));
+	assert_eq!(Locale::from_name("C"), Some(Locale::En));
+	assert_eq!(Locale::from_name("xx"), None);
+}
@@ -0,0 +1,22 @@
+use evm_asm::ast::{parse, print_minified};
+use evm_asm::lexer::tokenize;
+
+fn minify(source: &str) -> String {
+	print_minified(&parse(tokenize(source, "a.asm").unwrap()).unwrap())
+}
+
+#[test]
+fn spaces_only_where_tokens_would_join() {
+	let source = "; comment\niload 0 {\n\tpush 0.5 +   ; half\n}\npush [1 \"a b\" -0.25 [true nil]] drop\npush {} readln concat\n";
+
+	assert_eq!(minify(source), "iload 0{push .5 +}push[1\"a b\"-.25[true nil]]drop push{}readln concat\n");
+}
+
+#[test]
+fn reparses_to_the_same_program() {
+	let source = "iload 3 {dup push 2 % push 0 = if}\npush [10 0.125 \"x\"] push 3 map print\npush -2.5 push 1 - print\n";
+	let minified = minify(source);
+
+	assert_eq!(parse(tokenize(&minified, "a.asm").unwrap()).unwrap(), parse(tokenize(source, "a.asm").unwrap()).unwrap());
+	assert!(minified.len() < source.len());
+}
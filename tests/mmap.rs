@@ -0,0 +1,27 @@
+use std::fs;
+use std::path::PathBuf;
+
+use evm_asm::mmap::Mmap;
+use evm_asm::{assemble, AssembleOptions};
+
+fn file(name: &str, contents: &[u8]) -> PathBuf {
+	let path = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join(name);
+	fs::write(&path, contents).unwrap();
+	path
+}
+
+#[test]
+fn maps_the_source_text() {
+	let source = "iload 0 {push 1 +} ; \"ü\"\npush 2 push 0 call\n".repeat(1000);
+	let mapped = Mmap::open(file("mmap.asm", source.as_bytes())).unwrap();
+
+	assert_eq!(mapped.text().unwrap(), source);
+	assert_eq!(assemble(mapped.text().unwrap(), &AssembleOptions::default()).unwrap(), assemble(&source, &AssembleOptions::default()).unwrap());
+}
+
+#[test]
+fn maps_empty_and_rejects_invalid_files() {
+	assert_eq!(Mmap::open(file("mmap-empty.asm", b"")).unwrap().text().unwrap(), "");
+	assert!(Mmap::open(file("mmap-invalid.asm", b"push \"\xff\"")).unwrap().text().is_err());
+	assert!(Mmap::open(PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("mmap-missing.asm")).is_err());
+}
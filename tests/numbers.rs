@@ -0,0 +1,45 @@
+use evm_asm::lexer::{tokenize, tokenize_legacy, TokenType, TokenizeError};
+use evm_asm::{assemble, AssembleOptions};
+
+fn numbers(source: &str) -> Vec<String> {
+	tokenize(source, "<test>").unwrap().into_iter().filter_map(|token| match token.typ {
+		TokenType::Number(text) => Some(text),
+		_ => None
+	}).collect()
+}
+
+#[test]
+fn well_formed_numbers() {
+	assert_eq!(numbers("push 1 push -2 push 0.5 push .5 push -.5 push 3."), ["1", "-2", "0.5", ".5", "-.5", "3."]);
+
+	// brackets, quotes and comments end a number
+	assert_eq!(numbers("push [1 2]\npush 3;comment\npush [4]"), ["1", "2", "3", "4"]);
+}
+
+#[test]
+fn malformed_numbers_are_one_error() {
+	for text in ["1.2.3", "1-2", "5abc", "-1.5e3", ".", "1.-"] {
+		let error = tokenize(&format!("push {}", text), "<test>").err().unwrap();
+
+		match error {
+			TokenizeError::MalformedNumber {text: found, loc} => {
+				assert_eq!(found, text);
+				assert_eq!(loc.col, 6);
+			},
+			error => panic!("{}: {:?}", text, error)
+		}
+	}
+}
+
+#[test]
+fn legacy_numbers_split_as_before() {
+	let legacy = |source| tokenize_legacy(source, "<test>", usize::MAX, &mut |_| {}).unwrap().len();
+
+	// push, 1.2, .3, Eof
+	assert_eq!(legacy("push 1.2.3"), 4);
+	assert_eq!(legacy("push 1-2 +"), 5);
+
+	let options = AssembleOptions {legacy_numbers: true, ..AssembleOptions::default()};
+	assert!(assemble("push 1push 2 +", &options).is_ok());
+	assert!(assemble("push 1push 2 +", &AssembleOptions::default()).is_err());
+}
@@ -0,0 +1,183 @@
+use proptest::prelude::*;
+
+use evm_asm::ast::{Command, Value};
+use evm_asm::vm::{self, lower, Limits, Vm};
+use evm_asm::{opcode, optimizer, parse, tokenize};
+
+// what a program leaves on the stack, printed, or None if it fails; Err if it runs out of fuel or
+// hits a limit, where optimized and unoptimized programs may stop at different points
+fn run(commands: &[Command]) -> Result<Option<Vec<String>>, ()> {
+	let mut vm = Vm::default();
+	vm.fuel = Some(2000);
+	vm.limits = Limits {max_memory: Some(1 << 16), max_depth: Some(64), ..Limits::default()};
+
+	match vm.load(lower(commands)).run() {
+		Ok(stack) => Ok(Some(stack.iter().map(show).collect())),
+		Err(e) if e.message.starts_with("Fuel") || e.message.contains("limit") => Err(()),
+		Err(_) => Ok(None)
+	}
+}
+
+// functions are optimized themselves, so only that there is one is compared
+fn show(value: &vm::Value) -> String {
+	match value {
+		vm::Value::Function(_) => "function".to_owned(),
+		vm::Value::Array(values) => format!("[{}]", values.iter().map(show).collect::<Vec<_>>().join(" ")),
+		value => value.to_string()
+	}
+}
+
+fn optimized(commands: Vec<Command>, level: u8) -> Vec<Command> {
+	optimizer::optimize(commands, &optimizer::Options {level, ..Default::default()}, &mut vec![])
+}
+
+// small numbers, so pushes are often registers, and a few strings and booleans for the operators
+fn value() -> BoxedStrategy<Value> {
+	let leaf = prop_oneof![
+		Just(Value::Nil),
+		(0..4u8).prop_map(|val| Value::Number(val as f64)),
+		prop::sample::select(vec![-1.5, 0.5, 16.0]).prop_map(Value::Number),
+		prop::sample::select(vec!["", "a", "1"]).prop_map(|val| Value::String(val.to_owned())),
+		any::<bool>().prop_map(Value::Boolean),
+	];
+
+	leaf.prop_recursive(3, 16, 4, |inner| prop_oneof![
+		prop::collection::vec(inner.clone(), 0..4).prop_map(Value::Array),
+		prop::collection::vec(command(inner), 0..6).prop_map(Value::Function),
+	]).boxed()
+}
+
+// any command but tostr, which shows a function's body as optimized; the console and host ones fail
+// or find nothing the same way at every level
+fn command(value: BoxedStrategy<Value>) -> impl Strategy<Value = Command> {
+	let simple: Vec<Command> = opcode::COMMANDS.iter().filter(|command| !matches!(command, Command::Push(_) | Command::ILoad(..) | Command::ToStr)).cloned().collect();
+
+	prop_oneof![
+		2 => prop::sample::select(simple),
+		2 => value.clone().prop_map(Command::Push),
+		1 => (0..4u8, value).prop_map(|(reg, value)| Command::ILoad(reg, value)),
+	]
+}
+
+fn program() -> impl Strategy<Value = Vec<Command>> {
+	prop::collection::vec(command(value()), 0..16)
+}
+
+proptest! {
+	#![proptest_config(ProptestConfig::with_cases(2000))]
+
+	#[test]
+	fn optimizing_keeps_what_a_program_does(commands in program()) {
+		let unoptimized = run(&commands);
+		prop_assume!(unoptimized.is_ok());
+
+		for level in 1..=optimizer::MAX_LEVEL {
+			let optimized = optimized(commands.clone(), level);
+
+			if let Ok(result) = run(&optimized) {
+				prop_assert_eq!(&result, unoptimized.as_ref().unwrap(), "-O{}: {:?}", level, optimized);
+			}
+		}
+	}
+}
+
+#[test]
+fn functions_are_compared_as_the_vm_does() {
+	// two literals are two functions, even with the same body, in arrays as well
+	let function = Value::Array(vec![Value::Function(vec![])]);
+	let commands = vec![Command::Push(function.clone()), Command::Push(function), Command::Eq];
+
+	assert_eq!(run(&commands), Ok(Some(vec!["false".to_owned()])));
+	assert_eq!(run(&optimized(commands, 2)), Ok(Some(vec!["false".to_owned()])));
+}
+
+fn parsed(source: &str) -> Vec<Command> {
+	parse(tokenize(source, "<test>").unwrap()).unwrap()
+}
+
+#[test]
+fn constants_are_folded() {
+	let fold = |source| optimizer::fold_constants(parsed(source));
+
+	assert_eq!(fold("push 2 push 3 + push 4 *"), parsed("push 20"));
+	assert_eq!(fold("push \"a\" push \"b\" concat push [1] push [2] concat"), parsed("push \"ab\" push [1 2]"));
+	assert_eq!(fold("push 1 push 2 < not push nil push nil ="), parsed("push false push true"));
+	assert_eq!(fold("iload 0 {push 1 push 1 +}"), parsed("iload 0 {push 2}"));
+
+	// what fails at run time is left to fail there
+	for source in ["push 1 push 0 /", "push 1 push \"a\" +", "push 1 not"] {
+		assert_eq!(fold(source), parsed(source));
+	}
+}
+
+#[test]
+fn dead_code_is_removed() {
+	let dce = |source| {
+		let mut removed = vec![];
+		let commands = optimizer::eliminate_dead_code(parsed(source), &mut removed);
+		(commands, removed.len())
+	};
+
+	// a pure computation that is dropped, and a load overwritten before it is read
+	assert_eq!(dce("push 1 push 2 swap = drop push 3"), (parsed("push 3"), 5));
+	assert_eq!(dce("iload 0 nil iload 0 {push 1} push 0 call"), (parsed("iload 0 {push 1} push 0 call"), 1));
+	assert_eq!(dce("iload 1 {push 1 push 2 drop}"), (parsed("iload 1 {push 1}"), 2));
+
+	// a load that a call may read, and a drop of a value from below or that may fail to be made
+	for source in ["iload 0 nil push 0 call iload 0 nil", "drop", "push 1 + drop", "push 1 push 2 + drop"] {
+		assert_eq!(dce(source), (parsed(source), 0));
+	}
+}
+
+#[test]
+fn small_functions_are_inlined() {
+	let inline = |source, threshold| optimizer::inline_functions(parsed(source), threshold, false);
+	let closed = |source| optimizer::inline_functions(parsed(source), 8, true);
+
+	// the load stays for whatever reads the register after the program, unless nothing does
+	assert_eq!(inline("iload 0 {push 2 *} push 1 push 0 call push 0 call", 8), parsed("iload 0 {push 2 *} push 1 push 2 * push 2 *"));
+	assert_eq!(closed("iload 0 {push 2 *} push 1 push 0 call push 0 call"), parsed("push 1 push 2 * push 2 *"));
+	assert_eq!(closed("iload 0 {push 2 *} push 1 iload 0 {push 3 *} push 0 call"), parsed("push 1 push 3 *"));
+
+	// too large, or called through a computed register
+	for (source, threshold) in [
+		("iload 0 {push 2 *} push 1 push 0 call", 1),
+		("iload 0 {push 2 *} push 1 push 0 push 0 + call", 8)
+	] {
+		let inlined = inline(source, threshold);
+		assert_eq!(run(&inlined), run(&parsed(source)), "{}", source);
+		assert!(inlined.contains(&Command::Call), "{}", source);
+	}
+}
+
+#[test]
+fn levels_and_toggles_choose_the_passes() {
+	let source = "iload 0 {push 2 push 3 +} push 0 call push 1 drop";
+	let passes = |options: &optimizer::Options| {
+		let mut report = vec![];
+		optimizer::optimize(parsed(source), options, &mut report);
+		report.iter().filter(|line| !line.starts_with(' ')).map(|line| line.split(':').next().unwrap().to_owned()).collect::<Vec<_>>()
+	};
+
+	let mut options = optimizer::Options::default();
+	assert_eq!(passes(&options), ["inline", "fold", "peephole", "dce"]);
+
+	options.set("1").unwrap();
+	assert_eq!(passes(&options), ["fold", "peephole", "dce"]);
+
+	options.toggle("inline").unwrap();
+	options.toggle("no-fold").unwrap();
+	assert_eq!(passes(&options), ["inline", "peephole", "dce"]);
+
+	options.set("0").unwrap();
+	assert_eq!(passes(&options), ["inline"]);
+	assert_eq!(optimized(parsed(source), 2), parsed("iload 0 {push 5} push 5"));
+
+	assert!(options.set("3").is_err());
+	assert!(options.set("inline-threshold=x").is_err());
+
+	let mut closed = optimizer::Options::default();
+	closed.set("closed").unwrap();
+	assert_eq!(optimizer::optimize(parsed(source), &closed, &mut vec![]), parsed("push 5"));
+	assert!(options.toggle("no-such-pass").is_err());
+}
@@ -0,0 +1,37 @@
+use evm_asm::ast::{parse, parse_outline, Command, ParseError, Value};
+use evm_asm::lexer::tokenize;
+
+#[test]
+fn skips_function_bodies_until_asked() {
+	let source = "iload 0 {push [1 {dup}] drop}\npush [{} 2 {push 3}]\n.test \"t\" {push {push 1}} .expect [1]\npush 0 call\n";
+	let outline = parse_outline(tokenize(source, "<source>").unwrap()).unwrap();
+
+	assert_eq!(outline.commands, vec![
+		Command::ILoad(0, Value::Function(vec![])),
+		Command::Push(Value::Array(vec![Value::Function(vec![]), Value::Number(2.0), Value::Function(vec![])])),
+		Command::Push(Value::Number(0.0)),
+		Command::Call
+	]);
+	assert_eq!(outline.locs.len(), 4);
+	assert_eq!(outline.bodies.len(), 3);
+	assert_eq!(outline.tests[0].body, vec![Command::Push(Value::Function(vec![Command::Push(Value::Number(1.0))]))]);
+
+	let (body, locs) = outline.body(&outline.bodies[2]).unwrap();
+	assert_eq!(body, vec![Command::Push(Value::Number(3.0))]);
+	assert_eq!((locs[0].line, locs[0].col), (2, 13));
+	assert_eq!(outline.end(&outline.bodies[0]).col, 29);
+
+	assert_eq!(outline.program().unwrap(), parse(tokenize(source, "<source>").unwrap()).unwrap());
+}
+
+#[test]
+fn errors_in_a_body_wait_for_it() {
+	let outline = parse_outline(tokenize("iload 0 {push ]}\npush 1", "<source>").unwrap()).unwrap();
+	assert!(matches!(outline.body(&outline.bodies[0]), Err(ParseError::UnexpectedToken {..})));
+
+	let deep = format!("{}{}", "push {".repeat(300), "}".repeat(300));
+	let outline = parse_outline(tokenize(&deep, "<source>").unwrap()).unwrap();
+	assert!(matches!(outline.program(), Err(ParseError::NestingTooDeep {..})));
+
+	assert!(matches!(parse_outline(tokenize("push {dup", "<source>").unwrap()), Err(ParseError::UnexpectedToken {..})));
+}
@@ -0,0 +1,31 @@
+use std::process::{Command, Output};
+use std::{env, fs};
+
+fn assemble(args: &[&str]) -> (Output, Vec<String>) {
+	let dir = env::temp_dir().join(format!("evm-asm-output-{}-{}", std::process::id(), args.join("_").replace(['/', ','], "_")));
+	let _ = fs::remove_dir_all(&dir);
+	fs::create_dir_all(&dir).unwrap();
+	fs::write(dir.join("a.asm"), "push 1 push 2 +\n").unwrap();
+
+	let output = Command::new(env!("CARGO_BIN_EXE_evm-asm")).args(args).arg("a.asm").current_dir(&dir).output().unwrap();
+	let mut files: Vec<String> = fs::read_dir(&dir).unwrap().map(|entry| entry.unwrap().file_name().into_string().unwrap()).collect();
+	files.sort();
+
+	(output, files)
+}
+
+#[test]
+fn a_dash_is_stdout() {
+	let (plain, _) = assemble(&[]);
+	let (dash, files) = assemble(&["-o", "-"]);
+
+	assert!(dash.status.success());
+	assert_eq!(dash.stdout, plain.stdout);
+	assert_eq!(files, ["a.asm"]);
+
+	// every kind, rather than a file per kind named after the input
+	let (several, files) = assemble(&["--emit", "listing,min-asm", "-o", "-"]);
+	assert!(several.status.success());
+	assert!(String::from_utf8_lossy(&several.stdout).ends_with("push 3\npush 3\n"));
+	assert_eq!(files, ["a.asm"]);
+}
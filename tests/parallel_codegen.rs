@@ -0,0 +1,22 @@
+use evm_asm::{codegen, parse, tokenize};
+
+fn generate_on(threads: usize, source: &str) -> Vec<u8> {
+	let commands = parse(tokenize(source, "<source>").unwrap()).unwrap();
+	let pool = rayon::ThreadPoolBuilder::new().num_threads(threads).build().unwrap();
+	pool.install(|| codegen::generate_parallel(commands)).to_vec()
+}
+
+#[test]
+fn joins_the_runs_in_order() {
+	let mut source = String::new();
+
+	for i in 0..20_000 {
+		source.push_str(&format!("iload {} {{push \"{}\" print}} push [{} {{dup}}] drop\n", i % 16, "x".repeat(i % 50), i));
+	}
+
+	let expected = codegen::generate(parse(tokenize(&source, "<source>").unwrap()).unwrap()).to_vec();
+	assert!(expected.len() > 1 << 20);
+	assert_eq!(generate_on(4, &source), expected);
+	assert_eq!(generate_on(1, &source), expected);
+	assert_eq!(generate_on(4, "push 1 print"), codegen::generate(parse(tokenize("push 1 print", "<source>").unwrap()).unwrap()).to_vec());
+}
@@ -0,0 +1,30 @@
+use evm_asm::optimizer::peephole;
+use evm_asm::ast::print_minified;
+use evm_asm::{parse, tokenize};
+
+fn optimized(source: &str) -> String {
+	print_minified(&peephole(parse(tokenize(source, "<test>").unwrap()).unwrap())).trim_end().to_owned()
+}
+
+#[test]
+fn pairs_that_cancel_are_removed() {
+	assert_eq!(optimized("push 1 push 2 drop"), "push 1");
+	assert_eq!(optimized("push 1 dup drop"), "push 1");
+	assert_eq!(optimized("push 1 push 2 swap swap"), "push 1 push 2");
+	assert_eq!(optimized("push 1 push 2 < not not"), "push 1 push 2 <");
+
+	// inside functions as well, and the pair left by removing one is removed too
+	assert_eq!(optimized("iload 0 {push true not not push 3 dup drop drop}"), "iload 0{push true}");
+}
+
+#[test]
+fn pairs_that_would_fail_are_kept() {
+	// not of a number, and swap and dup with too few values, are errors the program still gets
+	assert_eq!(optimized("push 1 not not"), "push 1 not not");
+	assert_eq!(optimized("push 1 swap swap"), "push 1 swap swap");
+	assert_eq!(optimized("dup drop"), "dup drop");
+
+	// a function body may be called with nothing on the stack, and a call may take what was pushed
+	assert_eq!(optimized("iload 0 {swap swap}"), "iload 0{swap swap}");
+	assert_eq!(optimized("push 1 push 2 push 0 call swap swap"), "push 1 push 2 push 0 call swap swap");
+}
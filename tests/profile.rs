@@ -0,0 +1,54 @@
+use std::collections::HashMap;
+
+use evm_asm::ast::parse_with_locs;
+use evm_asm::profile::Profile;
+use evm_asm::vm::{lower, Vm};
+use evm_asm::tokenize;
+
+const PROGRAM: &str = "\
+iload 0 {push 1 +}
+push [1 2 3] push 0 map
+iload 1 {push 5} push 1 call
+";
+
+// each table of the report as name => count; rows are ordered by time, which varies between runs
+fn profile(source: &str) -> (HashMap<String, u64>, HashMap<String, u64>) {
+	let (commands, locs) = parse_with_locs(tokenize(source, "<test>").unwrap()).unwrap();
+	let mut profile = Profile::new(&commands);
+
+	Vm::default().load(lower(&commands)).run_with(&mut |instr, _| profile.record(instr.id, &instr.command)).unwrap();
+
+	let report = profile.report(&locs);
+	let (opcodes, functions) = report.split_once("\n\n").unwrap();
+
+	let table = |text: &str| text.lines().skip(1).map(|line| {
+		let columns: Vec<&str> = line.split("  ").map(str::trim).filter(|column| !column.is_empty()).collect();
+		(columns[0].to_owned(), columns[1].parse().unwrap())
+	}).collect();
+
+	(table(opcodes), table(functions))
+}
+
+#[test]
+fn opcodes_are_counted() {
+	let (opcodes, _) = profile(PROGRAM);
+
+	assert_eq!(opcodes, HashMap::from([
+		("iload".to_owned(), 2),
+		("push".to_owned(), 7),
+		("+".to_owned(), 3),
+		("map".to_owned(), 1),
+		("call".to_owned(), 1)
+	]));
+}
+
+#[test]
+fn functions_are_named_by_their_definition() {
+	let (_, functions) = profile(PROGRAM);
+
+	assert_eq!(functions, HashMap::from([
+		("(top level)".to_owned(), 7),
+		("iload 0 at 1:1".to_owned(), 6),
+		("iload 1 at 3:1".to_owned(), 1)
+	]));
+}
@@ -0,0 +1,32 @@
+use std::sync::{Arc, Mutex};
+
+use evm_asm::{assemble, AssembleOptions, Phase, Progress};
+
+#[test]
+fn reports_every_phase_in_order() {
+	let source = "push [1 2 3] push { push 1 + } map\n".repeat(500);
+	let seen = Arc::new(Mutex::new(vec![]));
+
+	let options = AssembleOptions {
+		on_progress: Some(Progress::new({
+			let seen = seen.clone();
+			move |phase, percent| seen.lock().unwrap().push((phase, percent))
+		})),
+		..AssembleOptions::default()
+	};
+
+	assemble(&source, &options).unwrap();
+
+	let seen = seen.lock().unwrap();
+
+	for phase in [Phase::Tokenize, Phase::Parse, Phase::Codegen] {
+		let percents: Vec<u8> = seen.iter().filter(|(p, _)| *p == phase).map(|(_, percent)| *percent).collect();
+
+		assert!(percents.len() > 2, "{:?}: {:?}", phase, percents);
+		assert!(percents.windows(2).all(|pair| pair[0] < pair[1]), "{:?}: {:?}", phase, percents);
+		assert_eq!(percents.last(), Some(&100));
+	}
+
+	let phases: Vec<Phase> = seen.iter().map(|(phase, _)| *phase).collect();
+	assert!(phases.is_sorted_by_key(|phase| *phase as u8));
+}
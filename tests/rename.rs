@@ -0,0 +1,30 @@
+use evm_asm::rename::{rename, RenameError};
+
+#[test]
+fn renames_iloads_and_register_operands() {
+	let source = "iload 0 {push 0 +} ; adds 0\npush 1   push 0 call\npush [1] push 0 push 0 reduce\npush 0 load\niload 2 {push 0.0 call}\n";
+	let renamed = rename(source, "a.asm", 0, 12).unwrap();
+
+	assert_eq!(renamed.source, "iload 12 {push 0 +} ; adds 0\npush 1   push 12 call\npush [1] push 12 push 0 reduce\npush 12 load\niload 2 {push 12 call}\n");
+	assert_eq!(renamed.edits, 5);
+	assert!(renamed.computed.is_empty());
+}
+
+#[test]
+fn computed_registers_are_reported() {
+	let renamed = rename("iload 1 {}\npush 1 push 0 + call\n", "a.asm", 1, 2).unwrap();
+
+	assert_eq!(renamed.source, "iload 2 {}\npush 1 push 0 + call\n");
+	assert_eq!(renamed.computed.iter().map(|loc| (loc.line, loc.col)).collect::<Vec<_>>(), [(2, 17)]);
+}
+
+#[test]
+fn refuses_a_register_in_use() {
+	match rename("iload 0 {}\niload 1 {}\n", "a.asm", 0, 1) {
+		Err(RenameError::InUse {register: 1, loc}) => assert_eq!(loc.line, 2),
+		_ => panic!("expected InUse")
+	}
+
+	assert!(matches!(rename("push 1 push 3 call\n", "a.asm", 1, 3), Err(RenameError::InUse {..})));
+	assert!(matches!(rename("push [", "a.asm", 0, 1), Err(RenameError::Parse(_))));
+}
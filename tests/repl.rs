@@ -0,0 +1,70 @@
+use std::io::Write;
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+use std::{env, fs};
+
+// a home directory of its own, so the history file stays out of the way
+fn home(name: &str) -> PathBuf {
+	let dir = env::temp_dir().join(format!("evm-asm-repl-{}-{}", name, std::process::id()));
+	let _ = fs::remove_dir_all(&dir);
+	fs::create_dir_all(&dir).unwrap();
+	dir
+}
+
+// stdout and stderr of a repl session fed the given lines
+fn session(home: &PathBuf, input: &str) -> (String, String) {
+	let mut child = Command::new(env!("CARGO_BIN_EXE_evm-asm"))
+		.arg("repl")
+		.env("HOME", home)
+		.stdin(Stdio::piped())
+		.stdout(Stdio::piped())
+		.stderr(Stdio::piped())
+		.spawn()
+		.unwrap();
+
+	child.stdin.take().unwrap().write_all(input.as_bytes()).unwrap();
+	let output = child.wait_with_output().unwrap();
+	assert!(output.status.success());
+
+	(String::from_utf8(output.stdout).unwrap(), String::from_utf8(output.stderr).unwrap())
+}
+
+#[test]
+fn lines_share_the_stack_and_registers() {
+	let (out, err) = session(&home("shared"), "push 1 push 2 +\niload 0 {\npush 2 *\n}\npush 0 call\n:stack\n");
+
+	assert_eq!(out.lines().collect::<Vec<_>>(), ["<1> 3", "<1> 3", "<1> 6", "   0: 6", ""]);
+	assert_eq!(err, "");
+}
+
+#[test]
+fn errors_leave_the_session_running() {
+	let (out, err) = session(&home("errors"), "push 1\nbogus\ndrop drop\npush 2\n:bogus\n:reset\npush 3\n");
+
+	assert_eq!(out.lines().collect::<Vec<_>>(), ["<1> 1", "<1> 1", "<0> ", "<1> 2", "<1> 3", ""]);
+	assert!(err.contains("Stack underflow"));
+	assert!(err.contains("Unknown command :bogus, try :help"));
+}
+
+#[test]
+fn files_are_loaded_into_the_session() {
+	let home = home("load");
+	let file = home.join("double.evm");
+	fs::write(&file, "push 2 *").unwrap();
+
+	let (out, err) = session(&home, &format!("push 21\n:load {}\n:load missing.evm\n:quit\npush 1\n", file.display()));
+
+	assert_eq!(out.lines().collect::<Vec<_>>(), ["<1> 21", "<1> 42", "<1> 42"]);
+	assert!(err.starts_with("File cannot be read: missing.evm"));
+}
+
+#[test]
+fn history_is_kept_between_sessions() {
+	let home = home("history");
+
+	session(&home, "push 1\n\niload 0 {\npush 2\n}\n:stack\n");
+	session(&home, "push 3\n");
+
+	let history = fs::read_to_string(home.join(".evm_asm_history")).unwrap();
+	assert_eq!(history.lines().collect::<Vec<_>>(), ["#V2", "push 1", "iload 0 {\\npush 2\\n}", ":stack", "push 3"]);
+}
@@ -0,0 +1,59 @@
+use proptest::prelude::*;
+
+use evm_asm::ast::{parse, print, Command, Value};
+use evm_asm::{codegen, disasm, opcode, tokenize};
+
+// values that survive printing: numbers are finite (source has no NaN or infinity) and strings
+// have no quotes (source has no escapes)
+fn value() -> impl Strategy<Value = Value> {
+	let leaf = prop_oneof![
+		Just(Value::Nil),
+		any::<f64>().prop_filter("finite", |val| val.is_finite()).prop_map(Value::Number),
+		"[^\"]{0,12}".prop_map(Value::String),
+		any::<bool>().prop_map(Value::Boolean),
+	];
+
+	leaf.prop_recursive(4, 32, 6, |inner| prop_oneof![
+		prop::collection::vec(inner, 0..6).prop_map(Value::Array),
+		prop::collection::vec(simple_command(), 0..6).prop_map(Value::Function),
+	])
+}
+
+// a command without an operand; every one in opcode::COMMANDS, so new opcodes are covered
+fn simple_command() -> impl Strategy<Value = Command> {
+	let simple: Vec<Command> = opcode::COMMANDS.iter().filter(|command| !matches!(command, Command::Push(_) | Command::ILoad(..))).cloned().collect();
+	prop::sample::select(simple)
+}
+
+fn command() -> impl Strategy<Value = Command> {
+	prop_oneof![
+		simple_command(),
+		value().prop_map(Command::Push),
+		(0..16u8, value()).prop_map(|(reg, value)| Command::ILoad(reg, value)),
+	]
+}
+
+fn program() -> impl Strategy<Value = Vec<Command>> {
+	prop::collection::vec(command(), 0..24)
+}
+
+proptest! {
+	#[test]
+	fn print_then_parse(commands in program()) {
+		let source = print(&commands);
+		let tokens = tokenize(&source, "<printed>").map_err(|e| TestCaseError::fail(format!("{}\n{}", e, source)))?;
+		let parsed = parse(tokens).map_err(|e| TestCaseError::fail(format!("{}\n{}", e, source)))?;
+
+		prop_assert_eq!(parsed, commands, "{}", source);
+	}
+
+	#[test]
+	fn generate_then_disassemble(commands in program()) {
+		codegen::validate(&commands).map_err(|e| TestCaseError::fail(e.to_string()))?;
+
+		let bytecode = codegen::generate(commands.clone());
+		let decoded = disasm::disassemble(&bytecode).map_err(|e| TestCaseError::fail(e.to_string()))?;
+
+		prop_assert_eq!(decoded, commands);
+	}
+}
@@ -0,0 +1,43 @@
+use evm_asm::vm::{lower, Value, Vm};
+use evm_asm::{parse, tokenize};
+
+// the stack a program leaves, printed, or the error that stopped it
+fn run(source: &str) -> Result<Vec<String>, String> {
+	Vm::default().load(lower(&parse(tokenize(source, "<test>").unwrap()).unwrap())).run()
+		.map(|stack| stack.iter().map(Value::to_string).collect())
+		.map_err(|e| e.message)
+}
+
+#[test]
+fn stack_and_registers() {
+	assert_eq!(run("push 1 push 2 swap dup drop").unwrap(), ["2", "1"]);
+	assert_eq!(run("iload 3 \"x\" push 3 load").unwrap(), ["\"x\""]);
+	assert_eq!(run("drop").unwrap_err(), "Stack underflow");
+	assert_eq!(run("push 16 load").unwrap_err(), "Register must be between 0-15: 16");
+}
+
+#[test]
+fn operators() {
+	assert_eq!(run("push 7 push 2 - push 3 * push 4 / push 3 %").unwrap(), ["0.75"]);
+	assert_eq!(run("push 1 push 2 < push 2 push 2 >= and push false or not").unwrap(), ["false"]);
+	assert_eq!(run("push \"a\" push \"b\" concat push [1] push [2] concat").unwrap(), ["\"ab\"", "[1 2]"]);
+	assert_eq!(run("push \"a,b\" push \",\" split reverse").unwrap(), ["[\"b\" \"a\"]"]);
+	assert_eq!(run("push \"abc\" push \"^a.c$\" match push 3 iota").unwrap(), ["true", "[0 1 2]"]);
+	assert_eq!(run("push \" 12 \" tonum push true tonum push 12 tostr push nil push nil =").unwrap(), ["12", "1", "\"12\"", "true"]);
+
+	assert_eq!(run("push 1 push 0 /").unwrap_err(), "Division by zero");
+	assert_eq!(run("push 1 push \"a\" +").unwrap_err(), "Expected number, got string");
+	assert_eq!(run("push 1 push \"a\" <").unwrap_err(), "Cannot compare number with string");
+}
+
+#[test]
+fn functions_and_loops() {
+	assert_eq!(run("iload 0 {push 2 *} push 21 push 0 call").unwrap(), ["42"]);
+	assert_eq!(run("iload 0 {push \"yes\"} push true push 0 if push nil push 0 if").unwrap(), ["\"yes\""]);
+	assert_eq!(run("iload 0 {push 10 *} push [1 2 3] push 0 map").unwrap(), ["[10 20 30]"]);
+	assert_eq!(run("iload 0 {push 1 >} push [1 2 3] push 0 filter").unwrap(), ["[2 3]"]);
+	assert_eq!(run("iload 0 {+} push [1 2 3] push 0 push 10 reduce").unwrap(), ["16"]);
+	assert_eq!(run("iload 0 {push 1 +} push [1 2] push 0 each").unwrap(), ["2", "3"]);
+
+	assert_eq!(run("push 0 call").unwrap_err(), "Register 0 holds nil, not a function");
+}
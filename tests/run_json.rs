@@ -0,0 +1,54 @@
+use std::process::Command;
+use std::{env, fs};
+
+use serde_json::{json, Value};
+
+// the json printed for a program, and the exit code
+fn run(name: &str, source: &str, args: &[&str]) -> (Value, Option<i32>) {
+	let path = env::temp_dir().join(format!("evm-asm-json-{}-{}.evm", name, std::process::id()));
+	fs::write(&path, source).unwrap();
+
+	let output = Command::new(env!("CARGO_BIN_EXE_evm-asm")).args(["run", "--output", "json"]).args(args).arg(&path).output().unwrap();
+	(serde_json::from_slice(&output.stdout).unwrap(), output.status.code())
+}
+
+#[test]
+fn the_stack_is_printed_as_ast_json() {
+	let (out, code) = run("stack", "push 1 push \"a\" push [true nil]", &[]);
+
+	assert_eq!(code, Some(0));
+	assert_eq!(out, json!({"stack": [
+		{"type": "number", "value": 1.0},
+		{"type": "string", "value": "a"},
+		{"type": "array", "values": [{"type": "boolean", "value": true}, {"type": "nil"}]}
+	]}));
+}
+
+#[test]
+fn registers_are_printed_on_request() {
+	let (out, _) = run("registers", "iload 2 \"x\"", &["--registers"]);
+	let registers = out["registers"].as_array().unwrap();
+
+	assert_eq!(registers.len(), 16);
+	assert_eq!(registers[2], json!({"type": "string", "value": "x"}));
+	assert_eq!(registers[0], json!({"type": "nil"}));
+}
+
+#[test]
+fn errors_are_printed_with_their_location() {
+	let (out, code) = run("error", "push 7\niload 0 {\n\tdrop drop\n}\npush 0 call\n", &[]);
+
+	assert_eq!(code, Some(70));
+	assert_eq!(out, json!({
+		"stack": [],
+		"error": {"message": "Stack underflow", "command": 3, "backtrace": [5], "line": 3, "column": 7}
+	}));
+}
+
+#[test]
+fn unknown_formats_are_refused() {
+	let output = Command::new(env!("CARGO_BIN_EXE_evm-asm")).args(["run", "--output", "yaml", "missing.evm"]).output().unwrap();
+
+	assert_eq!(output.status.code(), Some(64));
+	assert_eq!(String::from_utf8_lossy(&output.stderr), "Unknown output format: yaml\n");
+}
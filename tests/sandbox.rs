@@ -0,0 +1,81 @@
+use std::process::Command;
+use std::{env, fs};
+
+use evm_asm::vm::{lower, Capabilities, Host, Sandbox, StubHost, Value, Vm};
+use evm_asm::{parse, tokenize};
+
+// a host that answers everything, so only the sandbox can refuse
+struct Open;
+
+impl Host for Open {
+	fn query(&mut self, _selector: &str) -> Result<Value, String> {
+		Ok(Value::Array(vec!["item".into()]))
+	}
+
+	fn info(&mut self, _item: &Value, _key: &str) -> Result<Value, String> {
+		Ok(1.0.into())
+	}
+
+	fn print(&mut self, _text: &str) -> Result<(), String> {
+		Ok(())
+	}
+
+	fn read_line(&mut self) -> Result<Option<String>, String> {
+		Ok(Some("line".to_owned()))
+	}
+}
+
+fn run(allow: Capabilities, source: &str) -> Result<usize, String> {
+	Vm::new(Sandbox {host: Box::new(Open), allow}).load(lower(&parse(tokenize(source, "<test>").unwrap()).unwrap())).run()
+		.map(|stack| stack.len())
+		.map_err(|e| e.message)
+}
+
+#[test]
+fn capabilities_gate_host_operations() {
+	let query = "push \"x\" query push 1 push \"k\" info";
+	let io = "readln print";
+
+	assert_eq!(run(Capabilities {query: true, io: false}, query), Ok(2));
+	assert_eq!(run(Capabilities {query: false, io: true}, io), Ok(0));
+
+	assert_eq!(run(Capabilities::default(), query).unwrap_err(), "`query` needs the query capability, which this program was not given (--allow query)");
+	assert_eq!(run(Capabilities::default(), "push 1 push \"k\" info").unwrap_err(), "`info` needs the query capability, which this program was not given (--allow query)");
+	assert_eq!(run(Capabilities {query: true, io: false}, io).unwrap_err(), "`readln` needs the io capability, which this program was not given (--allow io)");
+	assert_eq!(run(Capabilities {query: true, io: false}, "push 1 print").unwrap_err(), "`print` needs the io capability, which this program was not given (--allow io)");
+
+	// allowed operations still fail where the host cannot do them
+	let stub = Vm::new(Sandbox {host: Box::new(StubHost), allow: Capabilities {query: true, io: true}}).load(lower(&parse(tokenize(io, "<test>").unwrap()).unwrap())).run().err().unwrap();
+	assert_eq!(stub.message, "Console input is not available on this host");
+}
+
+#[test]
+fn capabilities_are_allowed_by_name() {
+	let mut allow = Capabilities::default();
+	allow.allow("io").unwrap();
+	assert!(allow.io && !allow.query);
+
+	let mut all = Capabilities::default();
+	all.allow("all").unwrap();
+	assert!(all.io && all.query);
+
+	assert_eq!(allow.allow("net").unwrap_err(), "Unknown capability: net (expected query, io or all)");
+}
+
+#[test]
+fn run_denies_what_is_not_allowed() {
+	let path = env::temp_dir().join(format!("evm-asm-sandbox-{}.evm", std::process::id()));
+	fs::write(&path, "push \"hi\" print").unwrap();
+
+	let run = |args: &[&str]| Command::new(env!("CARGO_BIN_EXE_evm-asm")).arg("run").args(args).arg(&path).output().unwrap();
+
+	let denied = run(&[]);
+	assert_eq!(denied.status.code(), Some(70));
+	assert!(String::from_utf8_lossy(&denied.stderr).contains("`print` needs the io capability"));
+
+	let allowed = run(&["--allow", "query,io"]);
+	assert!(allowed.status.success());
+	assert_eq!(String::from_utf8_lossy(&allowed.stdout), "hi\n");
+
+	assert_eq!(run(&["--allow", "net"]).status.code(), Some(64));
+}
@@ -0,0 +1,29 @@
+use evm_asm::diagnostics::{sarif, Diagnostic, Span};
+use evm_asm::lexer::Loc;
+
+#[test]
+fn results_point_at_described_rules() {
+	let loc = Loc {line: 2, col: 7, filename: "/src/my prog.evmasm".to_owned()};
+	let diagnostics = [
+		Diagnostic::warning("W001", "console I/O"),
+		Diagnostic::error("P005", "Register must be between 0-15: 16").with_span(Span::new(&loc, 2)),
+	];
+
+	let log = sarif(&diagnostics);
+	let run = &log["runs"][0];
+	let rules = run["tool"]["driver"]["rules"].as_array().unwrap();
+
+	assert_eq!(log["version"], "2.1.0");
+	assert_eq!(rules.len(), 2);
+
+	for result in run["results"].as_array().unwrap() {
+		let rule = &rules[result["ruleIndex"].as_u64().unwrap() as usize];
+		assert_eq!(rule["id"], result["ruleId"]);
+		assert!(rule["shortDescription"]["text"].is_string());
+	}
+
+	let region = &run["results"][1]["locations"][0]["physicalLocation"];
+	assert_eq!(region["artifactLocation"]["uri"], "file:///src/my%20prog.evmasm");
+	assert_eq!(region["region"]["startColumn"], 7);
+	assert_eq!(region["region"]["endColumn"], 9);
+}
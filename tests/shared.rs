@@ -0,0 +1,70 @@
+use std::path::PathBuf;
+use std::process::{Command, Output};
+use std::{env, fs};
+
+use serde_json::{json, Value};
+
+// writes each program to its own file
+fn files(programs: &[(&str, &str)]) -> Vec<PathBuf> {
+	let dir = env::temp_dir().join(format!("evm-asm-shared-{}", std::process::id()));
+	fs::create_dir_all(&dir).unwrap();
+
+	programs.iter().map(|(name, source)| {
+		let path = dir.join(name);
+		fs::write(&path, source).unwrap();
+		path
+	}).collect()
+}
+
+fn run(args: &[&str], files: &[PathBuf]) -> Output {
+	Command::new(env!("CARGO_BIN_EXE_evm-asm")).arg("run").args(args).args(files).output().unwrap()
+}
+
+#[test]
+fn programs_share_the_stack_and_registers() {
+	let files = files(&[("define.evm", "iload 0 {push 2 *}\npush 21\n"), ("use.evm", "push 0 call\n")]);
+	let output = run(&["--shared"], &files);
+
+	assert!(output.status.success());
+	assert_eq!(String::from_utf8_lossy(&output.stdout), "42\n");
+}
+
+#[test]
+fn optimized_bytecode_keeps_the_registers_it_sets() {
+	let sources = files(&[("chain-define.evm", "iload 0 {push 2 *}\npush 21\npush 0 call\n"), ("chain-use.evm", "push 0 call\n")]);
+
+	// assembled at the default level, which inlines the call in the first program
+	let bytecode: Vec<PathBuf> = sources.iter().map(|source| {
+		let path = source.with_extension("bin");
+		let status = Command::new(env!("CARGO_BIN_EXE_evm-asm")).arg("-o").arg(&path).arg(source).status().unwrap();
+		assert!(status.success());
+		path
+	}).collect();
+
+	let output = run(&["--shared"], &bytecode);
+	assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+	assert_eq!(String::from_utf8_lossy(&output.stdout), "84\n");
+}
+
+#[test]
+fn errors_name_the_failing_program() {
+	let files = files(&[("push.evm", "push 1\n"), ("fail.evm", "drop drop\n"), ("never.evm", "push 2\n")]);
+
+	let text = run(&["--shared"], &files);
+	assert_eq!(text.status.code(), Some(70));
+	assert!(String::from_utf8_lossy(&text.stderr).contains("fail.evm:1:6"));
+
+	let output = run(&["--shared", "--output", "json"], &files);
+	let json: Value = serde_json::from_slice(&output.stdout).unwrap();
+	assert_eq!(json["error"]["program"], json!(files[1]));
+	assert_eq!(json["stack"], json!([]));
+}
+
+#[test]
+fn several_programs_need_shared() {
+	let files = files(&[("one.evm", "push 1\n"), ("two.evm", "push 2\n")]);
+	let output = run(&[], &files);
+
+	assert_eq!(output.status.code(), Some(64));
+	assert_eq!(String::from_utf8_lossy(&output.stderr), "Pass --shared to run several programs.\n");
+}
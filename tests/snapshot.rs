@@ -0,0 +1,70 @@
+use serde_json::json;
+
+use evm_asm::vm::{lower, Value, Vm};
+use evm_asm::{parse, tokenize};
+
+const PROGRAM: &str = "\
+iload 0 {push 2 *}
+iload 1 {push 0 call push 1 +}
+iload 2 {+}
+push [1 2 3] push 1 map
+push 4 iota push 2 push 0 reduce
+push \"done\"
+";
+
+fn stack(vm: &Vm) -> Vec<String> {
+	vm.stack.iter().map(Value::to_string).collect()
+}
+
+#[test]
+fn restored_programs_finish_as_if_never_stopped() {
+	let program = lower(&parse(tokenize(PROGRAM, "<test>").unwrap()).unwrap());
+
+	let mut whole = Vm::default();
+	whole.load(program.clone()).run().ok().unwrap();
+
+	for fuel in 0..whole.steps {
+		let mut stopped = Vm::default();
+		stopped.fuel = Some(fuel);
+		assert!(stopped.load(program.clone()).run().is_err());
+
+		// through text, as run --snapshot and --resume do
+		let snapshot = serde_json::from_str(&stopped.snapshot().to_string()).unwrap();
+
+		let mut resumed = Vm::default();
+		resumed.restore(&program, &snapshot).unwrap();
+		assert_eq!(resumed.steps, fuel);
+		resumed.run().ok().unwrap();
+
+		assert_eq!(stack(&resumed), stack(&whole), "stopped after {} steps", fuel);
+		assert_eq!(resumed.steps, whole.steps);
+	}
+}
+
+#[test]
+fn snapshots_must_match_the_program() {
+	let program = lower(&parse(tokenize(PROGRAM, "<test>").unwrap()).unwrap());
+	let other = lower(&parse(tokenize("push 1", "<test>").unwrap()).unwrap());
+
+	let mut vm = Vm::default();
+	vm.fuel = Some(8);
+	assert!(vm.load(program.clone()).run().is_err());
+	let snapshot = vm.snapshot();
+
+	let restore = |snapshot: &serde_json::Value| Vm::default().restore(&program, snapshot).err().unwrap();
+
+	assert_eq!(Vm::default().restore(&other, &snapshot).err().unwrap(), "Snapshot refers to a function not in this program: 1");
+	assert_eq!(restore(&json!({"version": 99})), "Unsupported snapshot version: 99");
+
+	let mut registers = snapshot.clone();
+	registers["registers"].as_array_mut().unwrap().pop();
+	assert_eq!(restore(&registers), "Snapshot has 15 registers, expected 16");
+
+	let mut ip = snapshot.clone();
+	ip["frames"][0]["ip"] = json!(100);
+	assert_eq!(restore(&ip), "Snapshot ip 100 is past the end of its function");
+
+	let mut index = snapshot;
+	index["frames"][1]["index"] = json!(4);
+	assert_eq!(restore(&index), "Snapshot loop index 4 is past its 3 items");
+}
@@ -0,0 +1,65 @@
+use evm_asm::ast::parse_with_locs;
+use evm_asm::depth::{analyze, Analysis, Depth, StackError};
+use evm_asm::lexer::tokenize;
+
+fn check(source: &str, max_stack: Option<usize>) -> Result<Analysis, StackError> {
+	let (commands, locs) = parse_with_locs(tokenize(source, "<test>").unwrap()).unwrap();
+	analyze(&commands, &locs, max_stack)
+}
+
+#[test]
+fn depths_follow_calls() {
+	let analysis = check("iload 0 {push 1 +} push 1 push 0 call push 2", None).unwrap();
+
+	assert_eq!(analysis.end, Depth {min: 2, max: Some(2)});
+	assert_eq!(analysis.peak, Some(2));
+}
+
+#[test]
+fn underflow_names_the_command() {
+	let error = check("push 1 push 2 drop drop drop", None).unwrap_err();
+
+	assert!(matches!(error, StackError::Underflow {command: "drop", takes: 1, depth: 0, ..}), "{:?}", error);
+	assert_eq!((error.loc().line, error.loc().col), (1, 25));
+}
+
+#[test]
+fn underflow_in_a_called_function_shows_the_call() {
+	let error = check("iload 3 {+}\npush 1\npush 3 call", None).unwrap_err();
+
+	assert!(matches!(error, StackError::Underflow {command: "+", takes: 2, depth: 1, ..}), "{:?}", error);
+	assert_eq!((error.loc().line, error.loc().col), (1, 10));
+	assert_eq!(error.called().iter().map(|loc| (loc.line, loc.col)).collect::<Vec<_>>(), [(3, 8)]);
+}
+
+#[test]
+fn conditional_code_is_not_an_error() {
+	// the body of if may not run, and a loop's may run for no element
+	assert!(check("iload 0 {drop drop} push true push 0 if", None).is_ok());
+	assert!(check("iload 0 {drop drop} push [] push 0 each", None).is_ok());
+
+	// the if itself is sure to run
+	assert!(matches!(check("iload 0 {} push 0 if", None), Err(StackError::Underflow {command: "if", ..})));
+}
+
+#[test]
+fn loops_that_grow_the_stack_are_unbounded() {
+	let analysis = check("iload 0 {dup} push [1 2] push 0 each", None).unwrap();
+
+	assert_eq!(analysis.end, Depth {min: 0, max: None});
+	assert_eq!(analysis.peak, None);
+}
+
+#[test]
+fn unknown_functions_end_the_proof() {
+	// the function in register 1 depends on the condition, so the drops may be fine
+	assert!(check("push 2 iload 1 {} iload 0 {iload 1 {push 1 push 2}} push true push 0 if push 1 call drop drop drop", None).is_ok());
+}
+
+#[test]
+fn overflow_past_the_maximum() {
+	assert!(check("push 1 push 2 +", Some(2)).is_ok());
+
+	let error = check("push 1 push 2 push 3", Some(2)).unwrap_err();
+	assert!(matches!(error, StackError::Overflow {command: "push", depth: 3, max: 2, ..}), "{:?}", error);
+}
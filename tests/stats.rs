@@ -0,0 +1,48 @@
+use serde_json::json;
+
+use evm_asm::stats::Stats;
+use evm_asm::{assemble, AssembleOptions};
+
+#[test]
+fn counts_commands_and_sizes() {
+	let mut stats = Stats::default();
+	stats.add_source("push \"a\" print push \"a\" print\n", "a.asm").unwrap();
+	stats.add_source("iload 0 {push 7 +}\nquery push 0 map\n", "b.asm").unwrap();
+
+	assert_eq!(stats.programs, 2);
+	assert_eq!(stats.opcodes["print"].count, 2);
+	assert_eq!(stats.opcodes["push"].count, 4);
+	assert_eq!(stats.opcodes["+"].count, 1);
+
+	// the iload header: opcode, register, value tag and body length
+	assert_eq!(stats.opcodes["iload"].bytes, 11);
+	assert_eq!(stats.smallest, Some(stats.bytes - stats.largest));
+}
+
+#[test]
+fn repeated_constants() {
+	let mut stats = Stats::default();
+	stats.add_source("push \"ab\" push \"ab\" push [1 {push \"ab\"}]\n", "a.asm").unwrap();
+	stats.add_source("push \"ab\"\n", "b.asm").unwrap();
+
+	let constant = &stats.constants["\"ab\""];
+	assert_eq!((constant.count, constant.programs, constant.size), (4, 2, 11));
+	assert_eq!(stats.constants["1"].count, 1);
+	assert_eq!(stats.repeated_bytes(), 33);
+
+	let json = stats.to_json(1);
+	assert_eq!(json["constants"]["most_repeated"], json!([{"value": "\"ab\"", "count": 4, "programs": 2, "bytes": 33}]));
+	assert_eq!(json["programs"], 2);
+}
+
+#[test]
+fn bytecode_and_errors() {
+	let mut stats = Stats::default();
+	stats.add(&assemble("push 1 print\n", &AssembleOptions::default()).unwrap()).unwrap();
+
+	assert_eq!(stats.programs, 1);
+	assert!(stats.add(&[0xff]).is_err());
+	assert!(stats.add_source("push [", "bad.asm").is_err());
+	assert_eq!(stats.programs, 1);
+	assert!(stats.text(10).starts_with("Programs: 1\nBytes: "));
+}
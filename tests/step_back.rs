@@ -0,0 +1,69 @@
+use evm_asm::vm::{lower, Vm};
+use evm_asm::{parse, tokenize};
+
+fn vm(source: &str, history: usize) -> Vm {
+	let mut vm = Vm::default();
+	vm.history_limit = history;
+	vm.load(lower(&parse(tokenize(source, "<test>").unwrap()).unwrap()));
+	vm
+}
+
+const PROGRAMS: [&str; 4] = [
+	"push 1 push 2 + dup * iload 3 \"x\" drop",
+	"iload 0 {push 10 *} push [1 2 3] push 0 map iload 1 {push 2 >} push 1 filter",
+	"iload 0 {+} push [1 2 3] push 0 push 0 reduce iload 1 {drop} push [4 5] push 1 each",
+	"iload 0 {push 1 - dup push 0 > push 0 if} push 3 push 0 call push [] push 0 map"
+];
+
+#[test]
+fn every_step_undoes_to_the_state_before_it() {
+	for source in PROGRAMS {
+		let mut vm = vm(source, usize::MAX);
+		vm.peek().unwrap();
+		let mut states = vec![vm.snapshot()];
+
+		while vm.step().unwrap() {
+			vm.peek().unwrap();
+			states.push(vm.snapshot());
+		}
+
+		// the last step finished the program; the others stopped before a command
+		states.pop();
+
+		while let Some(state) = states.pop() {
+			assert!(vm.step_back(), "{}", source);
+			assert_eq!(vm.snapshot(), state, "{}", source);
+		}
+
+		assert!(!vm.step_back());
+	}
+}
+
+#[test]
+fn history_is_limited() {
+	let mut vm = vm("push 1 push 2 push 3 push 4", 2);
+	vm.run().unwrap();
+
+	assert!(vm.step_back() && vm.step_back());
+	assert!(!vm.step_back());
+	assert_eq!(vm.stack.len(), 2);
+}
+
+#[test]
+fn a_failed_step_can_be_undone_after_abort() {
+	let mut vm = vm("iload 0 {push 1 push \"a\" +} push 0 call", 10);
+	// up to the +, inside the call
+	for _ in 0..5 {
+		vm.step().unwrap();
+	}
+
+	vm.peek().unwrap();
+	let before = vm.snapshot();
+
+	assert!(vm.step().is_err());
+	vm.abort();
+	assert!(vm.peek().unwrap().is_none());
+
+	assert!(vm.step_back());
+	assert_eq!(vm.snapshot(), before);
+}
@@ -0,0 +1,34 @@
+use evm_asm::stream::{assemble_stream, StreamError};
+use evm_asm::codegen::TargetLimits;
+use evm_asm::{assemble, optimizer, AssembleOptions, Error};
+
+fn unoptimized(source: &str) -> Vec<u8> {
+	let options = AssembleOptions {optimizer: optimizer::Options {level: 0, ..Default::default()}, ..Default::default()};
+	assemble(source, &options).unwrap()
+}
+
+#[test]
+fn writes_what_the_unoptimized_assembler_does() {
+	let source = "#!/usr/bin/env evm-asm\niload 0 {push 1 +} ; adds one\npush [2 \"a]b\" {dup drop}] push 0 call\n.test \"t\" {push 1} .expect [1]\npush .5 print\n";
+	let mut out = vec![];
+
+	assert_eq!(assemble_stream(source, "<source>", None, &TargetLimits::default(), false, &mut out).unwrap(), out.len());
+	assert_eq!(out, unoptimized(source));
+}
+
+#[test]
+fn reports_the_first_error_in_the_source() {
+	let mut out = vec![];
+	let error = assemble_stream("push 1\npush push\n\"unterminated", "<source>", None, &TargetLimits::default(), false, &mut out).unwrap_err();
+
+	assert!(matches!(error, StreamError::Assemble(Error::Parse(_))), "{:?}", error);
+	assert_eq!(out, unoptimized("push 1"));
+	assert!(matches!(assemble_stream("push [1", "<source>", None, &TargetLimits::default(), false, &mut vec![]), Err(StreamError::Assemble(Error::Parse(_)))));
+	assert!(matches!(assemble_stream("push 1 2", "<source>", None, &TargetLimits::default(), false, &mut vec![]), Err(StreamError::Assemble(Error::Parse(_)))));
+}
+
+#[test]
+fn stops_at_the_maximum_size() {
+	let error = assemble_stream(&"push 1\n".repeat(100), "<source>", Some(50), &TargetLimits::default(), false, &mut vec![]).unwrap_err();
+	assert!(matches!(error, StreamError::Assemble(Error::TooLarge {max: 50, ..})), "{:?}", error);
+}
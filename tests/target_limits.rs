@@ -0,0 +1,32 @@
+use evm_asm::codegen::{CodegenError, TargetLimits};
+use evm_asm::{assemble, AssembleOptions, Error};
+
+fn with(target: TargetLimits) -> AssembleOptions {
+	AssembleOptions {target, ..AssembleOptions::default()}
+}
+
+fn over(source: &str, target: TargetLimits) -> (&'static str, usize, usize) {
+	match assemble(source, &with(target)) {
+		Err(Error::Codegen(CodegenError::OverLimit {limit, len, max, ..})) => (limit, len, max),
+		result => panic!("{:?}", result)
+	}
+}
+
+#[test]
+fn each_limit_stops_a_larger_program() {
+	assert_eq!(over("push [1 2 3] print", TargetLimits {max_array_length: Some(2), ..TargetLimits::default()}), ("max-array-length", 3, 2));
+	assert_eq!(over("push [1 2] iload 0 { print push 1 print push 2 } push 0 each", TargetLimits {max_function_length: Some(3), ..TargetLimits::default()}), ("max-function-length", 4, 3));
+	assert_eq!(over("push \"hello\" print", TargetLimits {max_string_size: Some(4), ..TargetLimits::default()}), ("max-string-size", 5, 4));
+	assert_eq!(over("push [[[1]]] print", TargetLimits {max_nesting_depth: Some(2), ..TargetLimits::default()}), ("max-nesting-depth", 3, 2));
+
+	let error = assemble("push [1 2 3] print", &with(TargetLimits {max_array_length: Some(2), ..TargetLimits::default()})).unwrap_err();
+	assert!(error.to_string().contains("--max-array-length"), "{}", error);
+}
+
+#[test]
+fn programs_at_the_limits_pass() {
+	let source = "push [[\"hi\"] 2] print";
+	let target = TargetLimits {max_array_length: Some(2), max_function_length: Some(0), max_string_size: Some(2), max_nesting_depth: Some(2)};
+
+	assert_eq!(assemble(source, &with(target)).unwrap(), assemble(source, &AssembleOptions::default()).unwrap());
+}
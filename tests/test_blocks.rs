@@ -0,0 +1,85 @@
+use std::process::Command;
+use std::{env, fs};
+
+use evm_asm::ast::{parse_with_tests, Parsed};
+use evm_asm::testing::{run_test, Outcome};
+use evm_asm::tokenize;
+
+const PROGRAM: &str = "\
+iload 0 {push 2 *}
+push 1 drop
+
+.test \"doubles\" {
+	push 21 push 0 call
+} .expect [42]
+
+.test \"wrong\" {
+	push 1 push 0 call push \"x\"
+} .expect [3]
+
+.test \"fails\" {
+	push 0 call
+}
+
+.test \"no expectation\" {
+	push 1
+}
+";
+
+fn parsed(source: &str) -> Parsed {
+	parse_with_tests(tokenize(source, "<test>").unwrap()).unwrap()
+}
+
+// why each test failed, None for those that passed
+fn failures(source: &str, fuel: Option<u64>) -> Vec<Option<String>> {
+	let parsed = parsed(source);
+
+	parsed.tests.iter().map(|test| match run_test(test, &parsed.commands, &parsed.locs, source, fuel) {
+		Outcome::Pass => None,
+		Outcome::Fail(message) => Some(message)
+	}).collect()
+}
+
+#[test]
+fn test_blocks_are_left_out_of_the_program() {
+	let parsed = parsed(PROGRAM);
+
+	assert_eq!(parsed.commands.len(), 3);
+	assert_eq!(parsed.locs.len(), 5);
+	assert_eq!(parsed.tests.iter().map(|test| test.name.as_str()).collect::<Vec<_>>(), ["doubles", "wrong", "fails", "no expectation"]);
+	assert_eq!(parsed.tests[0].loc.line, 4);
+	assert_eq!(parsed.tests[0].locs[0].line, 5);
+	assert!(parsed.tests[2].expect.is_none());
+}
+
+#[test]
+fn tests_run_after_the_registers_are_loaded() {
+	let failures = failures(PROGRAM, None);
+
+	assert_eq!(failures[0], None);
+	assert_eq!(failures[1].as_deref().unwrap(), "expected [3]\n  actual [2 \"x\"]\nat 0: expected 3, got 2\nat 1: expected nothing, got \"x\"");
+	assert!(failures[2].as_ref().unwrap().contains("Stack underflow"));
+	assert!(failures[2].as_ref().unwrap().contains("<test>:1:17"));
+	assert!(failures[2].as_ref().unwrap().contains("13 | \tpush 0 call"));
+	assert_eq!(failures[3], None);
+}
+
+#[test]
+fn tests_have_a_fuel_limit() {
+	let endless = ".test \"endless\" {iload 0 {push 0 call} push 0 call}";
+	assert!(failures(endless, Some(100))[0].as_ref().unwrap().contains("Fuel exhausted after 100 steps"));
+}
+
+#[test]
+fn the_test_subcommand_reports_each_test() {
+	let path = env::temp_dir().join(format!("evm-asm-tests-{}.evm", std::process::id()));
+	fs::write(&path, PROGRAM).unwrap();
+
+	let output = Command::new(env!("CARGO_BIN_EXE_evm-asm")).arg("test").arg(&path).output().unwrap();
+	let stdout = String::from_utf8(output.stdout).unwrap();
+
+	assert_eq!(output.status.code(), Some(70));
+	assert!(stdout.starts_with("PASS doubles\nFAIL wrong (line 8)\n  expected [3]\n"));
+	assert!(stdout.contains("FAIL fails (line 12)\n"));
+	assert!(stdout.ends_with("PASS no expectation\n2 passed, 2 failed\n"));
+}
@@ -0,0 +1,56 @@
+use std::sync::Arc;
+use std::thread;
+
+use evm_asm::{assemble, AssembleOptions, Error};
+
+fn program(i: usize) -> String {
+	format!("iload 0 {{ push {} + }} push [{} \"item {}\" true] push {} push 0 call", i, i, i, i * 2)
+}
+
+#[test]
+fn assembles_concurrently() {
+	let options = Arc::new(AssembleOptions::default());
+	let expected: Vec<Vec<u8>> = (0..200).map(|i| assemble(&program(i), &options).unwrap()).collect();
+	let expected = Arc::new(expected);
+
+	let threads: Vec<_> = (0..8).map(|t| {
+		let options = options.clone();
+		let expected = expected.clone();
+
+		thread::spawn(move || {
+			for i in (t..200).step_by(8) {
+				assert_eq!(assemble(&program(i), &options).unwrap(), expected[i], "program {}", i);
+			}
+		})
+	}).collect();
+
+	for thread in threads {
+		thread.join().unwrap();
+	}
+}
+
+#[test]
+fn errors_cross_threads() {
+	let errors: Vec<Error> = (0..8).map(|i| {
+		thread::spawn(move || assemble(&format!("push {} push \"unterminated", i), &AssembleOptions::default()).unwrap_err())
+	}).map(|thread| thread.join().unwrap()).collect();
+
+	for e in errors {
+		assert!(matches!(e, Error::Tokenize(_)), "{}", e);
+		assert_eq!(e.loc().map(|loc| loc.line), Some(1));
+	}
+}
+
+#[test]
+fn assemble_all_keeps_the_order_of_the_inputs() {
+	let options = AssembleOptions::default();
+	let sources: Vec<String> = (0..100).map(|i| if i % 7 == 3 { "push [".to_owned() } else { program(i) }).collect();
+	let inputs: Vec<(&str, &str)> = sources.iter().map(|source| ("a.asm", source.as_str())).collect();
+
+	for (i, result) in evm_asm::assemble_all(&inputs, &options).into_iter().enumerate() {
+		match result {
+			Ok(bytecode) => assert_eq!(bytecode, assemble(&program(i), &options).unwrap(), "program {}", i),
+			Err(e) => assert!(i % 7 == 3 && matches!(e, Error::Parse(_)), "program {}: {}", i, e)
+		}
+	}
+}
@@ -0,0 +1,56 @@
+use std::process::{Command, Output};
+use std::{env, fs};
+
+const PROGRAM: &str = "push 1\npush 2\n+\niload 0 {push 10 *} push [1] push 0 map\n";
+
+// runs the program with the given options
+fn run(name: &str, args: &[&str]) -> Output {
+	let path = env::temp_dir().join(format!("evm-asm-trace-{}-{}.evm", name, std::process::id()));
+	fs::write(&path, PROGRAM).unwrap();
+
+	Command::new(env!("CARGO_BIN_EXE_evm-asm")).arg("run").args(args).arg(&path).output().unwrap()
+}
+
+fn lines(bytes: &[u8]) -> Vec<String> {
+	String::from_utf8_lossy(bytes).lines().map(|line| line.trim_end().to_owned()).collect()
+}
+
+#[test]
+fn every_command_is_traced() {
+	let output = run("stderr", &["--trace", "--trace-depth", "2"]);
+
+	assert!(output.status.success());
+	assert_eq!(lines(&output.stdout), ["3", "[10]"]);
+	assert_eq!(lines(&output.stderr), [
+		"     1:1  push 1                    1",
+		"     2:1  push 2                    1 2",
+		"     3:1  +                         3",
+		"     4:1  iload 0 {push 10 *}       3",
+		"    4:21  push [1]                  3 [1]",
+		"    4:30  push 0                    ... [1] 0",
+		"    4:37  map                       3 1",
+		"    4:10  push 10                   ... 1 10",
+		"    4:18  *                         3 10"
+	]);
+}
+
+#[test]
+fn traces_go_to_a_file() {
+	let path = env::temp_dir().join(format!("evm-asm-trace-{}.txt", std::process::id()));
+	let output = run("file", &["--trace-file", path.to_str().unwrap(), "--trace-depth", "0"]);
+
+	assert!(output.status.success());
+	assert!(output.stderr.is_empty());
+
+	let trace = lines(&fs::read(&path).unwrap());
+	assert_eq!(trace.len(), 9);
+	assert_eq!(trace[2], "     3:1  +                         ...");
+}
+
+#[test]
+fn trace_depth_must_be_a_number() {
+	let output = run("depth", &["--trace", "--trace-depth", "x"]);
+
+	assert_eq!(output.status.code(), Some(64));
+	assert_eq!(lines(&output.stderr), ["Invalid trace depth: x"]);
+}
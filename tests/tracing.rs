@@ -0,0 +1,66 @@
+#![cfg(feature = "tracing")]
+
+use std::sync::{Arc, Mutex};
+
+use tracing::span::{Attributes, Id, Record};
+use tracing::{Event, Metadata, Subscriber};
+
+use evm_asm::{assemble, AssembleOptions};
+
+// records the names of the spans opened and the messages of the events, in order
+#[derive(Clone, Default)]
+struct Recorder(Arc<Mutex<Vec<String>>>);
+
+struct MessageVisitor<'a>(&'a mut String);
+
+impl tracing::field::Visit for MessageVisitor<'_> {
+	fn record_debug(&mut self, field: &tracing::field::Field, value: &dyn std::fmt::Debug) {
+		if field.name() == "message" {
+			*self.0 = format!("{:?}", value);
+		}
+	}
+}
+
+impl Subscriber for Recorder {
+	fn enabled(&self, _: &Metadata) -> bool {
+		true
+	}
+
+	fn new_span(&self, span: &Attributes) -> Id {
+		let mut names = self.0.lock().unwrap();
+		names.push(format!("span {}", span.metadata().name()));
+		Id::from_u64(names.len() as u64)
+	}
+
+	fn record(&self, _: &Id, _: &Record) {}
+
+	fn record_follows_from(&self, _: &Id, _: &Id) {}
+
+	fn event(&self, event: &Event) {
+		let mut message = String::new();
+		event.record(&mut MessageVisitor(&mut message));
+		self.0.lock().unwrap().push(format!("event {}", message));
+	}
+
+	fn enter(&self, _: &Id) {}
+
+	fn exit(&self, _: &Id) {}
+}
+
+#[test]
+fn phases_are_spans() {
+	let recorder = Recorder::default();
+
+	tracing::subscriber::with_default(recorder.clone(), || {
+		assemble("push { push 1 } call", &AssembleOptions::default()).unwrap();
+	});
+
+	let records = recorder.0.lock().unwrap();
+	let position = |record: &str| records.iter().position(|other| other == record).unwrap_or_else(|| panic!("no {} in {:?}", record, records));
+
+	assert!(position("span assemble") < position("span tokenize"));
+	assert!(position("span tokenize") < position("event tokenized"));
+	assert!(position("span parse") < position("event parsed"));
+	assert!(position("span optimize") < position("span generate"));
+	assert!(position("event function") < position("event generated"));
+}
@@ -0,0 +1,46 @@
+use evm_asm::ast::parse_with_locs;
+use evm_asm::lexer::tokenize;
+use evm_asm::typecheck::{check, Kind, TypeError};
+
+fn typecheck(source: &str) -> Result<(), TypeError> {
+	let (commands, locs) = parse_with_locs(tokenize(source, "<test>").unwrap()).unwrap();
+	check(&commands, &locs)
+}
+
+#[test]
+fn guaranteed_errors_are_found() {
+	let error = typecheck("push \"a\" push 1 +").unwrap_err();
+	assert!(matches!(error, TypeError::Mismatch {command: "+", expected: "number", found: Kind::String, ..}), "{:?}", error);
+	assert_eq!((error.loc().line, error.loc().col), (1, 17));
+
+	assert!(matches!(typecheck("push 5 iload 0 {print} push 0 each"), Err(TypeError::Mismatch {command: "each", expected: "array", found: Kind::Number, ..})));
+	assert!(matches!(typecheck("push 1 push \"a\" <"), Err(TypeError::Mismatch {command: "<", found: Kind::String, ..})));
+	assert!(matches!(typecheck("iload 0 5 push 0 call"), Err(TypeError::NotAFunction {reg: 0, found: Kind::Number, ..})));
+}
+
+#[test]
+fn kinds_follow_registers_and_calls() {
+	assert!(matches!(typecheck("iload 2 \"x\" push 2 load push 1 +"), Err(TypeError::Mismatch {found: Kind::String, ..})));
+	assert!(matches!(typecheck("push [1 2] iload 0 {push 1 +} push 0 map push 1 +"), Err(TypeError::Mismatch {found: Kind::Array, ..})));
+
+	let error = typecheck("iload 0 {push 1 concat}\npush \"x\" push 0 call").unwrap_err();
+	assert!(matches!(error, TypeError::Mismatch {command: "concat", ..}), "{:?}", error);
+	assert_eq!(error.called().iter().map(|loc| (loc.line, loc.col)).collect::<Vec<_>>(), [(2, 17)]);
+}
+
+#[test]
+fn unknown_kinds_pass() {
+	// the host decides what query returns, and a string may or may not be a number
+	assert!(typecheck("push \"a\" query push 1 +").is_ok());
+	assert!(typecheck("push \"1\" tonum push 1 +").is_ok());
+
+	// the body of if may not run, and a register it iloads may hold either value after it
+	assert!(typecheck("push true iload 0 {push \"a\"} push 0 if push 1 +").is_ok());
+	assert!(typecheck("iload 1 5 iload 0 {iload 1 {}} push true push 0 if push 1 call").is_ok());
+}
+
+#[test]
+fn well_kinded_programs_pass() {
+	assert!(typecheck(&std::fs::read_to_string("test.asm").unwrap()).is_ok());
+	assert!(typecheck("push \"a\" push \"b\" concat push [1] push [2] concat iload 0 {+} push 0 push 0 reduce").is_ok());
+}
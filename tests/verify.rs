@@ -0,0 +1,42 @@
+use evm_asm::ast::{Command, Value};
+use evm_asm::codegen::{generate, CodegenError};
+use evm_asm::disasm::{crc32, verify, DisasmError};
+use evm_asm::opcode;
+use evm_asm::{parse, tokenize, Error};
+
+#[test]
+fn valid_bytecode_is_counted() {
+	let bytecode = generate(parse(tokenize("iload 0 {push 1 +}\npush [1 2] push 0 map", "<test>").unwrap()).unwrap()).to_vec();
+	let verified = verify(&bytecode, None).unwrap();
+
+	// iload, its two commands, two pushes and map
+	assert_eq!(verified.commands, 6);
+	assert_eq!(verified.size, bytecode.len());
+	assert_eq!(verify(&bytecode, Some(verified.checksum)).unwrap(), verified);
+}
+
+#[test]
+fn checksum_is_crc32() {
+	assert_eq!(crc32(b"123456789"), 0xcbf4_3926);
+	assert_eq!(crc32(b""), 0);
+}
+
+#[test]
+fn a_different_checksum_fails_before_decoding() {
+	let bytecode = [0xff];
+
+	assert!(matches!(verify(&bytecode, Some(1)), Err(Error::ChecksumMismatch {expected: 1, ..})));
+	assert!(matches!(verify(&bytecode, None), Err(Error::Disasm(DisasmError::UnknownOpcode {offset: 0, opcode: 0xff}))));
+}
+
+#[test]
+fn structural_errors_are_found() {
+	let bytecode = generate(vec![Command::Push(Value::String("abc".to_owned()))]).to_vec();
+
+	// a length past the end of the file
+	assert!(matches!(verify(&bytecode[..bytecode.len() - 1], None), Err(Error::Disasm(DisasmError::LengthTooLarge {..}))));
+
+	// a register the VM does not have
+	let iload = [opcode::opcode(&Command::ILoad(0, Value::Nil)), 16, opcode::NIL];
+	assert!(matches!(verify(&iload, None), Err(Error::Codegen(CodegenError::InvalidRegister(16)))));
+}
@@ -0,0 +1,56 @@
+use evm_asm::vm::{lower, Limits, Value, Vm};
+use evm_asm::{parse, tokenize};
+
+fn program(source: &str) -> std::rc::Rc<evm_asm::vm::Function> {
+	lower(&parse(tokenize(source, "<test>").unwrap()).unwrap())
+}
+
+fn stack(vm: &Vm) -> Vec<String> {
+	vm.stack.iter().map(Value::to_string).collect()
+}
+
+#[test]
+fn values_seeded_by_the_embedder_can_be_popped() {
+	let mut vm = Vm::default();
+	vm.stack.push(Value::from("seeded"));
+	vm.stack.push(Value::from(vec![1.0, 2.0]));
+	vm.load(program("drop drop")).run().unwrap();
+	assert!(vm.stack.is_empty());
+
+	// stepping a loaded program without run
+	vm.stack.push(Value::from("seeded"));
+	vm.load(program("drop"));
+	while vm.step().unwrap() {}
+	assert!(vm.stack.is_empty());
+
+	// pushed after load, between steps
+	vm.load(program("drop"));
+	vm.stack.push(Value::from("late"));
+	while vm.step().unwrap() {}
+
+	vm.registers[0] = Value::from("seeded");
+	vm.load(program("iload 0 nil")).run().unwrap();
+	assert!(vm.registers[0] == Value::Nil);
+}
+
+#[test]
+fn seeded_values_count_towards_the_memory_limit() {
+	let mut vm = Vm::default();
+	vm.limits = Limits {max_memory: Some(8), ..Limits::default()};
+	vm.registers[3] = Value::from("12345678");
+
+	let error = vm.load(program("push \"9\"")).run().err().unwrap();
+	assert_eq!(error.message, "Memory limit of 8 bytes exceeded");
+}
+
+#[test]
+fn programs_share_the_stack_and_registers() {
+	let mut vm = Vm::default();
+	vm.load(program("iload 1 {push 2 *} push 21")).run().unwrap();
+	vm.load(program("push 1 call")).run().unwrap();
+
+	assert_eq!(stack(&vm), ["42"]);
+
+	vm.reset();
+	assert!(vm.stack.is_empty() && vm.registers[1] == Value::Nil);
+}
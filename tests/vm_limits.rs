@@ -0,0 +1,48 @@
+use std::mem;
+
+use evm_asm::vm::{lower, Limits, Value, Vm};
+use evm_asm::{parse, tokenize};
+
+// the stack a program leaves, printed
+fn run(source: &str, limits: Limits) -> Result<Vec<String>, String> {
+	let mut vm = Vm::default();
+	vm.limits = limits;
+	vm.load(lower(&parse(tokenize(source, "<test>").unwrap()).unwrap()));
+	vm.run().map(|stack| stack.iter().map(Value::to_string).collect()).map_err(|e| e.message)
+}
+
+fn memory(max: usize) -> Limits {
+	Limits {max_memory: Some(max), ..Limits::default()}
+}
+
+#[test]
+fn stack_and_depth_limits() {
+	assert!(run("push 1 push 2 push 3", Limits {max_stack: Some(3), ..Limits::default()}).is_ok());
+	assert_eq!(run("push 1 push 2 push 3", Limits {max_stack: Some(2), ..Limits::default()}).unwrap_err(), "Stack limit of 2 values exceeded");
+
+	let recursive = "iload 0 {push 0 call} push 0 call";
+	assert_eq!(run(recursive, Limits {max_depth: Some(10), ..Limits::default()}).unwrap_err(), "Nesting limit of 10 calls exceeded");
+}
+
+#[test]
+fn strings_count_towards_memory() {
+	assert!(run("push \"abcd\"", memory(4)).is_ok());
+	assert_eq!(run("push \"abcd\" push \"e\"", memory(4)).unwrap_err(), "Memory limit of 4 bytes exceeded");
+	assert_eq!(run("iload 1 \"abcde\"", memory(4)).unwrap_err(), "Memory limit of 4 bytes exceeded");
+}
+
+#[test]
+fn loops_count_their_items_and_results() {
+	// the array of 1000 numbers fits, but not alongside the results map builds from it
+	let array = 1000 * mem::size_of::<Value>();
+	let limit = memory(array * 3 / 2);
+
+	assert!(run("push 1000 iota", limit).is_ok());
+	assert_eq!(run("iload 0 {push 1 +} push 1000 iota push 0 map", limit).unwrap_err(), format!("Memory limit of {} bytes exceeded", array * 3 / 2));
+	assert_eq!(run("iload 0 {drop push true} push 1000 iota push 0 filter", limit).unwrap_err(), format!("Memory limit of {} bytes exceeded", array * 3 / 2));
+
+	// 3 items and 3 results at the end of the loop, then the items are freed for the next array
+	let six = memory(6 * mem::size_of::<Value>());
+	assert_eq!(run("iload 0 {push 1 +} push 3 iota push 0 map", six).unwrap(), ["[1 2 3]"]);
+	assert_eq!(run("iload 0 {push 1 +} push 3 iota push 0 map push 3 iota", six).unwrap(), ["[1 2 3]", "[0 1 2]"]);
+}
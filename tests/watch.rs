@@ -0,0 +1,41 @@
+use std::io::{BufRead, BufReader, Read};
+use std::process::{Command, Stdio};
+use std::time::Duration;
+use std::{env, fs, thread};
+
+#[test]
+fn changes_rerun_the_program() {
+	let path = env::temp_dir().join(format!("evm-asm-watch-{}.evm", std::process::id()));
+	fs::write(&path, "push 1").unwrap();
+
+	let mut child = Command::new(env!("CARGO_BIN_EXE_evm-asm"))
+		.args(["run", "--watch"])
+		.arg(&path)
+		.stdout(Stdio::piped())
+		.stderr(Stdio::piped())
+		.spawn()
+		.unwrap();
+
+	let mut stderr = BufReader::new(child.stderr.take().unwrap()).lines().map(Result::unwrap);
+	let mut expect = |prefix: &str| {
+		let line = stderr.next().unwrap();
+		assert!(line.starts_with(prefix), "{}", line);
+	};
+
+	expect("[watch] running");
+	expect("[watch] exited with exit status: 0, waiting for changes");
+
+	// past the file system's timestamp granularity
+	thread::sleep(Duration::from_millis(50));
+	fs::write(&path, "push 2").unwrap();
+
+	expect("[watch] running");
+	expect("[watch] exited with exit status: 0");
+
+	child.kill().unwrap();
+	child.wait().unwrap();
+
+	let mut stdout = String::new();
+	child.stdout.take().unwrap().read_to_string(&mut stdout).unwrap();
+	assert_eq!(stdout, "1\n2\n");
+}